@@ -0,0 +1,279 @@
+//! Compares the cost of hashing with the Rescue-Prime chip against the Poseidon chip.
+
+use ff::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner},
+    dev::CircuitCost,
+    pasta::{Eq, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+        ConstraintSystem, Error, SingleVerifier,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use pasta_curves::{pallas, vesta};
+
+use halo2_gadgets::{
+    poseidon::{Hash, Pow5Chip, Pow5Config, RescueChip, RescueConfig},
+    primitives::{
+        poseidon::{self, ConstantLength, P128Pow5T3},
+        rescue::{self, R128Pow5T3},
+    },
+};
+use std::convert::TryInto;
+use std::fmt;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::OsRng;
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+const L: usize = 2;
+
+fn load_message(
+    layouter: &mut impl Layouter<Fp>,
+    input: &[Column<Advice>; RATE],
+    message: Option<[Fp; L]>,
+) -> Result<[AssignedCell<Fp, Fp>; L], Error> {
+    layouter.assign_region(
+        || "load message",
+        |mut region| {
+            let message_word = |i: usize| {
+                let value = message.map(|message_vals| message_vals[i]);
+                region.assign_advice(
+                    || format!("load message_{}", i),
+                    input[i],
+                    0,
+                    || value.ok_or(Error::Synthesis),
+                )
+            };
+
+            let message: Result<Vec<_>, Error> = (0..L).map(message_word).collect();
+            Ok(message?.try_into().unwrap())
+        },
+    )
+}
+
+fn constrain_output(
+    layouter: &mut impl Layouter<Fp>,
+    input: &[Column<Advice>; RATE],
+    output: &AssignedCell<Fp, Fp>,
+    expected: Option<Fp>,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "constrain output",
+        |mut region| {
+            let expected_var = region.assign_advice(
+                || "load output",
+                input[0],
+                0,
+                || expected.ok_or(Error::Synthesis),
+            )?;
+            region.constrain_equal(output.cell(), expected_var.cell())
+        },
+    )
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct PoseidonCircuit {
+    message: Option<[Fp; L]>,
+    output: Option<Fp>,
+}
+
+#[derive(Debug, Clone)]
+struct PoseidonConfig {
+    input: [Column<Advice>; RATE],
+    poseidon_config: Pow5Config<Fp, WIDTH, RATE>,
+}
+
+impl Circuit<Fp> for PoseidonCircuit {
+    type Config = PoseidonConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+        let partial_sbox = meta.advice_column();
+
+        let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+        let rc_b = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+        meta.enable_constant(rc_b[0]);
+
+        PoseidonConfig {
+            input: state[..RATE].try_into().unwrap(),
+            poseidon_config: Pow5Chip::configure::<P128Pow5T3>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            ),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = Pow5Chip::construct(config.poseidon_config.clone());
+        let message = load_message(&mut layouter, &config.input, self.message)?;
+
+        let hasher = Hash::<_, _, P128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init(
+            chip,
+            layouter.namespace(|| "init"),
+        )?;
+        let output = hasher.hash(layouter.namespace(|| "hash"), message)?;
+
+        constrain_output(&mut layouter, &config.input, &output, self.output)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct RescueCircuit {
+    message: Option<[Fp; L]>,
+    output: Option<Fp>,
+}
+
+#[derive(Debug, Clone)]
+struct RescueCircuitConfig {
+    input: [Column<Advice>; RATE],
+    rescue_config: RescueConfig<Fp, WIDTH, RATE>,
+}
+
+impl Circuit<Fp> for RescueCircuit {
+    type Config = RescueCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+        let inv_sbox = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+
+        let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+        let rc_b = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+        meta.enable_constant(rc_b[0]);
+
+        RescueCircuitConfig {
+            input: state[..RATE].try_into().unwrap(),
+            rescue_config: RescueChip::configure::<R128Pow5T3>(
+                meta,
+                state.try_into().unwrap(),
+                inv_sbox.try_into().unwrap(),
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            ),
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = RescueChip::construct(config.rescue_config.clone());
+        let message = load_message(&mut layouter, &config.input, self.message)?;
+
+        let hasher = Hash::<_, _, R128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init(
+            chip,
+            layouter.namespace(|| "init"),
+        )?;
+        let output = hasher.hash(layouter.namespace(|| "hash"), message)?;
+
+        constrain_output(&mut layouter, &config.input, &output, self.output)
+    }
+}
+
+const K: u32 = 6;
+
+fn bench_circuit<C: Circuit<Fp> + Copy + fmt::Debug>(
+    name: &str,
+    empty_circuit: C,
+    circuit: C,
+    c: &mut Criterion,
+) {
+    // Report the static cost of the circuit.
+    let cost = CircuitCost::<Eq, C>::measure(K as usize, &circuit);
+    println!(
+        "{}: proof size {} bytes, {:?}",
+        name,
+        usize::from(cost.proof_size(1)),
+        cost
+    );
+
+    // Initialize the polynomial commitment parameters
+    let params: Params<vesta::Affine> = Params::new(K);
+
+    // Initialize the proving key
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let prover_name = name.to_string() + "-prover";
+    let verifier_name = name.to_string() + "-verifier";
+
+    let mut rng = OsRng;
+
+    c.bench_function(&prover_name, |b| {
+        b.iter(|| {
+            // Create a proof
+            let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+            create_proof(&params, &pk, &[circuit], &[&[]], &mut rng, &mut transcript)
+                .expect("proof generation should not fail")
+        })
+    });
+
+    // Create a proof
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&[]], &mut rng, &mut transcript)
+        .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    c.bench_function(&verifier_name, |b| {
+        b.iter(|| {
+            let strategy = SingleVerifier::new(&params);
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            assert!(verify_proof(&params, pk.get_vk(), strategy, &[&[]], &mut transcript).is_ok());
+        });
+    });
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let rng = OsRng;
+    let message = [pallas::Base::random(rng), pallas::Base::random(rng)];
+
+    let output =
+        poseidon::Hash::<_, P128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init().hash(message);
+    bench_circuit(
+        "Pow5Chip",
+        PoseidonCircuit::default(),
+        PoseidonCircuit {
+            message: Some(message),
+            output: Some(output),
+        },
+        c,
+    );
+
+    let output =
+        rescue::Hash::<_, R128Pow5T3, ConstantLength<L>, WIDTH, RATE>::init().hash(message);
+    bench_circuit(
+        "RescueChip",
+        RescueCircuit::default(),
+        RescueCircuit {
+            message: Some(message),
+            output: Some(output),
+        },
+        c,
+    );
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
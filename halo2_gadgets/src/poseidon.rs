@@ -1,4 +1,5 @@
-//! Gadget and chips for the Poseidon algebraic hash function.
+//! Gadget and chips for the Poseidon algebraic hash function, and the Poseidon2 and
+//! Rescue-Prime permutations that can be used in its place.
 
 use std::array;
 use std::convert::TryInto;
@@ -12,11 +13,19 @@ use halo2_proofs::{
     plonk::Error,
 };
 
+mod poseidon2;
 mod pow5;
+#[cfg(feature = "rescue")]
+mod rescue;
+mod sponge;
+pub use poseidon2::{Poseidon2Chip, Poseidon2Config};
 pub use pow5::{Pow5Chip, Pow5Config, StateWord};
+#[cfg(feature = "rescue")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rescue")))]
+pub use rescue::{RescueChip, RescueConfig};
 
 use crate::primitives::poseidon::{
-    Absorbing, ConstantLength, Domain, Spec, SpongeMode, Squeezing, State,
+    Absorbing, ConstantLength, Domain, SpongeMode, Squeezing, State,
 };
 
 /// A word from the padded input to a Poseidon sponge.
@@ -29,9 +38,15 @@ pub enum PaddedWord<F: Field> {
 }
 
 /// The set of circuit instructions required to use the Poseidon permutation.
-pub trait PoseidonInstructions<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>:
-    Chip<F>
-{
+///
+/// `S` is the specification of the permutation implemented by the chip, such as a
+/// Poseidon [`Spec`], a Poseidon2 [`poseidon2::Spec`], or (with the `rescue` feature)
+/// a Rescue-Prime `rescue::Spec`. `S` is not bounded here (or in [`Word`], [`Sponge`]
+/// and [`Hash`]); each chip bounds it in its own implementation.
+///
+/// [`Spec`]: crate::primitives::poseidon::Spec
+/// [`poseidon2::Spec`]: crate::primitives::poseidon2::Spec
+pub trait PoseidonInstructions<F: FieldExt, S, const T: usize, const RATE: usize>: Chip<F> {
     /// Variable representing the word over which the Poseidon permutation operates.
     type Word: Clone + fmt::Debug + From<AssignedCell<F, F>> + Into<AssignedCell<F, F>>;
 
@@ -48,7 +63,7 @@ pub trait PoseidonInstructions<F: FieldExt, S: Spec<F, T, RATE>, const T: usize,
 /// [`Hash`]: self::Hash
 pub trait PoseidonSpongeInstructions<
     F: FieldExt,
-    S: Spec<F, T, RATE>,
+    S,
     D: Domain<F, RATE>,
     const T: usize,
     const RATE: usize,
@@ -75,7 +90,7 @@ pub trait PoseidonSpongeInstructions<
 pub struct Word<
     F: FieldExt,
     PoseidonChip: PoseidonInstructions<F, S, T, RATE>,
-    S: Spec<F, T, RATE>,
+    S,
     const T: usize,
     const RATE: usize,
 > {
@@ -85,7 +100,7 @@ pub struct Word<
 impl<
         F: FieldExt,
         PoseidonChip: PoseidonInstructions<F, S, T, RATE>,
-        S: Spec<F, T, RATE>,
+        S,
         const T: usize,
         const RATE: usize,
     > Word<F, PoseidonChip, S, T, RATE>
@@ -104,7 +119,7 @@ impl<
 fn poseidon_sponge<
     F: FieldExt,
     PoseidonChip: PoseidonSpongeInstructions<F, S, D, T, RATE>,
-    S: Spec<F, T, RATE>,
+    S,
     D: Domain<F, RATE>,
     const T: usize,
     const RATE: usize,
@@ -126,7 +141,7 @@ fn poseidon_sponge<
 pub struct Sponge<
     F: FieldExt,
     PoseidonChip: PoseidonSpongeInstructions<F, S, D, T, RATE>,
-    S: Spec<F, T, RATE>,
+    S,
     M: SpongeMode,
     D: Domain<F, RATE>,
     const T: usize,
@@ -141,7 +156,7 @@ pub struct Sponge<
 impl<
         F: FieldExt,
         PoseidonChip: PoseidonSpongeInstructions<F, S, D, T, RATE>,
-        S: Spec<F, T, RATE>,
+        S,
         D: Domain<F, RATE>,
         const T: usize,
         const RATE: usize,
@@ -214,7 +229,7 @@ impl<
 impl<
         F: FieldExt,
         PoseidonChip: PoseidonSpongeInstructions<F, S, D, T, RATE>,
-        S: Spec<F, T, RATE>,
+        S,
         D: Domain<F, RATE>,
         const T: usize,
         const RATE: usize,
@@ -245,7 +260,7 @@ impl<
 pub struct Hash<
     F: FieldExt,
     PoseidonChip: PoseidonSpongeInstructions<F, S, D, T, RATE>,
-    S: Spec<F, T, RATE>,
+    S,
     D: Domain<F, RATE>,
     const T: usize,
     const RATE: usize,
@@ -256,7 +271,7 @@ pub struct Hash<
 impl<
         F: FieldExt,
         PoseidonChip: PoseidonSpongeInstructions<F, S, D, T, RATE>,
-        S: Spec<F, T, RATE>,
+        S,
         D: Domain<F, RATE>,
         const T: usize,
         const RATE: usize,
@@ -271,7 +286,7 @@ impl<
 impl<
         F: FieldExt,
         PoseidonChip: PoseidonSpongeInstructions<F, S, ConstantLength<L>, T, RATE>,
-        S: Spec<F, T, RATE>,
+        S,
         const T: usize,
         const RATE: usize,
         const L: usize,
@@ -296,3 +311,192 @@ impl<
             .squeeze(layouter.namespace(|| "squeeze"))
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::convert::TryInto;
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        circuit::{AssignedCell, Layouter, SimpleFloorPlanner},
+        pasta::Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    use super::{Hash, PoseidonInstructions, PoseidonSpongeInstructions};
+    use crate::primitives::poseidon::ConstantLength;
+
+    /// A permutation chip that can be tested with [`PermuteCircuit`] and [`HashCircuit`].
+    pub(crate) trait TestChip<S, const WIDTH: usize, const RATE: usize>:
+        PoseidonInstructions<Fp, S, WIDTH, RATE>
+    {
+        /// Configures the chip on fresh columns, with a fixed column enabled for
+        /// constants.
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config;
+
+        fn construct(config: Self::Config) -> Self;
+
+        fn state(config: &Self::Config) -> [Column<Advice>; WIDTH];
+
+        /// Applies the permutation outside the circuit.
+        fn reference_permute(state: &mut [Fp; WIDTH]);
+    }
+
+    pub(crate) struct PermuteCircuit<C, S, const WIDTH: usize, const RATE: usize>(
+        pub(crate) PhantomData<(C, S)>,
+    );
+
+    impl<C: TestChip<S, WIDTH, RATE>, S, const WIDTH: usize, const RATE: usize> Circuit<Fp>
+        for PermuteCircuit<C, S, WIDTH, RATE>
+    {
+        type Config = C::Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            PermuteCircuit(PhantomData)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> C::Config {
+            C::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: C::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let state = C::state(&config);
+
+            let initial_state = layouter.assign_region(
+                || "prepare initial state",
+                |mut region| {
+                    let state_word = |i: usize| {
+                        let value = Some(Fp::from(i as u64));
+                        let var = region.assign_advice(
+                            || format!("load state_{}", i),
+                            state[i],
+                            0,
+                            || value.ok_or(Error::Synthesis),
+                        )?;
+                        Ok(C::Word::from(var))
+                    };
+
+                    let state: Result<Vec<_>, Error> = (0..WIDTH).map(state_word).collect();
+                    Ok(state?.try_into().unwrap())
+                },
+            )?;
+
+            let chip = C::construct(config);
+            let final_state = chip.permute(&mut layouter, &initial_state)?;
+
+            // For the purpose of this test, compute the real final state inline.
+            let mut expected_final_state = (0..WIDTH)
+                .map(|idx| Fp::from(idx as u64))
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            C::reference_permute(&mut expected_final_state);
+
+            layouter.assign_region(
+                || "constrain final state",
+                |mut region| {
+                    let mut final_state_word = |i: usize| {
+                        let var = region.assign_advice(
+                            || format!("load final_state_{}", i),
+                            state[i],
+                            0,
+                            || Ok(expected_final_state[i]),
+                        )?;
+                        let word: AssignedCell<Fp, Fp> = final_state[i].clone().into();
+                        region.constrain_equal(word.cell(), var.cell())
+                    };
+
+                    for i in 0..(WIDTH) {
+                        final_state_word(i)?;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    pub(crate) struct HashCircuit<C, S, const WIDTH: usize, const RATE: usize, const L: usize> {
+        pub(crate) message: Option<[Fp; L]>,
+        // For the purpose of this test, witness the result.
+        // TODO: Move this into an instance column.
+        pub(crate) output: Option<Fp>,
+        pub(crate) _marker: PhantomData<(C, S)>,
+    }
+
+    impl<
+            C: TestChip<S, WIDTH, RATE>
+                + PoseidonSpongeInstructions<Fp, S, ConstantLength<L>, WIDTH, RATE>,
+            S,
+            const WIDTH: usize,
+            const RATE: usize,
+            const L: usize,
+        > Circuit<Fp> for HashCircuit<C, S, WIDTH, RATE, L>
+    {
+        type Config = C::Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                message: None,
+                output: None,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> C::Config {
+            C::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: C::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let state = C::state(&config);
+            let chip = C::construct(config);
+
+            let message = layouter.assign_region(
+                || "load message",
+                |mut region| {
+                    let message_word = |i: usize| {
+                        let value = self.message.map(|message_vals| message_vals[i]);
+                        region.assign_advice(
+                            || format!("load message_{}", i),
+                            state[i],
+                            0,
+                            || value.ok_or(Error::Synthesis),
+                        )
+                    };
+
+                    let message: Result<Vec<_>, Error> = (0..L).map(message_word).collect();
+                    Ok(message?.try_into().unwrap())
+                },
+            )?;
+
+            let hasher = Hash::<_, _, S, ConstantLength<L>, WIDTH, RATE>::init(
+                chip,
+                layouter.namespace(|| "init"),
+            )?;
+            let output = hasher.hash(layouter.namespace(|| "hash"), message)?;
+
+            layouter.assign_region(
+                || "constrain output",
+                |mut region| {
+                    let expected_var = region.assign_advice(
+                        || "load output",
+                        state[0],
+                        0,
+                        || self.output.ok_or(Error::Synthesis),
+                    )?;
+                    region.constrain_equal(output.cell(), expected_var.cell())
+                },
+            )
+        }
+    }
+}
@@ -0,0 +1,567 @@
+use std::convert::TryInto;
+use std::iter;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Any, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::{
+    sponge::{self, SpongeConfig},
+    PaddedWord, PoseidonInstructions, PoseidonSpongeInstructions, StateWord,
+};
+use crate::primitives::{
+    poseidon::{Absorbing, Domain, Mds, Squeezing, State},
+    poseidon2::{self, external_matrix, internal_linear_layer, Spec},
+};
+
+/// Configuration for a [`Poseidon2Chip`].
+#[derive(Clone, Debug)]
+pub struct Poseidon2Config<F: FieldExt, const WIDTH: usize, const RATE: usize> {
+    pub(crate) state: [Column<Advice>; WIDTH],
+    partial_sbox: Column<Advice>,
+    rc_a: [Column<Fixed>; WIDTH],
+    rc_b: Column<Fixed>,
+    s_initial: Selector,
+    s_full: Selector,
+    s_partial: Selector,
+    sponge: SpongeConfig<WIDTH, RATE>,
+
+    half_full_rounds: usize,
+    half_partial_rounds: usize,
+    alpha: [u64; 4],
+    round_constants: Vec<[F; WIDTH]>,
+    m_ext: Mds<F, WIDTH>,
+    m_int_diag: [F; WIDTH],
+}
+
+/// A Poseidon2 chip using an $x^5$ S-Box.
+///
+/// The chip is implemented using a single round per row for full rounds, and two rounds
+/// per row for partial rounds. The initial external linear layer takes one additional
+/// row.
+///
+/// This chip implements the same [`PoseidonInstructions`] and
+/// [`PoseidonSpongeInstructions`] as [`Pow5Chip`], so it can be used with the
+/// [`Sponge`] and [`Hash`] gadgets in its place.
+///
+/// [`Pow5Chip`]: super::Pow5Chip
+/// [`Sponge`]: super::Sponge
+/// [`Hash`]: super::Hash
+#[derive(Debug)]
+pub struct Poseidon2Chip<F: FieldExt, const WIDTH: usize, const RATE: usize> {
+    config: Poseidon2Config<F, WIDTH, RATE>,
+}
+
+impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Poseidon2Chip<F, WIDTH, RATE> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// # Side-effects
+    ///
+    /// All columns in `state`, and `rc_b`, will be equality-enabled.
+    pub fn configure<S: Spec<F, WIDTH, RATE>>(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        partial_sbox: Column<Advice>,
+        rc_a: [Column<Fixed>; WIDTH],
+        rc_b: Column<Fixed>,
+    ) -> Poseidon2Config<F, WIDTH, RATE> {
+        assert!(RATE < WIDTH);
+        // Generate constants for the Poseidon2 permutation.
+        // This gadget requires R_F and R_P to be even.
+        assert!(S::full_rounds() & 1 == 0);
+        assert!(S::partial_rounds() & 1 == 0);
+        let half_full_rounds = S::full_rounds() / 2;
+        let half_partial_rounds = S::partial_rounds() / 2;
+        let round_constants = S::constants();
+        let m_ext = external_matrix::<F, WIDTH>();
+        let m_int_diag = S::internal_diagonal();
+
+        // This allows state words to be initialized (by constraining them equal to fixed
+        // values), and used in a permutation from an arbitrary region. rc_b is empty in
+        // the initial and final full rounds, so we use it as "scratch space" for fixed
+        // values (enabling potential layouter optimisations).
+        for column in iter::empty()
+            .chain(state.iter().cloned().map(Column::<Any>::from))
+            .chain(Some(rc_b.into()))
+        {
+            meta.enable_equality(column);
+        }
+
+        let s_initial = meta.selector();
+        let s_full = meta.selector();
+        let s_partial = meta.selector();
+
+        let alpha = [5, 0, 0, 0];
+        let pow_5 = |v: Expression<F>| {
+            let v2 = v.clone() * v.clone();
+            v2.clone() * v2 * v
+        };
+
+        meta.create_gate("initial linear layer", |meta| {
+            let s_initial = meta.query_selector(s_initial);
+
+            (0..WIDTH)
+                .map(|next_idx| {
+                    let state_next = meta.query_advice(state[next_idx], Rotation::next());
+                    let expr = (0..WIDTH)
+                        .map(|idx| {
+                            meta.query_advice(state[idx], Rotation::cur()) * m_ext[next_idx][idx]
+                        })
+                        .reduce(|acc, term| acc + term)
+                        .expect("WIDTH > 0");
+                    s_initial.clone() * (expr - state_next)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("full round", |meta| {
+            let s_full = meta.query_selector(s_full);
+
+            (0..WIDTH)
+                .map(|next_idx| {
+                    let state_next = meta.query_advice(state[next_idx], Rotation::next());
+                    let expr = (0..WIDTH)
+                        .map(|idx| {
+                            let state_cur = meta.query_advice(state[idx], Rotation::cur());
+                            let rc_a = meta.query_fixed(rc_a[idx], Rotation::cur());
+                            pow_5(state_cur + rc_a) * m_ext[next_idx][idx]
+                        })
+                        .reduce(|acc, term| acc + term)
+                        .expect("WIDTH > 0");
+                    s_full.clone() * (expr - state_next)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("partial rounds", |meta| {
+            let cur_0 = meta.query_advice(state[0], Rotation::cur());
+            let mid_0 = meta.query_advice(partial_sbox, Rotation::cur());
+
+            let rc_a0 = meta.query_fixed(rc_a[0], Rotation::cur());
+            let rc_b = meta.query_fixed(rc_b, Rotation::cur());
+
+            let s_partial = meta.query_selector(s_partial);
+
+            // Applies M_I = J + diag(mu) to the given words.
+            let internal = |words: Vec<Expression<F>>| {
+                let sum = words
+                    .iter()
+                    .cloned()
+                    .reduce(|acc, word| acc + word)
+                    .expect("WIDTH > 0");
+                words
+                    .into_iter()
+                    .zip(m_int_diag.iter())
+                    .map(|(word, mu)| sum.clone() + word * *mu)
+                    .collect::<Vec<_>>()
+            };
+
+            // The state after round a.
+            let mid = internal(
+                iter::once(mid_0.clone())
+                    .chain((1..WIDTH).map(|idx| meta.query_advice(state[idx], Rotation::cur())))
+                    .collect(),
+            );
+
+            // The state after round b.
+            let next = internal(
+                iter::once(pow_5(mid[0].clone() + rc_b))
+                    .chain(mid[1..].iter().cloned())
+                    .collect(),
+            );
+
+            iter::empty()
+                // state[0] round a
+                .chain(Some(
+                    s_partial.clone() * (pow_5(cur_0 + rc_a0) - mid_0.clone()),
+                ))
+                .chain(next.into_iter().enumerate().map(|(idx, expr)| {
+                    let state_next = meta.query_advice(state[idx], Rotation::next());
+                    s_partial.clone() * (expr - state_next)
+                }))
+                .collect::<Vec<_>>()
+        });
+
+        let sponge = SpongeConfig::configure(meta, state, vec![rc_b]);
+
+        Poseidon2Config {
+            state,
+            partial_sbox,
+            rc_a,
+            rc_b,
+            s_initial,
+            s_full,
+            s_partial,
+            sponge,
+            half_full_rounds,
+            half_partial_rounds,
+            alpha,
+            round_constants,
+            m_ext,
+            m_int_diag,
+        }
+    }
+
+    /// Construct a [`Poseidon2Chip`].
+    pub fn construct(config: Poseidon2Config<F, WIDTH, RATE>) -> Self {
+        Poseidon2Chip { config }
+    }
+}
+
+impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Chip<F> for Poseidon2Chip<F, WIDTH, RATE> {
+    type Config = Poseidon2Config<F, WIDTH, RATE>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>
+    PoseidonInstructions<F, S, WIDTH, RATE> for Poseidon2Chip<F, WIDTH, RATE>
+{
+    type Word = StateWord<F>;
+
+    fn permute(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initial_state: &State<Self::Word, WIDTH>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "permute state",
+            |mut region| {
+                // Load the initial state into this region.
+                let state = Poseidon2State::load(&mut region, config, initial_state)?;
+
+                // Apply the initial external linear layer.
+                let state = state.initial_linear_layer(&mut region, config)?;
+
+                let state = (0..config.half_full_rounds).fold(Ok(state), |res, r| {
+                    res.and_then(|state| state.full_round(&mut region, config, r, r + 1))
+                })?;
+
+                let state = (0..config.half_partial_rounds).fold(Ok(state), |res, r| {
+                    res.and_then(|state| {
+                        state.partial_round(
+                            &mut region,
+                            config,
+                            config.half_full_rounds + 2 * r,
+                            config.half_full_rounds + r + 1,
+                        )
+                    })
+                })?;
+
+                let state = (0..config.half_full_rounds).fold(Ok(state), |res, r| {
+                    res.and_then(|state| {
+                        state.full_round(
+                            &mut region,
+                            config,
+                            config.half_full_rounds + 2 * config.half_partial_rounds + r,
+                            config.half_full_rounds + config.half_partial_rounds + r + 1,
+                        )
+                    })
+                })?;
+
+                Ok(state.0)
+            },
+        )
+    }
+}
+
+impl<
+        F: FieldExt,
+        S: Spec<F, WIDTH, RATE>,
+        D: Domain<F, RATE>,
+        const WIDTH: usize,
+        const RATE: usize,
+    > PoseidonSpongeInstructions<F, S, D, WIDTH, RATE> for Poseidon2Chip<F, WIDTH, RATE>
+{
+    fn initial_state(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        self.config.sponge.initial_state::<F, D>(layouter)
+    }
+
+    fn add_input(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initial_state: &State<Self::Word, WIDTH>,
+        input: &Absorbing<PaddedWord<F>, RATE>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        self.config
+            .sponge
+            .add_input::<F, D>(layouter, initial_state, input)
+    }
+
+    fn get_output(state: &State<Self::Word, WIDTH>) -> Squeezing<Self::Word, RATE> {
+        sponge::get_output(state)
+    }
+}
+
+#[derive(Debug)]
+struct Poseidon2State<F: FieldExt, const WIDTH: usize>([StateWord<F>; WIDTH]);
+
+impl<F: FieldExt, const WIDTH: usize> Poseidon2State<F, WIDTH> {
+    fn values(&self) -> Option<[F; WIDTH]> {
+        let values: Option<Vec<_>> = self.0.iter().map(|word| word.0.value().cloned()).collect();
+        values.map(|values| values.try_into().unwrap())
+    }
+
+    fn initial_linear_layer<const RATE: usize>(
+        self,
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+    ) -> Result<Self, Error> {
+        config.s_initial.enable(region, 0)?;
+
+        let next_state = self.values().map(|mut state| {
+            poseidon2::apply_matrix(&mut state, &config.m_ext);
+            state
+        });
+
+        Self::assign_next(region, config, 0, 1, next_state)
+    }
+
+    fn full_round<const RATE: usize>(
+        self,
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        round: usize,
+        offset: usize,
+    ) -> Result<Self, Error> {
+        config.s_full.enable(region, offset)?;
+
+        // Load the round constants.
+        for i in 0..WIDTH {
+            region.assign_fixed(
+                || format!("round_{} rc_{}", round, i),
+                config.rc_a[i],
+                offset,
+                || Ok(config.round_constants[round][i]),
+            )?;
+        }
+
+        let next_state = self.values().map(|mut state| {
+            for (word, rc) in state.iter_mut().zip(config.round_constants[round].iter()) {
+                *word = (*word + rc).pow(&config.alpha);
+            }
+            poseidon2::apply_matrix(&mut state, &config.m_ext);
+            state
+        });
+
+        Self::assign_next(region, config, round + 1, offset + 1, next_state)
+    }
+
+    fn partial_round<const RATE: usize>(
+        self,
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        round: usize,
+        offset: usize,
+    ) -> Result<Self, Error> {
+        config.s_partial.enable(region, offset)?;
+
+        // Load the round constants for both rounds.
+        region.assign_fixed(
+            || format!("round_{} rc_0", round),
+            config.rc_a[0],
+            offset,
+            || Ok(config.round_constants[round][0]),
+        )?;
+        region.assign_fixed(
+            || format!("round_{} rc_0", round + 1),
+            config.rc_b,
+            offset,
+            || Ok(config.round_constants[round + 1][0]),
+        )?;
+
+        let state = self.values();
+
+        let mid_0 =
+            state.map(|state| (state[0] + config.round_constants[round][0]).pow(&config.alpha));
+        region.assign_advice(
+            || format!("round_{} partial_sbox", round),
+            config.partial_sbox,
+            offset,
+            || mid_0.ok_or(Error::Synthesis),
+        )?;
+
+        let next_state = state.zip(mid_0).map(|(mut state, mid_0)| {
+            state[0] = mid_0;
+            internal_linear_layer(&mut state, &config.m_int_diag);
+
+            state[0] = (state[0] + config.round_constants[round + 1][0]).pow(&config.alpha);
+            internal_linear_layer(&mut state, &config.m_int_diag);
+            state
+        });
+
+        Self::assign_next(region, config, round + 2, offset + 1, next_state)
+    }
+
+    fn load<const RATE: usize>(
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        initial_state: &State<StateWord<F>, WIDTH>,
+    ) -> Result<Self, Error> {
+        let load_state_word = |i: usize| {
+            initial_state[i]
+                .0
+                .copy_advice(|| format!("load state_{}", i), region, config.state[i], 0)
+                .map(StateWord)
+        };
+
+        let state: Result<Vec<_>, _> = (0..WIDTH).map(load_state_word).collect();
+        state.map(|state| Poseidon2State(state.try_into().unwrap()))
+    }
+
+    fn assign_next<const RATE: usize>(
+        region: &mut Region<F>,
+        config: &Poseidon2Config<F, WIDTH, RATE>,
+        next_round: usize,
+        offset: usize,
+        next_state: Option<[F; WIDTH]>,
+    ) -> Result<Self, Error> {
+        let next_state_word = |i: usize| {
+            let value = next_state.map(|state| state[i]);
+            let var = region.assign_advice(
+                || format!("round_{} state_{}", next_round, i),
+                config.state[i],
+                offset,
+                || value.ok_or(Error::Synthesis),
+            )?;
+            Ok(StateWord(var))
+        };
+
+        let next_state: Result<Vec<_>, _> = (0..WIDTH).map(next_state_word).collect();
+        next_state.map(|next_state| Poseidon2State(next_state.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::ff::Field;
+    use halo2_proofs::{
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Advice, Column, ConstraintSystem},
+    };
+    use rand::rngs::OsRng;
+
+    use super::{Poseidon2Chip, Poseidon2Config};
+    use crate::{
+        poseidon::tests::{HashCircuit, PermuteCircuit, TestChip},
+        primitives::{
+            poseidon::ConstantLength,
+            poseidon2::{self, P128Pow5T3, Spec},
+        },
+    };
+    use std::convert::TryInto;
+    use std::marker::PhantomData;
+
+    impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> TestChip<S, WIDTH, RATE>
+        for Poseidon2Chip<Fp, WIDTH, RATE>
+    {
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Poseidon2Config<Fp, WIDTH, RATE> {
+            let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+
+            let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = meta.fixed_column();
+
+            meta.enable_constant(rc_b);
+
+            Poseidon2Chip::configure::<S>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b,
+            )
+        }
+
+        fn construct(config: Poseidon2Config<Fp, WIDTH, RATE>) -> Self {
+            Poseidon2Chip::construct(config)
+        }
+
+        fn state(config: &Poseidon2Config<Fp, WIDTH, RATE>) -> [Column<Advice>; WIDTH] {
+            config.state
+        }
+
+        fn reference_permute(state: &mut [Fp; WIDTH]) {
+            let round_constants = S::constants();
+            poseidon2::permute::<_, S, WIDTH, RATE>(state, &round_constants);
+        }
+    }
+
+    #[test]
+    fn poseidon2_permute() {
+        let k = 6;
+        let circuit = PermuteCircuit::<Poseidon2Chip<Fp, 3, 2>, P128Pow5T3, 3, 2>(PhantomData);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn poseidon2_hash() {
+        let rng = OsRng;
+
+        let message = [Fp::random(rng), Fp::random(rng)];
+        let output =
+            poseidon2::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash(message);
+
+        let k = 6;
+        let circuit = HashCircuit::<Poseidon2Chip<Fp, 3, 2>, P128Pow5T3, 3, 2, 2> {
+            message: Some(message),
+            output: Some(output),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn poseidon2_hash_longer_input() {
+        let rng = OsRng;
+
+        let message = [Fp::random(rng), Fp::random(rng), Fp::random(rng)];
+        let output =
+            poseidon2::Hash::<_, P128Pow5T3, ConstantLength<3>, 3, 2>::init().hash(message);
+
+        let k = 7;
+        let circuit = HashCircuit::<Poseidon2Chip<Fp, 3, 2>, P128Pow5T3, 3, 2, 3> {
+            message: Some(message),
+            output: Some(output),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn poseidon2_hash_wrong_output() {
+        let rng = OsRng;
+
+        let message = [Fp::random(rng), Fp::random(rng)];
+        let output =
+            poseidon2::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash(message);
+
+        let k = 6;
+        let circuit = HashCircuit::<Poseidon2Chip<Fp, 3, 2>, P128Pow5T3, 3, 2, 2> {
+            message: Some(message),
+            output: Some(output + Fp::one()),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err())
+    }
+}
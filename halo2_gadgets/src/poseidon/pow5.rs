@@ -8,7 +8,10 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
-use super::{PaddedWord, PoseidonInstructions, PoseidonSpongeInstructions};
+use super::{
+    sponge::{self, SpongeConfig},
+    PaddedWord, PoseidonInstructions, PoseidonSpongeInstructions,
+};
 use crate::primitives::poseidon::{Domain, Mds, Spec, State};
 use crate::{
     primitives::poseidon::{Absorbing, Squeezing},
@@ -24,7 +27,7 @@ pub struct Pow5Config<F: FieldExt, const WIDTH: usize, const RATE: usize> {
     rc_b: [Column<Fixed>; WIDTH],
     s_full: Selector,
     s_partial: Selector,
-    s_pad_and_add: Selector,
+    sponge: SpongeConfig<WIDTH, RATE>,
 
     half_full_rounds: usize,
     half_partial_rounds: usize,
@@ -60,7 +63,7 @@ impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Pow5Chip<F, WIDTH, RATE
         rc_a: [Column<Fixed>; WIDTH],
         rc_b: [Column<Fixed>; WIDTH],
     ) -> Pow5Config<F, WIDTH, RATE> {
-        assert_eq!(RATE, WIDTH - 1);
+        assert!(RATE < WIDTH);
         // Generate constants for the Poseidon permutation.
         // This gadget requires R_F and R_P to be even.
         assert!(S::full_rounds() & 1 == 0);
@@ -83,7 +86,6 @@ impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Pow5Chip<F, WIDTH, RATE
 
         let s_full = meta.selector();
         let s_partial = meta.selector();
-        let s_pad_and_add = meta.selector();
 
         let alpha = [5, 0, 0, 0];
         let pow_5 = |v: Expression<F>| {
@@ -160,30 +162,7 @@ impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Pow5Chip<F, WIDTH, RATE
                 .collect::<Vec<_>>()
         });
 
-        meta.create_gate("pad-and-add", |meta| {
-            let initial_state_rate = meta.query_advice(state[RATE], Rotation::prev());
-            let output_state_rate = meta.query_advice(state[RATE], Rotation::next());
-
-            let s_pad_and_add = meta.query_selector(s_pad_and_add);
-
-            let pad_and_add = |idx: usize| {
-                let initial_state = meta.query_advice(state[idx], Rotation::prev());
-                let input = meta.query_advice(state[idx], Rotation::cur());
-                let output_state = meta.query_advice(state[idx], Rotation::next());
-
-                // We pad the input by storing the required padding in fixed columns and
-                // then constraining the corresponding input columns to be equal to it.
-                s_pad_and_add.clone() * (initial_state + input - output_state)
-            };
-
-            (0..RATE)
-                .map(pad_and_add)
-                // The capacity element is never altered by the input.
-                .chain(Some(
-                    s_pad_and_add.clone() * (initial_state_rate - output_state_rate),
-                ))
-                .collect::<Vec<_>>()
-        });
+        let sponge = SpongeConfig::configure(meta, state, rc_b.to_vec());
 
         Pow5Config {
             state,
@@ -192,7 +171,7 @@ impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Pow5Chip<F, WIDTH, RATE
             rc_b,
             s_full,
             s_partial,
-            s_pad_and_add,
+            sponge,
             half_full_rounds,
             half_partial_rounds,
             alpha,
@@ -283,33 +262,7 @@ impl<
         &self,
         layouter: &mut impl Layouter<F>,
     ) -> Result<State<Self::Word, WIDTH>, Error> {
-        let config = self.config();
-        let state = layouter.assign_region(
-            || format!("initial state for domain {}", D::name()),
-            |mut region| {
-                let mut state = Vec::with_capacity(WIDTH);
-                let mut load_state_word = |i: usize, value: F| -> Result<_, Error> {
-                    let var = region.assign_advice_from_constant(
-                        || format!("state_{}", i),
-                        config.state[i],
-                        0,
-                        value,
-                    )?;
-                    state.push(StateWord(var));
-
-                    Ok(())
-                };
-
-                for i in 0..RATE {
-                    load_state_word(i, F::zero())?;
-                }
-                load_state_word(RATE, D::initial_capacity_element())?;
-
-                Ok(state)
-            },
-        )?;
-
-        Ok(state.try_into().unwrap())
+        self.config.sponge.initial_state::<F, D>(layouter)
     }
 
     fn add_input(
@@ -318,93 +271,19 @@ impl<
         initial_state: &State<Self::Word, WIDTH>,
         input: &Absorbing<PaddedWord<F>, RATE>,
     ) -> Result<State<Self::Word, WIDTH>, Error> {
-        let config = self.config();
-        layouter.assign_region(
-            || format!("add input for domain {}", D::name()),
-            |mut region| {
-                config.s_pad_and_add.enable(&mut region, 1)?;
-
-                // Load the initial state into this region.
-                let load_state_word = |i: usize| {
-                    initial_state[i]
-                        .0
-                        .copy_advice(
-                            || format!("load state_{}", i),
-                            &mut region,
-                            config.state[i],
-                            0,
-                        )
-                        .map(StateWord)
-                };
-                let initial_state: Result<Vec<_>, Error> =
-                    (0..WIDTH).map(load_state_word).collect();
-                let initial_state = initial_state?;
-
-                // Load the input into this region.
-                let load_input_word = |i: usize| {
-                    let constraint_var = match input.0[i].clone() {
-                        Some(PaddedWord::Message(word)) => word,
-                        Some(PaddedWord::Padding(padding_value)) => region.assign_fixed(
-                            || format!("load pad_{}", i),
-                            config.rc_b[i],
-                            1,
-                            || Ok(padding_value),
-                        )?,
-                        _ => panic!("Input is not padded"),
-                    };
-                    constraint_var
-                        .copy_advice(
-                            || format!("load input_{}", i),
-                            &mut region,
-                            config.state[i],
-                            1,
-                        )
-                        .map(StateWord)
-                };
-                let input: Result<Vec<_>, Error> = (0..RATE).map(load_input_word).collect();
-                let input = input?;
-
-                // Constrain the output.
-                let constrain_output_word = |i: usize| {
-                    let value = initial_state[i].0.value().and_then(|initial_word| {
-                        input
-                            .get(i)
-                            .map(|word| word.0.value().cloned())
-                            // The capacity element is never altered by the input.
-                            .unwrap_or_else(|| Some(F::zero()))
-                            .map(|input_word| *initial_word + input_word)
-                    });
-                    region
-                        .assign_advice(
-                            || format!("load output_{}", i),
-                            config.state[i],
-                            2,
-                            || value.ok_or(Error::Synthesis),
-                        )
-                        .map(StateWord)
-                };
-
-                let output: Result<Vec<_>, Error> = (0..WIDTH).map(constrain_output_word).collect();
-                output.map(|output| output.try_into().unwrap())
-            },
-        )
+        self.config
+            .sponge
+            .add_input::<F, D>(layouter, initial_state, input)
     }
 
     fn get_output(state: &State<Self::Word, WIDTH>) -> Squeezing<Self::Word, RATE> {
-        Squeezing(
-            state[..RATE]
-                .iter()
-                .map(|word| Some(word.clone()))
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
-        )
+        sponge::get_output(state)
     }
 }
 
 /// A word in the Poseidon state.
 #[derive(Clone, Debug)]
-pub struct StateWord<F: FieldExt>(AssignedCell<F, F>);
+pub struct StateWord<F: FieldExt>(pub(super) AssignedCell<F, F>);
 
 impl<F: FieldExt> From<StateWord<F>> for AssignedCell<F, F> {
     fn from(state_word: StateWord<F>) -> AssignedCell<F, F> {
@@ -597,36 +476,26 @@ impl<F: FieldExt, const WIDTH: usize> Pow5State<F, WIDTH> {
 mod tests {
     use group::ff::{Field, PrimeField};
     use halo2_proofs::{
-        circuit::{Layouter, SimpleFloorPlanner},
         dev::MockProver,
         pasta::Fp,
-        plonk::{Circuit, ConstraintSystem, Error},
+        plonk::{Advice, Column, ConstraintSystem},
     };
     use pasta_curves::pallas;
     use rand::rngs::OsRng;
 
-    use super::{PoseidonInstructions, Pow5Chip, Pow5Config, StateWord};
+    use super::{Pow5Chip, Pow5Config};
     use crate::{
-        poseidon::Hash,
-        primitives::poseidon::{self, ConstantLength, P128Pow5T3 as OrchardNullifier, Spec},
+        poseidon::tests::{HashCircuit, PermuteCircuit, TestChip},
+        primitives::poseidon::{
+            self, ConstantLength, P128Pow5, P128Pow5T3 as OrchardNullifier, P128Pow5T5, Spec,
+        },
     };
     use std::convert::TryInto;
     use std::marker::PhantomData;
 
-    struct PermuteCircuit<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>(
-        PhantomData<S>,
-    );
-
-    impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> Circuit<Fp>
-        for PermuteCircuit<S, WIDTH, RATE>
+    impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> TestChip<S, WIDTH, RATE>
+        for Pow5Chip<Fp, WIDTH, RATE>
     {
-        type Config = Pow5Config<Fp, WIDTH, RATE>;
-        type FloorPlanner = SimpleFloorPlanner;
-
-        fn without_witnesses(&self) -> Self {
-            PermuteCircuit::<S, WIDTH, RATE>(PhantomData)
-        }
-
         fn configure(meta: &mut ConstraintSystem<Fp>) -> Pow5Config<Fp, WIDTH, RATE> {
             let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
             let partial_sbox = meta.advice_column();
@@ -634,6 +503,8 @@ mod tests {
             let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
             let rc_b = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
 
+            meta.enable_constant(rc_b[0]);
+
             Pow5Chip::configure::<S>(
                 meta,
                 state.try_into().unwrap(),
@@ -643,173 +514,28 @@ mod tests {
             )
         }
 
-        fn synthesize(
-            &self,
-            config: Pow5Config<Fp, WIDTH, RATE>,
-            mut layouter: impl Layouter<Fp>,
-        ) -> Result<(), Error> {
-            let initial_state = layouter.assign_region(
-                || "prepare initial state",
-                |mut region| {
-                    let state_word = |i: usize| {
-                        let value = Some(Fp::from(i as u64));
-                        let var = region.assign_advice(
-                            || format!("load state_{}", i),
-                            config.state[i],
-                            0,
-                            || value.ok_or(Error::Synthesis),
-                        )?;
-                        Ok(StateWord(var))
-                    };
-
-                    let state: Result<Vec<_>, Error> = (0..WIDTH).map(state_word).collect();
-                    Ok(state?.try_into().unwrap())
-                },
-            )?;
+        fn construct(config: Pow5Config<Fp, WIDTH, RATE>) -> Self {
+            Pow5Chip::construct(config)
+        }
 
-            let chip = Pow5Chip::construct(config.clone());
-            let final_state = <Pow5Chip<_, WIDTH, RATE> as PoseidonInstructions<
-                Fp,
-                S,
-                WIDTH,
-                RATE,
-            >>::permute(&chip, &mut layouter, &initial_state)?;
-
-            // For the purpose of this test, compute the real final state inline.
-            let mut expected_final_state = (0..WIDTH)
-                .map(|idx| Fp::from(idx as u64))
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap();
+        fn state(config: &Pow5Config<Fp, WIDTH, RATE>) -> [Column<Advice>; WIDTH] {
+            config.state
+        }
+
+        fn reference_permute(state: &mut [Fp; WIDTH]) {
             let (round_constants, mds, _) = S::constants();
-            poseidon::permute::<_, S, WIDTH, RATE>(
-                &mut expected_final_state,
-                &mds,
-                &round_constants,
-            );
-
-            layouter.assign_region(
-                || "constrain final state",
-                |mut region| {
-                    let mut final_state_word = |i: usize| {
-                        let var = region.assign_advice(
-                            || format!("load final_state_{}", i),
-                            config.state[i],
-                            0,
-                            || Ok(expected_final_state[i]),
-                        )?;
-                        region.constrain_equal(final_state[i].0.cell(), var.cell())
-                    };
-
-                    for i in 0..(WIDTH) {
-                        final_state_word(i)?;
-                    }
-
-                    Ok(())
-                },
-            )
+            poseidon::permute::<_, S, WIDTH, RATE>(state, &mds, &round_constants);
         }
     }
 
     #[test]
     fn poseidon_permute() {
         let k = 6;
-        let circuit = PermuteCircuit::<OrchardNullifier, 3, 2>(PhantomData);
+        let circuit = PermuteCircuit::<Pow5Chip<Fp, 3, 2>, OrchardNullifier, 3, 2>(PhantomData);
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         assert_eq!(prover.verify(), Ok(()))
     }
 
-    struct HashCircuit<
-        S: Spec<Fp, WIDTH, RATE>,
-        const WIDTH: usize,
-        const RATE: usize,
-        const L: usize,
-    > {
-        message: Option<[Fp; L]>,
-        // For the purpose of this test, witness the result.
-        // TODO: Move this into an instance column.
-        output: Option<Fp>,
-        _spec: PhantomData<S>,
-    }
-
-    impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize, const L: usize>
-        Circuit<Fp> for HashCircuit<S, WIDTH, RATE, L>
-    {
-        type Config = Pow5Config<Fp, WIDTH, RATE>;
-        type FloorPlanner = SimpleFloorPlanner;
-
-        fn without_witnesses(&self) -> Self {
-            Self {
-                message: None,
-                output: None,
-                _spec: PhantomData,
-            }
-        }
-
-        fn configure(meta: &mut ConstraintSystem<Fp>) -> Pow5Config<Fp, WIDTH, RATE> {
-            let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
-            let partial_sbox = meta.advice_column();
-
-            let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
-            let rc_b = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
-
-            meta.enable_constant(rc_b[0]);
-
-            Pow5Chip::configure::<S>(
-                meta,
-                state.try_into().unwrap(),
-                partial_sbox,
-                rc_a.try_into().unwrap(),
-                rc_b.try_into().unwrap(),
-            )
-        }
-
-        fn synthesize(
-            &self,
-            config: Pow5Config<Fp, WIDTH, RATE>,
-            mut layouter: impl Layouter<Fp>,
-        ) -> Result<(), Error> {
-            let chip = Pow5Chip::construct(config.clone());
-
-            let message = layouter.assign_region(
-                || "load message",
-                |mut region| {
-                    let message_word = |i: usize| {
-                        let value = self.message.map(|message_vals| message_vals[i]);
-                        region.assign_advice(
-                            || format!("load message_{}", i),
-                            config.state[i],
-                            0,
-                            || value.ok_or(Error::Synthesis),
-                        )
-                    };
-
-                    let message: Result<Vec<_>, Error> = (0..L).map(message_word).collect();
-                    Ok(message?.try_into().unwrap())
-                },
-            )?;
-
-            let hasher = Hash::<_, _, S, ConstantLength<L>, WIDTH, RATE>::init(
-                chip,
-                layouter.namespace(|| "init"),
-            )?;
-            let output = hasher.hash(layouter.namespace(|| "hash"), message)?;
-
-            layouter.assign_region(
-                || "constrain output",
-                |mut region| {
-                    let expected_var = region.assign_advice(
-                        || "load output",
-                        config.state[0],
-                        0,
-                        || self.output.ok_or(Error::Synthesis),
-                    )?;
-                    region.constrain_equal(output.cell(), expected_var.cell())
-                },
-            )
-        }
-    }
-
     #[test]
     fn poseidon_hash() {
         let rng = OsRng;
@@ -819,10 +545,10 @@ mod tests {
             poseidon::Hash::<_, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash(message);
 
         let k = 6;
-        let circuit = HashCircuit::<OrchardNullifier, 3, 2, 2> {
+        let circuit = HashCircuit::<Pow5Chip<Fp, 3, 2>, OrchardNullifier, 3, 2, 2> {
             message: Some(message),
             output: Some(output),
-            _spec: PhantomData,
+            _marker: PhantomData,
         };
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         assert_eq!(prover.verify(), Ok(()))
@@ -837,10 +563,10 @@ mod tests {
             poseidon::Hash::<_, OrchardNullifier, ConstantLength<3>, 3, 2>::init().hash(message);
 
         let k = 7;
-        let circuit = HashCircuit::<OrchardNullifier, 3, 2, 3> {
+        let circuit = HashCircuit::<Pow5Chip<Fp, 3, 2>, OrchardNullifier, 3, 2, 3> {
             message: Some(message),
             output: Some(output),
-            _spec: PhantomData,
+            _marker: PhantomData,
         };
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         assert_eq!(prover.verify(), Ok(()))
@@ -857,16 +583,64 @@ mod tests {
                 .hash(message);
 
             let k = 6;
-            let circuit = HashCircuit::<OrchardNullifier, 3, 2, 2> {
+            let circuit = HashCircuit::<Pow5Chip<Fp, 3, 2>, OrchardNullifier, 3, 2, 2> {
                 message: Some(message),
                 output: Some(output),
-                _spec: PhantomData,
+                _marker: PhantomData,
             };
             let prover = MockProver::run(k, &circuit, vec![]).unwrap();
             assert_eq!(prover.verify(), Ok(()));
         }
     }
 
+    #[test]
+    fn poseidon_permute_width_5() {
+        let k = 7;
+        let circuit = PermuteCircuit::<Pow5Chip<Fp, 5, 4>, P128Pow5T5, 5, 4>(PhantomData);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn poseidon_hash_width_5() {
+        let rng = OsRng;
+
+        let message = [
+            Fp::random(rng),
+            Fp::random(rng),
+            Fp::random(rng),
+            Fp::random(rng),
+        ];
+        let output = poseidon::Hash::<_, P128Pow5T5, ConstantLength<4>, 5, 4>::init().hash(message);
+
+        let k = 7;
+        let circuit = HashCircuit::<Pow5Chip<Fp, 5, 4>, P128Pow5T5, 5, 4, 4> {
+            message: Some(message),
+            output: Some(output),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn poseidon_hash_width_5_rate_2() {
+        let rng = OsRng;
+
+        let message = [Fp::random(rng), Fp::random(rng), Fp::random(rng)];
+        let output =
+            poseidon::Hash::<_, P128Pow5<5, 2>, ConstantLength<3>, 5, 2>::init().hash(message);
+
+        let k = 7;
+        let circuit = HashCircuit::<Pow5Chip<Fp, 5, 2>, P128Pow5<5, 2>, 5, 2, 3> {
+            message: Some(message),
+            output: Some(output),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn print_poseidon_chip() {
@@ -878,10 +652,10 @@ mod tests {
             .titled("Poseidon Chip Layout", ("sans-serif", 60))
             .unwrap();
 
-        let circuit = HashCircuit::<OrchardNullifier, 3, 2, 2> {
+        let circuit = HashCircuit::<Pow5Chip<Fp, 3, 2>, OrchardNullifier, 3, 2, 2> {
             message: None,
             output: None,
-            _spec: PhantomData,
+            _marker: PhantomData,
         };
         halo2_proofs::dev::CircuitLayout::default()
             .render(6, &circuit, &root)
@@ -0,0 +1,438 @@
+use std::convert::TryInto;
+use std::iter;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Any, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::{
+    sponge::{self, SpongeConfig},
+    PaddedWord, PoseidonInstructions, PoseidonSpongeInstructions, StateWord,
+};
+use crate::primitives::{
+    poseidon::{Absorbing, Domain, Mds, Squeezing, State},
+    rescue::{params, Spec},
+};
+
+/// Configuration for a [`RescueChip`].
+#[derive(Clone, Debug)]
+pub struct RescueConfig<F: FieldExt, const WIDTH: usize, const RATE: usize> {
+    pub(crate) state: [Column<Advice>; WIDTH],
+    inv_sbox: [Column<Advice>; WIDTH],
+    rc_a: [Column<Fixed>; WIDTH],
+    rc_b: [Column<Fixed>; WIDTH],
+    s_round: Selector,
+    sponge: SpongeConfig<WIDTH, RATE>,
+
+    rounds: usize,
+    alpha: [u64; 1],
+    alpha_inv: Vec<u64>,
+    round_constants: Vec<[F; WIDTH]>,
+    mds: Mds<F, WIDTH>,
+}
+
+/// A Rescue-Prime chip.
+///
+/// The chip is implemented using a single round per row. The output of the inverse
+/// S-box is witnessed in the `inv_sbox` columns, and constrained by raising it to the
+/// power of $\alpha$.
+///
+/// This chip implements the same [`PoseidonInstructions`] and
+/// [`PoseidonSpongeInstructions`] as [`Pow5Chip`], so it can be used with the
+/// [`Sponge`] and [`Hash`] gadgets in its place.
+///
+/// [`Pow5Chip`]: super::Pow5Chip
+/// [`Sponge`]: super::Sponge
+/// [`Hash`]: super::Hash
+#[derive(Debug)]
+pub struct RescueChip<F: FieldExt, const WIDTH: usize, const RATE: usize> {
+    config: RescueConfig<F, WIDTH, RATE>,
+}
+
+impl<F: FieldExt, const WIDTH: usize, const RATE: usize> RescueChip<F, WIDTH, RATE> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// # Side-effects
+    ///
+    /// All columns in `state` and `rc_b` will be equality-enabled.
+    pub fn configure<S: Spec<F, WIDTH, RATE>>(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        inv_sbox: [Column<Advice>; WIDTH],
+        rc_a: [Column<Fixed>; WIDTH],
+        rc_b: [Column<Fixed>; WIDTH],
+    ) -> RescueConfig<F, WIDTH, RATE> {
+        assert!(RATE < WIDTH);
+        let rounds = S::rounds();
+        let alpha = S::alpha();
+        let alpha_inv = params::alpha_inv::<F>(alpha);
+        let (round_constants, mds) = S::constants();
+
+        // This allows state words to be initialized (by constraining them equal to fixed
+        // values), and used in a permutation from an arbitrary region. rc_b is only
+        // used within permutation regions, so we use it as "scratch space" for fixed
+        // values elsewhere (enabling potential layouter optimisations).
+        for column in iter::empty()
+            .chain(state.iter().cloned().map(Column::<Any>::from))
+            .chain(rc_b.iter().cloned().map(Column::<Any>::from))
+        {
+            meta.enable_equality(column);
+        }
+
+        let s_round = meta.selector();
+
+        let pow_alpha = |v: Expression<F>| (1..alpha).fold(v.clone(), |acc, _| acc * v.clone());
+
+        meta.create_gate("rescue round", |meta| {
+            let s_round = meta.query_selector(s_round);
+
+            let cur: Vec<_> = (0..WIDTH)
+                .map(|idx| meta.query_advice(state[idx], Rotation::cur()))
+                .collect();
+            let mid: Vec<_> = (0..WIDTH)
+                .map(|idx| meta.query_advice(inv_sbox[idx], Rotation::cur()))
+                .collect();
+
+            // The first step: mid^alpha = MDS * cur^alpha + rc_a
+            let forward = (0..WIDTH).map(|idx| {
+                let rc_a = meta.query_fixed(rc_a[idx], Rotation::cur());
+                let expr = cur
+                    .iter()
+                    .zip(mds[idx].iter())
+                    .map(|(cur, m)| pow_alpha(cur.clone()) * *m)
+                    .fold(rc_a, |acc, term| acc + term);
+                s_round.clone() * (pow_alpha(mid[idx].clone()) - expr)
+            });
+            let forward: Vec<_> = forward.collect();
+
+            // The second step: next = MDS * mid + rc_b
+            let backward = (0..WIDTH).map(|idx| {
+                let rc_b = meta.query_fixed(rc_b[idx], Rotation::cur());
+                let next = meta.query_advice(state[idx], Rotation::next());
+                let expr = mid
+                    .iter()
+                    .zip(mds[idx].iter())
+                    .map(|(mid, m)| mid.clone() * *m)
+                    .fold(rc_b, |acc, term| acc + term);
+                s_round.clone() * (expr - next)
+            });
+            let backward: Vec<_> = backward.collect();
+
+            forward.into_iter().chain(backward).collect::<Vec<_>>()
+        });
+
+        let sponge = SpongeConfig::configure(meta, state, rc_b.to_vec());
+
+        RescueConfig {
+            state,
+            inv_sbox,
+            rc_a,
+            rc_b,
+            s_round,
+            sponge,
+            rounds,
+            alpha: [alpha],
+            alpha_inv,
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Construct a [`RescueChip`].
+    pub fn construct(config: RescueConfig<F, WIDTH, RATE>) -> Self {
+        RescueChip { config }
+    }
+}
+
+impl<F: FieldExt, const WIDTH: usize, const RATE: usize> Chip<F> for RescueChip<F, WIDTH, RATE> {
+    type Config = RescueConfig<F, WIDTH, RATE>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>
+    PoseidonInstructions<F, S, WIDTH, RATE> for RescueChip<F, WIDTH, RATE>
+{
+    type Word = StateWord<F>;
+
+    fn permute(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initial_state: &State<Self::Word, WIDTH>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "permute state",
+            |mut region| {
+                // Load the initial state into this region.
+                let state = RescueState::load(&mut region, config, initial_state)?;
+
+                let state = (0..config.rounds).fold(Ok(state), |res, r| {
+                    res.and_then(|state| state.round(&mut region, config, r))
+                })?;
+
+                Ok(state.0)
+            },
+        )
+    }
+}
+
+impl<
+        F: FieldExt,
+        S: Spec<F, WIDTH, RATE>,
+        D: Domain<F, RATE>,
+        const WIDTH: usize,
+        const RATE: usize,
+    > PoseidonSpongeInstructions<F, S, D, WIDTH, RATE> for RescueChip<F, WIDTH, RATE>
+{
+    fn initial_state(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        self.config.sponge.initial_state::<F, D>(layouter)
+    }
+
+    fn add_input(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initial_state: &State<Self::Word, WIDTH>,
+        input: &Absorbing<PaddedWord<F>, RATE>,
+    ) -> Result<State<Self::Word, WIDTH>, Error> {
+        self.config
+            .sponge
+            .add_input::<F, D>(layouter, initial_state, input)
+    }
+
+    fn get_output(state: &State<Self::Word, WIDTH>) -> Squeezing<Self::Word, RATE> {
+        sponge::get_output(state)
+    }
+}
+
+#[derive(Debug)]
+struct RescueState<F: FieldExt, const WIDTH: usize>([StateWord<F>; WIDTH]);
+
+impl<F: FieldExt, const WIDTH: usize> RescueState<F, WIDTH> {
+    fn round<const RATE: usize>(
+        self,
+        region: &mut Region<F>,
+        config: &RescueConfig<F, WIDTH, RATE>,
+        round: usize,
+    ) -> Result<Self, Error> {
+        let offset = round;
+        let rc_a = config.round_constants[2 * round];
+        let rc_b = config.round_constants[2 * round + 1];
+
+        // Enable the required gate.
+        config.s_round.enable(region, offset)?;
+
+        // Load the round constants.
+        for i in 0..WIDTH {
+            region.assign_fixed(
+                || format!("round_{} rc_a_{}", round, i),
+                config.rc_a[i],
+                offset,
+                || Ok(rc_a[i]),
+            )?;
+            region.assign_fixed(
+                || format!("round_{} rc_b_{}", round, i),
+                config.rc_b[i],
+                offset,
+                || Ok(rc_b[i]),
+            )?;
+        }
+
+        let apply_mds = |words: Vec<F>, rcs: &[F; WIDTH]| -> Vec<F> {
+            config
+                .mds
+                .iter()
+                .zip(rcs.iter())
+                .map(|(m_i, rc)| {
+                    m_i.iter()
+                        .zip(words.iter())
+                        .fold(*rc, |acc, (m_ij, w_j)| acc + *m_ij * w_j)
+                })
+                .collect()
+        };
+
+        let cur: Option<Vec<_>> = self.0.iter().map(|word| word.0.value().cloned()).collect();
+
+        // The first step, followed by the inverse S-box of the second step.
+        let mid = cur.map(|cur| {
+            let cur = cur
+                .into_iter()
+                .map(|word| word.pow_vartime(&config.alpha))
+                .collect();
+            apply_mds(cur, &rc_a)
+                .into_iter()
+                .map(|word| word.pow_vartime(&config.alpha_inv))
+                .collect::<Vec<_>>()
+        });
+        for i in 0..WIDTH {
+            region.assign_advice(
+                || format!("round_{} inv_sbox_{}", round, i),
+                config.inv_sbox[i],
+                offset,
+                || mid.as_ref().map(|mid| mid[i]).ok_or(Error::Synthesis),
+            )?;
+        }
+
+        let next = mid.map(|mid| apply_mds(mid, &rc_b));
+
+        let next_state_word = |i: usize| {
+            let value = next.as_ref().map(|next| next[i]);
+            let var = region.assign_advice(
+                || format!("round_{} state_{}", round + 1, i),
+                config.state[i],
+                offset + 1,
+                || value.ok_or(Error::Synthesis),
+            )?;
+            Ok(StateWord(var))
+        };
+
+        let next_state: Result<Vec<_>, _> = (0..WIDTH).map(next_state_word).collect();
+        next_state.map(|next_state| RescueState(next_state.try_into().unwrap()))
+    }
+
+    fn load<const RATE: usize>(
+        region: &mut Region<F>,
+        config: &RescueConfig<F, WIDTH, RATE>,
+        initial_state: &State<StateWord<F>, WIDTH>,
+    ) -> Result<Self, Error> {
+        let load_state_word = |i: usize| {
+            initial_state[i]
+                .0
+                .copy_advice(|| format!("load state_{}", i), region, config.state[i], 0)
+                .map(StateWord)
+        };
+
+        let state: Result<Vec<_>, _> = (0..WIDTH).map(load_state_word).collect();
+        state.map(|state| RescueState(state.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::ff::Field;
+    use halo2_proofs::{
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Advice, Column, ConstraintSystem},
+    };
+    use rand::rngs::OsRng;
+
+    use super::{RescueChip, RescueConfig};
+    use crate::{
+        poseidon::tests::{HashCircuit, PermuteCircuit, TestChip},
+        primitives::{
+            poseidon::ConstantLength,
+            rescue::{self, R128Pow5T3, Spec},
+        },
+    };
+    use std::convert::TryInto;
+    use std::marker::PhantomData;
+
+    impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> TestChip<S, WIDTH, RATE>
+        for RescueChip<Fp, WIDTH, RATE>
+    {
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> RescueConfig<Fp, WIDTH, RATE> {
+            let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let inv_sbox = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+
+            let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+            meta.enable_constant(rc_b[0]);
+
+            RescueChip::configure::<S>(
+                meta,
+                state.try_into().unwrap(),
+                inv_sbox.try_into().unwrap(),
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            )
+        }
+
+        fn construct(config: RescueConfig<Fp, WIDTH, RATE>) -> Self {
+            RescueChip::construct(config)
+        }
+
+        fn state(config: &RescueConfig<Fp, WIDTH, RATE>) -> [Column<Advice>; WIDTH] {
+            config.state
+        }
+
+        fn reference_permute(state: &mut [Fp; WIDTH]) {
+            let (round_constants, mds) = S::constants();
+            rescue::permute::<_, S, WIDTH, RATE>(state, &mds, &round_constants);
+        }
+    }
+
+    #[test]
+    fn rescue_permute() {
+        let k = 6;
+        let circuit = PermuteCircuit::<RescueChip<Fp, 3, 2>, R128Pow5T3, 3, 2>(PhantomData);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn rescue_hash() {
+        let rng = OsRng;
+
+        let message = [Fp::random(rng), Fp::random(rng)];
+        let output = rescue::Hash::<_, R128Pow5T3, ConstantLength<2>, 3, 2>::init().hash(message);
+
+        let k = 6;
+        let circuit = HashCircuit::<RescueChip<Fp, 3, 2>, R128Pow5T3, 3, 2, 2> {
+            message: Some(message),
+            output: Some(output),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn rescue_hash_longer_input() {
+        let rng = OsRng;
+
+        let message = [Fp::random(rng), Fp::random(rng), Fp::random(rng)];
+        let output = rescue::Hash::<_, R128Pow5T3, ConstantLength<3>, 3, 2>::init().hash(message);
+
+        let k = 6;
+        let circuit = HashCircuit::<RescueChip<Fp, 3, 2>, R128Pow5T3, 3, 2, 3> {
+            message: Some(message),
+            output: Some(output),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn rescue_hash_wrong_output() {
+        let rng = OsRng;
+
+        let message = [Fp::random(rng), Fp::random(rng)];
+        let output = rescue::Hash::<_, R128Pow5T3, ConstantLength<2>, 3, 2>::init().hash(message);
+
+        let k = 6;
+        let circuit = HashCircuit::<RescueChip<Fp, 3, 2>, R128Pow5T3, 3, 2, 2> {
+            message: Some(message),
+            output: Some(output + Fp::one()),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err())
+    }
+}
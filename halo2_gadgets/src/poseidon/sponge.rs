@@ -0,0 +1,204 @@
+//! Sponge regions shared by the permutation chips in this module.
+
+use std::convert::TryInto;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector, VirtualCells},
+    poly::Rotation,
+};
+
+use super::{PaddedWord, StateWord};
+use crate::primitives::poseidon::{Absorbing, Domain, Squeezing, State};
+
+/// Configuration for the regions that initialize a sponge state and add input to it.
+///
+/// These regions do not depend on the permutation, so every chip that implements
+/// [`PoseidonSpongeInstructions`] in this module delegates to them.
+///
+/// [`PoseidonSpongeInstructions`]: super::PoseidonSpongeInstructions
+#[derive(Clone, Debug)]
+pub(super) struct SpongeConfig<const WIDTH: usize, const RATE: usize> {
+    state: [Column<Advice>; WIDTH],
+    pad: Vec<Column<Fixed>>,
+    s_pad_and_add: Selector,
+}
+
+impl<const WIDTH: usize, const RATE: usize> SpongeConfig<WIDTH, RATE> {
+    /// Configures the pad-and-add gate over the given state columns.
+    ///
+    /// Padding words are loaded into the `pad` columns, which must be equality-enabled.
+    /// Padding word `i` is assigned to `pad[i % pad.len()]` on row `1 + i / pad.len()`
+    /// of the region, so a single column suffices.
+    pub(super) fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        state: [Column<Advice>; WIDTH],
+        pad: Vec<Column<Fixed>>,
+    ) -> Self {
+        assert!(RATE < WIDTH);
+        assert!(!pad.is_empty());
+
+        let s_pad_and_add = meta.selector();
+
+        meta.create_gate("pad-and-add", |meta| {
+            let s_pad_and_add = meta.query_selector(s_pad_and_add);
+
+            let pad_and_add = |idx: usize, meta: &mut VirtualCells<F>| {
+                let initial_state = meta.query_advice(state[idx], Rotation::prev());
+                let input = meta.query_advice(state[idx], Rotation::cur());
+                let output_state = meta.query_advice(state[idx], Rotation::next());
+
+                // We pad the input by storing the required padding in fixed columns and
+                // then constraining the corresponding input columns to be equal to it.
+                s_pad_and_add.clone() * (initial_state + input - output_state)
+            };
+
+            let keep_capacity = |idx: usize, meta: &mut VirtualCells<F>| {
+                let initial_state = meta.query_advice(state[idx], Rotation::prev());
+                let output_state = meta.query_advice(state[idx], Rotation::next());
+
+                s_pad_and_add.clone() * (initial_state - output_state)
+            };
+
+            let mut constraints: Vec<_> = (0..RATE).map(|idx| pad_and_add(idx, meta)).collect();
+            // The capacity elements are never altered by the input.
+            constraints.extend((RATE..WIDTH).map(|idx| keep_capacity(idx, meta)));
+            constraints
+        });
+
+        SpongeConfig {
+            state,
+            pad,
+            s_pad_and_add,
+        }
+    }
+
+    /// Returns the initial empty state for the given domain.
+    pub(super) fn initial_state<F: FieldExt, D: Domain<F, RATE>>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<State<StateWord<F>, WIDTH>, Error> {
+        let state = layouter.assign_region(
+            || format!("initial state for domain {}", D::name()),
+            |mut region| {
+                let mut state = Vec::with_capacity(WIDTH);
+                let mut load_state_word = |i: usize, value: F| -> Result<_, Error> {
+                    let var = region.assign_advice_from_constant(
+                        || format!("state_{}", i),
+                        self.state[i],
+                        0,
+                        value,
+                    )?;
+                    state.push(StateWord(var));
+
+                    Ok(())
+                };
+
+                for i in 0..RATE {
+                    load_state_word(i, F::zero())?;
+                }
+                load_state_word(RATE, D::initial_capacity_element())?;
+                for i in (RATE + 1)..WIDTH {
+                    load_state_word(i, F::zero())?;
+                }
+
+                Ok(state)
+            },
+        )?;
+
+        Ok(state.try_into().unwrap())
+    }
+
+    /// Adds the given input to the state.
+    pub(super) fn add_input<F: FieldExt, D: Domain<F, RATE>>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        initial_state: &State<StateWord<F>, WIDTH>,
+        input: &Absorbing<PaddedWord<F>, RATE>,
+    ) -> Result<State<StateWord<F>, WIDTH>, Error> {
+        layouter.assign_region(
+            || format!("add input for domain {}", D::name()),
+            |mut region| {
+                self.s_pad_and_add.enable(&mut region, 1)?;
+
+                // Load the initial state into this region.
+                let load_state_word = |i: usize| {
+                    initial_state[i]
+                        .0
+                        .copy_advice(
+                            || format!("load state_{}", i),
+                            &mut region,
+                            self.state[i],
+                            0,
+                        )
+                        .map(StateWord)
+                };
+                let initial_state: Result<Vec<_>, Error> =
+                    (0..WIDTH).map(load_state_word).collect();
+                let initial_state = initial_state?;
+
+                // Load the input into this region.
+                let load_input_word = |i: usize| {
+                    let constraint_var = match input.0[i].clone() {
+                        Some(PaddedWord::Message(word)) => word,
+                        Some(PaddedWord::Padding(padding_value)) => region.assign_fixed(
+                            || format!("load pad_{}", i),
+                            self.pad[i % self.pad.len()],
+                            1 + i / self.pad.len(),
+                            || Ok(padding_value),
+                        )?,
+                        _ => panic!("Input is not padded"),
+                    };
+                    constraint_var
+                        .copy_advice(
+                            || format!("load input_{}", i),
+                            &mut region,
+                            self.state[i],
+                            1,
+                        )
+                        .map(StateWord)
+                };
+                let input: Result<Vec<_>, Error> = (0..RATE).map(load_input_word).collect();
+                let input = input?;
+
+                // Constrain the output.
+                let constrain_output_word = |i: usize| {
+                    let value = initial_state[i].0.value().and_then(|initial_word| {
+                        input
+                            .get(i)
+                            .map(|word| word.0.value().cloned())
+                            // The capacity element is never altered by the input.
+                            .unwrap_or_else(|| Some(F::zero()))
+                            .map(|input_word| *initial_word + input_word)
+                    });
+                    region
+                        .assign_advice(
+                            || format!("load output_{}", i),
+                            self.state[i],
+                            2,
+                            || value.ok_or(Error::Synthesis),
+                        )
+                        .map(StateWord)
+                };
+
+                let output: Result<Vec<_>, Error> = (0..WIDTH).map(constrain_output_word).collect();
+                output.map(|output| output.try_into().unwrap())
+            },
+        )
+    }
+}
+
+/// Extracts sponge output from the given state.
+pub(super) fn get_output<F: FieldExt, const WIDTH: usize, const RATE: usize>(
+    state: &State<StateWord<F>, WIDTH>,
+) -> Squeezing<StateWord<F>, RATE> {
+    Squeezing(
+        state[..RATE]
+            .iter()
+            .map(|word| Some(word.clone()))
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap(),
+    )
+}
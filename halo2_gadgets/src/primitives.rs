@@ -5,4 +5,8 @@
 //     - EphemeralSecretKey
 
 pub mod poseidon;
+pub mod poseidon2;
+#[cfg(feature = "rescue")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rescue")))]
+pub mod rescue;
 pub mod sinsemilla;
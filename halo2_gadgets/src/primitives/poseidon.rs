@@ -12,11 +12,15 @@ pub(crate) mod fp;
 pub(crate) mod fq;
 pub(crate) mod grain;
 pub(crate) mod mds;
+#[cfg(test)]
+mod rounds;
 
 #[cfg(test)]
 pub(crate) mod test_vectors;
 
+mod p128pow5;
 mod p128pow5t3;
+pub use p128pow5::{P128Pow5, P128Pow5T5};
 pub use p128pow5t3::P128Pow5T3;
 
 use grain::SboxType;
@@ -372,6 +376,35 @@ impl<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, const
     }
 }
 
+/// Hashes a constant-length message with the [`Hash`] sponge construction, using
+/// `permute` as the permutation.
+///
+/// This lets hash functions built from permutations other than Poseidon share the same
+/// padding and absorption logic.
+pub(crate) fn hash_constant_length<
+    F: FieldExt,
+    const T: usize,
+    const RATE: usize,
+    const L: usize,
+>(
+    state: &mut State<F, T>,
+    message: [F; L],
+    mut permute: impl FnMut(&mut State<F, T>),
+) -> F {
+    let input: Vec<_> = array::IntoIter::new(message)
+        .chain(<ConstantLength<L> as Domain<F, RATE>>::padding(L))
+        .collect();
+
+    for chunk in input.chunks(RATE) {
+        for (word, value) in state.iter_mut().zip(chunk.iter()) {
+            *word += value;
+        }
+        permute(state);
+    }
+
+    state[0]
+}
+
 #[cfg(test)]
 mod tests {
     use halo2_proofs::arithmetic::FieldExt;
@@ -26,7 +26,7 @@ impl FieldType {
 }
 
 #[derive(Debug, Clone, Copy)]
-pub(super) enum SboxType {
+pub(crate) enum SboxType {
     /// x^alpha
     Pow,
     /// x^(-1)
@@ -43,14 +43,14 @@ impl SboxType {
     }
 }
 
-pub(super) struct Grain<F: FieldExt> {
+pub(crate) struct Grain<F: FieldExt> {
     state: BitArr!(for 80, in Msb0, u8),
     next_bit: usize,
     _field: PhantomData<F>,
 }
 
 impl<F: FieldExt> Grain<F> {
-    pub(super) fn new(sbox: SboxType, t: u16, r_f: u16, r_p: u16) -> Self {
+    pub(crate) fn new(sbox: SboxType, t: u16, r_f: u16, r_p: u16) -> Self {
         // Initialize the LFSR state.
         let mut state = bitarr![Msb0, u8; 1; STATE];
         let mut set_bits = |offset: usize, len, value| {
@@ -109,7 +109,7 @@ impl<F: FieldExt> Grain<F> {
     }
 
     /// Returns the next field element from this Grain instantiation.
-    pub(super) fn next_field_element(&mut self) -> F {
+    pub(crate) fn next_field_element(&mut self) -> F {
         // Loop until we get an element in the field.
         loop {
             let mut bytes = F::Repr::default();
@@ -2,26 +2,46 @@ use halo2_proofs::arithmetic::FieldExt;
 
 use super::{grain::Grain, Mds};
 
+/// Generates two [F; T] arrays of unique field elements, used to construct a
+/// candidate Cauchy matrix.
+fn generate_xs_ys<F: FieldExt, const T: usize>(grain: &mut Grain<F>) -> (Vec<F>, Vec<F>) {
+    loop {
+        let mut vals: Vec<_> = (0..2 * T)
+            .map(|_| grain.next_field_element_without_rejection())
+            .collect();
+
+        // Check that we have unique field elements.
+        let mut unique = vals.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        if vals.len() == unique.len() {
+            let rhs = vals.split_off(T);
+            break (vals, rhs);
+        }
+    }
+}
+
+/// Constructs the Cauchy matrix with elements a_ij = 1/(x_i + y_j), or returns `None`
+/// if x_i + y_j = 0 for some i, j.
+#[cfg(test)]
+fn cauchy<F: FieldExt, const T: usize>(xs: &[F], ys: &[F]) -> Option<Mds<F, T>> {
+    let mut mds = [[F::zero(); T]; T];
+    #[allow(clippy::needless_range_loop)]
+    for i in 0..T {
+        for j in 0..T {
+            mds[i][j] = Option::from((xs[i] + ys[j]).invert())?;
+        }
+    }
+    Some(mds)
+}
+
 pub(super) fn generate_mds<F: FieldExt, const T: usize>(
     grain: &mut Grain<F>,
     mut select: usize,
 ) -> (Mds<F, T>, Mds<F, T>) {
     let (xs, ys, mds) = loop {
         // Generate two [F; T] arrays of unique field elements.
-        let (xs, ys) = loop {
-            let mut vals: Vec<_> = (0..2 * T)
-                .map(|_| grain.next_field_element_without_rejection())
-                .collect();
-
-            // Check that we have unique field elements.
-            let mut unique = vals.clone();
-            unique.sort_unstable();
-            unique.dedup();
-            if vals.len() == unique.len() {
-                let rhs = vals.split_off(T);
-                break (vals, rhs);
-            }
-        };
+        let (xs, ys) = generate_xs_ys::<F, T>(grain);
 
         // We need to ensure that the MDS is secure. Instead of checking the MDS against
         // the relevant algorithms directly, we witness a fixed number of MDS matrices
@@ -96,11 +116,115 @@ pub(super) fn generate_mds<F: FieldExt, const T: usize>(
     (mds, mds_inv)
 }
 
+/// Returns the index of the first secure MDS matrix that will be generated by
+/// [`generate_mds`] from the given Grain state.
+///
+/// A matrix is considered secure if it is well-defined and passes [`is_secure`].
+#[cfg(test)]
+pub(super) fn find_secure_mds<F: FieldExt, const T: usize>(grain: &mut Grain<F>) -> usize {
+    let mut select = 0;
+    loop {
+        let (xs, ys) = generate_xs_ys::<F, T>(grain);
+        match cauchy::<F, T>(&xs, &ys) {
+            Some(mds) if is_secure(&mds) => break select,
+            _ => select += 1,
+        }
+    }
+}
+
+/// Checks that no infinitely long subspace trail exists through the partial rounds
+/// of a Poseidon permutation using the given MDS matrix.
+///
+/// In a partial round, the S-box is only applied to the first state word. A subspace
+/// of differences that never activates this S-box must lie in the kernel of every row
+/// $e_0^T M^j$ for $j \geq 0$. We require that these rows span the whole space, for
+/// $M$ and for each of its powers $M^r$ up to $r = 4t$ (which covers trails that only
+/// repeat after several rounds). In particular, this rejects any $M^r$ that is a
+/// multiple of the identity.
+#[cfg(test)]
+pub(super) fn is_secure<F: FieldExt, const T: usize>(mds: &Mds<F, T>) -> bool {
+    let mut m_r = *mds;
+    for r in 1..=(4 * T) {
+        if r > 1 {
+            m_r = mat_mul(&m_r, mds);
+        }
+
+        // Rows e_0^T (M^r)^j for j in 0..T.
+        let mut rows = Vec::with_capacity(T);
+        let mut row = [F::zero(); T];
+        row[0] = F::one();
+        for _ in 0..T {
+            rows.push(row);
+            row = vec_mat_mul(&row, &m_r);
+        }
+
+        if rank(rows) < T {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+fn mat_mul<F: FieldExt, const T: usize>(a: &Mds<F, T>, b: &Mds<F, T>) -> Mds<F, T> {
+    let mut ret = [[F::zero(); T]; T];
+    #[allow(clippy::needless_range_loop)]
+    for i in 0..T {
+        for j in 0..T {
+            for k in 0..T {
+                ret[i][j] += a[i][k] * b[k][j];
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+fn vec_mat_mul<F: FieldExt, const T: usize>(v: &[F; T], m: &Mds<F, T>) -> [F; T] {
+    let mut ret = [F::zero(); T];
+    #[allow(clippy::needless_range_loop)]
+    for j in 0..T {
+        for k in 0..T {
+            ret[j] += v[k] * m[k][j];
+        }
+    }
+    ret
+}
+
+/// Computes the rank of the given rows using Gaussian elimination.
+#[cfg(test)]
+fn rank<F: FieldExt, const T: usize>(mut rows: Vec<[F; T]>) -> usize {
+    let mut rank = 0;
+    for col in 0..T {
+        let pivot = match (rank..rows.len()).find(|&i| !rows[i][col].is_zero_vartime()) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+
+        let inv = rows[rank][col].invert().unwrap();
+        let pivot_row = rows[rank];
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != rank {
+                let factor = row[col] * inv;
+                for (entry, pivot_entry) in row.iter_mut().zip(pivot_row.iter()) {
+                    *entry -= factor * pivot_entry;
+                }
+            }
+        }
+        rank += 1;
+    }
+    rank
+}
+
 #[cfg(test)]
 mod tests {
     use pasta_curves::Fp;
 
-    use super::{generate_mds, Grain};
+    use super::{
+        super::{fp, fq},
+        generate_mds, is_secure, Grain,
+    };
 
     #[test]
     fn poseidon_mds() {
@@ -120,4 +244,17 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn secure_mds() {
+        assert!(is_secure(&fp::MDS));
+        assert!(is_secure(&fq::MDS));
+
+        // A multiple of the identity leaves every subspace invariant.
+        let mut scalar = [[Fp::zero(); 3]; 3];
+        for (i, row) in scalar.iter_mut().enumerate() {
+            row[i] = Fp::from(2);
+        }
+        assert!(!is_secure(&scalar));
+    }
 }
@@ -0,0 +1,218 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+
+use super::Spec;
+
+/// The S-box exponent $\alpha = 5$.
+const ALPHA: u64 = 5;
+
+/// The number of full rounds, for every supported width.
+const FULL_ROUNDS: usize = 8;
+
+/// The number of partial rounds for each supported width `T`, starting from `T = 2`.
+///
+/// [`Pow5Chip`] applies partial rounds in pairs, so it requires an even $R_P$. The
+/// widths above 5 would otherwise have $R_P = 57$; this rounds them up to 58, which
+/// only adds to the security margin.
+///
+/// [`Pow5Chip`]: crate::poseidon::Pow5Chip
+const PARTIAL_ROUNDS: [usize; 11] = [56, 56, 56, 56, 58, 58, 58, 58, 58, 58, 58];
+
+/// Poseidon-128 using the $x^5$ S-box, with a width of `T` field elements and a rate
+/// of `RATE` field elements. Widths from 2 to 12 are supported.
+///
+/// Unlike [`P128Pow5T3`], the round constants and MDS matrix for this specification
+/// are generated at runtime, as the reference implementation would:
+/// - $R_F$ and $R_P$ are the standard number of rounds for 128-bit security "with
+///   margin" over a 255-bit field, as computed by the reference
+///   `calc_round_numbers.py` script. If $R_P$ is odd it is rounded up to the next
+///   even number.
+/// - The round constants are sampled from the Grain LFSR.
+/// - The MDS matrix is the first matrix sampled from the Grain LFSR (after the round
+///   constants) that is secure against infinitely long subspace trails. For the
+///   Pasta fields, this is the first matrix sampled at every supported width.
+///
+/// The round numbers and MDS selection are hard-coded for the Pasta fields.
+///
+/// [`P128Pow5T3`]: super::P128Pow5T3
+#[derive(Debug)]
+pub struct P128Pow5<const T: usize, const RATE: usize>(PhantomData<[(); T]>);
+
+/// Poseidon-128 using the $x^5$ S-box, with a width of 5 field elements.
+pub type P128Pow5T5 = P128Pow5<5, 4>;
+
+impl<F: FieldExt, const T: usize, const RATE: usize> Spec<F, T, RATE> for P128Pow5<T, RATE> {
+    fn full_rounds() -> usize {
+        FULL_ROUNDS
+    }
+
+    fn partial_rounds() -> usize {
+        assert!(
+            (2..=12).contains(&T),
+            "P128Pow5 supports widths from 2 to 12"
+        );
+        PARTIAL_ROUNDS[T - 2]
+    }
+
+    fn sbox(val: F) -> F {
+        val.pow_vartime(&[ALPHA])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::PrimeField;
+    use pasta_curves::{arithmetic::FieldExt, Fp, Fq};
+
+    use super::{P128Pow5, P128Pow5T5, ALPHA};
+    use crate::primitives::poseidon::{
+        fp, fq,
+        grain::{Grain, SboxType},
+        mds, permute, rounds, test_vectors, ConstantLength, Hash, Mds, Spec,
+    };
+
+    #[test]
+    fn t3_matches_p128pow5t3() {
+        fn check<F: FieldExt>(
+            expected_round_constants: [[F; 3]; 64],
+            expected_mds: Mds<F, 3>,
+            expected_mds_inv: Mds<F, 3>,
+        ) {
+            assert_eq!(<P128Pow5<3, 2> as Spec<F, 3, 2>>::full_rounds(), 8);
+            assert_eq!(<P128Pow5<3, 2> as Spec<F, 3, 2>>::partial_rounds(), 56);
+            assert_eq!(<P128Pow5<3, 2> as Spec<F, 3, 2>>::secure_mds(), 0);
+
+            let (round_constants, mds, mds_inv) = <P128Pow5<3, 2> as Spec<F, 3, 2>>::constants();
+            assert_eq!(round_constants, expected_round_constants.to_vec());
+            assert_eq!(mds, expected_mds);
+            assert_eq!(mds_inv, expected_mds_inv);
+        }
+
+        check(fp::ROUND_CONSTANTS, fp::MDS, fp::MDS_INV);
+        check(fq::ROUND_CONSTANTS, fq::MDS, fq::MDS_INV);
+    }
+
+    #[test]
+    fn t3_test_vectors() {
+        let (round_constants, mds, _) = <P128Pow5<3, 2> as Spec<Fp, 3, 2>>::constants();
+        for tv in test_vectors::fp::permute() {
+            let mut state = [
+                Fp::from_repr(tv.initial_state[0]).unwrap(),
+                Fp::from_repr(tv.initial_state[1]).unwrap(),
+                Fp::from_repr(tv.initial_state[2]).unwrap(),
+            ];
+
+            permute::<Fp, P128Pow5<3, 2>, 3, 2>(&mut state, &mds, &round_constants);
+
+            for (expected, actual) in tv.final_state.iter().zip(state.iter()) {
+                assert_eq!(&actual.to_repr(), expected);
+            }
+        }
+
+        for tv in test_vectors::fq::hash() {
+            let message = [
+                Fq::from_repr(tv.input[0]).unwrap(),
+                Fq::from_repr(tv.input[1]).unwrap(),
+            ];
+
+            let result = Hash::<_, P128Pow5<3, 2>, ConstantLength<2>, 3, 2>::init().hash(message);
+
+            assert_eq!(result.to_repr(), tv.output);
+        }
+    }
+
+    #[test]
+    fn t5_constants() {
+        fn check<F: FieldExt>() {
+            let r_f = <P128Pow5T5 as Spec<F, 5, 4>>::full_rounds();
+            let r_p = <P128Pow5T5 as Spec<F, 5, 4>>::partial_rounds();
+            assert_eq!(r_f & 1, 0);
+            assert_eq!(r_p & 1, 0);
+
+            let (round_constants, mds, mds_inv) = <P128Pow5T5 as Spec<F, 5, 4>>::constants();
+            assert_eq!(round_constants.len(), r_f + r_p);
+
+            // Verify that MDS * MDS^-1 = I.
+            #[allow(clippy::needless_range_loop)]
+            for i in 0..5 {
+                for j in 0..5 {
+                    let expected = if i == j { F::one() } else { F::zero() };
+                    assert_eq!(
+                        (0..5).fold(F::zero(), |acc, k| acc + (mds[i][k] * mds_inv[k][j])),
+                        expected
+                    );
+                }
+            }
+        }
+
+        check::<Fp>();
+        check::<Fq>();
+    }
+
+    #[test]
+    fn t5_test_vectors() {
+        fn check<F: FieldExt>(tvs: Vec<test_vectors::PermuteT5TestVector>) {
+            let (round_constants, mds, _) = <P128Pow5T5 as Spec<F, 5, 4>>::constants();
+            for tv in tvs {
+                let mut state = [F::zero(); 5];
+                for (word, initial) in state.iter_mut().zip(tv.initial_state.iter()) {
+                    let mut repr = F::Repr::default();
+                    repr.as_mut().copy_from_slice(initial);
+                    *word = F::from_repr(repr).unwrap();
+                }
+
+                permute::<F, P128Pow5T5, 5, 4>(&mut state, &mds, &round_constants);
+
+                for (expected, actual) in tv.final_state.iter().zip(state.iter()) {
+                    assert_eq!(actual.to_repr().as_ref(), expected);
+                }
+            }
+        }
+
+        check::<Fp>(test_vectors::fp::permute_t5());
+        check::<Fq>(test_vectors::fq::permute_t5());
+    }
+
+    #[test]
+    fn hardcoded_parameters_match_search() {
+        fn check<F: FieldExt, const T: usize>() {
+            // 128-bit security.
+            let (r_f, r_p) = rounds::round_numbers::<F>(T, ALPHA, 128);
+            let r_p = r_p + (r_p & 1);
+            assert_eq!(<P128Pow5<T, 1> as Spec<F, T, 1>>::full_rounds(), r_f);
+            assert_eq!(<P128Pow5<T, 1> as Spec<F, T, 1>>::partial_rounds(), r_p);
+
+            // Skip over the round constants, which are sampled before the MDS matrix.
+            let mut grain = Grain::<F>::new(SboxType::Pow, T as u16, r_f as u16, r_p as u16);
+            for _ in 0..((r_f + r_p) * T) {
+                grain.next_field_element();
+            }
+            assert_eq!(
+                <P128Pow5<T, 1> as Spec<F, T, 1>>::secure_mds(),
+                mds::find_secure_mds::<F, T>(&mut grain)
+            );
+        }
+
+        fn check_field<F: FieldExt>() {
+            check::<F, 2>();
+            check::<F, 3>();
+            check::<F, 4>();
+            check::<F, 5>();
+            check::<F, 6>();
+            check::<F, 7>();
+            check::<F, 8>();
+            check::<F, 9>();
+            check::<F, 10>();
+            check::<F, 11>();
+            check::<F, 12>();
+        }
+
+        check_field::<Fp>();
+        check_field::<Fq>();
+    }
+}
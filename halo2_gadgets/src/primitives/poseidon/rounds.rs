@@ -0,0 +1,112 @@
+//! Calculation of the number of rounds for a Poseidon instance, following the
+//! `calc_round_numbers.py` script in the Poseidon reference implementation.
+
+use halo2_proofs::arithmetic::FieldExt;
+
+/// Returns $\log_2(p)$ for the modulus $p$ of the field `F`.
+fn log2_modulus<F: FieldExt>() -> f64 {
+    // The bytes of p - 1, in little-endian order. This is indistinguishable from p at
+    // the precision of an f64.
+    let repr = (-F::one()).to_repr();
+    let bytes = repr.as_ref();
+    let top = bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .expect("p - 1 is non-zero");
+
+    // Take the (up to) eight most significant bytes as the mantissa.
+    let start = top.saturating_sub(7);
+    let mantissa = bytes[start..=top]
+        .iter()
+        .rev()
+        .fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+    (mantissa as f64).log2() + (8 * start) as f64
+}
+
+/// Returns `true` if `(r_f, r_p)` rounds prevent the statistical, interpolation and
+/// Gröbner basis attacks for the given parameters, at a security level of `m` bits.
+fn sat_inequiv_alpha(log2_p: f64, t: usize, r_f: usize, r_p: usize, alpha: u64, m: usize) -> bool {
+    let n = log2_p.ceil();
+    let t = t as f64;
+    let m = m as f64;
+    let r_p = r_p as f64;
+    let alpha = alpha as f64;
+    let log_alpha = |x: f64| x.ln() / alpha.ln();
+
+    // Statistical
+    let r_f_1 = if m <= (log2_p - (alpha - 1.0) / 2.0).floor() * (t + 1.0) {
+        6.0
+    } else {
+        10.0
+    };
+    // Interpolation
+    let r_f_2 = 1.0 + (log_alpha(2.0) * m.min(n)).ceil() + log_alpha(t).ceil() - r_p;
+    // Gröbner 1
+    let r_f_3 = 1.0 + log_alpha(2.0) * (m / 3.0).min(log2_p / 2.0) - r_p;
+    // Gröbner 2
+    let r_f_4 = t - 1.0 + (log_alpha(2.0) * m / (t + 1.0)).min(log_alpha(2.0) * log2_p / 2.0) - r_p;
+
+    let r_f_max = [r_f_1, r_f_2, r_f_3, r_f_4]
+        .iter()
+        .map(|r| r.ceil())
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    r_f as f64 >= r_f_max
+}
+
+/// Returns `(R_F, R_P)` for a Poseidon instance over `F` with width `t` and the
+/// $x^\alpha$ S-box, at a security level of `m` bits.
+///
+/// This searches for the secure round numbers with the fewest S-boxes, and then
+/// applies the standard security margin: $R_F$ is increased by 2, and $R_P$ by 7.5%.
+pub(super) fn round_numbers<F: FieldExt>(t: usize, alpha: u64, m: usize) -> (usize, usize) {
+    let log2_p = log2_modulus::<F>();
+    let cost = |r_f: usize, r_p: usize| r_f * t + r_p;
+
+    let mut best: Option<(usize, usize)> = None;
+    for r_p in 1..500 {
+        for r_f in (4..100).step_by(2) {
+            if !sat_inequiv_alpha(log2_p, t, r_f, r_p, alpha, m) {
+                continue;
+            }
+
+            // Apply the security margin.
+            let r_f = r_f + 2;
+            let r_p = (r_p as f64 * 1.075).ceil() as usize;
+
+            // Prefer fewer S-boxes, and then fewer full rounds.
+            let better = match best {
+                None => true,
+                Some((best_r_f, best_r_p)) => {
+                    let (c, best_c) = (cost(r_f, r_p), cost(best_r_f, best_r_p));
+                    c < best_c || (c == best_c && r_f < best_r_f)
+                }
+            };
+            if better {
+                best = Some((r_f, r_p));
+            }
+        }
+    }
+
+    best.expect("a secure number of rounds exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::{Fp, Fq};
+
+    use super::{log2_modulus, round_numbers};
+
+    #[test]
+    fn pasta_modulus() {
+        // Both Pasta moduli are slightly larger than 2^254.
+        assert!((log2_modulus::<Fp>() - 254.0).abs() < 1e-9);
+        assert!((log2_modulus::<Fq>() - 254.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn p128pow5t3_round_numbers() {
+        assert_eq!(round_numbers::<Fp>(3, 5, 128), (8, 56));
+        assert_eq!(round_numbers::<Fq>(3, 5, 128), (8, 56));
+    }
+}
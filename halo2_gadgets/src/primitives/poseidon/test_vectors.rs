@@ -5,6 +5,11 @@ pub(crate) struct PermuteTestVector {
     pub(crate) final_state: [[u8; 32]; 3],
 }
 
+pub(crate) struct PermuteT5TestVector {
+    pub(crate) initial_state: [[u8; 32]; 5],
+    pub(crate) final_state: [[u8; 32]; 5],
+}
+
 pub(crate) struct HashTestVector {
     pub(crate) input: [[u8; 32]; 2],
     pub(crate) output: [u8; 32],
@@ -633,6 +638,127 @@ pub(crate) mod fp {
             },
         ]
     }
+
+    pub(crate) fn permute_t5() -> Vec<PermuteT5TestVector> {
+        use PermuteT5TestVector as TestVector;
+
+        // <https://github.com/daira/pasta-hadeshash>, using parameters from
+        // `generate_parameters_grain.sage 1 0 255 5 8 56 0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001`.
+        vec![
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0xe5, 0x48, 0xff, 0xcf, 0x6c, 0xa2, 0xbd, 0xec, 0xed, 0x94, 0xa7, 0xd0,
+                        0x83, 0xea, 0xfa, 0x0f, 0x69, 0x1b, 0x2d, 0xb2, 0xa8, 0xac, 0x63, 0x53,
+                        0x75, 0x0e, 0x9a, 0x2f, 0x37, 0xeb, 0x81, 0x07,
+                    ],
+                    [
+                        0x28, 0x68, 0xba, 0xd9, 0xd1, 0xdb, 0x28, 0x63, 0xce, 0xb5, 0x5c, 0x09,
+                        0x90, 0xa2, 0xdb, 0x1a, 0xd0, 0x3f, 0xb0, 0x57, 0x3e, 0x94, 0x61, 0x0f,
+                        0x72, 0x3e, 0xb0, 0xf2, 0x24, 0x56, 0xbb, 0x3a,
+                    ],
+                    [
+                        0xfc, 0x12, 0xed, 0xca, 0x69, 0xbd, 0xb7, 0x90, 0x63, 0xed, 0x34, 0xb9,
+                        0xa8, 0xcc, 0x35, 0x90, 0x0e, 0xd3, 0x85, 0x1f, 0xf1, 0xf6, 0x33, 0xa7,
+                        0x99, 0x91, 0x43, 0xa7, 0x33, 0xdc, 0xde, 0x24,
+                    ],
+                    [
+                        0xc0, 0x3c, 0xe3, 0xbc, 0x44, 0x76, 0x0f, 0x1a, 0x86, 0xab, 0x86, 0x05,
+                        0xc6, 0x80, 0x5a, 0x64, 0x55, 0x40, 0x81, 0x34, 0x0a, 0xc5, 0xe3, 0x76,
+                        0x9f, 0x8c, 0x86, 0x61, 0xff, 0x4f, 0xfd, 0x02,
+                    ],
+                    [
+                        0x72, 0xce, 0xca, 0xc6, 0x75, 0x47, 0x9d, 0xb7, 0x5b, 0xe7, 0xe8, 0x68,
+                        0xa6, 0x4e, 0x9d, 0x12, 0x2d, 0xcb, 0x03, 0xab, 0xd3, 0x8a, 0x51, 0xa3,
+                        0xa2, 0xc5, 0xc6, 0xb0, 0x1f, 0x0e, 0x0d, 0x2d,
+                    ],
+                ],
+            },
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0xed, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xff, 0xff, 0xff, 0xff, 0xec, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfe, 0xff, 0xff, 0xff, 0xec, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfd, 0xff, 0xff, 0xff, 0xec, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfc, 0xff, 0xff, 0xff, 0xec, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0x59, 0x23, 0x6c, 0x8e, 0xdf, 0xac, 0x91, 0x21, 0xc4, 0xc3, 0x9d, 0xbb,
+                        0x5b, 0xd7, 0xb0, 0x9d, 0x37, 0x7b, 0x95, 0x5b, 0xc8, 0xb8, 0x79, 0xff,
+                        0x87, 0x34, 0x35, 0x30, 0xf3, 0x28, 0xb4, 0x2e,
+                    ],
+                    [
+                        0x12, 0x75, 0xf7, 0xd6, 0x6e, 0x2b, 0x48, 0xa7, 0x18, 0xdb, 0xb5, 0x32,
+                        0x6d, 0x62, 0xbd, 0xe6, 0x2a, 0xbf, 0x19, 0xaf, 0xa3, 0x3f, 0x19, 0x58,
+                        0x4f, 0xe3, 0x7b, 0x2b, 0x7f, 0xea, 0xce, 0x2c,
+                    ],
+                    [
+                        0xf6, 0xd8, 0xf7, 0x01, 0x07, 0x7a, 0x6e, 0x11, 0xd2, 0xb1, 0x8e, 0xde,
+                        0xae, 0xf0, 0x5d, 0xb1, 0x46, 0x5b, 0x38, 0x9b, 0x2d, 0xaa, 0x45, 0x6e,
+                        0xb3, 0xd6, 0x85, 0x0c, 0x17, 0x39, 0x3a, 0x09,
+                    ],
+                    [
+                        0x61, 0x79, 0x15, 0x60, 0x7e, 0xe9, 0x0c, 0x3b, 0x5a, 0x7a, 0x1a, 0xbd,
+                        0x61, 0x92, 0x45, 0xa6, 0x79, 0x0f, 0x15, 0x5d, 0xf4, 0x27, 0x58, 0x33,
+                        0xa0, 0x96, 0x61, 0xc0, 0x9b, 0x63, 0xa7, 0x38,
+                    ],
+                    [
+                        0x52, 0xd4, 0xc0, 0xbc, 0x23, 0x9c, 0x1d, 0x9d, 0xef, 0x42, 0x50, 0x47,
+                        0xa1, 0x5e, 0x39, 0xbb, 0x0d, 0xdc, 0xeb, 0x33, 0xa2, 0x66, 0xf7, 0x50,
+                        0x65, 0x2b, 0x91, 0x15, 0x4e, 0x4b, 0xe3, 0x14,
+                    ],
+                ],
+            },
+        ]
+    }
 }
 
 pub(crate) mod fq {
@@ -1258,4 +1384,125 @@ pub(crate) mod fq {
             },
         ]
     }
+
+    pub(crate) fn permute_t5() -> Vec<PermuteT5TestVector> {
+        use PermuteT5TestVector as TestVector;
+
+        // <https://github.com/daira/pasta-hadeshash>, using parameters from
+        // `generate_parameters_grain.sage 1 0 255 5 8 56 0x40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001`.
+        vec![
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0x5a, 0xd8, 0x6f, 0x41, 0xbc, 0xf4, 0xd4, 0x7a, 0x4a, 0x6b, 0x6d, 0x0b,
+                        0x5a, 0xff, 0xde, 0xa4, 0x99, 0xae, 0x66, 0xf9, 0x42, 0xee, 0x1c, 0xf1,
+                        0x7e, 0x4e, 0x6d, 0x21, 0x67, 0x75, 0xc7, 0x39,
+                    ],
+                    [
+                        0xfb, 0x04, 0xb9, 0xb0, 0x0a, 0x00, 0xac, 0xc7, 0xd5, 0xd7, 0x4d, 0xc7,
+                        0x84, 0x9d, 0xab, 0x5d, 0xc8, 0x84, 0x6b, 0x07, 0xca, 0xb3, 0xc5, 0xeb,
+                        0x69, 0xfb, 0x2b, 0x8d, 0xa1, 0xea, 0x2d, 0x20,
+                    ],
+                    [
+                        0x64, 0xd4, 0x34, 0x22, 0x1f, 0x09, 0x85, 0x2e, 0xb0, 0x01, 0x5f, 0x56,
+                        0xf1, 0xa5, 0x66, 0xdb, 0x61, 0xc6, 0xed, 0xa7, 0x77, 0x95, 0x01, 0xff,
+                        0x04, 0x92, 0x6d, 0xfe, 0x8b, 0xc2, 0xbf, 0x1d,
+                    ],
+                    [
+                        0x8e, 0x50, 0x6b, 0x53, 0x82, 0x71, 0xe5, 0x9e, 0xe4, 0x2e, 0x03, 0xa2,
+                        0xb0, 0x7d, 0x20, 0xce, 0x7b, 0x6b, 0x51, 0x0f, 0x05, 0xe7, 0x9b, 0xac,
+                        0xf8, 0x82, 0x96, 0xff, 0x7d, 0x58, 0x61, 0x29,
+                    ],
+                    [
+                        0x30, 0xcd, 0xbf, 0xbc, 0x34, 0xc0, 0xfb, 0x73, 0xb6, 0x67, 0x0e, 0xd7,
+                        0x6b, 0x11, 0x77, 0x64, 0xe1, 0xf0, 0x16, 0x97, 0xde, 0x1c, 0xf5, 0xec,
+                        0x27, 0x32, 0x97, 0xcd, 0x43, 0x0c, 0xa7, 0x3e,
+                    ],
+                ],
+            },
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x21, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xff, 0xff, 0xff, 0xff, 0x20, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfe, 0xff, 0xff, 0xff, 0x20, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfd, 0xff, 0xff, 0xff, 0x20, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfc, 0xff, 0xff, 0xff, 0x20, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0xa4, 0x53, 0x2d, 0xaa, 0x76, 0x7d, 0x30, 0xe5, 0xaa, 0x5e, 0xe7, 0xa5,
+                        0xb7, 0xa8, 0xfe, 0xa8, 0x69, 0x8a, 0x9e, 0x92, 0xdf, 0x5f, 0xc9, 0x63,
+                        0xe1, 0x28, 0x13, 0x5c, 0x11, 0x14, 0x17, 0x33,
+                    ],
+                    [
+                        0x6d, 0x33, 0x36, 0x28, 0xea, 0xe7, 0x33, 0x3c, 0xab, 0x57, 0x34, 0xf1,
+                        0x79, 0xa5, 0xe1, 0xec, 0x5e, 0x9a, 0xf6, 0x51, 0xa4, 0x6b, 0x87, 0x85,
+                        0xec, 0x21, 0xa6, 0x0b, 0xa7, 0xc0, 0xda, 0x07,
+                    ],
+                    [
+                        0x06, 0x92, 0xa8, 0x24, 0x24, 0xcf, 0xad, 0xc6, 0x54, 0x12, 0x0d, 0xa6,
+                        0x0d, 0x74, 0x87, 0x46, 0xe9, 0xeb, 0x81, 0xec, 0x9b, 0x83, 0xa2, 0xd7,
+                        0x3c, 0x61, 0x64, 0x04, 0x1c, 0x3c, 0x53, 0x3e,
+                    ],
+                    [
+                        0x9e, 0x0e, 0x2c, 0x19, 0x38, 0x49, 0x18, 0x12, 0xab, 0x21, 0x88, 0x9c,
+                        0x06, 0xc3, 0x24, 0xaf, 0x93, 0x9f, 0x75, 0x58, 0x7b, 0xd7, 0xbf, 0xef,
+                        0xb1, 0x25, 0x7f, 0x7a, 0x36, 0x58, 0xdb, 0x28,
+                    ],
+                    [
+                        0xf0, 0xdb, 0xef, 0xa9, 0x9b, 0xd0, 0x55, 0xb8, 0xbf, 0x65, 0x4a, 0x9b,
+                        0x1a, 0xbe, 0xd0, 0x6c, 0x03, 0x78, 0x09, 0x8a, 0x2f, 0x5a, 0x42, 0x75,
+                        0x03, 0xe1, 0xea, 0xac, 0x2d, 0x5b, 0xd0, 0x08,
+                    ],
+                ],
+            },
+        ]
+    }
 }
@@ -0,0 +1,296 @@
+//! The Poseidon2 algebraic hash function.
+//!
+//! Poseidon2 ([eprint 2023/323]) shares the round structure of Poseidon, but replaces
+//! the dense MDS matrix with two cheaper linear layers:
+//!
+//! - An "external" matrix $M_E$, applied once before the first round and after every
+//!   full round.
+//! - An "internal" matrix $M_I = J + \mathrm{diag}(\mu_0, \ldots, \mu_{t-1})$ (where $J$
+//!   is the all-ones matrix), applied after every partial round. Multiplying by $M_I$
+//!   costs a single sum and $t$ scalings.
+//!
+//! Partial rounds also only add a round constant to the first state word.
+//!
+//! [eprint 2023/323]: https://eprint.iacr.org/2023/323
+
+use std::fmt;
+use std::iter;
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::primitives::poseidon::{
+    grain::{Grain, SboxType},
+    hash_constant_length, ConstantLength, Domain, Mds, State,
+};
+
+mod p128pow5t3;
+pub use p128pow5t3::P128Pow5T3;
+
+#[cfg(test)]
+pub(crate) mod test_vectors;
+
+/// A specification for a Poseidon2 permutation.
+pub trait Spec<F: FieldExt, const T: usize, const RATE: usize>: fmt::Debug {
+    /// The number of full rounds for this specification.
+    ///
+    /// This must be an even number.
+    fn full_rounds() -> usize;
+
+    /// The number of partial rounds for this specification.
+    fn partial_rounds() -> usize;
+
+    /// The S-box for this specification.
+    fn sbox(val: F) -> F;
+
+    /// The diagonal $(\mu_0, \ldots, \mu_{t-1})$ of the internal matrix
+    /// $M_I = J + \mathrm{diag}(\mu_0, \ldots, \mu_{t-1})$.
+    ///
+    /// For $t \in \{2, 3\}$ the Poseidon2 paper fixes this to $(1, 2)$ and $(1, 1, 2)$
+    /// respectively. For larger widths, the diagonal must be chosen such that $M_I$ is
+    /// invertible and secure against infinitely long subspace trails.
+    fn internal_diagonal() -> [F; T];
+
+    /// Generates the round constants corresponding to this specification.
+    ///
+    /// The constants are sampled from the same Grain LFSR as Poseidon. As in the
+    /// reference implementation, partial rounds only sample the first constant of their
+    /// row; the rest of the row is set to zero.
+    fn constants() -> Vec<[F; T]> {
+        let r_f = Self::full_rounds();
+        let r_p = Self::partial_rounds();
+
+        let mut grain = Grain::new(SboxType::Pow, T as u16, r_f as u16, r_p as u16);
+
+        (0..(r_f + r_p))
+            .map(|round| {
+                let is_partial = round >= r_f / 2 && round < r_f / 2 + r_p;
+                let mut rc_row = [F::zero(); T];
+                for rc in rc_row.iter_mut().take(if is_partial { 1 } else { T }) {
+                    *rc = grain.next_field_element();
+                }
+                rc_row
+            })
+            .collect()
+    }
+}
+
+/// Returns the external matrix $M_E$ for a Poseidon2 permutation of width `T`.
+///
+/// For $t \in \{2, 3\}$ this is $\mathrm{circ}(2, 1)$ and $\mathrm{circ}(2, 1, 1)$. For
+/// $t = 4k$ this is the block matrix with $2 \cdot M_4$ on the diagonal and $M_4$
+/// elsewhere.
+///
+/// # Panics
+///
+/// Panics if `T` is not 2, 3, or a multiple of 4.
+pub(crate) fn external_matrix<F: FieldExt, const T: usize>() -> Mds<F, T> {
+    const M4: [[u64; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+
+    let mut m_ext = [[F::zero(); T]; T];
+    match T {
+        2 | 3 => {
+            for (i, row) in m_ext.iter_mut().enumerate() {
+                for (j, entry) in row.iter_mut().enumerate() {
+                    *entry = F::from(if i == j { 2 } else { 1 });
+                }
+            }
+        }
+        _ if T % 4 == 0 => {
+            for (i, row) in m_ext.iter_mut().enumerate() {
+                for (j, entry) in row.iter_mut().enumerate() {
+                    let scale = if i / 4 == j / 4 { 2 } else { 1 };
+                    *entry = F::from(scale * M4[i % 4][j % 4]);
+                }
+            }
+        }
+        _ => panic!(
+            "Poseidon2 does not define an external matrix for width {}",
+            T
+        ),
+    }
+    m_ext
+}
+
+/// Applies the internal matrix $M_I = J + \mathrm{diag}(\mu)$ to the given state.
+pub(crate) fn internal_linear_layer<F: FieldExt, const T: usize>(
+    state: &mut State<F, T>,
+    diagonal: &[F; T],
+) {
+    let sum = state.iter().fold(F::zero(), |acc, word| acc + word);
+    for (word, mu) in state.iter_mut().zip(diagonal.iter()) {
+        *word = sum + *mu * *word;
+    }
+}
+
+/// Applies the given dense matrix to the state.
+pub(crate) fn apply_matrix<F: FieldExt, const T: usize>(state: &mut State<F, T>, m: &Mds<F, T>) {
+    let mut new_state = [F::zero(); T];
+    // Matrix multiplication
+    #[allow(clippy::needless_range_loop)]
+    for i in 0..T {
+        for j in 0..T {
+            new_state[i] += m[i][j] * state[j];
+        }
+    }
+    *state = new_state;
+}
+
+/// Runs the Poseidon2 permutation on the given state.
+pub(crate) fn permute<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>(
+    state: &mut State<F, T>,
+    round_constants: &[[F; T]],
+) {
+    let r_f = S::full_rounds() / 2;
+    let r_p = S::partial_rounds();
+    let m_ext = external_matrix::<F, T>();
+    let diagonal = S::internal_diagonal();
+
+    let full_round = |state: &mut State<F, T>, rcs: &[F; T]| {
+        for (word, rc) in state.iter_mut().zip(rcs.iter()) {
+            *word = S::sbox(*word + rc);
+        }
+        apply_matrix(state, &m_ext);
+    };
+
+    let part_round = |state: &mut State<F, T>, rcs: &[F; T]| {
+        // In a partial round, the round constant and S-box are only applied to the
+        // first state word.
+        state[0] = S::sbox(state[0] + rcs[0]);
+        internal_linear_layer(state, &diagonal);
+    };
+
+    // The external matrix is applied once before the first round.
+    apply_matrix(state, &m_ext);
+
+    iter::empty()
+        .chain(iter::repeat(&full_round as &dyn Fn(&mut State<F, T>, &[F; T])).take(r_f))
+        .chain(iter::repeat(&part_round as &dyn Fn(&mut State<F, T>, &[F; T])).take(r_p))
+        .chain(iter::repeat(&full_round as &dyn Fn(&mut State<F, T>, &[F; T])).take(r_f))
+        .zip(round_constants.iter())
+        .fold(state, |state, (round, rcs)| {
+            round(state, rcs);
+            state
+        });
+}
+
+/// A Poseidon2 hash function, built around a sponge.
+///
+/// This uses the same sponge construction and [`Domain`]s as the Poseidon [`Hash`].
+///
+/// [`Hash`]: crate::primitives::poseidon::Hash
+pub struct Hash<
+    F: FieldExt,
+    S: Spec<F, T, RATE>,
+    D: Domain<F, RATE>,
+    const T: usize,
+    const RATE: usize,
+> {
+    state: State<F, T>,
+    round_constants: Vec<[F; T]>,
+    _marker: PhantomData<(S, D)>,
+}
+
+impl<F: FieldExt, S: Spec<F, T, RATE>, D: Domain<F, RATE>, const T: usize, const RATE: usize>
+    fmt::Debug for Hash<F, S, D, T, RATE>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hash")
+            .field("width", &T)
+            .field("rate", &RATE)
+            .field("R_F", &S::full_rounds())
+            .field("R_P", &S::partial_rounds())
+            .field("domain", &D::name())
+            .finish()
+    }
+}
+
+impl<F: FieldExt, S: Spec<F, T, RATE>, D: Domain<F, RATE>, const T: usize, const RATE: usize>
+    Hash<F, S, D, T, RATE>
+{
+    /// Initializes a new hasher.
+    pub fn init() -> Self {
+        let mut state = [F::zero(); T];
+        state[RATE] = D::initial_capacity_element();
+
+        Hash {
+            state,
+            round_constants: S::constants(),
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, const L: usize>
+    Hash<F, S, ConstantLength<L>, T, RATE>
+{
+    /// Hashes the given input.
+    pub fn hash(mut self, message: [F; L]) -> F {
+        let round_constants = &self.round_constants;
+        hash_constant_length::<_, T, RATE, L>(&mut self.state, message, |state| {
+            permute::<F, S, T, RATE>(state, round_constants)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::ff::Field;
+    use halo2_proofs::arithmetic::FieldExt;
+    use pasta_curves::pallas;
+
+    use super::{
+        apply_matrix, external_matrix, permute, ConstantLength, Hash, Mds, P128Pow5T3, Spec,
+    };
+
+    #[test]
+    fn external_matrix_t4k() {
+        let m4 = external_matrix::<pallas::Base, 4>();
+        let m8 = external_matrix::<pallas::Base, 8>();
+
+        for i in 0..8 {
+            for j in 0..8 {
+                let block = m4[i % 4][j % 4];
+                let expected = if i / 4 == j / 4 { block + block } else { block };
+                assert_eq!(m8[i][j], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn internal_layer_equivalence() {
+        let diagonal = <P128Pow5T3 as Spec<pallas::Base, 3, 2>>::internal_diagonal();
+
+        let mut m_int: Mds<pallas::Base, 3> = [[pallas::Base::one(); 3]; 3];
+        for (i, row) in m_int.iter_mut().enumerate() {
+            row[i] += diagonal[i];
+        }
+
+        let mut state = [
+            pallas::Base::from(1),
+            pallas::Base::from(2),
+            pallas::Base::from(3),
+        ];
+        let mut expected = state;
+        apply_matrix(&mut expected, &m_int);
+        super::internal_linear_layer(&mut state, &diagonal);
+
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn hash_equivalence() {
+        let message = [pallas::Base::from(6), pallas::Base::from(42)];
+
+        let round_constants = <P128Pow5T3 as Spec<pallas::Base, 3, 2>>::constants();
+
+        let hasher = Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init();
+        let result = hasher.hash(message);
+
+        // The result should be equivalent to just directly applying the permutation and
+        // taking the first state element as the output.
+        let mut state = [message[0], message[1], pallas::Base::from_u128(2 << 64)];
+        permute::<_, P128Pow5T3, 3, 2>(&mut state, &round_constants);
+        assert_eq!(state[0], result);
+    }
+}
@@ -0,0 +1,64 @@
+use halo2_proofs::arithmetic::FieldExt;
+
+use super::Spec;
+
+/// Poseidon2-128 using the $x^5$ S-box, with a width of 3 field elements, and the
+/// standard number of rounds for 128-bit security "with margin".
+///
+/// As with Poseidon, the standard specification for this set of parameters (on either
+/// of the Pasta fields) uses $R_F = 8, R_P = 56$. The internal matrix is fixed by the
+/// Poseidon2 paper to $M_I = J + \mathrm{diag}(1, 1, 2)$.
+#[derive(Debug)]
+pub struct P128Pow5T3;
+
+impl<F: FieldExt> Spec<F, 3, 2> for P128Pow5T3 {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        56
+    }
+
+    fn sbox(val: F) -> F {
+        val.pow_vartime(&[5])
+    }
+
+    fn internal_diagonal() -> [F; 3] {
+        [F::one(), F::one(), F::from(2)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::arithmetic::FieldExt;
+    use pasta_curves::{Fp, Fq};
+
+    use super::P128Pow5T3;
+    use crate::primitives::poseidon2::{permute, test_vectors, Spec};
+
+    #[test]
+    fn permute_test_vectors() {
+        fn check<F: FieldExt>(tvs: Vec<test_vectors::PermuteTestVector>) {
+            let round_constants = <P128Pow5T3 as Spec<F, 3, 2>>::constants();
+
+            for tv in tvs {
+                let mut state = [F::zero(); 3];
+                for (word, initial) in state.iter_mut().zip(tv.initial_state.iter()) {
+                    let mut repr = F::Repr::default();
+                    repr.as_mut().copy_from_slice(initial);
+                    *word = F::from_repr(repr).unwrap();
+                }
+
+                permute::<F, P128Pow5T3, 3, 2>(&mut state, &round_constants);
+
+                for (expected, actual) in tv.final_state.iter().zip(state.iter()) {
+                    assert_eq!(actual.to_repr().as_ref(), expected);
+                }
+            }
+        }
+
+        check::<Fp>(test_vectors::fp::permute());
+        check::<Fq>(test_vectors::fq::permute());
+    }
+}
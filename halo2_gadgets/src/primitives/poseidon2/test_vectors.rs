@@ -0,0 +1,175 @@
+//! Test vectors for [`P128Pow5T3`].
+//!
+//! <https://github.com/HorizenLabs/poseidon2>, using parameters from
+//! `poseidon2_rust_params.sage` with $t = 3, R_F = 8, R_P = 56$ and
+//! $M_I = J + \mathrm{diag}(1, 1, 2)$.
+//!
+//! [`P128Pow5T3`]: super::P128Pow5T3
+
+pub(crate) use crate::primitives::poseidon::test_vectors::PermuteTestVector;
+
+pub(crate) mod fp {
+    use super::*;
+
+    pub(crate) fn permute() -> Vec<PermuteTestVector> {
+        use PermuteTestVector as TestVector;
+
+        vec![
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0x7a, 0x8d, 0x26, 0xb2, 0x59, 0xe0, 0xaa, 0x0b, 0x75, 0x95, 0x9d, 0x0b,
+                        0x42, 0x51, 0x72, 0xea, 0x3f, 0x51, 0xb3, 0x44, 0x2c, 0x28, 0x78, 0xd7,
+                        0x4d, 0x91, 0x2a, 0x51, 0xc7, 0x54, 0x9b, 0x1a,
+                    ],
+                    [
+                        0xfc, 0x01, 0x79, 0x9f, 0x0c, 0x38, 0xa3, 0x9b, 0xd5, 0x88, 0xe9, 0x3f,
+                        0x88, 0xaf, 0xf5, 0x81, 0x86, 0x0c, 0xbf, 0x4d, 0xa5, 0x38, 0xa3, 0x4e,
+                        0x98, 0xd7, 0xa7, 0x94, 0x09, 0xea, 0x48, 0x1c,
+                    ],
+                    [
+                        0x01, 0xb4, 0x20, 0x31, 0xa2, 0xf8, 0x38, 0xc8, 0x42, 0x48, 0x4c, 0x5f,
+                        0x68, 0x66, 0x47, 0x96, 0x48, 0x04, 0x77, 0xa2, 0x26, 0xb5, 0x89, 0x44,
+                        0x41, 0xe9, 0xa3, 0x80, 0x0a, 0xdd, 0x9d, 0x07,
+                    ],
+                ],
+            },
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0xed, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xff, 0xff, 0xff, 0xff, 0xec, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfe, 0xff, 0xff, 0xff, 0xec, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0xfb, 0x53, 0x70, 0x1c, 0x58, 0xfa, 0xa6, 0x60, 0x21, 0xc1, 0xe3, 0xe9,
+                        0xe8, 0x49, 0xcc, 0xee, 0xd0, 0xb5, 0x00, 0xbb, 0xcc, 0x4e, 0xf2, 0x7a,
+                        0xa8, 0x0e, 0x33, 0x02, 0xd5, 0xb7, 0xff, 0x32,
+                    ],
+                    [
+                        0x86, 0x35, 0xb6, 0xc2, 0xdc, 0xc6, 0x70, 0xde, 0x86, 0xcf, 0xb8, 0x28,
+                        0xae, 0xd9, 0xa2, 0x1f, 0x15, 0x04, 0x4a, 0x3a, 0xc6, 0xc5, 0xe3, 0xea,
+                        0xa3, 0x6f, 0xe1, 0x64, 0x12, 0x0b, 0x80, 0x1b,
+                    ],
+                    [
+                        0x2a, 0x0b, 0x75, 0xb3, 0xbb, 0x40, 0xa0, 0xc3, 0x40, 0xb1, 0xfa, 0xe0,
+                        0x43, 0x2b, 0x04, 0x74, 0x56, 0xd0, 0xff, 0xe8, 0x6e, 0xc3, 0xb4, 0xf9,
+                        0x57, 0x62, 0xef, 0x02, 0xdf, 0x5d, 0xa4, 0x0d,
+                    ],
+                ],
+            },
+        ]
+    }
+}
+
+pub(crate) mod fq {
+    use super::*;
+
+    pub(crate) fn permute() -> Vec<PermuteTestVector> {
+        use PermuteTestVector as TestVector;
+
+        vec![
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0x92, 0x27, 0xce, 0x68, 0xef, 0x9b, 0x22, 0x61, 0x8f, 0x2b, 0x6a, 0x4a,
+                        0xb1, 0x31, 0x98, 0xfc, 0x88, 0xc7, 0x18, 0x5f, 0x70, 0x97, 0xd2, 0x82,
+                        0x7b, 0x61, 0x2c, 0xd6, 0xdf, 0xcb, 0x1e, 0x26,
+                    ],
+                    [
+                        0x6f, 0xc6, 0x18, 0xd5, 0x26, 0x85, 0xca, 0x93, 0xea, 0xcd, 0x0f, 0x88,
+                        0x83, 0xb1, 0x64, 0x23, 0x28, 0x5c, 0x54, 0xf8, 0x8c, 0x15, 0x63, 0x32,
+                        0x87, 0x53, 0x76, 0x0b, 0x7e, 0x32, 0x76, 0x2c,
+                    ],
+                    [
+                        0xbf, 0x01, 0x29, 0x1c, 0xdb, 0xf7, 0xca, 0x95, 0xdc, 0x1b, 0xd3, 0x2d,
+                        0x9d, 0x84, 0xe0, 0x3a, 0x76, 0x9d, 0xb5, 0x99, 0x32, 0x87, 0x75, 0x8c,
+                        0x83, 0x44, 0x52, 0xce, 0xc0, 0x16, 0x23, 0x26,
+                    ],
+                ],
+            },
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x21, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xff, 0xff, 0xff, 0xff, 0x20, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfe, 0xff, 0xff, 0xff, 0x20, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0xb4, 0x4d, 0x25, 0x93, 0x5d, 0x21, 0xa4, 0xe4, 0xb4, 0xbb, 0xbc, 0x6a,
+                        0x7d, 0x5a, 0xb0, 0x47, 0xca, 0xc3, 0xd2, 0x0a, 0xd2, 0x22, 0xbb, 0x10,
+                        0x1b, 0xa6, 0x17, 0x93, 0x44, 0x70, 0x9e, 0x3a,
+                    ],
+                    [
+                        0x18, 0x34, 0xff, 0x7c, 0x1c, 0xc2, 0x2b, 0x6b, 0x77, 0x9e, 0xef, 0xb5,
+                        0x2a, 0x21, 0xef, 0x63, 0x4a, 0x8b, 0x4f, 0x50, 0xb3, 0xff, 0x26, 0xe2,
+                        0x53, 0x97, 0xfe, 0xe7, 0xaf, 0x35, 0x54, 0x23,
+                    ],
+                    [
+                        0xbf, 0xb4, 0x58, 0x01, 0xd2, 0xe7, 0xd4, 0x92, 0xe5, 0xae, 0x0d, 0xa7,
+                        0xce, 0x12, 0x94, 0xe4, 0x4a, 0x2f, 0xa1, 0xc3, 0x37, 0xee, 0x56, 0xb8,
+                        0x08, 0xb9, 0x7e, 0x0e, 0x5f, 0xc0, 0x46, 0x03,
+                    ],
+                ],
+            },
+        ]
+    }
+}
@@ -0,0 +1,227 @@
+//! The Rescue-Prime algebraic hash function.
+//!
+//! Each round of the Rescue-Prime permutation ([eprint 2020/1143]) consists of two
+//! steps. The first applies the S-box $x^\alpha$ to every state word, followed by the
+//! MDS matrix and a row of round constants. The second is identical, except that it
+//! uses the inverse S-box $x^{1/\alpha}$.
+//!
+//! [eprint 2020/1143]: https://eprint.iacr.org/2020/1143
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use halo2_proofs::arithmetic::FieldExt;
+
+use crate::primitives::poseidon::{hash_constant_length, ConstantLength, Domain, Mds, State};
+
+pub(crate) mod params;
+
+mod r128pow5t3;
+pub use r128pow5t3::R128Pow5T3;
+
+#[cfg(test)]
+pub(crate) mod test_vectors;
+
+/// A specification for a Rescue-Prime permutation.
+///
+/// The default implementations generate the parameters in the same way as the
+/// Rescue-Prime reference implementation.
+pub trait Spec<F: FieldExt, const T: usize, const RATE: usize>: fmt::Debug {
+    /// The target security level of this specification, in bits.
+    fn security_level() -> usize;
+
+    /// The S-box exponent $\alpha$ for this specification.
+    ///
+    /// By default, this is the smallest $\alpha \geq 3$ for which $x^\alpha$ is a
+    /// permutation of `F`.
+    fn alpha() -> u64 {
+        params::alpha::<F>()
+    }
+
+    /// The number of rounds $N$ for this specification.
+    fn rounds() -> usize {
+        params::rounds(T, T - RATE, Self::security_level(), Self::alpha())
+    }
+
+    /// Generates `(round_constants, mds)` corresponding to this specification.
+    ///
+    /// There are two rows of round constants per round: one for each step.
+    fn constants() -> (Vec<[F; T]>, Mds<F, T>) {
+        (
+            params::round_constants::<F, T>(T - RATE, Self::security_level(), Self::rounds()),
+            params::mds::<F, T>(),
+        )
+    }
+}
+
+/// Runs the Rescue-Prime permutation on the given state.
+pub(crate) fn permute<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize>(
+    state: &mut State<F, T>,
+    mds: &Mds<F, T>,
+    round_constants: &[[F; T]],
+) {
+    let alpha = [S::alpha()];
+    let alpha_inv = params::alpha_inv::<F>(S::alpha());
+
+    let step = |state: &mut State<F, T>, exp: &[u64], rcs: &[F; T]| {
+        let mut new_state = [F::zero(); T];
+        // Matrix multiplication
+        #[allow(clippy::needless_range_loop)]
+        for i in 0..T {
+            for j in 0..T {
+                new_state[i] += mds[i][j] * state[j].pow_vartime(exp);
+            }
+        }
+        for (word, (new_word, rc)) in state.iter_mut().zip(new_state.iter().zip(rcs.iter())) {
+            *word = *new_word + rc;
+        }
+    };
+
+    for rcs in round_constants.chunks(2) {
+        step(state, &alpha, &rcs[0]);
+        step(state, &alpha_inv, &rcs[1]);
+    }
+}
+
+/// A Rescue-Prime hash function, built around a sponge.
+///
+/// This uses the same sponge construction and [`Domain`]s as the Poseidon [`Hash`], so
+/// that the [`RescueChip`] can be used with the Poseidon gadgets. It does not implement
+/// the padding of the Rescue-Prime reference hash function.
+///
+/// [`Hash`]: crate::primitives::poseidon::Hash
+/// [`RescueChip`]: crate::poseidon::RescueChip
+pub struct Hash<
+    F: FieldExt,
+    S: Spec<F, T, RATE>,
+    D: Domain<F, RATE>,
+    const T: usize,
+    const RATE: usize,
+> {
+    state: State<F, T>,
+    mds: Mds<F, T>,
+    round_constants: Vec<[F; T]>,
+    _marker: PhantomData<(S, D)>,
+}
+
+impl<F: FieldExt, S: Spec<F, T, RATE>, D: Domain<F, RATE>, const T: usize, const RATE: usize>
+    fmt::Debug for Hash<F, S, D, T, RATE>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hash")
+            .field("width", &T)
+            .field("rate", &RATE)
+            .field("N", &S::rounds())
+            .field("domain", &D::name())
+            .finish()
+    }
+}
+
+impl<F: FieldExt, S: Spec<F, T, RATE>, D: Domain<F, RATE>, const T: usize, const RATE: usize>
+    Hash<F, S, D, T, RATE>
+{
+    /// Initializes a new hasher.
+    pub fn init() -> Self {
+        let (round_constants, mds) = S::constants();
+
+        let mut state = [F::zero(); T];
+        state[RATE] = D::initial_capacity_element();
+
+        Hash {
+            state,
+            mds,
+            round_constants,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, const L: usize>
+    Hash<F, S, ConstantLength<L>, T, RATE>
+{
+    /// Hashes the given input.
+    pub fn hash(mut self, message: [F; L]) -> F {
+        let (mds, round_constants) = (&self.mds, &self.round_constants);
+        hash_constant_length::<_, T, RATE, L>(&mut self.state, message, |state| {
+            permute::<F, S, T, RATE>(state, mds, round_constants)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::arithmetic::FieldExt;
+    use pasta_curves::{pallas, Fq};
+
+    use super::{permute, test_vectors, ConstantLength, Hash, R128Pow5T3, Spec};
+
+    #[test]
+    fn mds() {
+        // The reference implementation produces a matrix of small integers.
+        let (_, mds) = <R128Pow5T3 as Spec<pallas::Base, 3, 2>>::constants();
+        let expected = [
+            [125, -155, 31],
+            [3875, -4680, 806],
+            [100750, -121055, 20306],
+        ];
+        for (row, expected_row) in mds.iter().zip(expected.iter()) {
+            for (entry, expected) in row.iter().zip(expected_row.iter()) {
+                let expected_entry = pallas::Base::from(expected.abs() as u64);
+                let expected_entry = if *expected < 0 {
+                    -expected_entry
+                } else {
+                    expected_entry
+                };
+                assert_eq!(*entry, expected_entry);
+            }
+        }
+
+        // The MDS matrix only depends on the multiplicative generator.
+        let (_, mds) = <R128Pow5T3 as Spec<Fq, 3, 2>>::constants();
+        assert_eq!(mds[0][0], Fq::from(125));
+        assert_eq!(mds[0][1], -Fq::from(155));
+    }
+
+    #[test]
+    fn permute_test_vectors() {
+        fn check<F: FieldExt>(tvs: Vec<test_vectors::PermuteTestVector>) {
+            assert_eq!(<R128Pow5T3 as Spec<F, 3, 2>>::rounds(), 14);
+
+            let (round_constants, mds) = <R128Pow5T3 as Spec<F, 3, 2>>::constants();
+
+            for tv in tvs {
+                let mut state = [F::zero(); 3];
+                for (word, initial) in state.iter_mut().zip(tv.initial_state.iter()) {
+                    let mut repr = F::Repr::default();
+                    repr.as_mut().copy_from_slice(initial);
+                    *word = F::from_repr(repr).unwrap();
+                }
+
+                permute::<F, R128Pow5T3, 3, 2>(&mut state, &mds, &round_constants);
+
+                for (expected, actual) in tv.final_state.iter().zip(state.iter()) {
+                    assert_eq!(actual.to_repr().as_ref(), expected);
+                }
+            }
+        }
+
+        check::<pallas::Base>(test_vectors::fp::permute());
+        check::<Fq>(test_vectors::fq::permute());
+    }
+
+    #[test]
+    fn hash_equivalence() {
+        let message = [pallas::Base::from(6), pallas::Base::from(42)];
+
+        let (round_constants, mds) = <R128Pow5T3 as Spec<pallas::Base, 3, 2>>::constants();
+
+        let hasher = Hash::<_, R128Pow5T3, ConstantLength<2>, 3, 2>::init();
+        let result = hasher.hash(message);
+
+        // The result should be equivalent to just directly applying the permutation and
+        // taking the first state element as the output.
+        let mut state = [message[0], message[1], pallas::Base::from_u128(2 << 64)];
+        permute::<_, R128Pow5T3, 3, 2>(&mut state, &mds, &round_constants);
+        assert_eq!(state[0], result);
+    }
+}
@@ -0,0 +1,276 @@
+//! Parameter generation for Rescue-Prime, following the reference implementation
+//! accompanying [eprint 2020/1143].
+//!
+//! [eprint 2020/1143]: https://eprint.iacr.org/2020/1143
+
+use std::convert::TryInto;
+
+use halo2_proofs::arithmetic::FieldExt;
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+use crate::primitives::poseidon::Mds;
+
+/// Returns the little-endian 64-bit limbs of the modulus $p$ of the field `F`.
+fn modulus<F: FieldExt>() -> Vec<u64> {
+    let repr = (-F::one()).to_repr();
+    let mut limbs: Vec<u64> = repr
+        .as_ref()
+        .chunks(8)
+        .map(|chunk| {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(bytes)
+        })
+        .collect();
+    // p is odd, so p - 1 is even and adding 1 cannot carry.
+    limbs[0] += 1;
+    limbs
+}
+
+/// Returns `a mod d` for a multi-limb `a`.
+fn rem_small(a: &[u64], d: u64) -> u64 {
+    a.iter()
+        .rev()
+        .fold(0u128, |rem, limb| ((rem << 64) | *limb as u128) % d as u128) as u64
+}
+
+/// Returns the number of bits in the modulus $p$ of the field `F`.
+fn modulus_bits<F: FieldExt>() -> usize {
+    let p = modulus::<F>();
+    let top = p
+        .iter()
+        .rposition(|limb| *limb != 0)
+        .expect("p is non-zero");
+    64 * top + (64 - p[top].leading_zeros() as usize)
+}
+
+/// Returns the modulus $p$ of the field `F` as a decimal string.
+fn modulus_decimal<F: FieldExt>() -> String {
+    const TEN_19: u64 = 10_000_000_000_000_000_000;
+
+    let mut p = modulus::<F>();
+    let mut chunks = vec![];
+    while p.iter().any(|limb| *limb != 0) {
+        // Divide p by 10^19 in place, keeping the remainder.
+        let mut rem = 0u128;
+        for limb in p.iter_mut().rev() {
+            let cur = (rem << 64) | *limb as u128;
+            *limb = (cur / TEN_19 as u128) as u64;
+            rem = cur % TEN_19 as u128;
+        }
+        chunks.push(rem as u64);
+    }
+
+    let mut chunks = chunks.into_iter().rev();
+    let head = chunks.next().unwrap_or(0).to_string();
+    chunks.fold(head, |acc, chunk| format!("{}{:019}", acc, chunk))
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Returns the smallest $\alpha \geq 3$ such that $x \mapsto x^\alpha$ is a permutation
+/// of `F`, i.e. such that $\gcd(\alpha, p - 1) = 1$.
+pub(crate) fn alpha<F: FieldExt>() -> u64 {
+    let mut p_minus_1 = modulus::<F>();
+    p_minus_1[0] -= 1;
+
+    (3..)
+        .find(|alpha| gcd(*alpha, rem_small(&p_minus_1, *alpha)) == 1)
+        .expect("p - 1 has finitely many factors")
+}
+
+/// Returns the little-endian 64-bit limbs of $\alpha^{-1} \bmod (p - 1)$, for use with
+/// [`Field::pow_vartime`].
+///
+/// [`Field::pow_vartime`]: ff::Field::pow_vartime
+pub(crate) fn alpha_inv<F: FieldExt>(alpha: u64) -> Vec<u64> {
+    let mut p_minus_1 = modulus::<F>();
+    p_minus_1[0] -= 1;
+
+    // alpha^-1 = (k * (p - 1) + 1) / alpha, for the k in [1, alpha) that makes the
+    // numerator divisible by alpha.
+    let r = rem_small(&p_minus_1, alpha) as u128;
+    let k = (1..alpha)
+        .find(|k| (*k as u128 * r + 1) % alpha as u128 == 0)
+        .expect("alpha is coprime to p - 1");
+
+    // Compute k * (p - 1) + 1.
+    let mut carry = 1u128;
+    let mut numerator: Vec<u64> = p_minus_1
+        .iter()
+        .map(|limb| {
+            let cur = *limb as u128 * k as u128 + carry;
+            carry = cur >> 64;
+            cur as u64
+        })
+        .collect();
+    numerator.push(carry as u64);
+
+    // Divide by alpha.
+    let mut rem = 0u128;
+    for limb in numerator.iter_mut().rev() {
+        let cur = (rem << 64) | *limb as u128;
+        *limb = (cur / alpha as u128) as u64;
+        rem = cur % alpha as u128;
+    }
+    assert_eq!(rem, 0);
+
+    numerator
+}
+
+/// Returns the number of rounds $N$ for a Rescue-Prime instance with state width `m`
+/// and the given capacity, at a security level of `security_level` bits.
+///
+/// This finds the smallest number of rounds $\ell_1$ that resists the Gröbner basis
+/// attack, and then returns $\lceil 1.5 \cdot \max(5, \ell_1) \rceil$.
+pub(crate) fn rounds(m: usize, capacity: usize, security_level: usize, alpha: u64) -> usize {
+    let rate = m - capacity;
+    let dcon = |n: usize| ((alpha - 1) as usize * m * (n - 1)) / 2 + 2;
+    let v = |n: usize| m * (n - 1) + rate;
+
+    // log2 of the binomial coefficient (n choose k).
+    let log2_binomial = |n: usize, k: usize| {
+        (1..=k).fold(0.0, |acc, i| acc + ((n - k + i) as f64 / i as f64).log2())
+    };
+
+    let l1 = (1..25)
+        .find(|l1| 2.0 * log2_binomial(v(*l1) + dcon(*l1), v(*l1)) > security_level as f64)
+        .unwrap_or(25);
+
+    (3 * std::cmp::max(5, l1) + 1) / 2
+}
+
+/// Returns the Rescue-Prime MDS matrix for a state of width `T`.
+///
+/// This is the transpose of the right half of the systematic generator matrix (in
+/// reduced row echelon form) of the Vandermonde matrix $V_{i,j} = g^{ij}$, where $g$ is
+/// the multiplicative generator of `F`. For the Pasta fields, $g = 5$ is also the
+/// smallest primitive element, as the reference implementation selects.
+pub(crate) fn mds<F: FieldExt, const T: usize>() -> Mds<F, T> {
+    let g = F::multiplicative_generator();
+
+    let mut v: Vec<Vec<F>> = (0..T)
+        .map(|i| {
+            (0..(2 * T))
+                .map(|j| g.pow_vartime(&[(i * j) as u64]))
+                .collect()
+        })
+        .collect();
+
+    // Reduce the left half of V to the identity. Its rows are distinct powers of g, so
+    // this is a non-singular Vandermonde matrix.
+    for col in 0..T {
+        let pivot = (col..T)
+            .find(|row| !bool::from(v[*row][col].is_zero()))
+            .expect("Vandermonde matrix is non-singular");
+        v.swap(col, pivot);
+
+        let inv = v[col][col].invert().unwrap();
+        for entry in v[col].iter_mut() {
+            *entry *= inv;
+        }
+
+        let pivot_row = v[col].clone();
+        for (row, entries) in v.iter_mut().enumerate() {
+            if row != col {
+                let factor = entries[col];
+                for (entry, pivot_entry) in entries.iter_mut().zip(pivot_row.iter()) {
+                    *entry -= factor * pivot_entry;
+                }
+            }
+        }
+    }
+
+    let mut mds = [[F::zero(); T]; T];
+    for (i, row) in mds.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            *entry = v[j][T + i];
+        }
+    }
+    mds
+}
+
+/// Returns the $2N$ rows of Rescue-Prime round constants, sampled from SHAKE256 as in
+/// the reference implementation.
+pub(crate) fn round_constants<F: FieldExt, const T: usize>(
+    capacity: usize,
+    security_level: usize,
+    rounds: usize,
+) -> Vec<[F; T]> {
+    let bytes_per_int = (modulus_bits::<F>() + 7) / 8 + 1;
+    assert!(bytes_per_int <= 64);
+
+    let seed = format!(
+        "Rescue-XLIX({},{},{},{})",
+        modulus_decimal::<F>(),
+        T,
+        capacity,
+        security_level
+    );
+    let mut shake = Shake256::default();
+    shake.update(seed.as_bytes());
+    let mut reader = shake.finalize_xof();
+
+    (0..(2 * rounds))
+        .map(|_| {
+            let constants: Vec<F> = (0..T)
+                .map(|_| {
+                    let mut bytes = [0u8; 64];
+                    reader.read(&mut bytes[..bytes_per_int]);
+                    F::from_bytes_wide(&bytes)
+                })
+                .collect();
+            constants.try_into().unwrap()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::arithmetic::FieldExt;
+    use pasta_curves::{Fp, Fq};
+
+    use super::{alpha, alpha_inv, modulus_bits, modulus_decimal, rounds};
+
+    #[test]
+    fn pasta_parameters() {
+        assert_eq!(modulus_bits::<Fp>(), 255);
+        assert_eq!(
+            modulus_decimal::<Fp>(),
+            "28948022309329048855892746252171976963363056481941560715954676764349967630337"
+        );
+        assert_eq!(
+            modulus_decimal::<Fq>(),
+            "28948022309329048855892746252171976963363056481941647379679742748393362948097"
+        );
+
+        assert_eq!(alpha::<Fp>(), 5);
+        assert_eq!(alpha::<Fq>(), 5);
+
+        let x = Fp::from_u128(0x1234_5678_9abc_def0);
+        assert_eq!(x.pow_vartime(&[5]).pow_vartime(&alpha_inv::<Fp>(5)), x);
+        let x = Fq::from_u128(0x1234_5678_9abc_def0);
+        assert_eq!(x.pow_vartime(&[5]).pow_vartime(&alpha_inv::<Fq>(5)), x);
+    }
+
+    #[test]
+    fn number_of_rounds() {
+        assert_eq!(rounds(2, 1, 128, 5), 20);
+        assert_eq!(rounds(3, 1, 128, 5), 14);
+        assert_eq!(rounds(4, 1, 128, 5), 11);
+        assert_eq!(rounds(5, 1, 128, 5), 9);
+        assert_eq!(rounds(12, 4, 128, 5), 8);
+
+        // The instance in the Anatomy of a STARK tutorial, with p = 407 * 2^119 + 1.
+        assert_eq!(rounds(2, 1, 128, 3), 27);
+    }
+}
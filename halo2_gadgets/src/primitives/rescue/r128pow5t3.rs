@@ -0,0 +1,17 @@
+use halo2_proofs::arithmetic::FieldExt;
+
+use super::Spec;
+
+/// Rescue-Prime with a width of 3 field elements (and a rate of 2), at a security level
+/// of 128 bits.
+///
+/// On either of the Pasta fields, the reference parameter generation selects
+/// $\alpha = 5$ and $N = 14$ rounds.
+#[derive(Debug)]
+pub struct R128Pow5T3;
+
+impl<F: FieldExt> Spec<F, 3, 2> for R128Pow5T3 {
+    fn security_level() -> usize {
+        128
+    }
+}
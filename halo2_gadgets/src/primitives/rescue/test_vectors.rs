@@ -0,0 +1,174 @@
+//! Test vectors for [`R128Pow5T3`].
+//!
+//! <https://eprint.iacr.org/2020/1143>, using parameters from `rescue_prime.sage` with
+//! $m = 3$, a capacity of 1, and a security level of 128 bits.
+//!
+//! [`R128Pow5T3`]: super::R128Pow5T3
+
+pub(crate) use crate::primitives::poseidon::test_vectors::PermuteTestVector;
+
+pub(crate) mod fp {
+    use super::*;
+
+    pub(crate) fn permute() -> Vec<PermuteTestVector> {
+        use PermuteTestVector as TestVector;
+
+        vec![
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0x98, 0x49, 0xef, 0x89, 0x0a, 0x25, 0x19, 0x61, 0x4e, 0x7e, 0xb8, 0x5d,
+                        0x41, 0xe0, 0x95, 0xbc, 0xe3, 0x42, 0xf4, 0x6e, 0x76, 0x05, 0x36, 0x66,
+                        0x7f, 0x50, 0x97, 0xc6, 0x03, 0x44, 0x7e, 0x37,
+                    ],
+                    [
+                        0x41, 0x5d, 0xa7, 0xb9, 0xd0, 0xa5, 0x25, 0xdc, 0x03, 0x93, 0x5e, 0x97,
+                        0x49, 0xfa, 0xa0, 0x5c, 0xc7, 0x3b, 0x88, 0x45, 0xf3, 0x29, 0x23, 0xcc,
+                        0x64, 0x82, 0xc2, 0x7c, 0x8b, 0x25, 0x12, 0x2b,
+                    ],
+                    [
+                        0xd2, 0xb7, 0xe6, 0x7f, 0xd8, 0x74, 0x42, 0xd7, 0x80, 0x9f, 0x8a, 0x2a,
+                        0x8a, 0x0b, 0x39, 0x9b, 0xaa, 0xbf, 0x01, 0x67, 0xba, 0xc5, 0x62, 0x5a,
+                        0xe5, 0x31, 0x7c, 0x02, 0x2b, 0x84, 0x95, 0x15,
+                    ],
+                ],
+            },
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0xed, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xff, 0xff, 0xff, 0xff, 0xec, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfe, 0xff, 0xff, 0xff, 0xec, 0x30, 0x2d, 0x99, 0x1b, 0xf9, 0x4c, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0x39, 0x81, 0x3b, 0xba, 0x7f, 0xe1, 0xb2, 0xe7, 0x29, 0xf5, 0x89, 0x2d,
+                        0x24, 0x86, 0xe4, 0xde, 0xf2, 0xcb, 0x08, 0xc9, 0x43, 0x5f, 0x49, 0x28,
+                        0x66, 0x4f, 0xd4, 0xb0, 0xef, 0x54, 0xe5, 0x26,
+                    ],
+                    [
+                        0xab, 0x85, 0xa5, 0x4a, 0xa6, 0xac, 0xef, 0x5e, 0x61, 0xd4, 0x4e, 0xa1,
+                        0x93, 0x27, 0x25, 0x44, 0x58, 0x62, 0x0b, 0x5f, 0x04, 0x78, 0x09, 0x46,
+                        0x5c, 0x80, 0xe6, 0xd6, 0x11, 0xf7, 0xdb, 0x13,
+                    ],
+                    [
+                        0xb8, 0x43, 0x51, 0xb8, 0x94, 0x7c, 0xb2, 0x34, 0xdc, 0xb7, 0x20, 0xef,
+                        0x45, 0xe6, 0x86, 0xeb, 0xfc, 0x86, 0x59, 0xc0, 0xf6, 0xaa, 0x11, 0x3a,
+                        0x05, 0x80, 0xd6, 0x57, 0x32, 0x6c, 0xdd, 0x25,
+                    ],
+                ],
+            },
+        ]
+    }
+}
+
+pub(crate) mod fq {
+    use super::*;
+
+    pub(crate) fn permute() -> Vec<PermuteTestVector> {
+        use PermuteTestVector as TestVector;
+
+        vec![
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                    [
+                        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0xe2, 0xa0, 0xa9, 0x10, 0x3e, 0xcb, 0x37, 0x34, 0x8a, 0x60, 0x22, 0xa6,
+                        0xe1, 0x24, 0x6d, 0xc2, 0x30, 0x8a, 0x72, 0x6c, 0x80, 0xca, 0x2a, 0x79,
+                        0x81, 0xcc, 0xfc, 0x3d, 0x3d, 0xe1, 0x1b, 0x09,
+                    ],
+                    [
+                        0xfb, 0xae, 0x4b, 0xb7, 0xe4, 0xe0, 0xb2, 0xcc, 0x18, 0xc9, 0xf9, 0x5a,
+                        0x8b, 0x14, 0x6b, 0xdb, 0x5b, 0xf9, 0xfc, 0x1a, 0xb0, 0x48, 0x66, 0xc0,
+                        0xc1, 0x8b, 0x0f, 0x5e, 0xb9, 0xd7, 0x22, 0x39,
+                    ],
+                    [
+                        0xdb, 0xde, 0xb1, 0xff, 0xe2, 0x21, 0xf9, 0x7c, 0x71, 0xdd, 0x96, 0x13,
+                        0x62, 0x45, 0xe2, 0x83, 0x1d, 0xe3, 0x84, 0x19, 0xf0, 0x6f, 0x82, 0x54,
+                        0xbb, 0x70, 0xaf, 0x5a, 0x22, 0x9b, 0x80, 0x29,
+                    ],
+                ],
+            },
+            TestVector {
+                initial_state: [
+                    [
+                        0x00, 0x00, 0x00, 0x00, 0x21, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xff, 0xff, 0xff, 0xff, 0x20, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                    [
+                        0xfe, 0xff, 0xff, 0xff, 0x20, 0xeb, 0x46, 0x8c, 0xdd, 0xa8, 0x94, 0x09,
+                        0xfc, 0x98, 0x46, 0x22, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    ],
+                ],
+                final_state: [
+                    [
+                        0x4c, 0x43, 0xd1, 0x2a, 0x71, 0x71, 0x79, 0x4a, 0x90, 0xe4, 0x9d, 0xd4,
+                        0x1e, 0x95, 0xdf, 0x02, 0x1e, 0x85, 0x1a, 0xc8, 0x7a, 0xe1, 0xd1, 0x9b,
+                        0xbe, 0x26, 0x11, 0xd9, 0x04, 0x9f, 0x31, 0x2b,
+                    ],
+                    [
+                        0x39, 0x9e, 0x25, 0x78, 0x00, 0xad, 0x6d, 0x70, 0x5f, 0x04, 0xa5, 0x8c,
+                        0x36, 0x0e, 0x87, 0xc2, 0xc3, 0x4f, 0x76, 0xfd, 0xc8, 0xb7, 0xc8, 0x2d,
+                        0xb9, 0xc5, 0xfe, 0xf4, 0x58, 0x0b, 0xbb, 0x14,
+                    ],
+                    [
+                        0x4f, 0x0a, 0x3c, 0x70, 0x44, 0x23, 0x7a, 0xfb, 0x68, 0x72, 0x30, 0x1f,
+                        0xec, 0xa8, 0xa4, 0x47, 0x8f, 0xf8, 0x8c, 0x4c, 0x62, 0x68, 0xb5, 0x74,
+                        0x16, 0x11, 0x61, 0x61, 0x86, 0xed, 0x88, 0x3d,
+                    ],
+                ],
+            },
+        ]
+    }
+}
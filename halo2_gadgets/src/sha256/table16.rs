@@ -3,6 +3,7 @@ use std::marker::PhantomData;
 
 use super::Sha256Instructions;
 use halo2_proofs::{
+    arithmetic::FieldExt,
     circuit::{AssignedCell, Chip, Layouter, Region},
     pasta::pallas,
     plonk::{Advice, Any, Assigned, Column, ConstraintSystem, Error},
@@ -324,6 +325,44 @@ impl Table16Chip {
     ) -> Result<(), Error> {
         SpreadTableChip::load(config.lookup, layouter)
     }
+
+    /// Given an initialized state and an input message block whose words have
+    /// already been assigned in the circuit, compress the message block and
+    /// return the final state.
+    ///
+    /// Each word $W_i$ of the message schedule is constrained to be equal to
+    /// `input[i]`. The message schedule itself constrains $W_i$ to 32 bits.
+    pub fn compress_assigned(
+        &self,
+        layouter: &mut impl Layouter<pallas::Base>,
+        initialized_state: &State,
+        input: [AssignedCell<pallas::Base, pallas::Base>; super::BLOCK_SIZE],
+    ) -> Result<State, Error> {
+        let config = self.config();
+
+        let block: [BlockWord; super::BLOCK_SIZE] = {
+            let mut block = [BlockWord(None); super::BLOCK_SIZE];
+            for (word, cell) in block.iter_mut().zip(input.iter()) {
+                *word = BlockWord(cell.value().map(|value| value.get_lower_32()));
+            }
+            block
+        };
+
+        let (w, w_halves) = config.message_schedule.process(layouter, block)?;
+        layouter.assign_region(
+            || "constrain input words",
+            |mut region| {
+                for (w_i, cell) in w.iter().zip(input.iter()) {
+                    region.constrain_equal(w_i.cell(), cell.cell())?;
+                }
+                Ok(())
+            },
+        )?;
+
+        config
+            .compression
+            .compress(layouter, initialized_state.clone(), w_halves)
+    }
 }
 
 impl Sha256Instructions<pallas::Base> for Table16Chip {
@@ -8,6 +8,8 @@ use halo2_proofs::{
 use pasta_curves::arithmetic::FieldExt;
 use std::{array, ops::Range};
 
+pub mod bytes;
+pub mod canonical_decompose;
 pub mod cond_swap;
 pub mod decompose_running_sum;
 pub mod lookup_range_check;
@@ -0,0 +1,785 @@
+//! Range-checked bytes, and gadgets to pack them into the input shapes of the
+//! hash gadgets in this crate.
+//!
+//! Each byte is range-checked to 8 bits using a short lookup range check on the
+//! 10-bit lookup table (see [`LookupRangeCheckConfig`]). Bytes are packed into a
+//! field element using a running accumulator $\mathsf{acc}$:
+//!
+//!   $$\mathsf{acc}_0 = 0, \quad \mathsf{acc}_{i+1} = 2^8 \cdot \mathsf{acc}_i + b_i,$$
+//!
+//! where $\mathsf{acc}_0$ is loaded from a fixed column. Feeding the bytes in
+//! order gives a big-endian packing, and feeding them in reverse order gives a
+//! little-endian packing.
+
+use ff::PrimeFieldBits;
+use halo2_proofs::{
+    circuit::{AssignedCell, Cell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+use pasta_curves::{arithmetic::FieldExt, pallas};
+
+use super::lookup_range_check::LookupRangeCheckConfig;
+use crate::{
+    ecc::FixedPoints,
+    primitives::sinsemilla,
+    sinsemilla::{chip::SinsemillaChip, CommitDomains, HashDomains, MessagePiece},
+};
+
+/// Number of bits in a byte.
+const BYTE_NUM_BITS: usize = 8;
+
+/// A byte that has been range-checked to 8 bits.
+#[derive(Clone, Debug)]
+pub struct AssignedByte<F: FieldExt>(AssignedCell<F, F>);
+
+impl<F: FieldExt> AssignedByte<F> {
+    /// The value of this byte, if known.
+    pub fn value(&self) -> Option<u8> {
+        self.0.value().map(|value| value.get_lower_32() as u8)
+    }
+
+    /// The cell containing this byte.
+    pub fn cell(&self) -> Cell {
+        self.0.cell()
+    }
+}
+
+/// A sequence of range-checked bytes.
+#[derive(Clone, Debug)]
+pub struct AssignedBytes<F: FieldExt>(Vec<AssignedByte<F>>);
+
+impl<F: FieldExt> std::ops::Deref for AssignedBytes<F> {
+    type Target = [AssignedByte<F>];
+
+    fn deref(&self) -> &[AssignedByte<F>] {
+        &self.0
+    }
+}
+
+impl<F: FieldExt> AssignedBytes<F> {
+    /// The values of these bytes, if known.
+    pub fn value(&self) -> Option<Vec<u8>> {
+        self.0.iter().map(|byte| byte.value()).collect()
+    }
+}
+
+/// Configuration that provides methods for range-checking and packing bytes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BytesConfig<F: FieldExt + PrimeFieldBits> {
+    q_pack: Selector,
+    advices: [Column<Advice>; 2],
+    lookup_config: LookupRangeCheckConfig<F, { sinsemilla::K }>,
+}
+
+impl<F: FieldExt + PrimeFieldBits> BytesConfig<F> {
+    /// Bytes are copied into `advices[0]`, and the packing accumulator is
+    /// assigned in `advices[1]`.
+    ///
+    /// The circuit MUST have a fixed column enabled for constants, which is
+    /// used to load the initial accumulator and by the short range checks.
+    ///
+    /// # Side-effects
+    ///
+    /// Both `advices` columns will be equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 2],
+        lookup_config: LookupRangeCheckConfig<F, { sinsemilla::K }>,
+    ) -> Self {
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+
+        let config = Self {
+            q_pack: meta.selector(),
+            advices,
+            lookup_config,
+        };
+
+        meta.create_gate("pack bytes", |meta| {
+            let q_pack = meta.query_selector(config.q_pack);
+            let byte = meta.query_advice(config.advices[0], Rotation::cur());
+            let acc_prev = meta.query_advice(config.advices[1], Rotation::prev());
+            let acc_cur = meta.query_advice(config.advices[1], Rotation::cur());
+
+            // acc_{i+1} = 2^8 ⋅ acc_i + b_i
+            vec![q_pack * (acc_cur - (acc_prev * F::from(1 << BYTE_NUM_BITS) + byte))]
+        });
+
+        config
+    }
+
+    /// Witnesses `bytes` and range-checks each of them to 8 bits.
+    pub fn witness_bytes(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: &[Option<u8>],
+    ) -> Result<AssignedBytes<F>, Error> {
+        bytes
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| {
+                self.lookup_config
+                    .witness_short_check(
+                        layouter.namespace(|| format!("byte {}", idx)),
+                        byte.map(|byte| F::from(byte as u64)),
+                        BYTE_NUM_BITS,
+                    )
+                    .map(AssignedByte)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(AssignedBytes)
+    }
+
+    /// Range-checks each of the existing `cells` to 8 bits.
+    ///
+    /// Returns an error if any cell is not in a column that was passed to
+    /// [`ConstraintSystem::enable_equality`] during circuit configuration.
+    pub fn range_check_bytes(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cells: &[AssignedCell<F, F>],
+    ) -> Result<AssignedBytes<F>, Error> {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| {
+                self.lookup_config
+                    .copy_short_check(
+                        layouter.namespace(|| format!("byte {}", idx)),
+                        cell.clone(),
+                        BYTE_NUM_BITS,
+                    )
+                    .map(|_| AssignedByte(cell.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(AssignedBytes)
+    }
+
+    /// Packs `bytes` into a single field element, interpreting them as a
+    /// little-endian integer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` do not fit within `F::CAPACITY` bits.
+    pub fn pack_le(
+        &self,
+        layouter: impl Layouter<F>,
+        bytes: &[AssignedByte<F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.pack(layouter, bytes.iter().rev())
+    }
+
+    /// Packs `bytes` into a single field element, interpreting them as a
+    /// big-endian integer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` do not fit within `F::CAPACITY` bits.
+    pub fn pack_be(
+        &self,
+        layouter: impl Layouter<F>,
+        bytes: &[AssignedByte<F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.pack(layouter, bytes.iter())
+    }
+
+    /// Packs `bytes` into as few field elements as possible, each containing
+    /// up to `F::CAPACITY / 8` bytes in little-endian order.
+    ///
+    /// This is suitable for absorbing arbitrary bytes into a hash taking field
+    /// elements, such as Poseidon.
+    pub fn pack_field_elems(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: &[AssignedByte<F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let bytes_per_elem = F::CAPACITY as usize / BYTE_NUM_BITS;
+        bytes
+            .chunks(bytes_per_elem)
+            .enumerate()
+            .map(|(idx, chunk)| {
+                self.pack_le(
+                    layouter.namespace(|| format!("field element {}", idx)),
+                    chunk,
+                )
+            })
+            .collect()
+    }
+
+    /// Accumulates `bytes` from the most significant to the least significant.
+    fn pack<'a>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: impl Iterator<Item = &'a AssignedByte<F>> + ExactSizeIterator + Clone,
+    ) -> Result<AssignedCell<F, F>, Error>
+    where
+        F: 'a,
+    {
+        assert!(bytes.len() * BYTE_NUM_BITS <= F::CAPACITY as usize);
+
+        layouter.assign_region(
+            || format!("pack {} bytes", bytes.len()),
+            |mut region| {
+                let mut acc = region.assign_advice_from_constant(
+                    || "acc_0",
+                    self.advices[1],
+                    0,
+                    F::zero(),
+                )?;
+
+                for (idx, byte) in bytes.clone().enumerate() {
+                    let offset = idx + 1;
+
+                    // Enable packing gate
+                    self.q_pack.enable(&mut region, offset)?;
+
+                    // Copy byte
+                    byte.0.copy_advice(
+                        || format!("b_{}", idx),
+                        &mut region,
+                        self.advices[0],
+                        offset,
+                    )?;
+
+                    // acc_{i+1} = 2^8 ⋅ acc_i + b_i
+                    let acc_val = acc
+                        .value()
+                        .zip(byte.0.value())
+                        .map(|(acc, byte)| *acc * F::from(1 << BYTE_NUM_BITS) + *byte);
+                    acc = region.assign_advice(
+                        || format!("acc_{}", offset),
+                        self.advices[1],
+                        offset,
+                        || acc_val.ok_or(Error::Synthesis),
+                    )?;
+                }
+
+                Ok(acc)
+            },
+        )
+    }
+}
+
+impl BytesConfig<pallas::Base> {
+    /// Converts `bytes` into a Sinsemilla [`MessagePiece`]. The bytes are
+    /// interpreted as a little-endian bitstring, matching the bit order used
+    /// by Sinsemilla messages.
+    ///
+    /// The message piece is constrained to be equal to the packed bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bitlength of `bytes` is not a multiple of `K`, or if the
+    /// resulting piece exceeds the maximum number of words in a piece.
+    #[allow(clippy::type_complexity)]
+    pub fn to_message_piece<Hash, Commit, Fixed>(
+        &self,
+        chip: SinsemillaChip<Hash, Commit, Fixed>,
+        mut layouter: impl Layouter<pallas::Base>,
+        bytes: &[AssignedByte<pallas::Base>],
+    ) -> Result<
+        MessagePiece<
+            pallas::Affine,
+            SinsemillaChip<Hash, Commit, Fixed>,
+            { sinsemilla::K },
+            { sinsemilla::C },
+        >,
+        Error,
+    >
+    where
+        Hash: HashDomains<pallas::Affine>,
+        Fixed: FixedPoints<pallas::Affine>,
+        Commit: CommitDomains<pallas::Affine, Fixed, Hash>,
+        SinsemillaChip<Hash, Commit, Fixed>: Eq,
+    {
+        let num_bits = bytes.len() * BYTE_NUM_BITS;
+        assert_eq!(num_bits % sinsemilla::K, 0);
+
+        let packed = self.pack_le(layouter.namespace(|| "pack message piece"), bytes)?;
+
+        let piece = MessagePiece::from_field_elem(
+            chip,
+            layouter.namespace(|| "witness message piece"),
+            packed.value().cloned(),
+            num_bits / sinsemilla::K,
+        )?;
+
+        layouter.assign_region(
+            || "constrain message piece",
+            |mut region| region.constrain_equal(packed.cell(), piece.inner().cell()),
+        )?;
+
+        Ok(piece)
+    }
+
+    /// Packs `bytes` into 32-bit SHA-256 message words. Each group of four
+    /// bytes is interpreted as a big-endian integer, as specified by SHA-256.
+    ///
+    /// The returned cells can be passed to [`Table16Chip::compress_assigned`],
+    /// which constrains the message schedule to use them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `bytes` is not a multiple of 4.
+    ///
+    /// [`Table16Chip::compress_assigned`]: crate::sha256::Table16Chip::compress_assigned
+    #[cfg(feature = "unstable")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+    pub fn to_block_words(
+        &self,
+        mut layouter: impl Layouter<pallas::Base>,
+        bytes: &[AssignedByte<pallas::Base>],
+    ) -> Result<Vec<AssignedCell<pallas::Base, pallas::Base>>, Error> {
+        assert_eq!(bytes.len() % 4, 0);
+        bytes
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(idx, word)| self.pack_be(layouter.namespace(|| format!("W_{}", idx)), word))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ecc::tests::TestFixedBases,
+        primitives::sinsemilla::K,
+        sinsemilla::{
+            chip::{SinsemillaChip, SinsemillaConfig},
+            tests::{TestCommitDomain, TestHashDomain},
+        },
+    };
+    use ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pasta_curves::pallas;
+
+    #[derive(Default)]
+    struct MyCircuit {
+        bytes: Vec<Option<u8>>,
+        // A value that is range-checked as a byte after being witnessed.
+        unchecked: Option<pallas::Base>,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = (BytesConfig<pallas::Base>, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                bytes: vec![None; self.bytes.len()],
+                unchecked: None,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [meta.advice_column(), meta.advice_column()];
+            let running_sum = meta.advice_column();
+            let table_idx = meta.lookup_table_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let lookup_config =
+                LookupRangeCheckConfig::<pallas::Base, K>::configure(meta, running_sum, table_idx);
+            (
+                BytesConfig::configure(meta, advices, lookup_config),
+                advices[0],
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (config, advice) = config;
+            config.lookup_config.load(&mut layouter)?;
+
+            let bytes = config.witness_bytes(layouter.namespace(|| "bytes"), &self.bytes)?;
+
+            // At most 31 bytes fit in a single field element.
+            let prefix = &bytes[..std::cmp::min(bytes.len(), 31)];
+            let le = config.pack_le(layouter.namespace(|| "pack_le"), prefix)?;
+            let be = config.pack_be(layouter.namespace(|| "pack_be"), prefix)?;
+            let elems =
+                config.pack_field_elems(layouter.namespace(|| "pack_field_elems"), &bytes)?;
+            assert_eq!(elems.len(), (bytes.len() + 30) / 31);
+
+            if let Some(bytes) = AssignedBytes(prefix.to_vec()).value() {
+                let le_expected = bytes.iter().rev().fold(pallas::Base::zero(), |acc, byte| {
+                    acc * pallas::Base::from(256) + pallas::Base::from(*byte as u64)
+                });
+                let be_expected = bytes.iter().fold(pallas::Base::zero(), |acc, byte| {
+                    acc * pallas::Base::from(256) + pallas::Base::from(*byte as u64)
+                });
+                assert_eq!(le.value(), Some(&le_expected));
+                assert_eq!(be.value(), Some(&be_expected));
+            }
+
+            let unchecked = layouter.assign_region(
+                || "unchecked",
+                |mut region| {
+                    region.assign_advice(
+                        || "unchecked",
+                        advice,
+                        0,
+                        || self.unchecked.ok_or(Error::Synthesis),
+                    )
+                },
+            )?;
+            config.range_check_bytes(layouter.namespace(|| "range_check_bytes"), &[unchecked])?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pack_bytes() {
+        let circuit = MyCircuit {
+            bytes: (0..40u8).map(|byte| Some(byte.wrapping_mul(37))).collect(),
+            unchecked: Some(pallas::Base::from(0xff)),
+        };
+        let prover = MockProver::<pallas::Base>::run(11, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn range_check_bytes() {
+        let circuit = MyCircuit {
+            bytes: vec![Some(0xff)],
+            unchecked: Some(pallas::Base::from(0x100)),
+        };
+        let prover = MockProver::<pallas::Base>::run(11, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Witnesses `values` in `column` without constraining them.
+    fn assign_unchecked(
+        mut layouter: impl Layouter<pallas::Base>,
+        column: Column<Advice>,
+        values: &[Option<pallas::Base>],
+    ) -> Result<Vec<AssignedCell<pallas::Base, pallas::Base>>, Error> {
+        layouter.assign_region(
+            || "unchecked bytes",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, value)| {
+                        region.assign_advice(
+                            || format!("unchecked {}", idx),
+                            column,
+                            idx,
+                            || value.ok_or(Error::Synthesis),
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn to_values(bytes: &[u64]) -> Vec<Option<pallas::Base>> {
+        bytes
+            .iter()
+            .map(|byte| Some(pallas::Base::from(*byte)))
+            .collect()
+    }
+
+    struct MessagePieceCircuit {
+        // Values that are range-checked as bytes and packed into a message piece.
+        bytes: Vec<Option<pallas::Base>>,
+    }
+
+    impl Circuit<pallas::Base> for MessagePieceCircuit {
+        type Config = (
+            BytesConfig<pallas::Base>,
+            SinsemillaConfig<TestHashDomain, TestCommitDomain, TestFixedBases>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                bytes: vec![None; self.bytes.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let running_sum = meta.advice_column();
+            let fixed_y_q = meta.fixed_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let table_idx = meta.lookup_table_column();
+            let lookup = (
+                table_idx,
+                meta.lookup_table_column(),
+                meta.lookup_table_column(),
+            );
+
+            let lookup_config =
+                LookupRangeCheckConfig::<pallas::Base, K>::configure(meta, running_sum, table_idx);
+            let sinsemilla_config = SinsemillaChip::configure(
+                meta,
+                advices,
+                advices[2],
+                fixed_y_q,
+                lookup,
+                lookup_config,
+            );
+            (
+                BytesConfig::configure(meta, [advices[0], advices[1]], lookup_config),
+                sinsemilla_config,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let (config, sinsemilla_config) = config;
+            // This also loads the range check table.
+            SinsemillaChip::load(sinsemilla_config.clone(), &mut layouter)?;
+            let chip = SinsemillaChip::construct(sinsemilla_config);
+
+            let cells = assign_unchecked(
+                layouter.namespace(|| "unchecked"),
+                config.advices[0],
+                &self.bytes,
+            )?;
+            let bytes = config.range_check_bytes(layouter.namespace(|| "bytes"), &cells)?;
+            let piece =
+                config.to_message_piece(chip, layouter.namespace(|| "to_message_piece"), &bytes)?;
+
+            assert_eq!(piece.inner().num_words(), bytes.len() * 8 / K);
+            if let Some(bytes) = bytes.value() {
+                let expected = bytes.iter().rev().fold(pallas::Base::zero(), |acc, byte| {
+                    acc * pallas::Base::from(256) + pallas::Base::from(*byte as u64)
+                });
+                assert_eq!(piece.inner().field_elem(), Some(expected));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn to_message_piece() {
+        // 40 bits make up four 10-bit Sinsemilla words.
+        let circuit = MessagePieceCircuit {
+            bytes: to_values(&[0x01, 0x23, 0x45, 0x67, 0x89]),
+        };
+        let prover = MockProver::<pallas::Base>::run(11, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // 0x100 does not fit in a byte, so the short range check fails.
+        let circuit = MessagePieceCircuit {
+            bytes: to_values(&[0x01, 0x23, 0x100, 0x67, 0x89]),
+        };
+        let prover = MockProver::<pallas::Base>::run(11, &circuit, vec![]).unwrap();
+        assert!(matches!(
+            prover.verify(),
+            Err(failures) if !failures.is_empty()
+                && failures.iter().all(|f| matches!(f, VerifyFailure::Lookup { .. }))
+        ));
+    }
+
+    #[cfg(feature = "unstable")]
+    mod sha256 {
+        use super::{assign_unchecked, to_values};
+        use crate::{
+            primitives::sinsemilla::K,
+            sha256::{Sha256Instructions, Table16Chip, Table16Config},
+            utilities::{bytes::BytesConfig, lookup_range_check::LookupRangeCheckConfig},
+        };
+        use halo2_proofs::{
+            circuit::{Layouter, SimpleFloorPlanner},
+            dev::{MockProver, VerifyFailure},
+            plonk::{Circuit, ConstraintSystem, Error},
+        };
+        use pasta_curves::pallas;
+
+        use std::convert::TryInto;
+
+        /// The SHA-256 initial hash value.
+        const IV: [u32; 8] = [
+            0x6a09_e667,
+            0xbb67_ae85,
+            0x3c6e_f372,
+            0xa54f_f53a,
+            0x510e_527f,
+            0x9b05_688c,
+            0x1f83_d9ab,
+            0x5be0_cd19,
+        ];
+
+        /// SHA-256("abc"), from FIPS 180-2 Appendix B.1.
+        const ABC_DIGEST: [u32; 8] = [
+            0xba78_16bf,
+            0x8f01_cfea,
+            0x4141_40de,
+            0x5dae_2223,
+            0xb003_61a3,
+            0x9617_7a9c,
+            0xb410_ff61,
+            0xf200_15ad,
+        ];
+
+        /// The single padded block of the message "abc".
+        fn abc_block() -> Vec<u64> {
+            let mut block = vec![0x61, 0x62, 0x63, 0x80];
+            block.resize(63, 0);
+            // The message length in bits, as a big-endian 64-bit integer.
+            block.push(24);
+            block
+        }
+
+        struct BlockCircuit {
+            // Values that are range-checked as bytes and packed into a block.
+            bytes: Vec<Option<pallas::Base>>,
+            // If set, 2^32 is added to the first block word before compression.
+            overflow: bool,
+            // The digest expected from compressing the block, if known.
+            expected: Option<[u32; 8]>,
+        }
+
+        impl Circuit<pallas::Base> for BlockCircuit {
+            type Config = (BytesConfig<pallas::Base>, Table16Config);
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    bytes: vec![None; self.bytes.len()],
+                    overflow: self.overflow,
+                    expected: None,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+                let advices = [meta.advice_column(), meta.advice_column()];
+                let running_sum = meta.advice_column();
+                let table_idx = meta.lookup_table_column();
+                let constants = meta.fixed_column();
+                meta.enable_constant(constants);
+
+                let lookup_config = LookupRangeCheckConfig::<pallas::Base, K>::configure(
+                    meta,
+                    running_sum,
+                    table_idx,
+                );
+                (
+                    BytesConfig::configure(meta, advices, lookup_config),
+                    Table16Chip::configure(meta),
+                )
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<pallas::Base>,
+            ) -> Result<(), Error> {
+                let (config, table16_config) = config;
+                config.lookup_config.load(&mut layouter)?;
+                Table16Chip::load(table16_config.clone(), &mut layouter)?;
+                let chip = Table16Chip::construct(table16_config);
+
+                let cells = assign_unchecked(
+                    layouter.namespace(|| "unchecked"),
+                    config.advices[0],
+                    &self.bytes,
+                )?;
+                let bytes = config.range_check_bytes(layouter.namespace(|| "bytes"), &cells)?;
+                let mut words =
+                    config.to_block_words(layouter.namespace(|| "to_block_words"), &bytes)?;
+
+                if self.overflow {
+                    let w_0 = words[0]
+                        .value()
+                        .map(|w_0| *w_0 + pallas::Base::from(1 << 32));
+                    words[0] = layouter.assign_region(
+                        || "overflow W_0",
+                        |mut region| {
+                            region.assign_advice(
+                                || "W_0 + 2^32",
+                                config.advices[1],
+                                0,
+                                || w_0.ok_or(Error::Synthesis),
+                            )
+                        },
+                    )?;
+                }
+
+                let state = chip.initialization_vector(&mut layouter)?;
+                let state =
+                    chip.compress_assigned(&mut layouter, &state, words.try_into().unwrap())?;
+                let digest = chip.digest(&mut layouter, &state)?;
+
+                if let Some(expected) = self.expected {
+                    for ((word, iv), expected) in digest.iter().zip(IV.iter()).zip(expected.iter())
+                    {
+                        assert_eq!(word.0.map(|word| word.wrapping_add(*iv)), Some(*expected));
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn compress_assigned() {
+            let circuit = BlockCircuit {
+                bytes: to_values(&abc_block()),
+                overflow: false,
+                expected: Some(ABC_DIGEST),
+            };
+            let prover = MockProver::<pallas::Base>::run(17, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The message schedule only witnesses the low 32 bits of W_0, so it
+            // cannot be made equal to an input word that overflows.
+            let circuit = BlockCircuit {
+                bytes: to_values(&abc_block()),
+                overflow: true,
+                expected: Some(ABC_DIGEST),
+            };
+            let prover = MockProver::<pallas::Base>::run(17, &circuit, vec![]).unwrap();
+            assert!(matches!(
+                prover.verify(),
+                Err(failures) if failures
+                    .iter()
+                    .any(|f| matches!(f, VerifyFailure::Permutation { .. }))
+            ));
+        }
+
+        #[test]
+        fn to_block_words() {
+            // 0x100 does not fit in a byte, so the short range check fails.
+            let mut block = abc_block();
+            block[3] = 0x100;
+            let circuit = BlockCircuit {
+                bytes: to_values(&block),
+                overflow: false,
+                expected: None,
+            };
+            let prover = MockProver::<pallas::Base>::run(17, &circuit, vec![]).unwrap();
+            assert!(matches!(
+                prover.verify(),
+                Err(failures) if !failures.is_empty()
+                    && failures.iter().all(|f| matches!(f, VerifyFailure::Lookup { .. }))
+            ));
+        }
+    }
+}
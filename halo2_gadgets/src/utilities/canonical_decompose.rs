@@ -0,0 +1,474 @@
+//! Decomposes a full-width field element $\alpha$ into $K$-bit windows, and
+//! constrains the decomposition to be canonical, i.e. to represent an integer
+//! less than the field modulus.
+//!
+//! This helper is supported for fields with modulus $p = 2^{254} + t$, where
+//! $t < 2^{130}$. Both Pasta fields satisfy this. We constrain $K \in \{1, 2\}$
+//! so that bit positions $130$ and $254$ fall on window boundaries.
+//!
+//! $\alpha$ is first decomposed with a strict [`RunningSumConfig`] into $W$
+//! windows $[k_0, ..., k_{W-1}]$, with running sum outputs $[z_0, ..., z_W]$,
+//! $z_W = 0$. This only proves that $\alpha \equiv \sum_i 2^{Ki} k_i \pmod{p}$;
+//! the integer on the right-hand side may still be $\geq p$. We additionally
+//! constrain:
+//!
+//! - $\alpha_{254} = z_{254/K}$ is boolean. Together with $z_W = 0$, this
+//!   means the decomposition represents an integer less than $2^{255}$.
+//! - If $\alpha_{254} = 1$, then bits $130..=253$ are all zero:
+//!                $$\alpha_{254} \cdot (z_{130/K} - 2^{124}) = 0.$$
+//! - If $\alpha_{254} = 1$, then $\alpha_{0..=129} < t$. We witness
+//!          $$\alpha' = \alpha_{0..=129} + 2^{130} - t
+//!                    = z_0 - 2^{130} \cdot z_{130/K} + 2^{130} - t$$
+//!   and decompose it with a non-strict running sum into $130/K$ windows. Let
+//!   $z'_{130/K}$ be its final output; then
+//!                $$\alpha_{254} \cdot z'_{130/K} = 0.$$
+//!
+//! If $\alpha_{254} = 0$, the integer represented is less than $2^{254} < p$.
+//! Otherwise, it is equal to $2^{254} + \alpha_{0..=129} < 2^{254} + t = p$.
+
+use ff::PrimeFieldBits;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+use pasta_curves::{arithmetic::FieldExt, pallas, vesta};
+
+use super::{
+    bool_check,
+    decompose_running_sum::{RunningSum, RunningSumConfig},
+};
+use crate::ecc::chip::{T_P, T_Q};
+
+use std::iter;
+
+/// A field whose modulus is $p = 2^{254} + t$, with $t < 2^{130}$.
+pub trait CanonicalField: FieldExt + PrimeFieldBits {
+    /// $t = p - 2^{254}$.
+    const T: u128;
+}
+
+impl CanonicalField for pallas::Base {
+    const T: u128 = T_P;
+}
+
+impl CanonicalField for vesta::Base {
+    const T: u128 = T_Q;
+}
+
+/// Number of bits in a canonical decomposition.
+const NUM_BITS: usize = 255;
+
+/// Number of low bits of $\alpha$ that are compared against $t$.
+const NUM_LO_BITS: usize = 130;
+
+/// Configuration that provides methods for canonical decomposition of a
+/// full-width field element.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CanonicalDecomposeConfig<F: CanonicalField, const WINDOW_NUM_BITS: usize> {
+    q_canonicity: Selector,
+    advices: [Column<Advice>; 2],
+    running_sum: RunningSumConfig<F, WINDOW_NUM_BITS>,
+}
+
+impl<F: CanonicalField, const WINDOW_NUM_BITS: usize> CanonicalDecomposeConfig<F, WINDOW_NUM_BITS> {
+    /// Number of windows in the decomposition of $\alpha$.
+    pub const NUM_WINDOWS: usize = (NUM_BITS + WINDOW_NUM_BITS - 1) / WINDOW_NUM_BITS;
+
+    /// The running sum is decomposed in `advices[0]`. `q_range_check` is used
+    /// to configure the underlying [`RunningSumConfig`].
+    ///
+    /// The circuit MUST have a fixed column enabled for constants, since the
+    /// final running sum output is constrained to be zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if WINDOW_NUM_BITS is not 1 or 2.
+    ///
+    /// # Side-effects
+    ///
+    /// Both `advices` columns will be equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_range_check: Selector,
+        advices: [Column<Advice>; 2],
+    ) -> Self {
+        assert!(WINDOW_NUM_BITS == 1 || WINDOW_NUM_BITS == 2);
+
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+
+        let config = Self {
+            q_canonicity: meta.selector(),
+            advices,
+            running_sum: RunningSumConfig::configure(meta, q_range_check, advices[0]),
+        };
+
+        config.create_gate(meta);
+
+        config
+    }
+
+    fn create_gate(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("canonicity checks", |meta| {
+            let q_canonicity = meta.query_selector(self.q_canonicity);
+
+            let two_pow_124 = Expression::Constant(F::from_u128(1 << 124));
+            let two_pow_130 = Expression::Constant(F::from_u128(1 << 65).square());
+            let t = Expression::Constant(F::from_u128(F::T));
+
+            let z_0 = meta.query_advice(self.advices[0], Rotation::prev());
+            let z_130 = meta.query_advice(self.advices[0], Rotation::cur());
+            let alpha_254 = meta.query_advice(self.advices[0], Rotation::next());
+
+            let alpha_prime = meta.query_advice(self.advices[1], Rotation::prev());
+            let z_prime_130 = meta.query_advice(self.advices[1], Rotation::cur());
+
+            // α' = α_{0..=129} + 2^130 - t
+            //    = z_0 - 2^130 ⋅ z_130 + 2^130 - t
+            let alpha_prime_check =
+                alpha_prime - (z_0 - z_130.clone() * two_pow_130.clone() + two_pow_130 - t);
+
+            // α_254 ∈ {0, 1}
+            let alpha_254_check = bool_check(alpha_254.clone());
+
+            // α_254 ⋅ (z_130 - 2^124) = 0
+            let lo_zero = alpha_254.clone() * (z_130 - two_pow_124);
+
+            // α_254 ⋅ z'_130 = 0
+            let canonicity = alpha_254 * z_prime_130;
+
+            iter::empty()
+                .chain(Some(("alpha_prime_check", alpha_prime_check)))
+                .chain(Some(("alpha_254_check", alpha_254_check)))
+                .chain(Some(("lo_zero", lo_zero)))
+                .chain(Some(("canonicity", canonicity)))
+                .map(|(name, poly)| (name, q_canonicity.clone() * poly))
+                .collect::<Vec<_>>()
+        });
+    }
+
+    /// Canonically decompose a field element alpha that is witnessed in this
+    /// helper.
+    ///
+    /// Returns the running sum $[z_0, ..., z_W]$, where $z_0 = \alpha$ and
+    /// $z_W = 0$.
+    pub fn witness_decompose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        alpha: Option<F>,
+    ) -> Result<RunningSum<F>, Error> {
+        let alpha = layouter.assign_region(
+            || "witness alpha",
+            |mut region| {
+                region.assign_advice(
+                    || "alpha",
+                    self.advices[0],
+                    0,
+                    || alpha.ok_or(Error::Synthesis),
+                )
+            },
+        )?;
+        self.copy_decompose(layouter, alpha)
+    }
+
+    /// Canonically decompose an existing variable alpha that is copied into
+    /// this helper.
+    ///
+    /// Returns the running sum $[z_0, ..., z_W]$, where $z_0 = \alpha$ and
+    /// $z_W = 0$.
+    pub fn copy_decompose(
+        &self,
+        layouter: impl Layouter<F>,
+        alpha: AssignedCell<F, F>,
+    ) -> Result<RunningSum<F>, Error> {
+        let words = alpha
+            .value()
+            .map(|alpha| super::decompose_word::<F>(alpha, NUM_BITS, WINDOW_NUM_BITS));
+        let words = if let Some(words) = words {
+            words.into_iter().map(Some).collect()
+        } else {
+            vec![None; Self::NUM_WINDOWS]
+        };
+
+        self.decompose(layouter, alpha, words)
+    }
+
+    /// Assigns the canonical decomposition of `alpha` over the given windows.
+    ///
+    /// `words` is not required to be the decomposition of `alpha`; the
+    /// constraints are what enforce this.
+    fn decompose(
+        &self,
+        mut layouter: impl Layouter<F>,
+        alpha: AssignedCell<F, F>,
+        words: Vec<Option<u8>>,
+    ) -> Result<RunningSum<F>, Error> {
+        assert_eq!(words.len(), Self::NUM_WINDOWS);
+        let num_windows = Self::NUM_WINDOWS;
+        let num_lo_windows = NUM_LO_BITS / WINDOW_NUM_BITS;
+
+        layouter.assign_region(
+            || "canonical decomposition",
+            |mut region| {
+                // Decompose alpha into `num_windows` windows, constraining the
+                // final running sum output to be zero.
+                let z_0 =
+                    alpha.copy_advice(|| "copy z_0 = alpha", &mut region, self.advices[0], 0)?;
+                let zs = self
+                    .running_sum
+                    .assign_words(&mut region, 0, z_0, true, words.clone())?;
+                let z_130 = zs[num_lo_windows].clone();
+                let alpha_254 = zs[(NUM_BITS - 1) / WINDOW_NUM_BITS].clone();
+
+                // The canonicity gate occupies three rows following the running sum.
+                let offset = num_windows + 1;
+
+                // Witness α' = z_0 - 2^130 ⋅ z_130 + 2^130 - t
+                let alpha_prime = {
+                    let two_pow_130 = F::from_u128(1 << 65).square();
+                    let t = F::from_u128(F::T);
+                    let alpha_prime = zs[0]
+                        .value()
+                        .zip(z_130.value())
+                        .map(|(z_0, z_130)| *z_0 - *z_130 * two_pow_130 + two_pow_130 - t);
+                    region.assign_advice(
+                        || "α' = α_{0..=129} + 2^130 - t",
+                        self.advices[1],
+                        offset,
+                        || alpha_prime.ok_or(Error::Synthesis),
+                    )?
+                };
+
+                // Decompose the low 130 bits of α' after the canonicity gate.
+                let zs_prime = self.running_sum.copy_decompose(
+                    &mut region,
+                    offset + 3,
+                    alpha_prime,
+                    false,
+                    NUM_LO_BITS,
+                    num_lo_windows,
+                )?;
+
+                // Enable canonicity gate
+                self.q_canonicity.enable(&mut region, offset + 1)?;
+
+                // Copy `z_0`
+                zs[0].copy_advice(|| "copy z_0", &mut region, self.advices[0], offset)?;
+
+                // Copy `z_130`
+                z_130.copy_advice(|| "copy z_130", &mut region, self.advices[0], offset + 1)?;
+
+                // Copy `α_254`
+                alpha_254.copy_advice(|| "copy α_254", &mut region, self.advices[0], offset + 2)?;
+
+                // Copy `z'_130`
+                zs_prime[num_lo_windows].copy_advice(
+                    || "copy z'_130",
+                    &mut region,
+                    self.advices[1],
+                    offset + 1,
+                )?;
+
+                Ok(zs)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use group::ff::{Field, PrimeField};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        plonk::{Any, Circuit, ConstraintSystem, Error},
+    };
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_canonical_decompose() {
+        struct MyCircuit<F: CanonicalField, const WINDOW_NUM_BITS: usize> {
+            alpha: Option<F>,
+        }
+
+        impl<F: CanonicalField, const WINDOW_NUM_BITS: usize> Circuit<F> for MyCircuit<F, WINDOW_NUM_BITS> {
+            type Config = CanonicalDecomposeConfig<F, WINDOW_NUM_BITS>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self { alpha: None }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+                let advices = [meta.advice_column(), meta.advice_column()];
+                let q_range_check = meta.selector();
+                let constants = meta.fixed_column();
+                meta.enable_constant(constants);
+
+                CanonicalDecomposeConfig::configure(meta, q_range_check, advices)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<F>,
+            ) -> Result<(), Error> {
+                let zs = config
+                    .witness_decompose(layouter.namespace(|| "witness_decompose"), self.alpha)?;
+                assert_eq!(
+                    zs.len(),
+                    CanonicalDecomposeConfig::<F, WINDOW_NUM_BITS>::NUM_WINDOWS + 1
+                );
+
+                config.copy_decompose(layouter.namespace(|| "copy_decompose"), zs[0].clone())?;
+
+                Ok(())
+            }
+        }
+
+        fn check<F: CanonicalField, const WINDOW_NUM_BITS: usize>(alpha: F) {
+            let circuit = MyCircuit::<F, WINDOW_NUM_BITS> { alpha: Some(alpha) };
+            let prover = MockProver::<F>::run(11, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        fn check_field<F: CanonicalField>() {
+            let two_pow_254 = F::from_u128(1 << 127).square();
+            let t = F::from_u128(F::T);
+            let alphas = [
+                F::zero(),
+                F::one(),
+                -F::one(),
+                two_pow_254 - F::one(),
+                two_pow_254,
+                two_pow_254 + t - F::from(2),
+                F::random(OsRng),
+            ];
+            for alpha in alphas.iter() {
+                check::<F, 1>(*alpha);
+                check::<F, 2>(*alpha);
+            }
+        }
+
+        check_field::<pallas::Base>();
+        check_field::<vesta::Base>();
+    }
+
+    #[test]
+    fn test_non_canonical_decompose() {
+        // Witnesses the windows of the integer N = 2^254 + t + delta = p + delta,
+        // which is congruent to alpha = delta but is not less than p.
+        struct MyCircuit<F: CanonicalField, const WINDOW_NUM_BITS: usize> {
+            delta: Option<u128>,
+        }
+
+        impl<F: CanonicalField, const WINDOW_NUM_BITS: usize> Circuit<F> for MyCircuit<F, WINDOW_NUM_BITS> {
+            type Config = CanonicalDecomposeConfig<F, WINDOW_NUM_BITS>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self { delta: None }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+                let advices = [meta.advice_column(), meta.advice_column()];
+                let q_range_check = meta.selector();
+                let constants = meta.fixed_column();
+                meta.enable_constant(constants);
+
+                CanonicalDecomposeConfig::configure(meta, q_range_check, advices)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<F>,
+            ) -> Result<(), Error> {
+                let num_windows = CanonicalDecomposeConfig::<F, WINDOW_NUM_BITS>::NUM_WINDOWS;
+
+                let alpha = layouter.assign_region(
+                    || "witness alpha",
+                    |mut region| {
+                        region.assign_advice(
+                            || "alpha",
+                            config.advices[0],
+                            0,
+                            || self.delta.map(F::from_u128).ok_or(Error::Synthesis),
+                        )
+                    },
+                )?;
+
+                // The low 130 bits of N are t + delta, and bit 254 is set.
+                let words = (0..num_windows)
+                    .map(|i| {
+                        self.delta.map(|delta| {
+                            let lo = F::T + delta;
+                            let shift = i * WINDOW_NUM_BITS;
+                            if shift == NUM_BITS - 1 {
+                                1
+                            } else if shift < 128 {
+                                ((lo >> shift) & ((1 << WINDOW_NUM_BITS) - 1)) as u8
+                            } else {
+                                0
+                            }
+                        })
+                    })
+                    .collect();
+
+                config.decompose(layouter.namespace(|| "decompose"), alpha, words)?;
+
+                Ok(())
+            }
+        }
+
+        fn check<F: CanonicalField, const WINDOW_NUM_BITS: usize>(delta: u128) {
+            let num_windows = CanonicalDecomposeConfig::<F, WINDOW_NUM_BITS>::NUM_WINDOWS;
+            let circuit = MyCircuit::<F, WINDOW_NUM_BITS> { delta: Some(delta) };
+            let prover = MockProver::<F>::run(11, &circuit, vec![]).unwrap();
+
+            // N is congruent to alpha and lies within 255 bits, with bits 130..=253
+            // all zero; only the final comparison against t catches it.
+            assert_eq!(
+                prover.verify(),
+                Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                    constraint: ((1, "canonicity checks").into(), 3, "canonicity").into(),
+                    location: FailureLocation::InRegion {
+                        region: (1, "canonical decomposition").into(),
+                        offset: num_windows + 2,
+                    },
+                    cell_values: vec![
+                        (((Any::Advice, 0).into(), 1).into(), "1".to_string()),
+                        (((Any::Advice, 1).into(), 0).into(), "1".to_string()),
+                    ],
+                }])
+            );
+        }
+
+        fn check_field<F: CanonicalField>() {
+            // delta = 0 is the edge case N = p.
+            for delta in [0, 1, 0xdead_beef].iter() {
+                check::<F, 1>(*delta);
+                check::<F, 2>(*delta);
+            }
+        }
+
+        check_field::<pallas::Base>();
+        check_field::<vesta::Base>();
+    }
+
+    #[test]
+    fn test_canonical_field_t() {
+        fn check<F: CanonicalField>() {
+            // p - 1 = 2^254 + t - 1
+            let two_pow_254 = F::from_u128(1 << 127).square();
+            assert_eq!(-F::one(), two_pow_254 + F::from_u128(F::T) - F::one());
+            assert_eq!(F::NUM_BITS as usize, NUM_BITS);
+        }
+
+        check::<pallas::Base>();
+        check::<vesta::Base>();
+    }
+}
@@ -163,11 +163,6 @@ impl<F: FieldExt + PrimeFieldBits, const WINDOW_NUM_BITS: usize>
         //
         assert!(WINDOW_NUM_BITS * num_windows < word_num_bits + WINDOW_NUM_BITS);
 
-        // Enable selectors
-        for idx in 0..num_windows {
-            self.q_range_check.enable(region, offset + idx)?;
-        }
-
         // Decompose base field element into K-bit words.
         let words: Vec<Option<u8>> = {
             let words = z_0
@@ -181,6 +176,30 @@ impl<F: FieldExt + PrimeFieldBits, const WINDOW_NUM_BITS: usize>
             }
         };
 
+        self.assign_words(region, offset, z_0, strict, words)
+    }
+
+    /// Assigns the running sum of `z_0` over the given K-bit `words`, one
+    /// window per word.
+    ///
+    /// This does not check that `words` is the decomposition of `z_0`; the
+    /// range check gate only constrains each window to be a K-bit word.
+    /// `z_0` must be the cell at `(self.z, offset)` in `region`.
+    pub(super) fn assign_words(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        z_0: AssignedCell<F, F>,
+        strict: bool,
+        words: Vec<Option<u8>>,
+    ) -> Result<RunningSum<F>, Error> {
+        let num_windows = words.len();
+
+        // Enable selectors
+        for idx in 0..num_windows {
+            self.q_range_check.enable(region, offset + idx)?;
+        }
+
         // Initialize empty vector to store running sum values [z_0, ..., z_W].
         let mut zs: Vec<AssignedCell<F, F>> = vec![z_0.clone()];
         let mut z = z_0;
@@ -1,13 +1,13 @@
 #[macro_use]
 extern crate criterion;
 
-use crate::arithmetic::small_multiexp;
+use crate::arithmetic::{best_fft, small_multiexp, FftTwiddles};
 use crate::pasta::{EqAffine, Fp};
 use crate::poly::commitment::Params;
-use group::ff::Field;
+use group::ff::{Field, PrimeField};
 use halo2_proofs::*;
 
-use criterion::{black_box, Criterion};
+use criterion::{black_box, BenchmarkId, Criterion};
 use rand_core::OsRng;
 
 fn criterion_benchmark(c: &mut Criterion) {
@@ -31,6 +31,35 @@ fn criterion_benchmark(c: &mut Criterion) {
             })
         });
     }
+
+    // FFTs, with and without precomputed twiddle factors
+    {
+        let mut group = c.benchmark_group("fft");
+        for k in [12, 14, 16].iter().cloned() {
+            let mut omega = Fp::root_of_unity();
+            for _ in k..Fp::S {
+                omega = omega.square();
+            }
+            let twiddles = FftTwiddles::new(omega, k);
+            let a: Vec<Fp> = (0..(1 << k)).map(|_| Fp::random(rng)).collect();
+
+            group.bench_with_input(BenchmarkId::new("best_fft", k), &a, |b, a| {
+                b.iter_batched(
+                    || a.clone(),
+                    |mut a| best_fft(&mut a, omega, k),
+                    criterion::BatchSize::LargeInput,
+                )
+            });
+            group.bench_with_input(BenchmarkId::new("twiddles", k), &a, |b, a| {
+                b.iter_batched(
+                    || a.clone(),
+                    |mut a| twiddles.fft(&mut a, k),
+                    criterion::BatchSize::LargeInput,
+                )
+            });
+        }
+        group.finish();
+    }
 }
 
 criterion_group!(benches, criterion_benchmark);
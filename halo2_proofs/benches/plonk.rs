@@ -273,6 +273,16 @@ fn criterion_benchmark(c: &mut Criterion) {
         assert!(verify_proof(params, vk, strategy, &[&[]], &mut transcript).is_ok());
     }
 
+    fn batch_verifier(params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>, proofs: &[Vec<u8>]) {
+        let mut strategy = BatchVerifier::new(params, OsRng);
+        for proof in proofs {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            strategy = verify_proof(params, vk, strategy, &[&[]], &mut transcript)
+                .expect("proof should be well-formed");
+        }
+        assert!(strategy.finalize());
+    }
+
     let k_range = 8..=16;
 
     let mut keygen_group = c.benchmark_group("plonk-keygen");
@@ -313,6 +323,24 @@ fn criterion_benchmark(c: &mut Criterion) {
         );
     }
     verifier_group.finish();
+
+    // Batch verification evaluates a single MSM for all of the proofs in the batch.
+    let batch_size = 8;
+    let mut batch_verifier_group = c.benchmark_group("plonk-batch-verifier");
+    batch_verifier_group.sample_size(10);
+    for k in 8..=12 {
+        let (params, pk) = keygen(k);
+        let proofs: Vec<_> = (0..batch_size).map(|_| prover(k, &params, &pk)).collect();
+
+        batch_verifier_group.bench_with_input(
+            BenchmarkId::from_parameter(k),
+            &(&params, pk.get_vk(), &proofs),
+            |b, &(params, vk, proofs)| {
+                b.iter(|| batch_verifier(params, vk, proofs));
+            },
+        );
+    }
+    batch_verifier_group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
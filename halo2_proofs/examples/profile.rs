@@ -0,0 +1,220 @@
+//! Profiles key generation, proving and verification of a small circuit, using the
+//! spans emitted by `halo2_proofs` when its `tracing` feature is enabled.
+//!
+//! Run with `cargo run --release --example profile --features tracing`.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+        ConstraintSystem, Error, Selector, SingleVerifier,
+    },
+    poly::{commitment::Params, Rotation},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// A circuit that repeatedly squares a private input.
+#[derive(Clone, Default)]
+struct SquaringCircuit {
+    input: Option<Fp>,
+    rounds: usize,
+}
+
+#[derive(Clone, Debug)]
+struct SquaringConfig {
+    a: Column<Advice>,
+    s: Selector,
+}
+
+impl Circuit<Fp> for SquaringCircuit {
+    type Config = SquaringConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        SquaringCircuit {
+            input: None,
+            rounds: self.rounds,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let a = meta.advice_column();
+        let s = meta.selector();
+
+        meta.create_gate("square", |meta| {
+            let a_cur = meta.query_advice(a, Rotation::cur());
+            let a_next = meta.query_advice(a, Rotation::next());
+            let s = meta.query_selector(s);
+            vec![s * (a_cur.clone() * a_cur - a_next)]
+        });
+
+        SquaringConfig { a, s }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "squarings",
+            |mut region| {
+                let mut value = self.input;
+                for row in 0..=self.rounds {
+                    if row < self.rounds {
+                        config.s.enable(&mut region, row)?;
+                    }
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        row,
+                        || value.ok_or(Error::Synthesis),
+                    )?;
+                    value = value.map(|v| v * v);
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Builds the label of a span from its name and fields, such as `fft{log_n=12}`.
+struct Label(String);
+
+impl Visit for Label {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let separator = if self.0.ends_with('{') { "" } else { ", " };
+        write!(self.0, "{}{}={:?}", separator, field.name(), value).unwrap();
+    }
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    /// The path (the labels of the span and its ancestors) of each span.
+    paths: HashMap<u64, Vec<String>>,
+    /// The total time spent in, and the number of entries into, the spans at each path.
+    totals: BTreeMap<Vec<String>, (Duration, usize)>,
+}
+
+thread_local! {
+    /// The spans entered on this thread, and when they were entered.
+    static STACK: RefCell<Vec<(u64, Instant)>> = RefCell::new(vec![]);
+}
+
+/// A subscriber that accumulates the time spent in each span, keyed by its ancestry.
+#[derive(Clone, Default)]
+struct Profiler(Arc<Mutex<State>>);
+
+impl Subscriber for Profiler {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let mut label = Label(format!("{}{{", attrs.metadata().name()));
+        attrs.record(&mut label);
+        let label = if label.0.ends_with('{') {
+            attrs.metadata().name().to_owned()
+        } else {
+            label.0 + "}"
+        };
+
+        let parent = if attrs.is_contextual() {
+            STACK.with(|stack| stack.borrow().last().map(|(id, _)| *id))
+        } else {
+            attrs.parent().map(|id| id.into_u64())
+        };
+
+        let mut state = self.0.lock().unwrap();
+        let mut path = parent
+            .and_then(|parent| state.paths.get(&parent).cloned())
+            .unwrap_or_default();
+        path.push(label);
+        state.next_id += 1;
+        let id = state.next_id;
+        state.paths.insert(id, path);
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, id: &span::Id) {
+        STACK.with(|stack| stack.borrow_mut().push((id.into_u64(), Instant::now())));
+    }
+
+    fn exit(&self, id: &span::Id) {
+        let entered = STACK.with(|stack| stack.borrow_mut().pop());
+        if let Some((entered_id, start)) = entered {
+            assert_eq!(entered_id, id.into_u64());
+            let mut state = self.0.lock().unwrap();
+            let path = state.paths[&entered_id].clone();
+            let total = state.totals.entry(path).or_default();
+            total.0 += start.elapsed();
+            total.1 += 1;
+        }
+    }
+}
+
+impl Profiler {
+    /// Prints the accumulated times as a tree, with each span below its parent.
+    fn print_summary(&self) {
+        let state = self.0.lock().unwrap();
+        println!("{:>12} {:>8}  span", "total", "count");
+        for (path, (total, count)) in state.totals.iter() {
+            println!(
+                "{:>10.3}ms {:>8}  {}{}",
+                total.as_secs_f64() * 1000.0,
+                count,
+                "  ".repeat(path.len() - 1),
+                path.last().unwrap()
+            );
+        }
+    }
+}
+
+fn main() {
+    const K: u32 = 10;
+
+    let profiler = Profiler::default();
+    tracing::subscriber::set_global_default(profiler.clone())
+        .expect("no other subscriber has been set");
+
+    let circuit = SquaringCircuit {
+        input: Some(Fp::from(3)),
+        rounds: 900,
+    };
+
+    let params: Params<EqAffine> = Params::new(K);
+    let vk = keygen_vk(&params, &circuit.without_witnesses()).expect("keygen_vk should not fail");
+    let pk =
+        keygen_pk(&params, vk, &circuit.without_witnesses()).expect("keygen_pk should not fail");
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&[]], OsRng, &mut transcript)
+        .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    let strategy = SingleVerifier::new(&params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    verify_proof(&params, pk.get_vk(), strategy, &[&[]], &mut transcript)
+        .expect("proof should verify");
+
+    profiler.print_summary();
+}
@@ -1,103 +1,389 @@
 //! This module provides common utilities, traits and structures for group,
 //! field and polynomial arithmetic.
 
+use std::array;
+use std::fmt;
+use std::ops::Range;
+
 use super::multicore;
 pub use ff::Field;
 use group::{
     ff::{BatchInvert, PrimeField},
-    Group as _,
+    prime::PrimeCurveAffine,
+    Curve as _, Group as _,
 };
 
 pub use pasta_curves::arithmetic::*;
 
-fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut C::Curve) {
-    let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
+mod glv;
+
+/// The smallest number of points for which a window's buckets are accumulated with
+/// batched affine additions; below this, projective additions are faster.
+const BATCH_AFFINE_MIN_POINTS: usize = 1 << 8;
+
+/// The smallest number of additions that are worth batching behind a single field
+/// inversion; once a round of batched affine additions has fewer than this, the
+/// remaining points in each bucket are added projectively.
+const BATCH_AFFINE_MIN_PAIRS: usize = 64;
 
-    let c = if bases.len() < 4 {
+/// Returns the window size, in bits, for a multiexp over `n` points.
+fn multiexp_window_size(n: usize) -> usize {
+    if n < 4 {
         1
-    } else if bases.len() < 32 {
+    } else if n < 32 {
         3
     } else {
-        (f64::from(bases.len() as u32)).ln().ceil() as usize
+        // Signed digits need 2^(c - 1) buckets, and each digit is at most 2^(c - 1), so
+        // the window must fit within an `i32`.
+        std::cmp::min((f64::from(n as u32)).ln().ceil() as usize, 16)
+    }
+}
+
+/// Returns the `len` bits of the little-endian integer `bytes` starting at bit `start`.
+fn get_bits(bytes: &[u8], start: usize, len: usize) -> u64 {
+    let skip_bytes = start / 8;
+    if skip_bytes >= bytes.len() {
+        return 0;
+    }
+
+    let mut v = [0; 8];
+    for (v, o) in v.iter_mut().zip(bytes[skip_bytes..].iter()) {
+        *v = *o;
+    }
+
+    let tmp = u64::from_le_bytes(v) >> (start - skip_bytes * 8);
+    tmp % (1 << len)
+}
+
+/// Returns the signed digit of the little-endian integer `bytes` in the given window
+/// of `c` bits.
+///
+/// The digits are those of the radix-$2^c$ Booth encoding, which lie in
+/// $[-2^{c-1}, 2^{c-1}]$. Each digit can be computed on its own: it is the window's
+/// unsigned value, plus one if the bit below the window is set, minus $2^c$ if the
+/// window's top bit is set (which is carried into the next window instead). The
+/// digits of $k$ windows sum to the integer if its top $k \cdot c$'th bit is unset.
+fn booth_digit(bytes: &[u8], window: usize, c: usize) -> i32 {
+    let carry = if window == 0 {
+        0
+    } else {
+        get_bits(bytes, window * c - 1, 1)
     };
+    let unsigned = get_bits(bytes, window * c, c);
+    let top = unsigned >> (c - 1);
+    unsigned as i32 + carry as i32 - ((top as i32) << c)
+}
 
-    fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
-        let skip_bits = segment * c;
-        let skip_bytes = skip_bits / 8;
+/// Sums buckets of points, where the bucket at index $i$ holds the points to be
+/// multiplied by $i + 1$. The buckets are given in order.
+fn sum_buckets<C: CurveAffine>(buckets: impl DoubleEndedIterator<Item = C::Curve>) -> C::Curve {
+    // Summation by parts
+    // e.g. 3a + 2b + 1c = a +
+    //                    (a) + b +
+    //                    ((a) + b) + c
+    let mut running_sum = C::Curve::identity();
+    let mut acc = C::Curve::identity();
+    for bucket in buckets.rev() {
+        running_sum += bucket;
+        acc += running_sum;
+    }
+    acc
+}
 
-        if skip_bytes >= 32 {
-            return 0;
-        }
+/// Buffers for computing window sums, which are reused between the window sums that
+/// each thread computes.
+struct WindowScratch<C: CurveAffine> {
+    /// The buckets, or the points in each bucket that could not be added affinely.
+    buckets: Vec<C::Curve>,
+    lens: Vec<usize>,
+    starts: Vec<usize>,
+    next: Vec<usize>,
+    terms: Vec<(usize, C::Base, C::Base)>,
+    points: Vec<(C::Base, C::Base)>,
+    denominators: Vec<C::Base>,
+}
 
-        let mut v = [0; 8];
-        for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
-            *v = *o;
+impl<C: CurveAffine> WindowScratch<C> {
+    fn new() -> Self {
+        WindowScratch {
+            buckets: vec![],
+            lens: vec![],
+            starts: vec![],
+            next: vec![],
+            terms: vec![],
+            points: vec![],
+            denominators: vec![],
         }
+    }
+}
 
-        let mut tmp = u64::from_le_bytes(v);
-        tmp >>= skip_bits - (skip_bytes * 8);
-        tmp = tmp % (1 << c);
+/// Computes the sum of each point multiplied by its coefficient's signed digit in the
+/// given window of `c` bits.
+fn multiexp_window<C: CurveAffine>(
+    coeffs: &[<C::Scalar as PrimeField>::Repr],
+    bases: &[C],
+    window: usize,
+    c: usize,
+    scratch: &mut WindowScratch<C>,
+) -> C::Curve {
+    multiexp_digits(
+        coeffs
+            .iter()
+            .zip(bases.iter())
+            .map(|(coeff, base)| (booth_digit(coeff.as_ref(), window, c), base, false)),
+        bases.len(),
+        c,
+        scratch,
+    )
+}
 
-        tmp as usize
+/// Computes the sum of `num_terms` points, each multiplied by a signed digit of at most
+/// $2^{c-1}$ in magnitude. Each term is `(digit, base, endo)`, where the point is the
+/// image of `base` under the endomorphism $\phi(x, y) = (\zeta x, y)$ if `endo` is set.
+fn multiexp_digits<'a, C: CurveAffine>(
+    terms: impl Iterator<Item = (i32, &'a C, bool)>,
+    num_terms: usize,
+    c: usize,
+    scratch: &mut WindowScratch<C>,
+) -> C::Curve {
+    if num_terms >= BATCH_AFFINE_MIN_POINTS {
+        return multiexp_digits_batch_affine(terms, c, scratch);
     }
 
-    let segments = (256 / c) + 1;
-
-    for current_segment in (0..segments).rev() {
-        for _ in 0..c {
-            *acc = acc.double();
+    let buckets = &mut scratch.buckets;
+    buckets.clear();
+    buckets.resize(1 << (c - 1), C::Curve::identity());
+    for (digit, base, endo) in terms {
+        let base = if endo {
+            glv::endo(base, C::Base::ZETA)
+        } else {
+            *base
+        };
+        if digit > 0 {
+            buckets[digit as usize - 1] += base;
+        } else if digit < 0 {
+            buckets[(-digit) as usize - 1] -= base;
         }
+    }
+
+    sum_buckets::<C>(buckets.iter().cloned())
+}
+
+/// Computes the same sum as [`multiexp_digits`], accumulating each bucket with affine
+/// additions.
+///
+/// An affine addition needs a field inversion, but the inversions of many additions
+/// can be shared with a batch inversion, after which each costs far less than a
+/// projective addition. In each round, the points in each bucket are added in pairs,
+/// with one batch inversion for all of the pairs in all of the buckets, halving the
+/// number of points in each bucket.
+fn multiexp_digits_batch_affine<'a, C: CurveAffine>(
+    terms: impl Iterator<Item = (i32, &'a C, bool)>,
+    c: usize,
+    scratch: &mut WindowScratch<C>,
+) -> C::Curve {
+    let num_buckets = 1 << (c - 1);
+    let WindowScratch {
+        buckets: extra,
+        lens,
+        starts,
+        next,
+        terms: collected,
+        points,
+        denominators,
+    } = scratch;
 
-        #[derive(Clone, Copy)]
-        enum Bucket<C: CurveAffine> {
-            None,
-            Affine(C),
-            Projective(C::Curve),
+    // Collect the points to be added into each bucket, negating each point with a
+    // negative digit.
+    lens.clear();
+    lens.resize(num_buckets, 0);
+    collected.clear();
+    collected.extend(terms.filter_map(|(digit, base, endo)| {
+        if digit == 0 {
+            return None;
         }
+        // The identity contributes nothing, and has no affine coordinates.
+        let coords = Option::<Coordinates<C>>::from(base.coordinates())?;
+        let x = if endo {
+            *coords.x() * C::Base::ZETA
+        } else {
+            *coords.x()
+        };
+        let (bucket, y) = if digit > 0 {
+            (digit as usize - 1, *coords.y())
+        } else {
+            ((-digit) as usize - 1, -*coords.y())
+        };
+        lens[bucket] += 1;
+        Some((bucket, x, y))
+    }));
 
-        impl<C: CurveAffine> Bucket<C> {
-            fn add_assign(&mut self, other: &C) {
-                *self = match *self {
-                    Bucket::None => Bucket::Affine(*other),
-                    Bucket::Affine(a) => Bucket::Projective(a + *other),
-                    Bucket::Projective(mut a) => {
-                        a += *other;
-                        Bucket::Projective(a)
-                    }
-                }
-            }
+    // Sort the points by bucket, so that each bucket's points are contiguous.
+    starts.clear();
+    starts.extend(lens.iter().scan(0, |start, len| {
+        let bucket_start = *start;
+        *start += len;
+        Some(bucket_start)
+    }));
+    points.clear();
+    points.resize(collected.len(), (C::Base::zero(), C::Base::zero()));
+    next.clear();
+    next.extend_from_slice(starts);
+    for &(bucket, x, y) in collected.iter() {
+        points[next[bucket]] = (x, y);
+        next[bucket] += 1;
+    }
 
-            fn add(self, mut other: C::Curve) -> C::Curve {
-                match self {
-                    Bucket::None => other,
-                    Bucket::Affine(a) => {
-                        other += a;
-                        other
-                    }
-                    Bucket::Projective(a) => other + &a,
-                }
+    // Sums of points that could not be added affinely, because they were equal or
+    // negations of each other.
+    extra.clear();
+    extra.resize(num_buckets, C::Curve::identity());
+
+    loop {
+        denominators.clear();
+        for (start, len) in starts.iter().zip(lens.iter()) {
+            for pair in points[*start..(start + len)].chunks_exact(2) {
+                denominators.push(pair[1].0 - pair[0].0);
             }
         }
+        if denominators.len() < BATCH_AFFINE_MIN_PAIRS {
+            break;
+        }
 
-        let mut buckets: Vec<Bucket<C>> = vec![Bucket::None; (1 << c) - 1];
+        // Zeroes (from points with equal x-coordinates) are left as they are.
+        denominators.iter_mut().batch_invert();
 
-        for (coeff, base) in coeffs.iter().zip(bases.iter()) {
-            let coeff = get_at::<C::Scalar>(current_segment, c, coeff);
-            if coeff != 0 {
-                buckets[coeff - 1].add_assign(base);
+        let mut inverses = denominators.iter();
+        for (bucket, (start, len)) in starts.iter().zip(lens.iter_mut()).enumerate() {
+            let mut new_len = 0;
+            for i in 0..(*len / 2) {
+                let inverse = inverses.next().unwrap();
+                let (x1, y1) = points[start + 2 * i];
+                let (x2, y2) = points[start + 2 * i + 1];
+                if x1 == x2 {
+                    // The points are either equal or negations of each other.
+                    extra[bucket] += C::from_xy(x1, y1).unwrap();
+                    extra[bucket] += C::from_xy(x2, y2).unwrap();
+                } else {
+                    let lambda = (y2 - y1) * inverse;
+                    let x3 = lambda.square() - x1 - x2;
+                    let y3 = lambda * (x1 - x3) - y1;
+                    points[start + new_len] = (x3, y3);
+                    new_len += 1;
+                }
             }
+            if *len % 2 == 1 {
+                points[start + new_len] = points[start + *len - 1];
+                new_len += 1;
+            }
+            *len = new_len;
         }
+    }
+
+    let points = &*points;
+    sum_buckets::<C>(starts.iter().zip(lens.iter()).zip(extra.iter()).map(
+        |((start, len), extra)| {
+            points[*start..(start + len)]
+                .iter()
+                .fold(*extra, |acc, (x, y)| acc + C::from_xy(*x, *y).unwrap())
+        },
+    ))
+}
+
+/// Computes $\sum_w 2^{wc} S_w$ over `num_windows` windows of `c` bits, where
+/// `window_sum(w, range, scratch)` computes the part of $S_w$ from the points in `range`
+/// of the `n` points, using the buffers in `scratch`.
+///
+/// The work is split evenly between threads by the total number of bucket additions,
+/// `num_windows * n`: each thread computes the partial window sums for a contiguous run
+/// of the (window, point) pairs, which covers part of one window or spans several, and
+/// reuses one set of buffers for all of them. As each partial window sum also sums its
+/// buckets, threads are only used while each still has as many points as a window has
+/// buckets.
+fn multiexp_by_windows<C: CurveAffine>(
+    n: usize,
+    num_windows: usize,
+    c: usize,
+    window_sum: &(dyn Fn(usize, Range<usize>, &mut WindowScratch<C>) -> C::Curve + Sync),
+) -> C::Curve {
+    let total = n * num_windows;
+    let num_threads = multicore::current_num_threads();
+    let num_threads = if n <= num_threads {
+        // Not worth splitting.
+        1
+    } else {
+        std::cmp::max(1, std::cmp::min(num_threads, total >> (c - 1)))
+    };
+    let share = (total + num_threads - 1) / num_threads;
 
-        // Summation by parts
-        // e.g. 3a + 2b + 1c = a +
-        //                    (a) + b +
-        //                    ((a) + b) + c
-        let mut running_sum = C::Curve::identity();
-        for exp in buckets.into_iter().rev() {
-            running_sum = exp.add(running_sum);
-            *acc = *acc + &running_sum;
+    // Computes the partial window sums for the pairs in `start..end`.
+    let partial_sums = |start: usize, end: usize| {
+        let mut scratch = WindowScratch::new();
+        let mut sums = vec![];
+        let mut pos = start;
+        while pos < end {
+            let window = pos / n;
+            let window_start = window * n;
+            let window_end = std::cmp::min(end, window_start + n);
+            sums.push((
+                window,
+                window_sum(
+                    window,
+                    (pos - window_start)..(window_end - window_start),
+                    &mut scratch,
+                ),
+            ));
+            pos = window_end;
         }
+        sums
+    };
+
+    let mut thread_sums = vec![vec![]; num_threads];
+    if num_threads == 1 {
+        thread_sums[0] = partial_sums(0, total);
+    } else {
+        multicore::scope(|scope| {
+            let partial_sums = &partial_sums;
+            for (thread, sums) in thread_sums.iter_mut().enumerate() {
+                let start = std::cmp::min(thread * share, total);
+                let end = std::cmp::min(start + share, total);
+                scope.spawn(move |_| {
+                    *sums = partial_sums(start, end);
+                });
+            }
+        });
     }
+
+    let mut window_sums = vec![C::Curve::identity(); num_windows];
+    for (window, sum) in thread_sums.into_iter().flatten() {
+        window_sums[window] += sum;
+    }
+
+    window_sums
+        .iter()
+        .rev()
+        .fold(C::Curve::identity(), |acc, sum| {
+            let acc = (0..c).fold(acc, |acc, _| acc.double());
+            acc + sum
+        })
+}
+
+/// Performs a multi-exponentiation with the window size `c`, where each coefficient is
+/// less than $2^{num\_bits}$.
+fn multiexp_windows<C: CurveAffine>(
+    coeffs: &[<C::Scalar as PrimeField>::Repr],
+    bases: &[C],
+    c: usize,
+    num_bits: usize,
+) -> C::Curve {
+    multiexp_by_windows::<C>(
+        bases.len(),
+        num_bits / c + 1,
+        c,
+        &|window: usize, range: Range<usize>, scratch: &mut WindowScratch<C>| {
+            multiexp_window(&coeffs[range.clone()], &bases[range], window, c, scratch)
+        },
+    )
 }
 
 /// Performs a small multi-exponentiation operation.
@@ -128,33 +414,205 @@ pub fn small_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::C
 ///
 /// This function will panic if coeffs and bases have a different length.
 ///
-/// This will use multithreading if beneficial.
+/// This uses Pippenger's algorithm with signed-digit windows, accumulating the buckets
+/// of large windows with batched affine additions. Work is split evenly between threads
+/// by the number of bucket additions, across windows and points.
+///
+/// On the Pasta curves, each scalar is first split into two of half the size with the
+/// curve's endomorphism (the GLV method), which halves the number of windows and the
+/// doublings between them, at the cost of twice as many points in each window.
 pub fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
+    enter_span!("best_multiexp", n = coeffs.len());
     assert_eq!(coeffs.len(), bases.len());
 
-    let num_threads = multicore::current_num_threads();
-    if coeffs.len() > num_threads {
-        let chunk = coeffs.len() / num_threads;
-        let num_chunks = coeffs.chunks(chunk).len();
-        let mut results = vec![C::Curve::identity(); num_chunks];
-        multicore::scope(|scope| {
-            let chunk = coeffs.len() / num_threads;
+    if let Some((coeffs, bases)) =
+        glv::GlvParams::for_curve::<C>().and_then(|glv| glv.split(coeffs, bases))
+    {
+        return multiexp_windows(
+            &coeffs,
+            &bases,
+            multiexp_window_size(coeffs.len()),
+            glv::SCALAR_BITS,
+        );
+    }
 
-            for ((coeffs, bases), acc) in coeffs
-                .chunks(chunk)
-                .zip(bases.chunks(chunk))
-                .zip(results.iter_mut())
-            {
-                scope.spawn(move |_| {
-                    multiexp_serial(coeffs, bases, acc);
-                });
-            }
-        });
-        results.iter().fold(C::Curve::identity(), |a, b| a + b)
-    } else {
-        let mut acc = C::Curve::identity();
-        multiexp_serial(coeffs, bases, &mut acc);
-        acc
+    let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
+    multiexp_windows(&coeffs, bases, multiexp_window_size(coeffs.len()), 256)
+}
+
+/// Precomputed multiples of a fixed list of bases, for faster multi-exponentiations
+/// against them.
+///
+/// A multi-exponentiation splits each scalar into windows, and combines the sums for
+/// each window with doublings. The table stores `num_copies` copies of the bases, each
+/// shifted (multiplied by a power of two) past the windows covered by the previous
+/// copy, so that the windows of each scalar are spread over the copies. This divides
+/// the number of window sums (and doublings between them) by `num_copies`, while each
+/// window sums `num_copies` times as many points in a single set of buckets.
+///
+/// On the Pasta curves, each scalar is split with the curve's endomorphism as in
+/// [`best_multiexp`], so the windows only cover the half-size scalars. The images of the
+/// bases under the endomorphism are not stored; as the endomorphism only scales the
+/// $x$-coordinate, they are computed as each window's buckets are filled.
+///
+/// The table takes `num_copies` times the memory of the bases themselves.
+#[derive(Clone)]
+pub struct FixedBaseTable<C: CurveAffine> {
+    /// The window size, in bits.
+    window_bits: usize,
+    /// The number of windows covered by each copy.
+    windows_per_copy: usize,
+    /// `copies[j][i] = [2^(j * windows_per_copy * window_bits)] bases[i]`.
+    copies: Vec<Vec<C>>,
+    /// The parameters for splitting scalars, if the curve has an efficient endomorphism.
+    glv: Option<&'static glv::GlvParams>,
+}
+
+impl<C: CurveAffine> fmt::Debug for FixedBaseTable<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The table itself is too large to be useful in debugging output.
+        f.debug_struct("FixedBaseTable")
+            .field("len", &self.len())
+            .field("num_copies", &self.copies.len())
+            .field("window_bits", &self.window_bits)
+            .finish()
+    }
+}
+
+impl<C: CurveAffine> FixedBaseTable<C> {
+    /// Precomputes a table for the given bases, with `num_copies` copies of them.
+    ///
+    /// Panics if `num_copies` is zero.
+    pub fn new(bases: &[C], num_copies: usize) -> Self {
+        assert!(num_copies > 0);
+
+        let glv = glv::GlvParams::for_curve::<C>();
+        let (num_points, num_bits) = match glv {
+            Some(_) => (2 * bases.len() * num_copies, glv::SCALAR_BITS),
+            None => (bases.len() * num_copies, 256),
+        };
+        let window_bits = multiexp_window_size(num_points);
+        let num_windows = num_bits / window_bits + 1;
+        let windows_per_copy = (num_windows + num_copies - 1) / num_copies;
+        let shift = windows_per_copy * window_bits;
+
+        let mut copies = vec![bases.to_vec()];
+        for _ in 1..num_copies {
+            let mut shifted: Vec<C::Curve> = copies
+                .last()
+                .unwrap()
+                .iter()
+                .map(|base| base.to_curve())
+                .collect();
+            parallelize(&mut shifted, |shifted, _| {
+                for point in shifted {
+                    for _ in 0..shift {
+                        *point = point.double();
+                    }
+                }
+            });
+
+            let mut copy = vec![C::identity(); bases.len()];
+            parallelize(&mut copy, |copy, start| {
+                C::Curve::batch_normalize(&shifted[start..(start + copy.len())], copy);
+            });
+            copies.push(copy);
+        }
+
+        FixedBaseTable {
+            window_bits,
+            windows_per_copy,
+            copies,
+            glv,
+        }
+    }
+
+    /// Returns the number of bases in this table.
+    pub fn len(&self) -> usize {
+        self.copies[0].len()
+    }
+
+    /// Returns `true` if this table has no bases.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Performs a multi-exponentiation of the first `coeffs.len()` bases in this table,
+    /// returning the same result as [`best_multiexp`].
+    ///
+    /// This function will panic if there are more coefficients than bases.
+    pub fn multiexp(&self, coeffs: &[C::Scalar]) -> C::Curve {
+        enter_span!("fixed_base_multiexp", n = coeffs.len());
+        assert!(coeffs.len() <= self.len());
+
+        let c = self.window_bits;
+        let windows_per_copy = self.windows_per_copy;
+
+        if let Some(glv) = self.glv {
+            // Each term [k] P becomes [k_1] P + [k_2] phi(P), with the signs of k_1 and
+            // k_2 applied to their digits.
+            let coeffs = match glv.decompose_all(coeffs) {
+                Some(coeffs) => coeffs,
+                None => return best_multiexp(coeffs, &self.copies[0][..coeffs.len()]),
+            };
+            return multiexp_by_windows::<C>(
+                coeffs.len(),
+                windows_per_copy,
+                c,
+                &|window: usize, range: Range<usize>, scratch: &mut WindowScratch<C>| {
+                    let coeffs = &coeffs[range.clone()];
+                    multiexp_digits(
+                        self.copies.iter().enumerate().flat_map(|(j, copy)| {
+                            let window = j * windows_per_copy + window;
+                            let digit =
+                                move |(neg, k): &(bool, <C::Scalar as PrimeField>::Repr)| {
+                                    let digit = booth_digit(k.as_ref(), window, c);
+                                    if *neg {
+                                        -digit
+                                    } else {
+                                        digit
+                                    }
+                                };
+                            coeffs.iter().zip(copy[range.clone()].iter()).flat_map(
+                                move |(terms, base)| {
+                                    array::IntoIter::new([
+                                        (digit(&terms[0]), base, false),
+                                        (digit(&terms[1]), base, true),
+                                    ])
+                                },
+                            )
+                        }),
+                        2 * range.len() * self.copies.len(),
+                        c,
+                        scratch,
+                    )
+                },
+            );
+        }
+
+        let coeffs: Vec<_> = coeffs.iter().map(|a| a.to_repr()).collect();
+        multiexp_by_windows::<C>(
+            coeffs.len(),
+            windows_per_copy,
+            c,
+            &|window: usize, range: Range<usize>, scratch: &mut WindowScratch<C>| {
+                let coeffs = &coeffs[range.clone()];
+                multiexp_digits(
+                    self.copies.iter().enumerate().flat_map(|(j, copy)| {
+                        let window = j * windows_per_copy + window;
+                        coeffs
+                            .iter()
+                            .zip(copy[range.clone()].iter())
+                            .map(move |(coeff, base)| {
+                                (booth_digit(coeff.as_ref(), window, c), base, false)
+                            })
+                    }),
+                    range.len() * self.copies.len(),
+                    c,
+                    scratch,
+                )
+            },
+        )
     }
 }
 
@@ -169,17 +627,18 @@ pub fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Cu
 ///
 /// This will use multithreading if beneficial.
 pub fn best_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
+    enter_span!("best_fft", log_n);
     let threads = multicore::current_num_threads();
     let log_threads = log2_floor(threads);
 
     if log_n <= log_threads {
         serial_fft(a, omega, log_n);
     } else {
-        parallel_fft(a, omega, log_n, log_threads);
+        parallel_fft(a, omega, log_n, log_threads, &serial_fft);
     }
 }
 
-fn serial_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
+fn bitreverse_permutation<G>(a: &mut [G], log_n: u32) {
     fn bitreverse(mut n: u32, l: u32) -> u32 {
         let mut r = 0;
         for _ in 0..l {
@@ -198,6 +657,11 @@ fn serial_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
             a.swap(rk as usize, k as usize);
         }
     }
+}
+
+fn serial_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
+    bitreverse_permutation(a, log_n);
+    let n = a.len() as u32;
 
     let mut m = 1;
     for _ in 0..log_n {
@@ -222,7 +686,15 @@ fn serial_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
     }
 }
 
-fn parallel_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32, log_threads: u32) {
+/// Splits an FFT of size $2^{log_n}$ into $2^{log_threads}$ FFTs that are performed in
+/// parallel by `sub_fft`, which is given each sub-FFT's root of unity and size.
+fn parallel_fft<G: Group>(
+    a: &mut [G],
+    omega: G::Scalar,
+    log_n: u32,
+    log_threads: u32,
+    sub_fft: &(dyn Fn(&mut [G], G::Scalar, u32) + Sync),
+) {
     assert!(log_n >= log_threads);
 
     let num_threads = 1 << log_threads;
@@ -253,7 +725,7 @@ fn parallel_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32, log_threads
                 }
 
                 // Perform sub-FFT
-                serial_fft(tmp, new_omega, log_new_n);
+                sub_fft(tmp, new_omega, log_new_n);
             });
         }
     });
@@ -265,6 +737,179 @@ fn parallel_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32, log_threads
     }
 }
 
+/// A table of precomputed powers ("twiddle factors") of a root of unity $\omega$ of
+/// order $N = 2^{log_n}$, for performing FFTs over the domain that it generates and
+/// over any of its subgroups.
+///
+/// Only $\omega^i$ for $0 \le i < N/2$ are stored; the remaining powers are obtained
+/// from $\omega^{N/2} = -1$. The same table serves inverse FFTs, as
+/// $\omega^{-i} = \omega^{N - i}$.
+///
+/// The FFTs use a radix-$4$ kernel, which combines two rounds of the radix-$2$ FFT
+/// performed by [`best_fft`] into a single pass over the data.
+#[derive(Clone)]
+pub struct FftTwiddles<F: Field> {
+    log_n: u32,
+    powers: Vec<F>,
+}
+
+impl<F: Field> fmt::Debug for FftTwiddles<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The table itself is too large to be useful in debugging output.
+        f.debug_struct("FftTwiddles")
+            .field("log_n", &self.log_n)
+            .finish()
+    }
+}
+
+impl<F: Field> FftTwiddles<F> {
+    /// Precomputes the twiddle factors for `omega`, which must have multiplicative
+    /// order $2^{log_n}$.
+    pub fn new(omega: F, log_n: u32) -> Self {
+        let mut powers = vec![F::zero(); std::cmp::max((1 << log_n) >> 1, 1)];
+        parallelize(&mut powers, |powers, start| {
+            let mut cur = omega.pow_vartime(&[start as u64, 0, 0, 0]);
+            for power in powers {
+                *power = cur;
+                cur *= &omega;
+            }
+        });
+
+        FftTwiddles { log_n, powers }
+    }
+
+    /// Returns the base-2 logarithm of the order of $\omega$, which is the size of the
+    /// largest FFT that this table can be used for.
+    pub fn log_n(&self) -> u32 {
+        self.log_n
+    }
+
+    /// Returns $\omega^{exp}$, or $\omega^{-exp}$ if `inverse` is set.
+    fn power(&self, exp: usize, inverse: bool) -> F {
+        let n = 1 << self.log_n;
+        let exp = exp % n;
+        let exp = if inverse { (n - exp) % n } else { exp };
+        if exp < self.powers.len() {
+            self.powers[exp]
+        } else {
+            -self.powers[exp - self.powers.len()]
+        }
+    }
+
+    /// Performs a Fast-Fourier Transformation (FFT) on a vector of size $n = 2^k$,
+    /// when provided `log_n` = $k$, evaluating it (interpreted as the coefficients of
+    /// a polynomial) at each power of $\omega^{N/n}$. This is the same transformation
+    /// as [`best_fft`] with that root of unity.
+    ///
+    /// This will use multithreading if beneficial, and panics if $n > N$.
+    pub fn fft<G: Group<Scalar = F>>(&self, a: &mut [G], log_n: u32) {
+        self.fft_inner(a, log_n, false)
+    }
+
+    /// Performs the same transformation as [`FftTwiddles::fft`], with $\omega^{-N/n}$
+    /// in place of $\omega^{N/n}$. To invert [`FftTwiddles::fft`], each resulting
+    /// element must then be divided by $n$.
+    pub fn inverse_fft<G: Group<Scalar = F>>(&self, a: &mut [G], log_n: u32) {
+        self.fft_inner(a, log_n, true)
+    }
+
+    fn fft_inner<G: Group<Scalar = F>>(&self, a: &mut [G], log_n: u32, inverse: bool) {
+        enter_span!("fft", log_n, inverse);
+        assert!(log_n <= self.log_n);
+        let stride = 1 << (self.log_n - log_n);
+
+        let threads = multicore::current_num_threads();
+        let log_threads = log2_floor(threads);
+
+        if log_n <= log_threads {
+            self.serial_fft(a, log_n, stride, inverse);
+        } else {
+            // Each sub-FFT is over the subgroup of order 2^(log_n - log_threads).
+            let sub_stride = stride << log_threads;
+            parallel_fft(
+                a,
+                self.power(stride, inverse),
+                log_n,
+                log_threads,
+                &|a: &mut [G], _, log_n| self.serial_fft(a, log_n, sub_stride, inverse),
+            );
+        }
+    }
+
+    /// Performs an FFT of size $n = 2^{log_n}$ with the root of unity
+    /// $\omega^{stride}$ (or its inverse), which must have order $n$.
+    fn serial_fft<G: Group<Scalar = F>>(
+        &self,
+        a: &mut [G],
+        log_n: u32,
+        stride: usize,
+        inverse: bool,
+    ) {
+        bitreverse_permutation(a, log_n);
+        let n = a.len();
+        let twiddle = |exp: usize| self.power(exp * stride, inverse);
+
+        // With an odd number of rounds, perform one radix-2 round first. Its only
+        // twiddle factor is 1.
+        let mut m = 1;
+        if log_n % 2 == 1 {
+            for pair in a.chunks_mut(2) {
+                let t = pair[1];
+                pair[1] = pair[0];
+                pair[1].group_sub(&t);
+                pair[0].group_add(&t);
+            }
+            m = 2;
+        }
+
+        // Each radix-4 round combines four FFTs of size m into one of size 4m. With
+        // w the (4m)'th root of unity and i = w^m the 4th root of unity, the j'th
+        // entries of the four FFTs become
+        //   c0 = (a0 + w^{2j} a1) + (w^j a2 + w^{3j} a3)
+        //   c1 = (a0 - w^{2j} a1) + i (w^j a2 - w^{3j} a3)
+        //   c2 = (a0 + w^{2j} a1) - (w^j a2 + w^{3j} a3)
+        //   c3 = (a0 - w^{2j} a1) - i (w^j a2 - w^{3j} a3)
+        let i = twiddle(n / 4);
+        while m < n {
+            // w = omega^(n / (4m))
+            let w_exp = n / (4 * m);
+            for group in a.chunks_mut(4 * m) {
+                for j in 0..m {
+                    let mut t1 = group[j + m];
+                    let mut t2 = group[j + 2 * m];
+                    let mut t3 = group[j + 3 * m];
+                    if j != 0 {
+                        t1.group_scale(&twiddle(2 * j * w_exp));
+                        t2.group_scale(&twiddle(j * w_exp));
+                        t3.group_scale(&twiddle(3 * j * w_exp));
+                    }
+
+                    let mut sum0 = group[j];
+                    sum0.group_add(&t1);
+                    let mut diff0 = group[j];
+                    diff0.group_sub(&t1);
+                    let mut sum1 = t2;
+                    sum1.group_add(&t3);
+                    let mut diff1 = t2;
+                    diff1.group_sub(&t3);
+                    diff1.group_scale(&i);
+
+                    group[j] = sum0;
+                    group[j].group_add(&sum1);
+                    group[j + m] = diff0;
+                    group[j + m].group_add(&diff1);
+                    group[j + 2 * m] = sum0;
+                    group[j + 2 * m].group_sub(&sum1);
+                    group[j + 3 * m] = diff0;
+                    group[j + 3 * m].group_sub(&diff1);
+                }
+            }
+
+            m *= 4;
+        }
+    }
+}
+
 /// This evaluates a provided polynomial (in coefficient form) at `point`.
 pub fn eval_polynomial<F: Field>(poly: &[F], point: F) -> F {
     // TODO: parallelize?
@@ -427,3 +1072,146 @@ fn test_lagrange_interpolate() {
         }
     }
 }
+
+#[test]
+fn test_fft_twiddles() {
+    let rng = OsRng;
+
+    for max_log_n in 0..=10 {
+        let mut omega = Fp::root_of_unity();
+        for _ in max_log_n..Fp::S {
+            omega = omega.square();
+        }
+        let twiddles = FftTwiddles::new(omega, max_log_n);
+
+        // The table can be used for FFTs over each subgroup of the domain.
+        for log_n in 0..=max_log_n {
+            let sub_omega = omega.pow_vartime(&[1 << (max_log_n - log_n), 0, 0, 0]);
+            let a: Vec<Fp> = (0..(1 << log_n)).map(|_| Fp::random(rng)).collect();
+
+            let mut expected = a.clone();
+            best_fft(&mut expected, sub_omega, log_n);
+            let mut actual = a.clone();
+            twiddles.fft(&mut actual, log_n);
+            assert_eq!(actual, expected);
+
+            let mut expected = a.clone();
+            best_fft(&mut expected, sub_omega.invert().unwrap(), log_n);
+            let mut actual = a;
+            twiddles.inverse_fft(&mut actual, log_n);
+            assert_eq!(actual, expected);
+        }
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use group::{
+        ff::{Field, PrimeField},
+        prime::PrimeCurveAffine,
+        Curve, Group,
+    };
+    use proptest::{collection::vec, prelude::*};
+
+    use super::{best_multiexp, multiexp_windows, small_multiexp, FieldExt, FixedBaseTable};
+    use crate::pasta::{Eq, EqAffine, Fp};
+
+    /// The number of points in [`bases`].
+    const NUM_BASES: usize = 17;
+
+    /// A few points, with their negations and the identity, so that buckets often hold
+    /// points that are equal to or negations of each other.
+    fn bases() -> Vec<EqAffine> {
+        let g = EqAffine::generator();
+        (1..=8u64)
+            .flat_map(|i| {
+                let p = (g * Fp::from(i)).to_affine();
+                vec![p, -p]
+            })
+            .chain(Some(EqAffine::identity()))
+            .collect()
+    }
+
+    /// Computes the expected result of a multiexp over `terms` with [`small_multiexp`],
+    /// after summing the coefficients of each base.
+    fn expected(terms: &[(Fp, usize)]) -> Eq {
+        let mut coeffs = vec![Fp::zero(); NUM_BASES];
+        for (coeff, base) in terms {
+            coeffs[*base] += coeff;
+        }
+        small_multiexp(&coeffs, &bases())
+    }
+
+    fn arb_scalar() -> impl Strategy<Value = Fp> {
+        prop_oneof![
+            Just(Fp::zero()),
+            Just(Fp::one()),
+            Just(-Fp::one()),
+            vec(any::<u8>(), 64).prop_map(|bytes| {
+                let mut buf = [0; 64];
+                buf.copy_from_slice(&bytes);
+                Fp::from_bytes_wide(&buf)
+            }),
+        ]
+    }
+
+    fn arb_terms(len: std::ops::Range<usize>) -> impl Strategy<Value = Vec<(Fp, usize)>> {
+        vec((arb_scalar(), 0..NUM_BASES), len)
+    }
+
+    fn multiexp_inputs(terms: &[(Fp, usize)]) -> (Vec<Fp>, Vec<EqAffine>) {
+        let bases = bases();
+        terms
+            .iter()
+            .map(|(coeff, base)| (*coeff, bases[*base]))
+            .unzip()
+    }
+
+    #[test]
+    fn fixed_base_table() {
+        let rng = rand_core::OsRng;
+
+        // Enough points that batched affine additions are used.
+        let bases: Vec<_> = bases().into_iter().cycle().take(20 * NUM_BASES).collect();
+        let coeffs: Vec<_> = (0..bases.len()).map(|_| Fp::random(rng)).collect();
+
+        for num_copies in 1..=4 {
+            let table = FixedBaseTable::new(&bases, num_copies);
+            assert_eq!(table.len(), bases.len());
+            assert_eq!(table.multiexp(&coeffs), best_multiexp(&coeffs, &bases));
+            assert_eq!(
+                table.multiexp(&coeffs[..50]),
+                best_multiexp(&coeffs[..50], &bases[..50])
+            );
+            assert_eq!(table.multiexp(&[]), Eq::identity());
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn best_multiexp_matches_small_multiexp(terms in arb_terms(0..600)) {
+            let (coeffs, bases) = multiexp_inputs(&terms);
+            prop_assert_eq!(best_multiexp(&coeffs, &bases), expected(&terms));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+
+        #[test]
+        fn multiexp_window_sizes(terms in arb_terms(300..400)) {
+            // Both projective and batched affine bucket accumulation are used, as the
+            // latter only applies to windows with many points.
+            let (coeffs, bases) = multiexp_inputs(&terms);
+            let coeffs: Vec<_> = coeffs.iter().map(|coeff| coeff.to_repr()).collect();
+            let all = expected(&terms);
+            let first = expected(&terms[..100]);
+            for c in 1..=16 {
+                prop_assert_eq!(multiexp_windows(&coeffs, &bases, c, 256), all);
+                prop_assert_eq!(multiexp_windows(&coeffs[..100], &bases[..100], c, 256), first);
+            }
+        }
+    }
+}
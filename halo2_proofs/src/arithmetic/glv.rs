@@ -0,0 +1,339 @@
+//! Scalar decomposition for curves with an efficient endomorphism, following
+//! [GLV](https://www.iacr.org/archive/crypto2001/21390189.pdf).
+//!
+//! The Pasta curves have the endomorphism $\phi(x, y) = (\zeta x, y)$, where $\zeta$ is
+//! a cube root of unity in the base field, which acts as multiplication by a cube root
+//! of unity $\lambda$ in the scalar field. A scalar $k$ can be split into $k_1$ and
+//! $k_2$ of about half its size with $k = k_1 + k_2 \lambda$, so that
+//! $[k] P = [k_1] P + [k_2] \phi(P)$ needs half as many doublings.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{parallelize, Coordinates, CurveAffine, FieldExt};
+use group::{ff::PrimeField, prime::PrimeCurveAffine};
+
+/// The number of bits in the magnitudes of the scalars produced by
+/// [`GlvParams::split`].
+pub(super) const SCALAR_BITS: usize = 130;
+
+/// The constants for decomposing scalars with the endomorphism $\phi(x, y) = (\zeta x, y)$
+/// for a particular $\zeta$.
+///
+/// The vectors $(a_1, -b_1)$ and $(a_2, b_2)$ form a reduced basis of the lattice of
+/// $(x, y)$ with $x + y \lambda = 0$ modulo the scalar field's modulus $r$. A scalar $k$ is
+/// decomposed by rounding its coordinates in this basis: with
+/// $c_1 = \lfloor k g_1 / 2^{256} \rfloor \approx k b_2 / r$ and
+/// $c_2 = \lfloor k g_2 / 2^{256} \rfloor \approx k b_1 / r$,
+/// $(k_1, k_2) = (k, 0) - c_1 (a_1, -b_1) - c_2 (a_2, b_2)$.
+pub(super) struct GlvParams {
+    /// $\zeta$, in the base field's little-endian representation.
+    zeta: [u8; 32],
+    /// $\lambda$ such that $\phi(P) = [\lambda] P$, in the scalar field's little-endian
+    /// representation.
+    #[cfg_attr(not(test), allow(dead_code))]
+    lambda: [u8; 32],
+    a1: u128,
+    b1: u128,
+    a2: u128,
+    b2: u128,
+    /// $g_1 = \mathrm{round}(2^{256} b_2 / r)$, as little-endian limbs.
+    g1: [u64; 3],
+    /// $g_2 = \mathrm{round}(2^{256} b_1 / r)$, as little-endian limbs.
+    g2: [u64; 3],
+}
+
+/// The parameters for each choice of $\zeta$ on Pallas and Vesta.
+const GLV_PARAMS: [GlvParams; 4] = [
+    // Pallas, with zeta = 0x12ccca834acdba712caad5dc57aab1b01d1f8bd237ad31491dad5ebdfdfe4ab9.
+    GlvParams {
+        zeta: [
+            0xb9, 0x4a, 0xfe, 0xfd, 0xbd, 0x5e, 0xad, 0x1d, 0x49, 0x31, 0xad, 0x37, 0xd2, 0x8b,
+            0x1f, 0x1d, 0xb0, 0xb1, 0xaa, 0x57, 0xdc, 0xd5, 0xaa, 0x2c, 0x71, 0xba, 0xcd, 0x4a,
+            0x83, 0xca, 0xcc, 0x12,
+        ],
+        lambda: [
+            0x4f, 0x0e, 0xaa, 0x50, 0xe0, 0xd2, 0xa9, 0x2a, 0xaf, 0x33, 0xc0, 0x47, 0x7d, 0x46,
+            0xed, 0x0f, 0x5a, 0x0f, 0xf7, 0x1c, 0xd8, 0xb4, 0x1d, 0x51, 0x8e, 0x52, 0x3e, 0x28,
+            0x58, 0x9a, 0x81, 0x06,
+        ],
+        a1: 0x49e69d1640f049157fcae1c700000001,
+        b1: 0x49e69d1640a899538cb1279300000000,
+        a2: 0x49e69d1640a899538cb1279300000000,
+        b2: 0x93cd3a2c8198e2690c7c095a00000001,
+        g1: [0x31f0256800000003, 0x4f34e8b2066389a4, 0x0000000000000002],
+        g2: [0x32c49e4bffffffff, 0x279a745902a2654e, 0x0000000000000001],
+    },
+    // Pallas, with zeta = 0x2d33357cb532458ed3552a23a8554e5005270d29d19fc7d27b7fd22f0201b547.
+    GlvParams {
+        zeta: [
+            0x47, 0xb5, 0x01, 0x02, 0x2f, 0xd2, 0x7f, 0x7b, 0xd2, 0xc7, 0x9f, 0xd1, 0x29, 0x0d,
+            0x27, 0x05, 0x50, 0x4e, 0x55, 0xa8, 0x23, 0x2a, 0x55, 0xd3, 0x8e, 0x45, 0x32, 0xb5,
+            0x7c, 0x35, 0x33, 0x2d,
+        ],
+        lambda: [
+            0xb1, 0xf1, 0x55, 0xaf, 0x40, 0x18, 0x9d, 0x61, 0x2e, 0x75, 0xd4, 0xc1, 0x7e, 0x52,
+            0x59, 0x12, 0xa6, 0xf0, 0x08, 0xe3, 0x27, 0x4b, 0xe2, 0xae, 0x71, 0xad, 0xc1, 0xd7,
+            0xa7, 0x65, 0x7e, 0x39,
+        ],
+        a1: 0x49e69d1640a899538cb1279300000000,
+        b1: 0x49e69d1640f049157fcae1c700000001,
+        a2: 0x93cd3a2c8198e2690c7c095a00000001,
+        b2: 0x49e69d1640a899538cb1279300000000,
+        g1: [0x32c49e4bffffffff, 0x279a745902a2654e, 0x0000000000000001],
+        g2: [0xff2b871c00000003, 0x279a745903c12455, 0x0000000000000001],
+    },
+    // Vesta, with zeta = 0x6819a58283e528e511db4d81cf70f5a0fed467d47c033af2aa9d2e050aa0e4f.
+    GlvParams {
+        zeta: [
+            0x4f, 0x0e, 0xaa, 0x50, 0xe0, 0xd2, 0xa9, 0x2a, 0xaf, 0x33, 0xc0, 0x47, 0x7d, 0x46,
+            0xed, 0x0f, 0x5a, 0x0f, 0xf7, 0x1c, 0xd8, 0xb4, 0x1d, 0x51, 0x8e, 0x52, 0x3e, 0x28,
+            0x58, 0x9a, 0x81, 0x06,
+        ],
+        lambda: [
+            0xb9, 0x4a, 0xfe, 0xfd, 0xbd, 0x5e, 0xad, 0x1d, 0x49, 0x31, 0xad, 0x37, 0xd2, 0x8b,
+            0x1f, 0x1d, 0xb0, 0xb1, 0xaa, 0x57, 0xdc, 0xd5, 0xaa, 0x2c, 0x71, 0xba, 0xcd, 0x4a,
+            0x83, 0xca, 0xcc, 0x12,
+        ],
+        a1: 0x49e69d1640f049157fcae1c700000000,
+        b1: 0x49e69d1640a899538cb1279300000001,
+        a2: 0x49e69d1640a899538cb1279300000001,
+        b2: 0x93cd3a2c8198e2690c7c095a00000001,
+        g1: [0x31f0256800000003, 0x4f34e8b2066389a4, 0x0000000000000002],
+        g2: [0x32c49e4c00000003, 0x279a745902a2654e, 0x0000000000000001],
+    },
+    // Vesta, with zeta = 0x397e65a7d7c1ad71aee24b27e308f0a61259527ec1d4752e619d1840af55f1b1.
+    GlvParams {
+        zeta: [
+            0xb1, 0xf1, 0x55, 0xaf, 0x40, 0x18, 0x9d, 0x61, 0x2e, 0x75, 0xd4, 0xc1, 0x7e, 0x52,
+            0x59, 0x12, 0xa6, 0xf0, 0x08, 0xe3, 0x27, 0x4b, 0xe2, 0xae, 0x71, 0xad, 0xc1, 0xd7,
+            0xa7, 0x65, 0x7e, 0x39,
+        ],
+        lambda: [
+            0x47, 0xb5, 0x01, 0x02, 0x2f, 0xd2, 0x7f, 0x7b, 0xd2, 0xc7, 0x9f, 0xd1, 0x29, 0x0d,
+            0x27, 0x05, 0x50, 0x4e, 0x55, 0xa8, 0x23, 0x2a, 0x55, 0xd3, 0x8e, 0x45, 0x32, 0xb5,
+            0x7c, 0x35, 0x33, 0x2d,
+        ],
+        a1: 0x49e69d1640a899538cb1279300000001,
+        b1: 0x49e69d1640f049157fcae1c700000000,
+        a2: 0x93cd3a2c8198e2690c7c095a00000001,
+        b2: 0x49e69d1640a899538cb1279300000001,
+        g1: [0x32c49e4c00000003, 0x279a745902a2654e, 0x0000000000000001],
+        g2: [0xff2b871bffffffff, 0x279a745903c12455, 0x0000000000000001],
+    },
+];
+
+/// Returns $\lfloor k g / 2^{256} \rfloor$, for $k < 2^{256}$ given as little-endian bytes,
+/// and $g < 2^{192}$ such that the result is less than $2^{128}$.
+fn mul_shift(k: &[u8], g: &[u64; 3]) -> u128 {
+    let mut k_limbs = [0u64; 4];
+    for (limb, bytes) in k_limbs.iter_mut().zip(k.chunks(8)) {
+        let mut buf = [0; 8];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        *limb = u64::from_le_bytes(buf);
+    }
+
+    let mut product = [0u64; 7];
+    for (i, k_limb) in k_limbs.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, g_limb) in g.iter().enumerate() {
+            let t = u128::from(*k_limb) * u128::from(*g_limb) + u128::from(product[i + j]) + carry;
+            product[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        product[i + 3] = carry as u64;
+    }
+    debug_assert_eq!(product[6], 0);
+
+    u128::from(product[4]) | (u128::from(product[5]) << 64)
+}
+
+/// Returns the magnitude of `x` and whether it is negative, or `None` if `x` is not
+/// less than $2^{SCALAR\_BITS}$ in absolute value.
+fn signed_magnitude<F: PrimeField>(x: F) -> Option<(bool, F::Repr)> {
+    let fits = |repr: &F::Repr| {
+        let bytes = repr.as_ref();
+        bytes[SCALAR_BITS / 8] >> (SCALAR_BITS % 8) == 0
+            && bytes[(SCALAR_BITS / 8 + 1)..].iter().all(|byte| *byte == 0)
+    };
+
+    let repr = x.to_repr();
+    if fits(&repr) {
+        Some((false, repr))
+    } else {
+        let repr = (-x).to_repr();
+        if fits(&repr) {
+            Some((true, repr))
+        } else {
+            None
+        }
+    }
+}
+
+/// Applies the endomorphism $\phi(x, y) = (\zeta x, y)$ to `point`.
+pub(super) fn endo<C: CurveAffine>(point: &C, zeta: C::Base) -> C {
+    match Option::<Coordinates<C>>::from(point.coordinates()) {
+        Some(coords) => C::from_xy(*coords.x() * zeta, *coords.y()).unwrap(),
+        None => C::identity(),
+    }
+}
+
+impl GlvParams {
+    /// Returns the parameters for the endomorphism of `C` with $\zeta$ set to
+    /// `C::Base::ZETA`, if `C` is one of the Pasta curves.
+    pub(super) fn for_curve<C: CurveAffine>() -> Option<&'static GlvParams> {
+        let zeta = C::Base::ZETA.to_repr();
+        GLV_PARAMS
+            .iter()
+            .find(|params| zeta.as_ref() == &params.zeta[..])
+    }
+
+    /// Decomposes `k` into $(k_1, k_2)$ with $k = k_1 + k_2 \lambda$, returning the
+    /// magnitude and sign of each.
+    ///
+    /// The reduced basis bounds both magnitudes well below $2^{SCALAR\_BITS}$, but
+    /// rather than rely on this, `None` is returned if either does not fit, and the
+    /// callers fall back to multi-exponentiations over the full scalars.
+    fn decompose<F: FieldExt>(&self, k: &F) -> Option<[(bool, F::Repr); 2]> {
+        let repr = k.to_repr();
+        let c1 = F::from_u128(mul_shift(repr.as_ref(), &self.g1));
+        let c2 = F::from_u128(mul_shift(repr.as_ref(), &self.g2));
+
+        let k1 = *k - c1 * F::from_u128(self.a1) - c2 * F::from_u128(self.a2);
+        let k2 = c1 * F::from_u128(self.b1) - c2 * F::from_u128(self.b2);
+
+        Some([signed_magnitude(k1)?, signed_magnitude(k2)?])
+    }
+
+    /// Decomposes each of `coeffs` with [`GlvParams::decompose`], or returns `None` if
+    /// any of them cannot be decomposed.
+    pub(super) fn decompose_all<F: FieldExt>(
+        &self,
+        coeffs: &[F],
+    ) -> Option<Vec<[(bool, F::Repr); 2]>> {
+        let failed = AtomicBool::new(false);
+        let term = (false, F::Repr::default());
+        let mut decomposed = vec![[term, term]; coeffs.len()];
+        parallelize(&mut decomposed, |decomposed, start| {
+            for (decomposed, coeff) in decomposed.iter_mut().zip(coeffs[start..].iter()) {
+                match self.decompose(coeff) {
+                    Some(terms) => *decomposed = terms,
+                    None => {
+                        failed.store(true, Ordering::Relaxed);
+                        return;
+                    }
+                }
+            }
+        });
+
+        if failed.load(Ordering::Relaxed) {
+            None
+        } else {
+            Some(decomposed)
+        }
+    }
+
+    /// Splits a multi-exponentiation into one of twice as many points, with scalars of
+    /// at most [`SCALAR_BITS`] bits: each $[k] P$ becomes $[k_1] P + [k_2] \phi(P)$,
+    /// with the points negated in place of negative scalars.
+    ///
+    /// Returns `None` if any of the scalars cannot be decomposed.
+    pub(super) fn split<C: CurveAffine>(
+        &self,
+        coeffs: &[C::Scalar],
+        bases: &[C],
+    ) -> Option<(Vec<<C::Scalar as PrimeField>::Repr>, Vec<C>)> {
+        let zeta = C::Base::ZETA;
+        let decomposed = self.decompose_all(coeffs)?;
+
+        let term = (<C::Scalar as PrimeField>::Repr::default(), C::identity());
+        let mut terms = vec![[term, term]; bases.len()];
+        parallelize(&mut terms, |terms, start| {
+            for ((terms, decomposed), base) in terms
+                .iter_mut()
+                .zip(decomposed[start..].iter())
+                .zip(bases[start..].iter())
+            {
+                let [(neg1, k1), (neg2, k2)] = *decomposed;
+                let endo_base = endo(base, zeta);
+                terms[0] = (k1, if neg1 { -*base } else { *base });
+                terms[1] = (k2, if neg2 { -endo_base } else { endo_base });
+            }
+        });
+
+        Some(terms.iter().flat_map(|terms| terms.iter().cloned()).unzip())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::{
+        ff::{Field, PrimeField},
+        prime::PrimeCurveAffine,
+        Curve,
+    };
+    use rand_core::OsRng;
+
+    use super::{endo, GlvParams, SCALAR_BITS};
+    use crate::arithmetic::{CurveAffine, FieldExt};
+    use crate::pasta::{EpAffine, EqAffine};
+
+    fn check_curve<C: CurveAffine>() {
+        let params = GlvParams::for_curve::<C>().expect("Pasta curves have GLV parameters");
+
+        // phi(P) = [lambda] P
+        let lambda = C::Scalar::from_repr({
+            let mut repr = <C::Scalar as PrimeField>::Repr::default();
+            repr.as_mut().copy_from_slice(&params.lambda);
+            repr
+        })
+        .unwrap();
+        let g = C::generator();
+        assert_eq!(endo(&g, C::Base::ZETA), (g * lambda).to_affine());
+        assert_eq!(endo(&C::identity(), C::Base::ZETA), C::identity());
+
+        let scalars = [C::Scalar::zero(), C::Scalar::one(), -C::Scalar::one()];
+        let random = (0..100).map(|_| C::Scalar::random(OsRng));
+        for k in scalars.iter().cloned().chain(random) {
+            let [(neg1, k1), (neg2, k2)] = params.decompose(&k).unwrap();
+            let k1 = C::Scalar::from_repr(k1).unwrap();
+            let k2 = C::Scalar::from_repr(k2).unwrap();
+            let k1 = if neg1 { -k1 } else { k1 };
+            let k2 = if neg2 { -k2 } else { k2 };
+            assert_eq!(k1 + k2 * lambda, k);
+        }
+
+        // Splitting preserves the result of a multi-exponentiation.
+        let coeffs: Vec<_> = (0..10).map(|_| C::Scalar::random(OsRng)).collect();
+        let bases: Vec<_> = (0..10)
+            .map(|_| (g * C::Scalar::random(OsRng)).to_affine())
+            .collect();
+        let (split_coeffs, split_bases) = params.split(&coeffs, &bases).unwrap();
+        assert_eq!(split_coeffs.len(), 20);
+        let expected = coeffs
+            .iter()
+            .zip(bases.iter())
+            .fold(C::Curve::identity(), |acc, (coeff, base)| {
+                acc + *base * coeff
+            });
+        let actual = split_coeffs.iter().zip(split_bases.iter()).fold(
+            C::Curve::identity(),
+            |acc, (coeff, base)| {
+                assert!(coeff.as_ref()[(SCALAR_BITS + 7) / 8..]
+                    .iter()
+                    .all(|b| *b == 0));
+                acc + *base * C::Scalar::from_repr(*coeff).unwrap()
+            },
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn pallas() {
+        check_curve::<EpAffine>();
+    }
+
+    #[test]
+    fn vesta() {
+        check_curve::<EqAffine>();
+    }
+}
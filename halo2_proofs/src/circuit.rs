@@ -402,6 +402,45 @@ pub trait Layouter<F: Field> {
         N: Fn() -> NR,
         NR: Into<String>;
 
+    /// Assigns several independent regions, whose assignments may be run in parallel.
+    ///
+    /// The resulting circuit is identical to calling [`Layouter::assign_region`] for each
+    /// assignment in turn, with `name(i)` naming the `i`th region; the return values
+    /// are likewise in order. Because the assignments may run concurrently, none of them
+    /// can depend on cells assigned by another. As with [`Layouter::assign_region`], each
+    /// assignment is run once per pass of the floor planner, and witness values are only
+    /// computed if the underlying [`Assignment`] requests them.
+    ///
+    /// The default implementation assigns the regions serially.
+    ///
+    /// ```ignore
+    /// let cells = layouter.assign_regions(
+    ///     |i| format!("row {}", i),
+    ///     rows.iter()
+    ///         .map(|row| move |mut region: Region<'_, F>| chip.assign_row(&mut region, row))
+    ///         .collect(),
+    /// )?;
+    /// ```
+    ///
+    /// [`Assignment`]: crate::plonk::Assignment
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        assignments
+            .into_iter()
+            .enumerate()
+            .map(|(i, assignment)| self.assign_region(|| name(i), assignment))
+            .collect()
+    }
+
     /// Assign a table region to an absolute row number.
     ///
     /// ```ignore
@@ -472,6 +511,20 @@ impl<'a, F: Field, L: Layouter<F> + 'a> Layouter<F> for NamespacedLayouter<'a, F
         self.0.assign_region(name, assignment)
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_regions(name, assignments)
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
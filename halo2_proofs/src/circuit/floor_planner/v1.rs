@@ -5,12 +5,12 @@ use ff::Field;
 use crate::{
     circuit::{
         floor_planner::single_pass::SimpleTableLayouter,
-        layouter::{RegionColumn, RegionLayouter, RegionShape, TableLayouter},
+        layouter::{self, RegionColumn, RegionLayouter, RegionShape, TableLayouter},
         Cell, Layouter, Region, RegionIndex, RegionStart, Table,
     },
     plonk::{
         Advice, Any, Assigned, Assignment, Circuit, Column, Error, Fixed, FloorPlanner, Instance,
-        Selector, TableColumn,
+        Selector, TableColumn, WitnessAccess,
     },
 };
 
@@ -175,6 +175,34 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for V1Pass<'p, 'a, F,
         }
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        let first_region = match &self.0 {
+            Pass::Measurement(pass) => pass.regions.len(),
+            Pass::Assignment(pass) => pass.region_index,
+        };
+        layouter::assign_regions_in_parallel(
+            self,
+            first_region,
+            |layouter| match &layouter.0 {
+                // Measurement only records the shape of each region.
+                Pass::Measurement(_) => WitnessAccess::Skipped,
+                Pass::Assignment(pass) => pass.plan.cs.witness_access(),
+            },
+            name,
+            assignments,
+        )
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
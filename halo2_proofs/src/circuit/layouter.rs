@@ -3,11 +3,15 @@
 use std::cmp;
 use std::collections::HashSet;
 use std::fmt;
+use std::mem;
 
 use ff::Field;
 
-use super::{Cell, RegionIndex};
-use crate::plonk::{Advice, Any, Assigned, Column, Error, Fixed, Instance, Selector, TableColumn};
+use super::{Cell, Layouter, Region, RegionIndex};
+use crate::multicore;
+use crate::plonk::{
+    Advice, Any, Assigned, Column, Error, Fixed, Instance, Selector, TableColumn, WitnessAccess,
+};
 
 /// Helper trait for implementing a custom [`Layouter`].
 ///
@@ -285,3 +289,338 @@ impl<F: Field> RegionLayouter<F> for RegionShape {
         Ok(())
     }
 }
+
+/// An operation performed on a [`RegionRecord`], with any witness value already computed.
+#[derive(Debug)]
+enum RegionOp<F: Field> {
+    EnableSelector {
+        annotation: String,
+        selector: Selector,
+        offset: usize,
+    },
+    AssignAdvice {
+        annotation: String,
+        column: Column<Advice>,
+        offset: usize,
+        value: Result<Assigned<F>, Error>,
+    },
+    AssignAdviceFromConstant {
+        annotation: String,
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    },
+    AssignAdviceFromInstance {
+        annotation: String,
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    },
+    AssignFixed {
+        annotation: String,
+        column: Column<Fixed>,
+        offset: usize,
+        value: Result<Assigned<F>, Error>,
+    },
+    ConstrainConstant {
+        cell: Cell,
+        constant: Assigned<F>,
+    },
+    ConstrainEqual {
+        left: Cell,
+        right: Cell,
+    },
+}
+
+/// Takes a recorded witness value. The layouters call `to` at most once per
+/// assignment, so an error is only ever handed back once.
+fn take_value<F: Field>(value: &mut Result<Assigned<F>, Error>) -> Result<Assigned<F>, Error> {
+    match value {
+        Ok(value) => Ok(*value),
+        Err(e) => Err(mem::replace(e, Error::Synthesis)),
+    }
+}
+
+/// Replays recorded operations, in order, into the given region.
+fn replay<F: Field>(
+    ops: &mut [RegionOp<F>],
+    region: &mut dyn RegionLayouter<F>,
+) -> Result<(), Error> {
+    for op in ops.iter_mut() {
+        match op {
+            RegionOp::EnableSelector {
+                annotation,
+                selector,
+                offset,
+            } => region.enable_selector(&|| annotation.clone(), selector, *offset)?,
+            RegionOp::AssignAdvice {
+                annotation,
+                column,
+                offset,
+                value,
+            } => {
+                region.assign_advice(&|| annotation.clone(), *column, *offset, &mut || {
+                    take_value(value)
+                })?;
+            }
+            RegionOp::AssignAdviceFromConstant {
+                annotation,
+                column,
+                offset,
+                constant,
+            } => {
+                region.assign_advice_from_constant(
+                    &|| annotation.clone(),
+                    *column,
+                    *offset,
+                    *constant,
+                )?;
+            }
+            RegionOp::AssignAdviceFromInstance {
+                annotation,
+                instance,
+                row,
+                advice,
+                offset,
+            } => {
+                region.assign_advice_from_instance(
+                    &|| annotation.clone(),
+                    *instance,
+                    *row,
+                    *advice,
+                    *offset,
+                )?;
+            }
+            RegionOp::AssignFixed {
+                annotation,
+                column,
+                offset,
+                value,
+            } => {
+                region.assign_fixed(&|| annotation.clone(), *column, *offset, &mut || {
+                    take_value(value)
+                })?;
+            }
+            RegionOp::ConstrainConstant { cell, constant } => {
+                region.constrain_constant(*cell, *constant)?
+            }
+            RegionOp::ConstrainEqual { left, right } => region.constrain_equal(*left, *right)?,
+        }
+    }
+    Ok(())
+}
+
+/// A region that records the operations performed on it, so that a region assignment
+/// can be run once on a worker thread and later replayed into the real layouter.
+///
+/// Witness values are computed while recording, unless the [`WitnessAccess`] says that
+/// they are not needed, and instance values are read through it.
+struct RegionRecord<'w, 'a, F: Field> {
+    region_index: RegionIndex,
+    witness: &'w WitnessAccess<'a, F>,
+    ops: Vec<RegionOp<F>>,
+}
+
+impl<'w, 'a, F: Field> fmt::Debug for RegionRecord<'w, 'a, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegionRecord")
+            .field("region_index", &self.region_index)
+            .field("witness", self.witness)
+            .field("ops", &self.ops)
+            .finish()
+    }
+}
+
+impl<'w, 'a, F: Field> RegionRecord<'w, 'a, F> {
+    fn new(region_index: RegionIndex, witness: &'w WitnessAccess<'a, F>) -> Self {
+        RegionRecord {
+            region_index,
+            witness,
+            ops: vec![],
+        }
+    }
+
+    fn cell(&self, column: Column<Any>, offset: usize) -> Cell {
+        Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column,
+        }
+    }
+}
+
+impl<'w, 'a, F: Field> RegionLayouter<F> for RegionRecord<'w, 'a, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.ops.push(RegionOp::EnableSelector {
+            annotation: annotation(),
+            selector: *selector,
+            offset,
+        });
+        Ok(())
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<Cell, Error> {
+        let value = match self.witness {
+            // The layouter will not ask for this value, as the assignment does not
+            // compute witness values.
+            WitnessAccess::Skipped => Err(Error::Synthesis),
+            _ => to(),
+        };
+        self.ops.push(RegionOp::AssignAdvice {
+            annotation: annotation(),
+            column,
+            offset,
+            value,
+        });
+        Ok(self.cell(column.into(), offset))
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        self.ops.push(RegionOp::AssignAdviceFromConstant {
+            annotation: annotation(),
+            column,
+            offset,
+            constant,
+        });
+        Ok(self.cell(column.into(), offset))
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Option<F>), Error> {
+        let value = match self.witness {
+            WitnessAccess::Computed(query_instance) => query_instance(instance, row)?,
+            _ => None,
+        };
+        self.ops.push(RegionOp::AssignAdviceFromInstance {
+            annotation: annotation(),
+            instance,
+            row,
+            advice,
+            offset,
+        });
+        Ok((self.cell(advice.into(), offset), value))
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<Cell, Error> {
+        self.ops.push(RegionOp::AssignFixed {
+            annotation: annotation(),
+            column,
+            offset,
+            value: to(),
+        });
+        Ok(self.cell(column.into(), offset))
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.ops
+            .push(RegionOp::ConstrainConstant { cell, constant });
+        Ok(())
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        self.ops.push(RegionOp::ConstrainEqual { left, right });
+        Ok(())
+    }
+}
+
+/// Helper for implementing [`Layouter::assign_regions`].
+///
+/// Each assignment is run once, in parallel, against a [`RegionRecord`] whose cells are
+/// given the region indices `first_region..first_region + assignments.len()`. The
+/// recorded regions are then replayed into `layouter` in order, so the circuit is laid
+/// out exactly as if `layouter.assign_region` had been called for each assignment in
+/// turn. This requires that `layouter` allocate region indices sequentially from
+/// `first_region`.
+///
+/// `witness_access` returns how the underlying [`Assignment`] can be read while the
+/// regions are recorded; if it can only be read serially, the regions are assigned
+/// serially instead.
+///
+/// [`Assignment`]: crate::plonk::Assignment
+pub(crate) fn assign_regions_in_parallel<F, L, W, A, AR, N, NR>(
+    layouter: &mut L,
+    first_region: usize,
+    witness_access: W,
+    name: N,
+    assignments: Vec<A>,
+) -> Result<Vec<AR>, Error>
+where
+    F: Field,
+    L: Layouter<F>,
+    W: FnOnce(&L) -> WitnessAccess<'_, F>,
+    A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+    AR: Send,
+    N: Fn(usize) -> NR,
+    NR: Into<String>,
+{
+    let witness = witness_access(&*layouter);
+    if let WitnessAccess::Serial = witness {
+        drop(witness);
+        return assignments
+            .into_iter()
+            .enumerate()
+            .map(|(i, assignment)| layouter.assign_region(|| name(i), assignment))
+            .collect();
+    }
+
+    let mut recorded: Vec<Option<(Vec<RegionOp<F>>, Result<AR, Error>)>> =
+        (0..assignments.len()).map(|_| None).collect();
+    multicore::scope(|scope| {
+        let witness = &witness;
+        for (i, (mut assignment, slot)) in
+            assignments.into_iter().zip(recorded.iter_mut()).enumerate()
+        {
+            scope.spawn(move |_| {
+                let mut record = RegionRecord::new((first_region + i).into(), witness);
+                let result = {
+                    let region: &mut dyn RegionLayouter<F> = &mut record;
+                    assignment(region.into())
+                };
+                *slot = Some((record.ops, result));
+            });
+        }
+    });
+    drop(witness);
+
+    recorded
+        .into_iter()
+        .enumerate()
+        .map(|(i, region)| {
+            let (mut ops, result) = region.expect("all regions were recorded");
+            // A region that failed would have failed in the same place serially.
+            let result = result?;
+            layouter.assign_region(|| name(i), |region| replay(&mut ops, region.region))?;
+            Ok(result)
+        })
+        .collect()
+}
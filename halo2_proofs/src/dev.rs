@@ -13,20 +13,42 @@ use crate::{
     arithmetic::{FieldExt, Group},
     plonk::{
         permutation, Advice, Any, Assignment, Circuit, Column, ColumnType, ConstraintSystem, Error,
-        Expression, Fixed, FloorPlanner, Instance, Selector, VirtualCell,
+        Expression, Fixed, FloorPlanner, Instance, Selector, VirtualCell, WitnessAccess,
     },
     poly::Rotation,
 };
 
+pub mod lint;
 pub mod metadata;
+pub mod mutation;
 mod util;
 
 pub mod cost;
 pub use cost::CircuitCost;
 
+mod coverage;
+pub use coverage::Coverage;
+
+mod diff;
+pub use diff::{diff, CircuitDiff, Difference};
+
+pub mod export;
+
+mod failure;
+
 mod gates;
 pub use gates::CircuitGates;
 
+mod layout;
+mod render;
+pub use render::LayoutRenderer;
+
+mod unconstrained;
+pub use unconstrained::ConstraintWarning;
+
+pub use crate::plonk::witness_dump::WitnessDump;
+use crate::plonk::witness_dump::WitnessRecorder;
+
 #[cfg(feature = "dev-graph")]
 mod graph;
 
@@ -179,6 +201,18 @@ pub enum VerifyFailure {
         /// The row on which this permutation is not satisfied.
         row: usize,
     },
+    /// An equality constraint was applied to a cell in a column that is not part of the
+    /// permutation. The real prover rejects such circuits during keygen.
+    ///
+    /// This is only reported by provers created with
+    /// [`MockProver::run_reporting_copy_failures`]; [`MockProver::run`] fails with
+    /// [`Error::ColumnNotInPermutation`] instead.
+    ColumnNotInPermutation {
+        /// The column that needs to be equality-enabled.
+        column: metadata::Column,
+        /// The location of the cell that the equality constraint was applied to.
+        location: FailureLocation,
+    },
 }
 
 impl fmt::Display for VerifyFailure {
@@ -221,15 +255,22 @@ impl fmt::Display for VerifyFailure {
             Self::Permutation { column, row } => {
                 write!(
                     f,
-                    "Equality constraint not satisfied by cell ({:?}, {})",
+                    "Equality constraint not satisfied by cell ({}, {})",
                     column, row
                 )
             }
+            Self::ColumnNotInPermutation { column, location } => {
+                write!(
+                    f,
+                    "Equality constraint applied to cell in {} {}, but {} is not equality-enabled",
+                    column, location, column
+                )
+            }
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Region {
     /// The name of the region. Not required to be unique.
     name: String,
@@ -461,7 +502,7 @@ impl<F: Group + Field> Mul<F> for Value<F> {
 ///     } if current_k == 2,
 /// ));
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MockProver<F: Group + Field> {
     k: u32,
     n: u32,
@@ -483,6 +524,12 @@ pub struct MockProver<F: Group + Field> {
     selectors: Vec<Vec<bool>>,
 
     permutation: permutation::keygen::Assembly,
+    // Whether equality constraints applied to columns that are not part of the
+    // permutation are recorded in `invalid_copies`, instead of failing synthesis.
+    record_invalid_copies: bool,
+    // Cells that had equality constraints applied to them, in columns that are not part
+    // of the permutation.
+    invalid_copies: Vec<(Column<Any>, usize)>,
 
     // A range of available rows for assignment and copies.
     usable_rows: Range<usize>,
@@ -544,6 +591,12 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
             .ok_or(Error::BoundsFailure)
     }
 
+    fn witness_access(&self) -> WitnessAccess<'_, F> {
+        WitnessAccess::Computed(Box::new(move |column, row| {
+            self.query_instance(column, row)
+        }))
+    }
+
     fn assign_advice<V, VR, A, AR>(
         &mut self,
         _: A,
@@ -617,8 +670,23 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
             return Err(Error::not_enough_rows_available(self.k));
         }
 
-        self.permutation
+        match self
+            .permutation
             .copy(left_column, left_row, right_column, right_row)
+        {
+            // Record the cells instead of failing synthesis, so that `verify` can report
+            // where the equality constraint was applied.
+            Err(Error::ColumnNotInPermutation(_)) if self.record_invalid_copies => {
+                let columns = self.cs.permutation.get_columns();
+                for &(column, row) in [(left_column, left_row), (right_column, right_row)].iter() {
+                    if !columns.contains(&column) {
+                        self.invalid_copies.push((column, row));
+                    }
+                }
+                Ok(())
+            }
+            res => res,
+        }
     }
 
     fn fill_from_row(
@@ -659,6 +727,80 @@ impl<F: FieldExt> MockProver<F> {
         circuit: &ConcreteCircuit,
         instance: Vec<Vec<F>>,
     ) -> Result<Self, Error> {
+        Self::run_with(k, instance, |prover, config, constants| {
+            ConcreteCircuit::FloorPlanner::synthesize(prover, circuit, config, constants)
+        })
+    }
+
+    /// Runs [`MockProver::run`] on the given circuit, but records equality constraints
+    /// that are applied to columns which have not been equality-enabled, instead of
+    /// failing with [`Error::ColumnNotInPermutation`] on the first one.
+    ///
+    /// [`MockProver::verify`] then reports each such cell as a
+    /// [`VerifyFailure::ColumnNotInPermutation`], together with its region and offset.
+    pub fn run_reporting_copy_failures<ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<F>>,
+    ) -> Result<Self, Error> {
+        Self::run_with(k, instance, |prover, config, constants| {
+            prover.record_invalid_copies = true;
+            ConcreteCircuit::FloorPlanner::synthesize(prover, circuit, config, constants)
+        })
+    }
+
+    /// Runs [`MockProver::run`] on the given circuit, and also returns a dump of its
+    /// assignments that can later be replayed with [`MockProver::replay`].
+    ///
+    /// The dump is returned even if the run fails, and then contains everything that was
+    /// assigned up to the failure.
+    pub fn run_with_dump<ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<F>>,
+    ) -> (Result<Self, Error>, WitnessDump<F>) {
+        let mut dump = WitnessDump::new(k, instance.clone());
+        let prover = Self::run_with(k, instance, |prover, config, constants| {
+            ConcreteCircuit::FloorPlanner::synthesize(
+                &mut WitnessRecorder::new(prover, &mut dump),
+                circuit,
+                config,
+                constants,
+            )
+        });
+        (prover, dump)
+    }
+
+    /// Replays a dump of a circuit's assignments, captured with
+    /// [`MockProver::run_with_dump`] or [`ProverOptions::dump_witnesses`], without running
+    /// the circuit's witness generation.
+    ///
+    /// The circuit is still configured with `ConcreteCircuit::configure`, so it must be
+    /// the same circuit that the dump was captured from. If the dump was captured from a
+    /// failed synthesis, the region that was being assigned when it failed is closed, so
+    /// that `verify` can report on the cells that were assigned within it.
+    ///
+    /// [`ProverOptions::dump_witnesses`]: crate::plonk::ProverOptions::dump_witnesses
+    pub fn replay<ConcreteCircuit: Circuit<F>>(dump: &WitnessDump<F>) -> Result<Self, Error> {
+        Self::run_with::<ConcreteCircuit, _>(dump.k(), dump.instance().to_vec(), |prover, _, _| {
+            dump.replay(prover)?;
+            if prover.current_region.is_some() {
+                prover.exit_region();
+            }
+            Ok(())
+        })
+    }
+
+    /// Configures the circuit and sets up the prover, synthesizes it with the given
+    /// closure, and then compresses its selectors.
+    fn run_with<ConcreteCircuit: Circuit<F>, S>(
+        k: u32,
+        instance: Vec<Vec<F>>,
+        synthesize: S,
+    ) -> Result<Self, Error>
+    where
+        S: FnOnce(&mut Self, ConcreteCircuit::Config, Vec<Column<Fixed>>) -> Result<(), Error>,
+    {
         let n = 1 << k;
 
         let mut cs = ConstraintSystem::default();
@@ -716,10 +858,12 @@ impl<F: FieldExt> MockProver<F> {
             instance,
             selectors,
             permutation,
+            record_invalid_copies: false,
+            invalid_copies: vec![],
             usable_rows: 0..usable_rows,
         };
 
-        ConcreteCircuit::FloorPlanner::synthesize(&mut prover, circuit, config, constants)?;
+        synthesize(&mut prover, config, constants)?;
 
         let (cs, selector_polys) = prover.cs.compress_selectors(prover.selectors.clone());
         prover.cs = cs;
@@ -848,6 +992,7 @@ impl<F: FieldExt> MockProver<F> {
                                     cell_values: util::cell_values(
                                         gate,
                                         poly,
+                                        &self.cs.column_annotations,
                                         &load(n, row, &self.cs.fixed_queries, &self.fixed),
                                         &load(n, row, &self.cs.advice_queries, &self.advice),
                                         &load_instance(
@@ -981,8 +1126,10 @@ impl<F: FieldExt> MockProver<F> {
                             None
                         } else {
                             Some(VerifyFailure::Permutation {
-                                column: (*self.cs.permutation.get_columns().get(column).unwrap())
-                                    .into(),
+                                column: metadata::Column::annotated(
+                                    self.cs.permutation.get_columns()[column],
+                                    &self.cs.column_annotations,
+                                ),
                                 row,
                             })
                         }
@@ -990,11 +1137,25 @@ impl<F: FieldExt> MockProver<F> {
                 })
         };
 
+        // Check that equality constraints were only applied to equality-enabled columns.
+        let copy_errors =
+            self.invalid_copies
+                .iter()
+                .map(|(column, row)| VerifyFailure::ColumnNotInPermutation {
+                    column: metadata::Column::annotated(*column, &self.cs.column_annotations),
+                    location: FailureLocation::find(
+                        &self.regions,
+                        *row,
+                        Some(*column).into_iter().collect(),
+                    ),
+                });
+
         let mut errors: Vec<_> = iter::empty()
             .chain(selector_errors)
             .chain(gate_errors)
             .chain(lookup_errors)
             .chain(perm_errors)
+            .chain(copy_errors)
             .collect();
         if errors.is_empty() {
             Ok(())
@@ -1100,6 +1261,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn annotated_columns() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            a: Column<Advice>,
+            q: Selector,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let q = meta.selector();
+
+                meta.annotate_column(a, || "input");
+                meta.annotate_column(b, || "output");
+
+                meta.create_gate("Equality check", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    vec![q * (a - b)]
+                });
+
+                FaultyCircuitConfig { a, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", config.a, 0, || Ok(Fp::zero()))?;
+
+                        // BUG: Forget to assign b.
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(
+            failures,
+            vec![VerifyFailure::CellNotAssigned {
+                gate: (0, "Equality check").into(),
+                region: (0, "Faulty synthesis").into(),
+                column: Column::new(1, Any::Advice),
+                offset: 0,
+            }]
+        );
+        assert_eq!(
+            failures[0].render(&prover),
+            "Region 0 ('Faulty synthesis') uses Gate 0 ('Equality check'), which requires \
+             cell in Column('Advice', 1 - output) at offset 0 to be assigned.\n"
+        );
+    }
+
     #[test]
     fn bad_lookup() {
         const K: u32 = 4;
@@ -1207,4 +1443,164 @@ mod tests {
             }])
         );
     }
+
+    #[test]
+    fn parallel_regions_match_serial() {
+        use std::marker::PhantomData;
+
+        use crate::{
+            circuit::{floor_planner::V1, AssignedCell, Region},
+            plonk::{Fixed, FloorPlanner, Instance},
+        };
+
+        const K: u32 = 6;
+
+        #[derive(Clone)]
+        struct ScaleConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            f: Column<Fixed>,
+            s: Selector,
+            instance: Column<Instance>,
+        }
+
+        struct ScaleCircuit<P: FloorPlanner> {
+            seed: Option<Fp>,
+            parallel: bool,
+            _marker: PhantomData<P>,
+        }
+
+        impl<P: FloorPlanner> Circuit<Fp> for ScaleCircuit<P> {
+            type Config = ScaleConfig;
+            type FloorPlanner = P;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let f = meta.fixed_column();
+                let s = meta.selector();
+                let instance = meta.instance_column();
+                let constants = meta.fixed_column();
+
+                meta.enable_equality(a);
+                meta.enable_equality(b);
+                meta.enable_equality(instance);
+                meta.enable_constant(constants);
+
+                // b = a * f
+                meta.create_gate("scale", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let f = cells.query_fixed(f, Rotation::cur());
+                    let s = cells.query_selector(s);
+
+                    vec![s * (a * f - b)]
+                });
+
+                ScaleConfig {
+                    a,
+                    b,
+                    f,
+                    s,
+                    instance,
+                }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                ScaleCircuit {
+                    seed: None,
+                    parallel: self.parallel,
+                    _marker: PhantomData,
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                let seed = layouter.assign_region(
+                    || "seed",
+                    |mut region| {
+                        region.assign_advice(
+                            || "seed",
+                            config.a,
+                            0,
+                            || self.seed.ok_or(Error::Synthesis),
+                        )
+                    },
+                )?;
+
+                // Each region scales the seed by its index (plus one), and the last one
+                // also copies in the public input.
+                let scale = |i: usize| {
+                    let config = config.clone();
+                    let seed = seed.clone();
+                    move |mut region: Region<'_, Fp>| -> Result<AssignedCell<Fp, Fp>, Error> {
+                        let factor = Fp::from(i as u64 + 1);
+                        config.s.enable(&mut region, 0)?;
+                        seed.copy_advice(|| "a", &mut region, config.a, 0)?;
+                        region.assign_fixed(|| "f", config.f, 0, || Ok(factor))?;
+                        let b = region.assign_advice(
+                            || "b",
+                            config.b,
+                            0,
+                            || seed.value().map(|v| *v * factor).ok_or(Error::Synthesis),
+                        )?;
+                        region.assign_advice_from_constant(|| "one", config.a, 1, Fp::one())?;
+                        if i == 3 {
+                            let public = region.assign_advice_from_instance(
+                                || "public",
+                                config.instance,
+                                0,
+                                config.b,
+                                1,
+                            )?;
+                            region.constrain_equal(b.cell(), public.cell())?;
+                        }
+                        Ok(b)
+                    }
+                };
+                let assignments: Vec<_> = (0..5).map(scale).collect();
+                let cells = if self.parallel {
+                    layouter.assign_regions(|i| format!("scale {}", i), assignments)?
+                } else {
+                    let mut cells = vec![];
+                    for (i, assignment) in assignments.into_iter().enumerate() {
+                        cells.push(layouter.assign_region(|| format!("scale {}", i), assignment)?);
+                    }
+                    cells
+                };
+
+                layouter.constrain_instance(cells[3].cell(), config.instance, 0)
+            }
+        }
+
+        fn check<P: FloorPlanner>() {
+            let circuit = |parallel| ScaleCircuit::<P> {
+                seed: Some(Fp::from(7)),
+                parallel,
+                _marker: PhantomData,
+            };
+            let instance = vec![vec![Fp::from(28)]];
+
+            let serial = MockProver::run(K, &circuit(false), instance.clone()).unwrap();
+            let parallel = MockProver::run(K, &circuit(true), instance).unwrap();
+            assert_eq!(parallel.verify(), Ok(()));
+            assert_eq!(serial.advice, parallel.advice);
+            assert_eq!(serial.fixed, parallel.fixed);
+            assert_eq!(serial.selectors, parallel.selectors);
+            assert_eq!(
+                format!("{:?}", serial.permutation),
+                format!("{:?}", parallel.permutation)
+            );
+            for (serial, parallel) in serial.regions.iter().zip(parallel.regions.iter()) {
+                assert_eq!(serial.name, parallel.name);
+                assert_eq!(serial.rows, parallel.rows);
+            }
+        }
+
+        check::<SimpleFloorPlanner>();
+        check::<V1>();
+    }
 }
@@ -13,7 +13,7 @@ use group::prime::PrimeGroup;
 use crate::{
     plonk::{
         Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
-        FloorPlanner, Instance, Selector,
+        FloorPlanner, Instance, Selector, WitnessAccess,
     },
     poly::Rotation,
 };
@@ -72,6 +72,10 @@ impl<F: Field> Assignment<F> for Assembly {
         Ok(None)
     }
 
+    fn witness_access(&self) -> WitnessAccess<'_, F> {
+        WitnessAccess::Skipped
+    }
+
     fn assign_advice<V, VR, A, AR>(
         &mut self,
         _: A,
@@ -0,0 +1,492 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use blake2b_simd::Params as Blake2bParams;
+#[cfg(feature = "dev-json")]
+use serde::{Deserialize, Serialize};
+
+use super::{CellValue, MockProver};
+use crate::{arithmetic::FieldExt, plonk::Expression};
+
+/// Identifies a constraint within a circuit by the indices and names of the constraint
+/// and its gate.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+struct ConstraintKey {
+    gate_index: usize,
+    gate_name: String,
+    index: usize,
+    name: String,
+}
+
+/// A coverage report for the gates, lookups and selectors of a circuit, collected from
+/// one or more [`MockProver`] runs.
+///
+/// A constraint or lookup is counted as active on a row if it is not trivially zero on
+/// that row, i.e. if it does not evaluate to zero for an arbitrary witness. In practice
+/// this means that the selectors (or fixed columns) that gate it are enabled.
+///
+/// Reports from many test runs (for example, of different witnesses for the same
+/// circuit) can be combined with [`Coverage::merge`], and then inspected for
+/// constraints, lookups and selectors that none of the tests exercised.
+///
+/// # Examples
+///
+/// ```
+/// use halo2_proofs::{
+///     circuit::{Layouter, SimpleFloorPlanner},
+///     dev::{Coverage, MockProver},
+///     pasta::Fp,
+///     plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+///     poly::Rotation,
+/// };
+///
+/// #[derive(Clone)]
+/// struct MyConfig {
+///     a: Column<Advice>,
+///     s_double: Selector,
+///     s_square: Selector,
+/// }
+///
+/// #[derive(Default)]
+/// struct MyCircuit {
+///     square: bool,
+/// }
+///
+/// impl Circuit<Fp> for MyCircuit {
+///     type Config = MyConfig;
+///     type FloorPlanner = SimpleFloorPlanner;
+///
+///     fn without_witnesses(&self) -> Self {
+///         Self::default()
+///     }
+///
+///     fn configure(meta: &mut ConstraintSystem<Fp>) -> MyConfig {
+///         let a = meta.advice_column();
+///         let s_double = meta.selector();
+///         let s_square = meta.selector();
+///
+///         meta.create_gate("double", |meta| {
+///             let cur = meta.query_advice(a, Rotation::cur());
+///             let next = meta.query_advice(a, Rotation::next());
+///             let s = meta.query_selector(s_double);
+///             Some(s * (cur.clone() + cur - next))
+///         });
+///         meta.create_gate("square", |meta| {
+///             let cur = meta.query_advice(a, Rotation::cur());
+///             let next = meta.query_advice(a, Rotation::next());
+///             let s = meta.query_selector(s_square);
+///             Some(s * (cur.clone() * cur - next))
+///         });
+///
+///         MyConfig { a, s_double, s_square }
+///     }
+///
+///     fn synthesize(&self, config: MyConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+///         layouter.assign_region(|| "op", |mut region| {
+///             let (s, out) = if self.square {
+///                 (config.s_square, 9)
+///             } else {
+///                 (config.s_double, 6)
+///             };
+///             s.enable(&mut region, 0)?;
+///             region.assign_advice(|| "in", config.a, 0, || Ok(Fp::from(3)))?;
+///             region.assign_advice(|| "out", config.a, 1, || Ok(Fp::from(out)))?;
+///             Ok(())
+///         })
+///     }
+/// }
+///
+/// let mut coverage = Coverage::default();
+/// let prover = MockProver::run(4, &MyCircuit { square: false }, vec![]).unwrap();
+/// assert_eq!(prover.verify(), Ok(()));
+/// coverage.merge(&prover.coverage());
+///
+/// // Our tests never use the "square" gate.
+/// assert_eq!(coverage.unused_selectors(), vec![1]);
+/// assert_eq!(
+///     format!("{}", coverage),
+///     r#####"Coverage over 1 run(s):
+/// - Constraint 0 in gate 0 ('double'): 1 row(s)
+/// - Constraint 0 in gate 1 ('square'): 0 row(s)
+/// - Selector 0: 1 row(s)
+/// - Selector 1: 0 row(s)
+/// Never activated:
+/// - Constraint 0 in gate 1 ('square')
+/// - Selector 1
+/// "#####,
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct Coverage {
+    runs: usize,
+    // JSON objects can only have string keys, so this is serialized as a list of
+    // `[key, count]` pairs.
+    #[cfg_attr(feature = "dev-json", serde(with = "entries"))]
+    constraints: BTreeMap<ConstraintKey, usize>,
+    lookups: BTreeMap<usize, usize>,
+    selectors: BTreeMap<usize, usize>,
+}
+
+impl Coverage {
+    /// Adds the counts from `other` to this report.
+    pub fn merge(&mut self, other: &Coverage) {
+        fn add<K: Clone + Ord>(a: &mut BTreeMap<K, usize>, b: &BTreeMap<K, usize>) {
+            for (key, count) in b.iter() {
+                *a.entry(key.clone()).or_default() += count;
+            }
+        }
+
+        self.runs += other.runs;
+        add(&mut self.constraints, &other.constraints);
+        add(&mut self.lookups, &other.lookups);
+        add(&mut self.selectors, &other.selectors);
+    }
+
+    /// Returns the number of [`MockProver`] runs included in this report.
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// Returns the constraints that were never active, as `(gate index, gate name,
+    /// constraint index, constraint name)`.
+    pub fn unused_constraints(&self) -> Vec<(usize, &str, usize, &str)> {
+        unused(&self.constraints)
+            .map(|key| (key.gate_index, &key.gate_name[..], key.index, &key.name[..]))
+            .collect()
+    }
+
+    /// Returns the indices of the lookups that were never active.
+    pub fn unused_lookups(&self) -> Vec<usize> {
+        unused(&self.lookups).cloned().collect()
+    }
+
+    /// Returns the indices of the selectors that were never enabled.
+    pub fn unused_selectors(&self) -> Vec<usize> {
+        unused(&self.selectors).cloned().collect()
+    }
+
+    /// Serializes this report as pretty-printed JSON.
+    #[cfg(feature = "dev-json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dev-json")))]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("coverage reports are always serializable")
+    }
+
+    /// Parses a report from JSON.
+    #[cfg(feature = "dev-json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dev-json")))]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Parses a report from JSON, such as one saved by an earlier test run, and adds its
+    /// counts to this report.
+    #[cfg(feature = "dev-json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dev-json")))]
+    pub fn merge_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        self.merge(&Self::from_json(json)?);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "dev-json")]
+mod entries {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<K: Serialize, S: Serializer>(
+        map: &BTreeMap<K, usize>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub(super) fn deserialize<'de, K: Deserialize<'de> + Ord, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<K, usize>, D::Error> {
+        Vec::<(K, usize)>::deserialize(deserializer).map(|entries| entries.into_iter().collect())
+    }
+}
+
+fn unused<K>(map: &BTreeMap<K, usize>) -> impl Iterator<Item = &K> {
+    map.iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(key, _)| key)
+}
+
+fn constraint_name(key: &ConstraintKey) -> String {
+    format!(
+        "Constraint {}{} in gate {} ('{}')",
+        key.index,
+        if key.name.is_empty() {
+            String::new()
+        } else {
+            format!(" ('{}')", key.name)
+        },
+        key.gate_index,
+        key.gate_name,
+    )
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Coverage over {} run(s):", self.runs)?;
+        for (key, count) in self.constraints.iter() {
+            writeln!(f, "- {}: {} row(s)", constraint_name(key), count)?;
+        }
+        for (index, count) in self.lookups.iter() {
+            writeln!(f, "- Lookup {}: {} row(s)", index, count)?;
+        }
+        for (index, count) in self.selectors.iter() {
+            writeln!(f, "- Selector {}: {} row(s)", index, count)?;
+        }
+
+        let unused: Vec<_> = iter_unused(self).collect();
+        if !unused.is_empty() {
+            writeln!(f, "Never activated:")?;
+            for name in unused {
+                writeln!(f, "- {}", name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn iter_unused(coverage: &Coverage) -> impl Iterator<Item = String> + '_ {
+    unused(&coverage.constraints)
+        .map(constraint_name)
+        .chain(unused(&coverage.lookups).map(|index| format!("Lookup {}", index)))
+        .chain(unused(&coverage.selectors).map(|index| format!("Selector {}", index)))
+}
+
+impl<F: FieldExt> MockProver<F> {
+    /// Collects a [`Coverage`] report of the constraints, lookups and selectors that are
+    /// active in this circuit.
+    pub fn coverage(&self) -> Coverage {
+        // Stand-in values for the advice and instance queries. These are derived
+        // deterministically, so that reports are reproducible; all that matters is that
+        // distinct queries are assigned unrelated values.
+        let stand_in = |kind: u8, index: usize| {
+            let hash = Blake2bParams::new()
+                .hash_length(64)
+                .personal(b"halo2__coverage_")
+                .to_state()
+                .update(&[kind])
+                .update(&(index as u64).to_le_bytes())
+                .finalize();
+            F::from_bytes_wide(hash.as_array())
+        };
+        let advice: Vec<F> = (0..self.cs.advice_queries.len())
+            .map(|index| stand_in(0, index))
+            .collect();
+        let instance: Vec<F> = (0..self.cs.instance_queries.len())
+            .map(|index| stand_in(1, index))
+            .collect();
+
+        let n = self.n as i32;
+        let is_active = |expression: &Expression<F>, row: usize| {
+            !expression
+                .evaluate(
+                    &|scalar| scalar,
+                    &|_| panic!("virtual selectors are removed during optimization"),
+                    &|index, _, _| {
+                        let (column, rotation) = self.cs.fixed_queries[index];
+                        let row = (row as i32 + n + rotation.0) % n;
+                        match self.fixed[column.index()][row as usize] {
+                            CellValue::Assigned(value) => value,
+                            _ => F::zero(),
+                        }
+                    },
+                    &|index, _, _| advice[index],
+                    &|index, _, _| instance[index],
+                    &|a| -a,
+                    &|a, b| a + b,
+                    &|a, b| a * b,
+                    &|a, scalar| a * scalar,
+                )
+                .is_zero_vartime()
+        };
+
+        let constraints = self
+            .cs
+            .gates
+            .iter()
+            .enumerate()
+            .flat_map(|(gate_index, gate)| {
+                gate.polynomials()
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, poly)| {
+                        let key = ConstraintKey {
+                            gate_index,
+                            gate_name: gate.name().to_owned(),
+                            index,
+                            name: gate.constraint_name(index).to_owned(),
+                        };
+                        (key, poly)
+                    })
+            })
+            .map(|(key, poly)| {
+                let count = self
+                    .usable_rows
+                    .clone()
+                    .filter(|row| is_active(poly, *row))
+                    .count();
+                (key, count)
+            })
+            .collect();
+
+        let lookups = self
+            .cs
+            .lookups
+            .iter()
+            .enumerate()
+            .map(|(index, lookup)| {
+                let count = self
+                    .usable_rows
+                    .clone()
+                    .filter(|row| {
+                        lookup
+                            .input_expressions
+                            .iter()
+                            .any(|input| is_active(input, *row))
+                    })
+                    .count();
+                (index, count)
+            })
+            .collect();
+
+        let selectors = self
+            .selectors
+            .iter()
+            .enumerate()
+            .map(|(index, enabled)| (index, enabled.iter().filter(|e| **e).count()))
+            .collect();
+
+        Coverage {
+            runs: 1,
+            constraints,
+            lookups,
+            selectors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::Coverage;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector, TableColumn},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct TestConfig {
+        a: Column<Advice>,
+        q_lookup: Selector,
+        table: TableColumn,
+    }
+
+    struct TestCircuit {
+        lookup: bool,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let q_lookup = meta.complex_selector();
+            let table = meta.lookup_table_column();
+
+            meta.lookup(|cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let q_lookup = cells.query_selector(q_lookup);
+                vec![(q_lookup * a, table)]
+            });
+
+            TestConfig { a, q_lookup, table }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self { lookup: false }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "table",
+                |mut table| {
+                    (0..4).try_for_each(|i| {
+                        table.assign_cell(|| "", config.table, i, || Ok(Fp::from(i as u64)))
+                    })
+                },
+            )?;
+
+            layouter.assign_region(
+                || "values",
+                |mut region| {
+                    for offset in 0..3 {
+                        if self.lookup {
+                            config.q_lookup.enable(&mut region, offset)?;
+                        }
+                        region.assign_advice(|| "a", config.a, offset, || Ok(Fp::from(2)))?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn lookup_coverage() {
+        let unused = MockProver::run(4, &TestCircuit { lookup: false }, vec![])
+            .unwrap()
+            .coverage();
+        assert_eq!(unused.unused_lookups(), vec![0]);
+        assert_eq!(unused.unused_selectors(), vec![0]);
+
+        let used = MockProver::run(4, &TestCircuit { lookup: true }, vec![])
+            .unwrap()
+            .coverage();
+        assert_eq!(used.unused_lookups(), Vec::<usize>::new());
+
+        let mut merged = Coverage::default();
+        merged.merge(&unused);
+        merged.merge(&used);
+        assert_eq!(merged.runs(), 2);
+        assert_eq!(merged.unused_selectors(), Vec::<usize>::new());
+        assert_eq!(
+            format!("{}", merged),
+            "Coverage over 2 run(s):\n- Lookup 0: 3 row(s)\n- Selector 0: 3 row(s)\n"
+        );
+    }
+
+    #[cfg(feature = "dev-json")]
+    #[test]
+    fn json_merge() {
+        let used = MockProver::run(4, &TestCircuit { lookup: true }, vec![])
+            .unwrap()
+            .coverage();
+        assert_eq!(Coverage::from_json(&used.to_json()).unwrap(), used);
+
+        // Reports saved by earlier runs can be loaded and merged.
+        let mut merged = MockProver::run(4, &TestCircuit { lookup: false }, vec![])
+            .unwrap()
+            .coverage();
+        merged.merge_json(&used.to_json()).unwrap();
+        assert_eq!(merged.runs(), 2);
+        assert_eq!(merged.unused_lookups(), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,784 @@
+//! Comparison of two versions of a circuit.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::export::{CircuitSnapshot, Column, ColumnType, Gate, Lookup, Region};
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, Error},
+};
+
+/// The maximum number of rows listed when describing a difference in a column.
+const MAX_LISTED_ROWS: usize = 8;
+
+/// Formats a column as a short label, such as `A0`.
+fn column_label(column: &Column) -> String {
+    let prefix = match column.column_type {
+        ColumnType::Instance => 'I',
+        ColumnType::Advice => 'A',
+        ColumnType::Fixed => 'F',
+    };
+    format!("{}{}", prefix, column.index)
+}
+
+fn format_columns(columns: &[Column]) -> String {
+    format!(
+        "[{}]",
+        columns
+            .iter()
+            .map(column_label)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn format_rows(rows: &[usize]) -> String {
+    let listed = rows
+        .iter()
+        .take(MAX_LISTED_ROWS)
+        .map(|row| row.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if rows.len() > MAX_LISTED_ROWS {
+        format!("{} and {} more", listed, rows.len() - MAX_LISTED_ROWS)
+    } else {
+        listed
+    }
+}
+
+fn format_region(region: &Region) -> String {
+    let rows = match region.offset {
+        Some(offset) => format!("rows {}..{}", offset, offset + region.rows),
+        None => "no rows".to_owned(),
+    };
+    format!(
+        "'{}' ({}, columns {}, selectors {:?})",
+        region.name,
+        rows,
+        format_columns(&region.columns),
+        region.selectors,
+    )
+}
+
+/// A difference between two versions of a circuit.
+///
+/// Gates, lookups and regions are matched up by their index, so inserting one of them
+/// shows up as a change to each of the following ones (and as an addition at the end).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Difference {
+    /// The circuits have a different number of rows. Fixed-column contents and copy
+    /// constraints are not compared in this case.
+    K {
+        /// The old `k`.
+        old: u32,
+        /// The new `k`.
+        new: u32,
+    },
+    /// The degree of the constraint system changed.
+    Degree {
+        /// The old degree.
+        old: usize,
+        /// The new degree.
+        new: usize,
+    },
+    /// The number of columns of a given type changed.
+    ColumnCount {
+        /// The type of the columns.
+        column_type: ColumnType,
+        /// The old number of columns.
+        old: usize,
+        /// The new number of columns.
+        new: usize,
+    },
+    /// The annotation of a column changed.
+    ColumnAnnotation {
+        /// The annotated column.
+        column: Column,
+        /// The old annotation, if any.
+        old: Option<String>,
+        /// The new annotation, if any.
+        new: Option<String>,
+    },
+    /// The fixed columns that selectors are compressed into changed.
+    SelectorColumns {
+        /// The old fixed column of each selector.
+        old: Vec<usize>,
+        /// The new fixed column of each selector.
+        new: Vec<usize>,
+    },
+    /// A gate was added.
+    GateAdded {
+        /// The index of the gate.
+        index: usize,
+        /// The new gate.
+        gate: Gate,
+    },
+    /// A gate was removed.
+    GateRemoved {
+        /// The index of the gate.
+        index: usize,
+        /// The old gate.
+        gate: Gate,
+    },
+    /// The name or the constraints of a gate changed.
+    GateChanged {
+        /// The index of the gate.
+        index: usize,
+        /// The old gate.
+        old: Gate,
+        /// The new gate.
+        new: Gate,
+    },
+    /// A lookup argument was added.
+    LookupAdded {
+        /// The index of the lookup argument.
+        index: usize,
+    },
+    /// A lookup argument was removed.
+    LookupRemoved {
+        /// The index of the lookup argument.
+        index: usize,
+    },
+    /// The expressions of a lookup argument changed.
+    LookupChanged {
+        /// The index of the lookup argument.
+        index: usize,
+        /// The old lookup argument.
+        old: Lookup,
+        /// The new lookup argument.
+        new: Lookup,
+    },
+    /// The columns that participate in the permutation argument changed. Copy
+    /// constraints are not compared in this case.
+    PermutationColumns {
+        /// The old permutation columns.
+        old: Vec<Column>,
+        /// The new permutation columns.
+        new: Vec<Column>,
+    },
+    /// The fixed columns that are used to load constants changed.
+    Constants {
+        /// The old constant columns.
+        old: Vec<usize>,
+        /// The new constant columns.
+        new: Vec<usize>,
+    },
+    /// The contents of a fixed column changed.
+    FixedColumn {
+        /// The index of the fixed column.
+        column: usize,
+        /// The rows on which the contents differ. If the column was added or removed,
+        /// these are all of its rows.
+        rows: Vec<usize>,
+    },
+    /// The copy constraints on a column in the permutation argument changed.
+    CopyConstraints {
+        /// The column.
+        column: Column,
+        /// The rows whose cells are now part of a different cycle of the permutation.
+        rows: Vec<usize>,
+    },
+    /// A region was added.
+    RegionAdded {
+        /// The index of the region.
+        index: usize,
+        /// The new region.
+        region: Region,
+    },
+    /// A region was removed.
+    RegionRemoved {
+        /// The index of the region.
+        index: usize,
+        /// The old region.
+        region: Region,
+    },
+    /// The name, position or shape of a region changed.
+    RegionChanged {
+        /// The index of the region.
+        index: usize,
+        /// The old region.
+        old: Region,
+        /// The new region.
+        new: Region,
+    },
+    /// The pinned constraint system changed in a way that is not described by any of
+    /// the other differences: the order in which columns are queried, or the minimum
+    /// degree.
+    PinnedConstraintSystem,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::K { old, new } => write!(f, "k changed from {} to {}", old, new),
+            Self::Degree { old, new } => write!(f, "Degree changed from {} to {}", old, new),
+            Self::ColumnCount {
+                column_type,
+                old,
+                new,
+            } => write!(
+                f,
+                "Number of {:?} columns changed from {} to {}",
+                column_type, old, new
+            ),
+            Self::ColumnAnnotation { column, old, new } => write!(
+                f,
+                "Annotation of column {} changed from {:?} to {:?}",
+                column_label(column),
+                old,
+                new
+            ),
+            Self::SelectorColumns { old, new } => {
+                write!(f, "Selector columns changed from {:?} to {:?}", old, new)
+            }
+            Self::GateAdded { index, gate } => write!(f, "Gate {} ('{}') added", index, gate.name),
+            Self::GateRemoved { index, gate } => {
+                write!(f, "Gate {} ('{}') removed", index, gate.name)
+            }
+            Self::GateChanged { index, old, new } => {
+                write!(f, "Gate {} ('{}')", index, old.name)?;
+                if old.name != new.name {
+                    write!(f, " renamed to '{}'", new.name)?;
+                }
+                let changed: Vec<String> =
+                    (0..cmp::max(old.constraints.len(), new.constraints.len()))
+                        .filter(|i| old.constraints.get(*i) != new.constraints.get(*i))
+                        .map(|i| i.to_string())
+                        .collect();
+                if !changed.is_empty() {
+                    if old.name != new.name {
+                        write!(f, ",")?;
+                    }
+                    write!(f, " changed constraints {}", changed.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::LookupAdded { index } => write!(f, "Lookup {} added", index),
+            Self::LookupRemoved { index } => write!(f, "Lookup {} removed", index),
+            Self::LookupChanged { index, .. } => write!(f, "Lookup {} changed", index),
+            Self::PermutationColumns { old, new } => write!(
+                f,
+                "Permutation columns changed from {} to {}",
+                format_columns(old),
+                format_columns(new)
+            ),
+            Self::Constants { old, new } => {
+                write!(f, "Constant columns changed from {:?} to {:?}", old, new)
+            }
+            Self::FixedColumn { column, rows } => write!(
+                f,
+                "Fixed column F{} differs on rows {}",
+                column,
+                format_rows(rows)
+            ),
+            Self::CopyConstraints { column, rows } => write!(
+                f,
+                "Copy constraints on column {} differ on rows {}",
+                column_label(column),
+                format_rows(rows)
+            ),
+            Self::RegionAdded { index, region } => {
+                write!(f, "Region {} added: {}", index, format_region(region))
+            }
+            Self::RegionRemoved { index, region } => {
+                write!(f, "Region {} removed: {}", index, format_region(region))
+            }
+            Self::RegionChanged { index, old, new } => write!(
+                f,
+                "Region {} changed from {} to {}",
+                index,
+                format_region(old),
+                format_region(new)
+            ),
+            Self::PinnedConstraintSystem => write!(f, "Pinned constraint system changed"),
+        }
+    }
+}
+
+/// The differences between two versions of a circuit.
+///
+/// # Examples
+///
+/// ```ignore
+/// use halo2_proofs::dev::diff;
+///
+/// let k = 5; // Suitable size for both circuits
+/// let diff = diff(k, &OldCircuit::default(), &NewCircuit::default()).unwrap();
+/// println!("{}", diff);
+/// assert!(!diff.verifying_key_changed());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitDiff {
+    differences: Vec<Difference>,
+    verifying_key_changed: bool,
+}
+
+/// Compares two versions of an indexed list, calling `changed` for each index that is
+/// in both lists, and `added` and `removed` for the indices that are only in one.
+fn diff_lists<T: PartialEq>(
+    old: &[T],
+    new: &[T],
+    differences: &mut Vec<Difference>,
+    changed: impl Fn(usize, &T, &T) -> Difference,
+    added: impl Fn(usize, &T) -> Difference,
+    removed: impl Fn(usize, &T) -> Difference,
+) {
+    for (index, (old, new)) in old.iter().zip(new.iter()).enumerate() {
+        if old != new {
+            differences.push(changed(index, old, new));
+        }
+    }
+    for (index, new) in new.iter().enumerate().skip(old.len()) {
+        differences.push(added(index, new));
+    }
+    for (index, old) in old.iter().enumerate().skip(new.len()) {
+        differences.push(removed(index, old));
+    }
+}
+
+/// Returns the rows on which two columns differ. Rows beyond the end of either column
+/// are counted as differing.
+fn diff_rows<T: PartialEq>(old: &[T], new: &[T]) -> Vec<usize> {
+    (0..cmp::max(old.len(), new.len()))
+        .filter(|row| old.get(*row) != new.get(*row))
+        .collect()
+}
+
+impl CircuitDiff {
+    /// Compares two snapshots of a circuit.
+    pub fn between(old: &CircuitSnapshot, new: &CircuitSnapshot) -> Self {
+        let mut differences = vec![];
+
+        if old.k != new.k {
+            differences.push(Difference::K {
+                old: old.k,
+                new: new.k,
+            });
+        }
+        if old.degree != new.degree {
+            differences.push(Difference::Degree {
+                old: old.degree,
+                new: new.degree,
+            });
+        }
+
+        for (column_type, old_count, new_count) in [
+            (
+                ColumnType::Instance,
+                old.num_instance_columns,
+                new.num_instance_columns,
+            ),
+            (
+                ColumnType::Advice,
+                old.num_advice_columns,
+                new.num_advice_columns,
+            ),
+            (
+                ColumnType::Fixed,
+                old.num_fixed_columns,
+                new.num_fixed_columns,
+            ),
+        ]
+        .iter()
+        {
+            if old_count != new_count {
+                differences.push(Difference::ColumnCount {
+                    column_type: *column_type,
+                    old: *old_count,
+                    new: *new_count,
+                });
+            }
+        }
+
+        let mut annotations: BTreeMap<&Column, (Option<String>, Option<String>)> = BTreeMap::new();
+        for annotation in old.column_annotations.iter() {
+            annotations.entry(&annotation.column).or_default().0 =
+                Some(annotation.annotation.clone());
+        }
+        for annotation in new.column_annotations.iter() {
+            annotations.entry(&annotation.column).or_default().1 =
+                Some(annotation.annotation.clone());
+        }
+        for (column, (old, new)) in annotations {
+            if old != new {
+                differences.push(Difference::ColumnAnnotation {
+                    column: column.clone(),
+                    old,
+                    new,
+                });
+            }
+        }
+
+        if old.selector_columns != new.selector_columns {
+            differences.push(Difference::SelectorColumns {
+                old: old.selector_columns.clone(),
+                new: new.selector_columns.clone(),
+            });
+        }
+
+        diff_lists(
+            &old.gates,
+            &new.gates,
+            &mut differences,
+            |index, old, new| Difference::GateChanged {
+                index,
+                old: old.clone(),
+                new: new.clone(),
+            },
+            |index, gate| Difference::GateAdded {
+                index,
+                gate: gate.clone(),
+            },
+            |index, gate| Difference::GateRemoved {
+                index,
+                gate: gate.clone(),
+            },
+        );
+        diff_lists(
+            &old.lookups,
+            &new.lookups,
+            &mut differences,
+            |index, old, new| Difference::LookupChanged {
+                index,
+                old: old.clone(),
+                new: new.clone(),
+            },
+            |index, _| Difference::LookupAdded { index },
+            |index, _| Difference::LookupRemoved { index },
+        );
+
+        if old.permutation_columns != new.permutation_columns {
+            differences.push(Difference::PermutationColumns {
+                old: old.permutation_columns.clone(),
+                new: new.permutation_columns.clone(),
+            });
+        }
+        if old.constants != new.constants {
+            differences.push(Difference::Constants {
+                old: old.constants.clone(),
+                new: new.constants.clone(),
+            });
+        }
+
+        // Fixed-column contents and copy constraints can only be compared row by row
+        // if the circuits have the same number of rows.
+        if old.k == new.k {
+            let empty = vec![];
+            for column in 0..cmp::max(old.fixed.len(), new.fixed.len()) {
+                let rows = diff_rows(
+                    old.fixed.get(column).unwrap_or(&empty),
+                    new.fixed.get(column).unwrap_or(&empty),
+                );
+                if !rows.is_empty() {
+                    differences.push(Difference::FixedColumn { column, rows });
+                }
+            }
+
+            if old.permutation_columns == new.permutation_columns {
+                for (column, (old, new)) in old.permutation_columns.iter().zip(
+                    old.permutation_mapping
+                        .iter()
+                        .zip(new.permutation_mapping.iter()),
+                ) {
+                    let rows = diff_rows(old, new);
+                    if !rows.is_empty() {
+                        differences.push(Difference::CopyConstraints {
+                            column: column.clone(),
+                            rows,
+                        });
+                    }
+                }
+            }
+        }
+
+        diff_lists(
+            &old.regions,
+            &new.regions,
+            &mut differences,
+            |index, old, new| Difference::RegionChanged {
+                index,
+                old: old.clone(),
+                new: new.clone(),
+            },
+            |index, region| Difference::RegionAdded {
+                index,
+                region: region.clone(),
+            },
+            |index, region| Difference::RegionRemoved {
+                index,
+                region: region.clone(),
+            },
+        );
+
+        // The pinned constraint system also covers the queries, which expressions refer
+        // to by index, and the minimum degree.
+        if old.advice_queries != new.advice_queries
+            || old.instance_queries != new.instance_queries
+            || old.fixed_queries != new.fixed_queries
+            || old.minimum_degree != new.minimum_degree
+        {
+            differences.push(Difference::PinnedConstraintSystem);
+        }
+
+        // The verifying key commits to the domain (determined by `k` and the degree), the
+        // pinned constraint system, the fixed columns, and the permutation. Gate names,
+        // column annotations and the region layout do not affect it.
+        let verifying_key_changed = old.k != new.k
+            || old.fixed != new.fixed
+            || old.permutation_mapping != new.permutation_mapping
+            || differences.iter().any(|difference| match difference {
+                Difference::Degree { .. }
+                | Difference::ColumnCount { .. }
+                | Difference::SelectorColumns { .. }
+                | Difference::LookupAdded { .. }
+                | Difference::LookupRemoved { .. }
+                | Difference::LookupChanged { .. }
+                | Difference::PermutationColumns { .. }
+                | Difference::Constants { .. }
+                | Difference::PinnedConstraintSystem => true,
+                Difference::GateAdded { .. } | Difference::GateRemoved { .. } => true,
+                Difference::GateChanged { old, new, .. } => {
+                    old.constraints.len() != new.constraints.len()
+                        || old
+                            .constraints
+                            .iter()
+                            .zip(new.constraints.iter())
+                            .any(|(old, new)| old.expression != new.expression)
+                }
+                _ => false,
+            });
+
+        CircuitDiff {
+            differences,
+            verifying_key_changed,
+        }
+    }
+
+    /// Returns the differences between the two versions of the circuit.
+    pub fn differences(&self) -> &[Difference] {
+        &self.differences
+    }
+
+    /// Returns `true` if the two versions of the circuit are identical.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Returns `true` if the verifying key of the circuit would change; that is, if
+    /// `VerifyingKey::pinned()` differs between the two versions of the circuit when
+    /// they are keyed with the same parameters.
+    ///
+    /// Proofs created for one version of the circuit will not verify against the other
+    /// if this is `true`.
+    pub fn verifying_key_changed(&self) -> bool {
+        self.verifying_key_changed
+    }
+}
+
+impl fmt::Display for CircuitDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.differences.is_empty() {
+            writeln!(f, "The circuits are identical.")?;
+        }
+        for difference in self.differences.iter() {
+            writeln!(f, "- {}", difference)?;
+        }
+        if self.verifying_key_changed {
+            writeln!(f, "The verifying key would change.")
+        } else {
+            writeln!(f, "The verifying key would not change.")
+        }
+    }
+}
+
+/// Compares two versions of a circuit with `2^k` rows.
+///
+/// The circuits are synthesized in the same way as keygen, so they do not need to
+/// contain witnesses. This is equivalent to taking a [`CircuitSnapshot`] of each
+/// circuit, and comparing them with [`CircuitDiff::between`].
+pub fn diff<F: FieldExt, OldCircuit: Circuit<F>, NewCircuit: Circuit<F>>(
+    k: u32,
+    old: &OldCircuit,
+    new: &NewCircuit,
+) -> Result<CircuitDiff, Error> {
+    Ok(CircuitDiff::between(
+        &CircuitSnapshot::collect(k, old)?,
+        &CircuitSnapshot::collect(k, new)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{diff, CircuitDiff, Difference};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::export::CircuitSnapshot,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct TestConfig {
+        a: Column<Advice>,
+        c: Column<Fixed>,
+        s: Selector,
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> TestConfig {
+        let a = meta.advice_column();
+        let c = meta.fixed_column();
+        let s = meta.selector();
+
+        meta.create_gate("add constant", |cells| {
+            let a = cells.query_advice(a, Rotation::cur());
+            let a_next = cells.query_advice(a, Rotation::next());
+            let c = cells.query_fixed(c, Rotation::cur());
+            let s = cells.query_selector(s);
+
+            Some(("sum", s * (a + c - a_next)))
+        });
+
+        TestConfig { a, c, s }
+    }
+
+    fn synthesize(
+        config: TestConfig,
+        mut layouter: impl Layouter<Fp>,
+        region_name: &str,
+        constant: u64,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || region_name,
+            |mut region| {
+                config.s.enable(&mut region, 0)?;
+                region.assign_fixed(|| "c", config.c, 0, || Ok(Fp::from(constant)))?;
+                Ok(())
+            },
+        )
+    }
+
+    struct Original {
+        constant: u64,
+    }
+
+    impl Circuit<Fp> for Original {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            configure(meta)
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Original {
+                constant: self.constant,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            synthesize(config, layouter, "add", self.constant)
+        }
+    }
+
+    /// The original circuit with an annotated column and a renamed region, neither of
+    /// which affect the verifying key.
+    struct Refactored;
+
+    impl Circuit<Fp> for Refactored {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let config = configure(meta);
+            meta.annotate_column(config.a, || "value");
+            config
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Refactored
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            synthesize(config, layouter, "add 5", 5)
+        }
+    }
+
+    #[test]
+    fn identical() {
+        let diff = diff(4, &Original { constant: 5 }, &Original { constant: 5 }).unwrap();
+        assert!(diff.is_empty());
+        assert!(!diff.verifying_key_changed());
+    }
+
+    #[test]
+    fn fixed_contents() {
+        let diff = diff(4, &Original { constant: 5 }, &Original { constant: 6 }).unwrap();
+        assert_eq!(
+            diff.differences(),
+            &[Difference::FixedColumn {
+                column: 0,
+                rows: vec![0],
+            }]
+        );
+        assert!(diff.verifying_key_changed());
+    }
+
+    #[test]
+    fn layout_only() {
+        let diff = diff(4, &Original { constant: 5 }, &Refactored).unwrap();
+        assert_eq!(
+            format!("{}", diff),
+            "- Annotation of column A0 changed from None to Some(\"value\")\n\
+             - Region 0 changed from 'add' (rows 0..1, columns [F0], selectors [0]) \
+             to 'add 5' (rows 0..1, columns [F0], selectors [0])\n\
+             The verifying key would not change.\n"
+        );
+        assert!(!diff.verifying_key_changed());
+    }
+
+    #[test]
+    fn gates() {
+        let old = CircuitSnapshot::collect(4, &Original { constant: 5 }).unwrap();
+
+        // Gate names are not part of the verifying key.
+        let mut new = old.clone();
+        new.gates[0].name = "add".to_owned();
+        let diff = CircuitDiff::between(&old, &new);
+        assert_eq!(
+            diff.differences()[0].to_string(),
+            "Gate 0 ('add constant') renamed to 'add'"
+        );
+        assert!(!diff.verifying_key_changed());
+
+        // Changing the number of rows changes the evaluation domain.
+        let new = CircuitSnapshot::collect(5, &Original { constant: 5 }).unwrap();
+        let diff = CircuitDiff::between(&old, &new);
+        assert_eq!(diff.differences()[0], Difference::K { old: 4, new: 5 });
+        assert!(diff.verifying_key_changed());
+    }
+
+    #[test]
+    fn query_order() {
+        let old = CircuitSnapshot::collect(4, &Original { constant: 5 }).unwrap();
+
+        // Expressions refer to queries by index, so reordering them changes the
+        // verifying key even though every expression is unchanged.
+        let mut new = old.clone();
+        new.advice_queries.reverse();
+        let diff = CircuitDiff::between(&old, &new);
+        assert_eq!(diff.differences(), &[Difference::PinnedConstraintSystem]);
+        assert!(diff.verifying_key_changed());
+    }
+}
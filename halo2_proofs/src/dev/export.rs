@@ -0,0 +1,526 @@
+//! Machine-readable snapshots of circuits.
+//!
+//! A [`CircuitSnapshot`] captures everything that keygen derives from a circuit: its
+//! constraint system (after selector compression), the contents of its fixed columns,
+//! and the permutation produced by its copy constraints, along with its region layout.
+//! With the `dev-json` feature, snapshots can be serialized to JSON, for consumption by
+//! external tools, or to check in alongside a circuit so that unintended changes to it
+//! show up in diffs. Two snapshots can be compared with [`diff`](super::diff).
+
+#[cfg(feature = "dev-json")]
+use serde::{Deserialize, Serialize};
+
+use super::layout::CollectedLayout;
+use crate::{
+    arithmetic::FieldExt,
+    circuit::layouter::RegionColumn,
+    plonk::{self, Any, Circuit, Error},
+    poly::Rotation,
+};
+
+/// Formats a field element as a hex string.
+fn format_field<F: FieldExt>(value: &F) -> String {
+    format!("{:?}", value)
+}
+
+/// The type of a column. Column types are ordered in the same way as [`Any`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "dev-json", serde(rename_all = "snake_case"))]
+pub enum ColumnType {
+    /// An instance column.
+    Instance,
+    /// An advice column.
+    Advice,
+    /// A fixed column.
+    Fixed,
+}
+
+/// A column within a circuit.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct Column {
+    /// The type of the column.
+    pub column_type: ColumnType,
+    /// The index of the column within its type.
+    pub index: usize,
+}
+
+impl From<plonk::Column<Any>> for Column {
+    fn from(column: plonk::Column<Any>) -> Self {
+        Column {
+            column_type: match column.column_type() {
+                Any::Advice => ColumnType::Advice,
+                Any::Fixed => ColumnType::Fixed,
+                Any::Instance => ColumnType::Instance,
+            },
+            index: column.index(),
+        }
+    }
+}
+
+/// An annotation attached to a column with `ConstraintSystem::annotate_column`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct ColumnAnnotation {
+    /// The annotated column.
+    pub column: Column,
+    /// The annotation.
+    pub annotation: String,
+}
+
+/// A polynomial expression, mirroring [`plonk::Expression`].
+///
+/// Field elements are encoded as hex strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "dev-json", serde(rename_all = "snake_case"))]
+pub enum Expression {
+    /// A constant.
+    Constant(String),
+    /// A virtual selector. These are removed by selector compression, and so only
+    /// appear in snapshots of uncompressed constraint systems.
+    Selector(usize),
+    /// A query to a fixed column.
+    Fixed {
+        /// The index of the queried column.
+        column: usize,
+        /// The rotation of the query.
+        rotation: i32,
+    },
+    /// A query to an advice column.
+    Advice {
+        /// The index of the queried column.
+        column: usize,
+        /// The rotation of the query.
+        rotation: i32,
+    },
+    /// A query to an instance column.
+    Instance {
+        /// The index of the queried column.
+        column: usize,
+        /// The rotation of the query.
+        rotation: i32,
+    },
+    /// A negated expression.
+    Negated(Box<Expression>),
+    /// The sum of two expressions.
+    Sum(Box<Expression>, Box<Expression>),
+    /// The product of two expressions.
+    Product(Box<Expression>, Box<Expression>),
+    /// An expression multiplied by a constant.
+    Scaled(Box<Expression>, String),
+}
+
+impl<F: FieldExt> From<&plonk::Expression<F>> for Expression {
+    fn from(expression: &plonk::Expression<F>) -> Self {
+        match expression {
+            plonk::Expression::Constant(value) => Expression::Constant(format_field(value)),
+            plonk::Expression::Selector(selector) => Expression::Selector(selector.0),
+            plonk::Expression::Fixed {
+                column_index,
+                rotation,
+                ..
+            } => Expression::Fixed {
+                column: *column_index,
+                rotation: rotation.0,
+            },
+            plonk::Expression::Advice {
+                column_index,
+                rotation,
+                ..
+            } => Expression::Advice {
+                column: *column_index,
+                rotation: rotation.0,
+            },
+            plonk::Expression::Instance {
+                column_index,
+                rotation,
+                ..
+            } => Expression::Instance {
+                column: *column_index,
+                rotation: rotation.0,
+            },
+            plonk::Expression::Negated(a) => Expression::Negated(Box::new(a.as_ref().into())),
+            plonk::Expression::Sum(a, b) => {
+                Expression::Sum(Box::new(a.as_ref().into()), Box::new(b.as_ref().into()))
+            }
+            plonk::Expression::Product(a, b) => {
+                Expression::Product(Box::new(a.as_ref().into()), Box::new(b.as_ref().into()))
+            }
+            plonk::Expression::Scaled(a, scalar) => {
+                Expression::Scaled(Box::new(a.as_ref().into()), format_field(scalar))
+            }
+        }
+    }
+}
+
+/// A query to a column at a particular rotation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct Query {
+    /// The index of the queried column.
+    pub column: usize,
+    /// The rotation of the query.
+    pub rotation: i32,
+}
+
+/// A polynomial constraint within a gate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct Constraint {
+    /// The name of the constraint.
+    pub name: String,
+    /// The constraint polynomial, which must evaluate to zero on every row.
+    pub expression: Expression,
+}
+
+/// A custom gate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct Gate {
+    /// The name of the gate.
+    pub name: String,
+    /// The constraints of the gate, in the order in which they were created.
+    pub constraints: Vec<Constraint>,
+}
+
+/// A lookup argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct Lookup {
+    /// The input expressions of the lookup.
+    pub input_expressions: Vec<Expression>,
+    /// The table expressions that the inputs are looked up in.
+    pub table_expressions: Vec<Expression>,
+}
+
+/// A region of the circuit's layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct Region {
+    /// The name of the region.
+    pub name: String,
+    /// The first row of the region, or `None` if nothing was assigned within it.
+    pub offset: Option<usize>,
+    /// The number of rows that the region takes up.
+    pub rows: usize,
+    /// The columns used by the region, sorted.
+    pub columns: Vec<Column>,
+    /// The selectors enabled within the region, sorted.
+    pub selectors: Vec<usize>,
+}
+
+/// A snapshot of a circuit, as seen by keygen.
+///
+/// # Examples
+///
+/// ```ignore
+/// use halo2_proofs::dev::export::CircuitSnapshot;
+///
+/// let snapshot = CircuitSnapshot::collect(k, &MyCircuit::default()).unwrap();
+/// std::fs::write("my-circuit.json", snapshot.to_json()).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "dev-json", derive(Serialize, Deserialize))]
+pub struct CircuitSnapshot {
+    /// The circuit has `2^k` rows.
+    pub k: u32,
+    /// The degree of the constraint system.
+    pub degree: usize,
+    /// The number of rows at the end of the circuit that are reserved for blinding.
+    pub blinding_factors: usize,
+    /// The number of advice columns.
+    pub num_advice_columns: usize,
+    /// The number of fixed columns, including those that selectors were compressed into.
+    pub num_fixed_columns: usize,
+    /// The number of instance columns.
+    pub num_instance_columns: usize,
+    /// The column annotations, sorted by column.
+    pub column_annotations: Vec<ColumnAnnotation>,
+    /// The index of the fixed column that each selector was compressed into.
+    pub selector_columns: Vec<usize>,
+    /// The custom gates.
+    pub gates: Vec<Gate>,
+    /// The lookup arguments.
+    pub lookups: Vec<Lookup>,
+    /// The columns that participate in the permutation argument.
+    pub permutation_columns: Vec<Column>,
+    /// The indices of the fixed columns that are used to load constants.
+    pub constants: Vec<usize>,
+    /// The contents of each fixed column, as assigned during keygen.
+    pub fixed: Vec<Vec<String>>,
+    /// The permutation produced by the copy constraints: for each column in
+    /// `permutation_columns` and each row, the `(column, row)` of the next cell in its
+    /// cycle.
+    pub permutation_mapping: Vec<Vec<(usize, usize)>>,
+    /// The regions of the circuit, in the order in which they were assigned.
+    pub regions: Vec<Region>,
+    /// The queries to advice columns, in the order in which they were first made.
+    ///
+    /// Expressions refer to queries by their position in these lists, so the order of
+    /// the queries is part of what the verifying key commits to.
+    pub advice_queries: Vec<Query>,
+    /// The queries to instance columns, in the order in which they were first made.
+    pub instance_queries: Vec<Query>,
+    /// The queries to fixed columns, in the order in which they were first made.
+    pub fixed_queries: Vec<Query>,
+    /// The minimum degree set with `ConstraintSystem::set_minimum_degree`, if any.
+    pub minimum_degree: Option<usize>,
+}
+
+impl CircuitSnapshot {
+    /// Synthesizes the given circuit with `2^k` rows, in the same way as keygen, and
+    /// returns a snapshot of it.
+    ///
+    /// The circuit does not need to contain a witness; `Circuit::without_witnesses` is
+    /// sufficient.
+    pub fn collect<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, Error> {
+        let (cs, fixed, permutation_mapping) = plonk::assemble_fixed(k, circuit)?;
+        let layout = CollectedLayout::<F>::collect(k, circuit).layout;
+
+        fn queries<C: plonk::ColumnType>(queries: &[(plonk::Column<C>, Rotation)]) -> Vec<Query> {
+            queries
+                .iter()
+                .map(|(column, rotation)| Query {
+                    column: column.index(),
+                    rotation: rotation.0,
+                })
+                .collect()
+        }
+
+        let mut column_annotations: Vec<_> = cs
+            .column_annotations
+            .iter()
+            .map(|(column, annotation)| ColumnAnnotation {
+                column: (*column).into(),
+                annotation: annotation.clone(),
+            })
+            .collect();
+        column_annotations.sort_by(|a, b| a.column.cmp(&b.column));
+
+        Ok(CircuitSnapshot {
+            k,
+            degree: cs.degree(),
+            blinding_factors: cs.blinding_factors(),
+            num_advice_columns: cs.num_advice_columns,
+            num_fixed_columns: cs.num_fixed_columns,
+            num_instance_columns: cs.num_instance_columns,
+            column_annotations,
+            selector_columns: cs
+                .selector_map
+                .iter()
+                .map(|column| column.index())
+                .collect(),
+            gates: cs
+                .gates
+                .iter()
+                .map(|gate| Gate {
+                    name: gate.name().to_owned(),
+                    constraints: gate
+                        .polynomials()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, poly)| Constraint {
+                            name: gate.constraint_name(i).to_owned(),
+                            expression: poly.into(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            lookups: cs
+                .lookups
+                .iter()
+                .map(|lookup| Lookup {
+                    input_expressions: lookup.input_expressions.iter().map(Into::into).collect(),
+                    table_expressions: lookup.table_expressions.iter().map(Into::into).collect(),
+                })
+                .collect(),
+            permutation_columns: cs
+                .permutation
+                .get_columns()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            constants: cs.constants.iter().map(|column| column.index()).collect(),
+            fixed: fixed
+                .iter()
+                .map(|column| column.iter().map(format_field).collect())
+                .collect(),
+            permutation_mapping,
+            regions: layout
+                .regions
+                .into_iter()
+                .map(|region| {
+                    let mut columns = vec![];
+                    let mut selectors = vec![];
+                    for column in region.columns {
+                        match column {
+                            RegionColumn::Column(column) => columns.push(column.into()),
+                            RegionColumn::Selector(selector) => selectors.push(selector.0),
+                        }
+                    }
+                    columns.sort();
+                    selectors.sort_unstable();
+                    Region {
+                        name: region.name,
+                        offset: region.offset,
+                        rows: region.rows,
+                        columns,
+                        selectors,
+                    }
+                })
+                .collect(),
+            advice_queries: queries(&cs.advice_queries),
+            instance_queries: queries(&cs.instance_queries),
+            fixed_queries: queries(&cs.fixed_queries),
+            minimum_degree: cs.minimum_degree,
+        })
+    }
+
+    /// Serializes this snapshot as pretty-printed JSON.
+    #[cfg(feature = "dev-json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dev-json")))]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("snapshots are always serializable")
+    }
+
+    /// Parses a snapshot from JSON.
+    #[cfg(feature = "dev-json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dev-json")))]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{CircuitSnapshot, Column, ColumnType, Expression, Query};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{self, Advice, Circuit, ConstraintSystem, Error, Fixed, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct TestConfig {
+        a: plonk::Column<Advice>,
+        c: plonk::Column<Fixed>,
+        s: Selector,
+    }
+
+    struct TestCircuit;
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let c = meta.fixed_column();
+            let s = meta.selector();
+
+            meta.enable_equality(a);
+            meta.annotate_column(a, || "value");
+
+            meta.create_gate("add constant", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let a_next = cells.query_advice(a, Rotation::next());
+                let c = cells.query_fixed(c, Rotation::cur());
+                let s = cells.query_selector(s);
+
+                Some(("sum", s * (a + c - a_next)))
+            });
+
+            TestConfig { a, c, s }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "add",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_fixed(|| "c", config.c, 0, || Ok(Fp::from(5)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn snapshot() {
+        let snapshot = CircuitSnapshot::collect(4, &TestCircuit).unwrap();
+
+        assert_eq!(snapshot.num_advice_columns, 1);
+        // The selector is compressed into a second fixed column.
+        assert_eq!(snapshot.num_fixed_columns, 2);
+        assert_eq!(snapshot.selector_columns, vec![1]);
+        assert_eq!(snapshot.column_annotations[0].annotation, "value");
+        assert_eq!(
+            snapshot.permutation_columns,
+            vec![Column {
+                column_type: ColumnType::Advice,
+                index: 0,
+            }]
+        );
+
+        assert_eq!(
+            snapshot.advice_queries,
+            vec![
+                Query {
+                    column: 0,
+                    rotation: 0,
+                },
+                Query {
+                    column: 0,
+                    rotation: 1,
+                },
+            ]
+        );
+
+        let gate = &snapshot.gates[0];
+        assert_eq!(gate.name, "add constant");
+        assert_eq!(gate.constraints[0].name, "sum");
+        match &gate.constraints[0].expression {
+            Expression::Product(selector, _) => assert_eq!(
+                **selector,
+                Expression::Fixed {
+                    column: 1,
+                    rotation: 0,
+                }
+            ),
+            e => panic!("unexpected expression {:?}", e),
+        }
+
+        // The fixed columns hold the assigned constant and the selector.
+        assert_eq!(snapshot.fixed.len(), 2);
+        assert_eq!(snapshot.fixed[0].len(), 16);
+        assert_eq!(snapshot.fixed[0][0], format!("{:?}", Fp::from(5)));
+        assert_eq!(snapshot.fixed[1][0], format!("{:?}", Fp::one()));
+        assert_eq!(snapshot.fixed[1][1], format!("{:?}", Fp::zero()));
+
+        assert_eq!(snapshot.regions.len(), 1);
+        assert_eq!(snapshot.regions[0].name, "add");
+        assert_eq!(snapshot.regions[0].offset, Some(0));
+        assert_eq!(snapshot.regions[0].selectors, vec![0]);
+
+        #[cfg(feature = "dev-json")]
+        assert_eq!(
+            CircuitSnapshot::from_json(&snapshot.to_json()).unwrap(),
+            snapshot
+        );
+    }
+}
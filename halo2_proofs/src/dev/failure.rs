@@ -0,0 +1,386 @@
+//! Detailed rendering of [`VerifyFailure`]s.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use super::{metadata, util, CellValue, FailureLocation, MockProver, VerifyFailure};
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{Any, Column},
+};
+
+/// Formats a table with a header row, right-aligning each column to its widest entry.
+/// Each row is paired with a marker that is appended to it.
+fn format_table(header: &[String], rows: &[(Vec<String>, &str)]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|(row, _)| row[i].len())
+                .chain(Some(header[i].len()))
+                .max()
+                .unwrap()
+        })
+        .collect();
+
+    let mut out = String::new();
+    let mut write_row = |row: &[String], marker: &str| {
+        write!(out, "    |").unwrap();
+        for (entry, width) in row.iter().zip(widths.iter()) {
+            write!(out, " {:>width$} |", entry, width = width).unwrap();
+        }
+        writeln!(out, "{}", marker).unwrap();
+    };
+    write_row(header, "");
+    write_row(
+        &widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>(),
+        "",
+    );
+    for (row, marker) in rows {
+        write_row(row, marker);
+    }
+    out
+}
+
+impl<F: FieldExt> MockProver<F> {
+    /// Returns the name of the given column: its annotation if it has one, the selectors
+    /// it holds if it is a fixed column created by selector compression, or otherwise its
+    /// short label (such as `A0`).
+    fn column_name(&self, column: Column<Any>) -> String {
+        if let Some(annotation) = self.cs.column_annotations.get(&column) {
+            return annotation.clone();
+        }
+
+        let selectors: Vec<_> = self
+            .cs
+            .selector_map
+            .iter()
+            .enumerate()
+            .filter(|(_, selector_column)| Column::<Any>::from(**selector_column) == column)
+            .map(|(selector, _)| format!("S{}", selector))
+            .collect();
+        if selectors.is_empty() {
+            util::column_label(column)
+        } else {
+            selectors.join("/")
+        }
+    }
+
+    /// Renders the region context of a constraint that is not satisfied at the given
+    /// offset within the given region.
+    fn render_region_context(
+        &self,
+        constraint: &metadata::Constraint,
+        region: &metadata::Region,
+        offset: usize,
+    ) -> String {
+        let n = self.n as i32;
+        let gate = &self.cs.gates[constraint.gate.index];
+        let poly = &gate.polynomials()[constraint.index];
+        let r = &self.regions[region.index];
+        let (region_start, region_end) = r.rows.unwrap();
+        let gate_row = (region_start + offset) as i32;
+
+        let mut out = String::new();
+
+        writeln!(out).unwrap();
+        writeln!(out, "  Constraint:").unwrap();
+        writeln!(
+            out,
+            "    {} = 0",
+            util::format_expression(poly, &|column| self.column_name(column))
+        )
+        .unwrap();
+
+        // The cells queried by this constraint, relative to the gate row.
+        let queried: BTreeSet<(Column<Any>, i32)> = poly.evaluate(
+            &|_| BTreeSet::default(),
+            &|_| BTreeSet::default(),
+            &|_, column, rotation| {
+                Some((Column::new(column, Any::Fixed), rotation.0))
+                    .into_iter()
+                    .collect()
+            },
+            &|_, column, rotation| {
+                Some((Column::new(column, Any::Advice), rotation.0))
+                    .into_iter()
+                    .collect()
+            },
+            &|_, column, rotation| {
+                Some((Column::new(column, Any::Instance), rotation.0))
+                    .into_iter()
+                    .collect()
+            },
+            &|a| a,
+            &|mut a, mut b| {
+                a.append(&mut b);
+                a
+            },
+            &|mut a, mut b| {
+                a.append(&mut b);
+                a
+            },
+            &|a, _| a,
+        );
+
+        // Show the columns queried by the constraint, and those assigned in the region.
+        let columns: BTreeSet<Column<Any>> = queried
+            .iter()
+            .map(|(column, _)| *column)
+            .chain(r.cells.iter().map(|(column, _)| *column))
+            .collect();
+
+        // Show the rows reached by the constraint's rotations, with (if they are inside
+        // the region) one row of context on either side.
+        let min_rotation = queried
+            .iter()
+            .map(|(_, rotation)| *rotation)
+            .min()
+            .unwrap_or(0);
+        let max_rotation = queried
+            .iter()
+            .map(|(_, rotation)| *rotation)
+            .max()
+            .unwrap_or(0);
+        let in_region = |row: i32| (region_start as i32..=region_end as i32).contains(&row);
+        let rotations = (min_rotation.min(0) - 1)..=(max_rotation.max(0) + 1);
+        let rotations: Vec<i32> = rotations
+            .filter(|rotation| {
+                (min_rotation..=max_rotation).contains(rotation) || in_region(gate_row + rotation)
+            })
+            .filter(|rotation| (0..n).contains(&(gate_row + rotation)))
+            .collect();
+
+        let cell_value = |column: Column<Any>, row: usize| match column.column_type() {
+            Any::Advice => self.advice[column.index()][row],
+            Any::Fixed => self.fixed[column.index()][row],
+            Any::Instance => CellValue::Assigned(self.instance[column.index()][row]),
+        };
+
+        let header: Vec<String> = Some("Offset".to_owned())
+            .into_iter()
+            .chain(columns.iter().map(|column| self.column_name(*column)))
+            .collect();
+        let rows: Vec<(Vec<String>, &str)> = rotations
+            .iter()
+            .map(|rotation| {
+                let row = (gate_row + rotation) as usize;
+                let cells = columns.iter().map(|column| {
+                    let value = match cell_value(*column, row) {
+                        CellValue::Assigned(value) => util::format_value(value),
+                        CellValue::Unassigned => "-".to_owned(),
+                        CellValue::Poison(_) => "~".to_owned(),
+                    };
+                    if queried.contains(&(*column, *rotation)) {
+                        format!("[{}]", value)
+                    } else {
+                        value
+                    }
+                });
+                let marker = if *rotation == 0 { " <-- gate row" } else { "" };
+                (
+                    Some((row as isize - region_start as isize).to_string())
+                        .into_iter()
+                        .chain(cells)
+                        .collect(),
+                    marker,
+                )
+            })
+            .collect();
+
+        writeln!(out).unwrap();
+        writeln!(out, "  Cells in {} (queried cells in brackets):", region).unwrap();
+        write!(out, "{}", format_table(&header, &rows)).unwrap();
+
+        writeln!(out).unwrap();
+        if gate.queried_selectors().is_empty() {
+            writeln!(
+                out,
+                "  {} has no selectors, and is active on every row.",
+                constraint.gate
+            )
+            .unwrap();
+        } else {
+            writeln!(out, "  Selectors at offset {}:", offset).unwrap();
+            for selector in gate.queried_selectors() {
+                let enabled = r
+                    .enabled_selectors
+                    .get(selector)
+                    .map(|rows| rows.contains(&(gate_row as usize)))
+                    .unwrap_or(false);
+                writeln!(
+                    out,
+                    "    S{}: {}",
+                    selector.0,
+                    if enabled { "enabled" } else { "disabled" }
+                )
+                .unwrap();
+            }
+        }
+
+        out
+    }
+
+    /// Panics if the circuit is not satisfied, after printing each failure with
+    /// [`VerifyFailure::render`].
+    ///
+    /// This is intended for use in tests, in place of `assert_eq!(prover.verify(), Ok(()))`.
+    pub fn assert_satisfied(&self) {
+        if let Err(failures) = self.verify() {
+            for failure in failures.iter() {
+                eprintln!("{}", failure.render(self));
+            }
+            panic!("circuit was not satisfied: {} failure(s)", failures.len());
+        }
+    }
+}
+
+impl VerifyFailure {
+    /// Renders this failure in detail, using the state of the prover that found it.
+    ///
+    /// For a [`VerifyFailure::ConstraintNotSatisfied`] inside a region, this adds the
+    /// constraint expression (with annotated column names), a table of the region's
+    /// cells around the failing offset with the cells queried by the constraint marked,
+    /// and the states of the gate's selectors. A [`VerifyFailure::CellNotAssigned`] is
+    /// rendered with the annotation of its column. Other failures are rendered with their
+    /// `Display` implementation.
+    pub fn render<F: FieldExt>(&self, prover: &MockProver<F>) -> String {
+        let mut out = match self {
+            Self::CellNotAssigned {
+                gate,
+                region,
+                column,
+                offset,
+            } => format!(
+                "{} uses {}, which requires cell in {} at offset {} to be assigned.",
+                region,
+                gate,
+                metadata::Column::annotated(*column, &prover.cs.column_annotations),
+                offset
+            ),
+            _ => self.to_string(),
+        };
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        if let Self::ConstraintNotSatisfied {
+            constraint,
+            location: FailureLocation::InRegion { region, offset },
+            ..
+        } = self
+        {
+            out.push_str(&prover.render_region_context(constraint, region, *offset));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct MulConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        s: Selector,
+    }
+
+    struct MulCircuit {
+        c: u64,
+    }
+
+    impl Circuit<Fp> for MulCircuit {
+        type Config = MulConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let s = meta.selector();
+
+            meta.annotate_column(a, || "lhs");
+
+            // a * b = a_next
+            meta.create_gate("mul", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let a_next = cells.query_advice(a, Rotation::next());
+                let s = cells.query_selector(s);
+
+                Some(("product", s * (a * b - a_next)))
+            });
+
+            MulConfig { a, b, s }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            MulCircuit { c: 0 }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(2)))?;
+                    region.assign_advice(|| "b", config.b, 0, || Ok(Fp::from(3)))?;
+                    region.assign_advice(|| "c", config.a, 1, || Ok(Fp::from(self.c)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn render_constraint_not_satisfied() {
+        MockProver::run(4, &MulCircuit { c: 6 }, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        let prover = MockProver::run(4, &MulCircuit { c: 7 }, vec![]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].render(&prover),
+            r#####"Constraint 0 ('product') in gate 0 ('mul') is not satisfied in Region 0 ('mul') at offset 0
+- Column('Advice', 0 - lhs)@0 = 0x2
+- Column('Advice', 0 - lhs)@1 = 0x7
+- Column('Advice', 1)@0 = 0x3
+
+  Constraint:
+    S0@0 * (lhs@0 * A1@0 - lhs@1) = 0
+
+  Cells in Region 0 ('mul') (queried cells in brackets):
+    | Offset |   lhs |    A1 |  S0 |
+    | ------ | ----- | ----- | --- |
+    |      0 | [0x2] | [0x3] | [1] | <-- gate row
+    |      1 | [0x7] |     - |   0 |
+
+  Selectors at offset 0:
+    S0: enabled
+"#####
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "circuit was not satisfied: 1 failure(s)")]
+    fn assert_satisfied_panics() {
+        MockProver::run(4, &MulCircuit { c: 7 }, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+}
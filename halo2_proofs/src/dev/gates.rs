@@ -56,6 +56,7 @@ struct Gate {
 ///         let b = meta.advice_column();
 ///         let c = meta.advice_column();
 ///         let s = meta.selector();
+///         meta.annotate_column(c, || "product");
 ///
 ///         meta.create_gate("R1CS constraint", |meta| {
 ///             let a = meta.query_advice(a, Rotation::cur());
@@ -82,6 +83,8 @@ struct Gate {
 ///     r#####"R1CS constraint:
 /// - R1CS:
 ///   S0 * (A0@0 * A1@0 - A2@0)
+/// Column annotations:
+/// - A2: product
 /// Total gates: 1
 /// Total custom constraint polynomials: 1
 /// Total negations: 1
@@ -93,6 +96,7 @@ struct Gate {
 #[derive(Debug)]
 pub struct CircuitGates {
     gates: Vec<Gate>,
+    column_annotations: Vec<(String, String)>,
     total_negations: usize,
     total_additions: usize,
     total_multiplications: usize,
@@ -116,40 +120,7 @@ impl CircuitGates {
                     .enumerate()
                     .map(|(i, constraint)| Constraint {
                         name: gate.constraint_name(i),
-                        expression: constraint.evaluate(
-                            &util::format_value,
-                            &|selector| format!("S{}", selector.0),
-                            &|_, column, rotation| format!("F{}@{}", column, rotation.0),
-                            &|_, column, rotation| format!("A{}@{}", column, rotation.0),
-                            &|_, column, rotation| format!("I{}@{}", column, rotation.0),
-                            &|a| {
-                                if a.contains(' ') {
-                                    format!("-({})", a)
-                                } else {
-                                    format!("-{}", a)
-                                }
-                            },
-                            &|a, b| {
-                                if let Some(b) = b.strip_prefix('-') {
-                                    format!("{} - {}", a, b)
-                                } else {
-                                    format!("{} + {}", a, b)
-                                }
-                            },
-                            &|a, b| match (a.contains(' '), b.contains(' ')) {
-                                (false, false) => format!("{} * {}", a, b),
-                                (false, true) => format!("{} * ({})", a, b),
-                                (true, false) => format!("({}) * {}", a, b),
-                                (true, true) => format!("({}) * ({})", a, b),
-                            },
-                            &|a, s| {
-                                if a.contains(' ') {
-                                    format!("({}) * {}", a, util::format_value(s))
-                                } else {
-                                    format!("{} * {}", a, util::format_value(s))
-                                }
-                            },
-                        ),
+                        expression: util::format_expression(constraint, &util::column_label),
                         queries: constraint.evaluate(
                             &|_| BTreeSet::default(),
                             &|selector| vec![format!("S{}", selector.0)].into_iter().collect(),
@@ -184,6 +155,17 @@ impl CircuitGates {
             })
             .collect();
 
+        let mut column_annotations: Vec<_> = cs
+            .column_annotations
+            .iter()
+            .map(|(column, annotation)| (*column, annotation.clone()))
+            .collect();
+        column_annotations.sort();
+        let column_annotations = column_annotations
+            .into_iter()
+            .map(|(column, annotation)| (util::column_label(column), annotation))
+            .collect();
+
         let (total_negations, total_additions, total_multiplications) = cs
             .gates
             .iter()
@@ -208,6 +190,7 @@ impl CircuitGates {
 
         CircuitGates {
             gates,
+            column_annotations,
             total_negations,
             total_additions,
             total_multiplications,
@@ -262,6 +245,12 @@ impl fmt::Display for CircuitGates {
                 }
             }
         }
+        if !self.column_annotations.is_empty() {
+            writeln!(f, "Column annotations:")?;
+            for (column, annotation) in &self.column_annotations {
+                writeln!(f, "- {}: {}", column, annotation)?;
+            }
+        }
         writeln!(f, "Total gates: {}", self.gates.len())?;
         writeln!(
             f,
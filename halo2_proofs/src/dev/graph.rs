@@ -3,7 +3,7 @@ use tabbycat::{AttrList, Edge, GraphBuilder, GraphType, Identity, StmtList};
 
 use crate::plonk::{
     Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
-    FloorPlanner, Instance, Selector,
+    FloorPlanner, Instance, Selector, WitnessAccess,
 };
 
 pub mod layout;
@@ -100,6 +100,10 @@ impl<F: Field> Assignment<F> for Graph {
         Ok(None)
     }
 
+    fn witness_access(&self) -> WitnessAccess<'_, F> {
+        WitnessAccess::Skipped
+    }
+
     fn assign_advice<V, VR, A, AR>(
         &mut self,
         _: A,
@@ -0,0 +1,258 @@
+//! Collection of circuit layouts, shared by the layout renderers.
+
+use ff::Field;
+use std::cmp;
+use std::collections::HashSet;
+
+use crate::circuit::layouter::RegionColumn;
+use crate::plonk::{
+    Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+    FloorPlanner, Instance, Selector, WitnessAccess,
+};
+
+/// A synthesized circuit layout, along with the constraint system it was laid out for.
+pub(crate) struct CollectedLayout<F: Field> {
+    /// The constraint system, after selectors have been compressed into fixed columns.
+    pub(crate) cs: ConstraintSystem<F>,
+    /// The number of fixed columns that hold compressed selectors. These are the last
+    /// fixed columns.
+    pub(crate) selector_columns: usize,
+    pub(crate) layout: Layout,
+}
+
+impl<F: Field> CollectedLayout<F> {
+    /// Synthesizes the given circuit with `2^k` rows, and collects its layout.
+    pub(crate) fn collect<ConcreteCircuit: Circuit<F>>(k: u32, circuit: &ConcreteCircuit) -> Self {
+        let n = 1 << k;
+        let mut cs = ConstraintSystem::default();
+        let config = ConcreteCircuit::configure(&mut cs);
+        let mut layout = Layout::new(k, n, cs.num_selectors);
+        ConcreteCircuit::FloorPlanner::synthesize(
+            &mut layout,
+            circuit,
+            config,
+            cs.constants.clone(),
+        )
+        .unwrap();
+        let (cs, selector_polys) = cs.compress_selectors(std::mem::take(&mut layout.selectors));
+
+        CollectedLayout {
+            cs,
+            selector_columns: selector_polys.len(),
+            layout,
+        }
+    }
+
+    /// The total number of columns in the circuit.
+    pub(crate) fn total_columns(&self) -> usize {
+        self.cs.num_instance_columns + self.cs.num_advice_columns + self.cs.num_fixed_columns
+    }
+
+    /// Returns the position of the given column in the rendered layout.
+    ///
+    /// TODO: For now, columns are rendered in the order they were configured: instance
+    /// columns first, then advice, then fixed (with selectors last).
+    pub(crate) fn column_index(&self, column: RegionColumn) -> usize {
+        let cs = &self.cs;
+        let column: Column<Any> = match column {
+            RegionColumn::Column(col) => col,
+            RegionColumn::Selector(selector) => cs.selector_map[selector.0].into(),
+        };
+        column.index()
+            + match column.column_type() {
+                Any::Instance => 0,
+                Any::Advice => cs.num_instance_columns,
+                Any::Fixed => cs.num_instance_columns + cs.num_advice_columns,
+            }
+    }
+
+    /// The number of usable rows in a circuit of `n` rows.
+    pub(crate) fn usable_rows(&self, n: usize) -> usize {
+        n - (self.cs.blinding_factors() + 1)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Region {
+    /// The name of the region. Not required to be unique.
+    pub(crate) name: String,
+    /// The columns used by this region.
+    pub(crate) columns: HashSet<RegionColumn>,
+    /// The row that this region starts on, if known.
+    pub(crate) offset: Option<usize>,
+    /// The number of rows that this region takes up.
+    pub(crate) rows: usize,
+    /// The cells assigned in this region. We store this as a `Vec` so that if any cells
+    /// are double-assigned, they will be visibly darker.
+    pub(crate) cells: Vec<(RegionColumn, usize)>,
+}
+
+#[derive(Default)]
+pub(crate) struct Layout {
+    k: u32,
+    pub(crate) regions: Vec<Region>,
+    current_region: Option<usize>,
+    pub(crate) total_rows: usize,
+    /// Any cells assigned outside of a region. We store this as a `Vec` so that if any
+    /// cells are double-assigned, they will be visibly darker.
+    pub(crate) loose_cells: Vec<(RegionColumn, usize)>,
+    /// Pairs of cells between which we have equality constraints.
+    pub(crate) equality: Vec<(Column<Any>, usize, Column<Any>, usize)>,
+    /// Selector assignments used for optimization pass
+    pub(crate) selectors: Vec<Vec<bool>>,
+}
+
+impl Layout {
+    pub(crate) fn new(k: u32, n: usize, num_selectors: usize) -> Self {
+        Layout {
+            k,
+            regions: vec![],
+            current_region: None,
+            total_rows: 0,
+            /// Any cells assigned outside of a region. We store this as a `Vec` so that if any
+            /// cells are double-assigned, they will be visibly darker.
+            loose_cells: vec![],
+            /// Pairs of cells between which we have equality constraints.
+            equality: vec![],
+            /// Selector assignments used for optimization pass
+            selectors: vec![vec![false; n]; num_selectors],
+        }
+    }
+
+    fn update(&mut self, column: RegionColumn, row: usize) {
+        self.total_rows = cmp::max(self.total_rows, row + 1);
+
+        if let Some(region) = self.current_region {
+            let region = &mut self.regions[region];
+            region.columns.insert(column);
+
+            // The region offset is the earliest row assigned to.
+            let mut offset = region.offset.unwrap_or(row);
+            if row < offset {
+                // The first row assigned was not at offset 0 within the region.
+                region.rows += offset - row;
+                offset = row;
+            }
+            // The number of rows in this region is the gap between the earliest and
+            // latest rows assigned.
+            region.rows = cmp::max(region.rows, row - offset + 1);
+            region.offset = Some(offset);
+
+            region.cells.push((column, row));
+        } else {
+            self.loose_cells.push((column, row));
+        }
+    }
+}
+
+impl<F: Field> Assignment<F> for Layout {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        assert!(self.current_region.is_none());
+        self.current_region = Some(self.regions.len());
+        self.regions.push(Region {
+            name: name_fn().into(),
+            columns: HashSet::default(),
+            offset: None,
+            rows: 0,
+            cells: vec![],
+        })
+    }
+
+    fn exit_region(&mut self) {
+        assert!(self.current_region.is_some());
+        self.current_region = None;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if let Some(cell) = self.selectors[selector.0].get_mut(row) {
+            *cell = true;
+        } else {
+            return Err(Error::not_enough_rows_available(self.k));
+        }
+
+        self.update((*selector).into(), row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Option<F>, Error> {
+        Ok(None)
+    }
+
+    fn witness_access(&self) -> WitnessAccess<'_, F> {
+        WitnessAccess::Skipped
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update(Column::<Any>::from(column).into(), row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update(Column::<Any>::from(column).into(), row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        l_col: Column<Any>,
+        l_row: usize,
+        r_col: Column<Any>,
+        r_row: usize,
+    ) -> Result<(), crate::plonk::Error> {
+        self.equality.push((l_col, l_row, r_col, r_row));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Option<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
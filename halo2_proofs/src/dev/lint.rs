@@ -0,0 +1,486 @@
+//! Static checks for circuit configurations.
+//!
+//! Many circuit bugs are visible from the [`ConstraintSystem`] alone, before any witness
+//! has been assigned. [`lint`] configures a circuit and checks it for common mistakes;
+//! [`assert_lint_free`] is a convenience for using it in tests.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use ff::Field;
+
+use super::metadata;
+use crate::plonk::{Any, Circuit, Column, ConstraintSystem, Expression};
+
+/// A potential problem with a circuit configuration.
+#[derive(Debug, PartialEq)]
+pub enum LintWarning {
+    /// A constraint does not depend on any selector or fixed column. It is therefore
+    /// active on every row, including the blinding rows, where it cannot be satisfied.
+    UngatedConstraint {
+        /// The constraint that is not gated.
+        constraint: metadata::Constraint,
+    },
+    /// A lookup input does not depend on any selector or fixed column. The lookup is
+    /// therefore active on every usable row, so every unassigned cell it queries must
+    /// also be present in the table.
+    UngatedLookup {
+        /// The index of the lookup. These indices are assigned in the order in which
+        /// `ConstraintSystem::lookup` is called during `Circuit::configure`.
+        lookup_index: usize,
+    },
+    /// A column is queried at a rotation that is larger than the number of blinding
+    /// rows. Gates using this query on rows near the end of the usable region will
+    /// read cells that wrap around to the start of the circuit.
+    LargeRotation {
+        /// The queried column.
+        column: metadata::Column,
+        /// The rotation at which the column is queried.
+        rotation: i32,
+        /// The number of blinding factors of the circuit.
+        blinding_factors: usize,
+    },
+    /// A column is never queried by any gate or lookup, and is not equality-enabled.
+    UnusedColumn {
+        /// The unused column.
+        column: metadata::Column,
+    },
+    /// A selector is never queried by any gate or lookup.
+    UnusedSelector {
+        /// The index of the selector. These indices are assigned in the order in which
+        /// `ConstraintSystem::selector` is called during `Circuit::configure`.
+        selector: usize,
+    },
+    /// A constraint has a degree that, on its own, increases the size of the extended
+    /// evaluation domain (and therefore the cost of proving) beyond what every other
+    /// constraint and argument in the circuit requires.
+    DegreeBlowUp {
+        /// The constraint with the large degree.
+        constraint: metadata::Constraint,
+        /// The degree of the constraint.
+        degree: usize,
+        /// The largest degree required by the rest of the circuit.
+        other_degree: usize,
+    },
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UngatedConstraint { constraint } => write!(
+                f,
+                "{} does not depend on a selector or fixed column, and is active on blinding rows",
+                constraint
+            ),
+            Self::UngatedLookup { lookup_index } => write!(
+                f,
+                "Lookup {} has inputs that do not depend on a selector or fixed column",
+                lookup_index
+            ),
+            Self::LargeRotation {
+                column,
+                rotation,
+                blinding_factors,
+            } => write!(
+                f,
+                "{} is queried at rotation {}, which exceeds the {} blinding factors",
+                column, rotation, blinding_factors
+            ),
+            Self::UnusedColumn { column } => write!(f, "{} is never used", column),
+            Self::UnusedSelector { selector } => write!(f, "Selector {} is never used", selector),
+            Self::DegreeBlowUp {
+                constraint,
+                degree,
+                other_degree,
+            } => write!(
+                f,
+                "{} has degree {}, which enlarges the extended domain (the rest of the circuit only requires degree {})",
+                constraint, degree, other_degree
+            ),
+        }
+    }
+}
+
+/// Returns whether every term of the given expression depends on a selector or fixed
+/// column. `constant` is returned for constant terms.
+///
+/// A sum is only gated if both of its summands are, while a product is gated if either
+/// of its factors is.
+fn is_gated<F: Field>(expression: &Expression<F>, constant: bool) -> bool {
+    expression.evaluate(
+        &|_| constant,
+        &|_| true,
+        &|_, _, _| true,
+        &|_, _, _| false,
+        &|_, _, _| false,
+        &|a| a,
+        &|a, b| a && b,
+        &|a, b| a || b,
+        &|a, _| a,
+    )
+}
+
+/// Returns the selectors queried by the given expression.
+fn queried_selectors<F: Field>(expression: &Expression<F>) -> BTreeSet<usize> {
+    fn merge(mut a: BTreeSet<usize>, mut b: BTreeSet<usize>) -> BTreeSet<usize> {
+        a.append(&mut b);
+        a
+    }
+
+    expression.evaluate(
+        &|_| BTreeSet::default(),
+        &|selector| Some(selector.0).into_iter().collect(),
+        &|_, _, _| BTreeSet::default(),
+        &|_, _, _| BTreeSet::default(),
+        &|_, _, _| BTreeSet::default(),
+        &|a| a,
+        &merge,
+        &merge,
+        &|a, _| a,
+    )
+}
+
+/// The factor by which the extended domain is larger than the circuit, for a circuit of
+/// the given degree.
+fn extension(degree: usize) -> usize {
+    std::cmp::max(degree, 2)
+        .saturating_sub(1)
+        .next_power_of_two()
+}
+
+/// Configures the circuit `C` and checks its constraint system for common mistakes.
+pub fn lint<F: Field, C: Circuit<F>>() -> Vec<LintWarning> {
+    let mut cs = ConstraintSystem::default();
+    let _ = C::configure(&mut cs);
+    lint_constraint_system(&cs)
+}
+
+/// Panics with a list of warnings if [`lint`] finds any problems with the circuit `C`.
+pub fn assert_lint_free<F: Field, C: Circuit<F>>() {
+    let warnings = lint::<F, C>();
+    if !warnings.is_empty() {
+        let mut message = format!("Circuit has {} lint warning(s):\n", warnings.len());
+        for warning in warnings {
+            message.push_str(&format!("- {}\n", warning));
+        }
+        panic!("{}", message);
+    }
+}
+
+fn lint_constraint_system<F: Field>(cs: &ConstraintSystem<F>) -> Vec<LintWarning> {
+    let constraint = |gate_index: usize, poly_index: usize| -> metadata::Constraint {
+        let gate = &cs.gates[gate_index];
+        (
+            (gate_index, gate.name()).into(),
+            poly_index,
+            gate.constraint_name(poly_index),
+        )
+            .into()
+    };
+
+    let ungated_constraints = cs.gates.iter().enumerate().flat_map(|(gate_index, gate)| {
+        gate.polynomials()
+            .iter()
+            .enumerate()
+            .filter(|(_, poly)| !is_gated(poly, false))
+            .map(move |(poly_index, _)| LintWarning::UngatedConstraint {
+                constraint: constraint(gate_index, poly_index),
+            })
+    });
+
+    let ungated_lookups = cs
+        .lookups
+        .iter()
+        .enumerate()
+        // A constant term is active on every row, but it only requires the constant to be
+        // in the table (as in the common `q * a + (1 - q) * default` pattern).
+        .filter(|(_, lookup)| {
+            !lookup
+                .input_expressions
+                .iter()
+                .all(|input| is_gated(input, true))
+        })
+        .map(|(lookup_index, _)| LintWarning::UngatedLookup { lookup_index });
+
+    let blinding_factors = cs.blinding_factors();
+    let queries = cs
+        .advice_queries
+        .iter()
+        .map(|(column, rotation)| (Column::<Any>::from(*column), *rotation))
+        .chain(
+            cs.fixed_queries
+                .iter()
+                .map(|(column, rotation)| ((*column).into(), *rotation)),
+        )
+        .chain(
+            cs.instance_queries
+                .iter()
+                .map(|(column, rotation)| ((*column).into(), *rotation)),
+        )
+        .collect::<Vec<_>>();
+    let large_rotations = queries
+        .iter()
+        .filter(|(_, rotation)| rotation.0.abs() as usize > blinding_factors)
+        .map(|(column, rotation)| LintWarning::LargeRotation {
+            column: metadata::Column::annotated(*column, &cs.column_annotations),
+            rotation: rotation.0,
+            blinding_factors,
+        })
+        .collect::<Vec<_>>();
+
+    // Every gate, lookup and equality-enabled column registers a query, so any column
+    // without one is unused.
+    let queried_columns: BTreeSet<_> = queries.iter().map(|(column, _)| *column).collect();
+    let unused_columns = [
+        (Any::Advice, cs.num_advice_columns),
+        (Any::Fixed, cs.num_fixed_columns),
+        (Any::Instance, cs.num_instance_columns),
+    ]
+    .iter()
+    .flat_map(|(column_type, count)| (0..*count).map(move |index| Column::new(index, *column_type)))
+    .filter(|column| !queried_columns.contains(column))
+    .map(|column| LintWarning::UnusedColumn {
+        column: metadata::Column::annotated(column, &cs.column_annotations),
+    })
+    .collect::<Vec<_>>();
+
+    let used_selectors: BTreeSet<usize> = cs
+        .gates
+        .iter()
+        .flat_map(|gate| gate.queried_selectors().iter().map(|selector| selector.0))
+        .chain(
+            cs.lookups
+                .iter()
+                .flat_map(|lookup| lookup.input_expressions.iter())
+                .flat_map(queried_selectors),
+        )
+        .collect();
+    let unused_selectors = (0..cs.num_selectors)
+        .filter(|selector| !used_selectors.contains(selector))
+        .map(|selector| LintWarning::UnusedSelector { selector });
+
+    // The degrees required by everything except the gates.
+    let base_degree = cs
+        .lookups
+        .iter()
+        .map(|lookup| lookup.required_degree())
+        .fold(cs.permutation.required_degree(), std::cmp::max);
+    let degrees: Vec<(usize, usize, usize)> = cs
+        .gates
+        .iter()
+        .enumerate()
+        .flat_map(|(gate_index, gate)| {
+            gate.polynomials()
+                .iter()
+                .enumerate()
+                .map(move |(poly_index, poly)| (gate_index, poly_index, poly.degree()))
+        })
+        .collect();
+    let degree_blow_ups = degrees
+        .iter()
+        .filter_map(|&(gate_index, poly_index, degree)| {
+            let other_degree = degrees
+                .iter()
+                .filter(|&&(g, p, _)| (g, p) != (gate_index, poly_index))
+                .map(|(_, _, degree)| *degree)
+                .fold(base_degree, std::cmp::max);
+            if extension(degree) > extension(other_degree) {
+                Some(LintWarning::DegreeBlowUp {
+                    constraint: constraint(gate_index, poly_index),
+                    degree,
+                    other_degree,
+                })
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    ungated_constraints
+        .chain(ungated_lookups)
+        .chain(large_rotations)
+        .chain(unused_columns)
+        .chain(unused_selectors)
+        .chain(degree_blow_ups)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{assert_lint_free, lint, LintWarning};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct GoodConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        q: Selector,
+    }
+
+    struct GoodCircuit;
+
+    impl Circuit<Fp> for GoodCircuit {
+        type Config = GoodConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let q = meta.selector();
+
+            meta.create_gate("square", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let q = cells.query_selector(q);
+                vec![q * (a.clone() * a - b)]
+            });
+
+            GoodConfig { a, b, q }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn synthesize(&self, _: Self::Config, _: impl Layouter<Fp>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    struct BadCircuit;
+
+    impl Circuit<Fp> for BadCircuit {
+        type Config = ();
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let _unused = meta.advice_column();
+            let q = meta.selector();
+            let _unused_selector = meta.selector();
+
+            meta.create_gate("ungated", |cells| {
+                let cur = cells.query_advice(a, Rotation::cur());
+                let far = cells.query_advice(a, Rotation(10));
+                vec![cur - far]
+            });
+
+            meta.create_gate("high degree", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let q = cells.query_selector(q);
+                vec![q * a.clone() * a.clone() * a.clone() * a.clone() * a]
+            });
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn synthesize(&self, _: Self::Config, _: impl Layouter<Fp>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    struct PartiallyGatedCircuit;
+
+    impl Circuit<Fp> for PartiallyGatedCircuit {
+        type Config = ();
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let q = meta.complex_selector();
+            let table = meta.lookup_table_column();
+
+            // BUG: b is not gated by q.
+            meta.create_gate("partially gated", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let q = cells.query_selector(q);
+                vec![q * a + b]
+            });
+
+            // This lookup is gated; the default value is looked up on every other row.
+            meta.lookup(|cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let q = cells.query_selector(q);
+                let not_q = Expression::Constant(Fp::one()) - q.clone();
+                vec![(q * a + not_q * Expression::Constant(Fp::from(2)), table)]
+            });
+
+            // BUG: b is not gated by q.
+            meta.lookup(|cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let q = cells.query_selector(q);
+                vec![(q * a + b, table)]
+            });
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn synthesize(&self, _: Self::Config, _: impl Layouter<Fp>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn good_circuit() {
+        assert_eq!(lint::<Fp, GoodCircuit>(), vec![]);
+        assert_lint_free::<Fp, GoodCircuit>();
+    }
+
+    #[test]
+    fn bad_circuit() {
+        assert_eq!(
+            lint::<Fp, BadCircuit>(),
+            vec![
+                LintWarning::UngatedConstraint {
+                    constraint: ((0, "ungated").into(), 0, "").into(),
+                },
+                LintWarning::LargeRotation {
+                    column: (Any::Advice, 0).into(),
+                    rotation: 10,
+                    blinding_factors: 5,
+                },
+                LintWarning::UnusedColumn {
+                    column: (Any::Advice, 1).into(),
+                },
+                LintWarning::UnusedSelector { selector: 1 },
+                LintWarning::DegreeBlowUp {
+                    constraint: ((1, "high degree").into(), 0, "").into(),
+                    degree: 6,
+                    other_degree: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn partially_gated_circuit() {
+        assert_eq!(
+            lint::<Fp, PartiallyGatedCircuit>(),
+            vec![
+                LintWarning::UngatedConstraint {
+                    constraint: ((0, "partially gated").into(), 0, "").into(),
+                },
+                LintWarning::UngatedLookup { lookup_index: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Circuit has 5 lint warning(s)")]
+    fn assert_bad_circuit() {
+        assert_lint_free::<Fp, BadCircuit>();
+    }
+}
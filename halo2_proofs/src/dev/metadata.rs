@@ -1,6 +1,7 @@
 //! Metadata about circuits.
 
 use crate::plonk::{self, Any};
+use std::collections::HashMap;
 use std::fmt;
 
 /// Metadata about a column within a circuit.
@@ -10,17 +11,52 @@ pub struct Column {
     column_type: Any,
     /// The index of the column.
     index: usize,
+    /// The annotation of the column, if any. This is specified with
+    /// `ConstraintSystem::annotate_column`.
+    annotation: Option<String>,
+}
+
+impl Column {
+    /// Returns the metadata for the given column, with its annotation (if any) from the
+    /// given set of column annotations.
+    pub(crate) fn annotated(
+        column: plonk::Column<Any>,
+        annotations: &HashMap<plonk::Column<Any>, String>,
+    ) -> Self {
+        Column {
+            annotation: annotations.get(&column).cloned(),
+            ..column.into()
+        }
+    }
 }
 
 impl fmt::Display for Column {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Column('{:?}', {})", self.column_type, self.index)
+        write!(f, "Column('{:?}', {}", self.column_type, self.index)?;
+        if let Some(annotation) = &self.annotation {
+            write!(f, " - {}", annotation)?;
+        }
+        write!(f, ")")
     }
 }
 
 impl From<(Any, usize)> for Column {
     fn from((column_type, index): (Any, usize)) -> Self {
-        Column { column_type, index }
+        Column {
+            column_type,
+            index,
+            annotation: None,
+        }
+    }
+}
+
+impl From<(Any, usize, &str)> for Column {
+    fn from((column_type, index, annotation): (Any, usize, &str)) -> Self {
+        Column {
+            column_type,
+            index,
+            annotation: Some(annotation.to_owned()),
+        }
     }
 }
 
@@ -29,6 +65,7 @@ impl From<plonk::Column<Any>> for Column {
         Column {
             column_type: *column.column_type(),
             index: column.index(),
+            annotation: None,
         }
     }
 }
@@ -87,7 +124,7 @@ impl fmt::Display for VirtualCell {
 pub struct Gate {
     /// The index of the active gate. These indices are assigned in the order in which
     /// `ConstraintSystem::create_gate` is called during `Circuit::configure`.
-    index: usize,
+    pub(super) index: usize,
     /// The name of the active gate. These are specified by the gate creator (such as
     /// a chip implementation), and is not enforced to be unique.
     name: &'static str,
@@ -109,11 +146,11 @@ impl From<(usize, &'static str)> for Gate {
 #[derive(Debug, PartialEq)]
 pub struct Constraint {
     /// The gate containing the constraint.
-    gate: Gate,
+    pub(super) gate: Gate,
     /// The index of the polynomial constraint within the gate. These indices correspond
     /// to the order in which the constraints are returned from the closure passed to
     /// `ConstraintSystem::create_gate` during `Circuit::configure`.
-    index: usize,
+    pub(super) index: usize,
     /// The name of the constraint. This is specified by the gate creator (such as a chip
     /// implementation), and is not enforced to be unique.
     name: &'static str,
@@ -147,7 +184,7 @@ impl From<(Gate, usize, &'static str)> for Constraint {
 pub struct Region {
     /// The index of the region. These indices are assigned in the order in which
     /// `Layouter::assign_region` is called during `Circuit::synthesize`.
-    index: usize,
+    pub(super) index: usize,
     /// The name of the region. This is specified by the region creator (such as a chip
     /// implementation), and is not enforced to be unique.
     name: String,
@@ -0,0 +1,408 @@
+//! Witness mutation testing for circuits.
+//!
+//! A circuit is under-constrained if the prover can change part of a valid witness
+//! without the constraints noticing. [`MutationTester`] searches for such witnesses by
+//! perturbing one advice cell of a satisfied [`MockProver`] at a time, and re-running
+//! [`MockProver::verify`]. Any mutation that is still accepted is reported.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use rand_core::RngCore;
+
+use super::{metadata, util, CellValue, MockProver, VerifyFailure};
+use crate::{
+    arithmetic::FieldExt,
+    multicore,
+    plonk::{Any, Column},
+};
+
+/// A perturbation applied to a single advice cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// The cell was set to zero.
+    Zero,
+    /// The cell was set to $-1$, i.e. $p - 1$.
+    MinusOne,
+    /// The cell was set to a uniformly random field element.
+    Random,
+    /// The cell was set to the value of the cell at the given rotation in the same
+    /// column.
+    Neighbour(i32),
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Zero => write!(f, "zero"),
+            Self::MinusOne => write!(f, "p - 1"),
+            Self::Random => write!(f, "random value"),
+            Self::Neighbour(rotation) => write!(f, "value of neighbour at rotation {}", rotation),
+        }
+    }
+}
+
+/// A mutation of a single advice cell that the circuit still accepts.
+#[derive(Debug, PartialEq)]
+pub struct SurvivingMutation {
+    /// The region in which the mutated cell was assigned.
+    pub region: metadata::Region,
+    /// The column of the mutated cell.
+    pub column: metadata::Column,
+    /// The offset (relative to the start of the region) of the mutated cell.
+    pub offset: usize,
+    /// The mutation that was applied.
+    pub mutation: Mutation,
+    /// The value that the cell was set to.
+    pub value: String,
+}
+
+impl fmt::Display for SurvivingMutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: cell in {} at offset {} can be set to {} ({}) without failing verification",
+            self.region, self.column, self.offset, self.value, self.mutation
+        )
+    }
+}
+
+/// A candidate mutation, applied to the cell `advice[column][row]`.
+struct Candidate<F> {
+    region: usize,
+    column: usize,
+    row: usize,
+    mutation: Mutation,
+    value: F,
+}
+
+/// Searches for under-constrained advice cells by mutating the witness of a satisfied
+/// [`MockProver`].
+///
+/// Each advice cell assigned within a region is set in turn to zero, to $p - 1$, to
+/// some random values, and to the values of its neighbours in the same column. Cells
+/// that are intended to be unconstrained can be excluded by region name, or by column
+/// (given directly, or by the name it was annotated with).
+/// The mutations are checked in parallel.
+#[derive(Debug)]
+pub struct MutationTester<'a, F: FieldExt> {
+    prover: &'a MockProver<F>,
+    excluded_regions: HashSet<String>,
+    excluded_columns: HashSet<Column<Any>>,
+    random_mutations: usize,
+}
+
+impl<'a, F: FieldExt> MutationTester<'a, F> {
+    /// Creates a mutation tester for the given prover, which should be satisfied.
+    pub fn new(prover: &'a MockProver<F>) -> Self {
+        MutationTester {
+            prover,
+            excluded_regions: HashSet::default(),
+            excluded_columns: HashSet::default(),
+            random_mutations: 1,
+        }
+    }
+
+    /// Skips cells assigned in any region with the given name.
+    pub fn exclude_region(mut self, name: &str) -> Self {
+        self.excluded_regions.insert(name.to_owned());
+        self
+    }
+
+    /// Skips cells assigned in the given column.
+    pub fn exclude_column<C: Into<Column<Any>>>(mut self, column: C) -> Self {
+        self.excluded_columns.insert(column.into());
+        self
+    }
+
+    /// Skips cells assigned in any column that was annotated with the given name, using
+    /// [`ConstraintSystem::annotate_column`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no column was annotated with the given name.
+    ///
+    /// [`ConstraintSystem::annotate_column`]: crate::plonk::ConstraintSystem::annotate_column
+    pub fn exclude_annotated_column(mut self, annotation: &str) -> Self {
+        let columns: Vec<_> = self
+            .prover
+            .cs
+            .column_annotations
+            .iter()
+            .filter(|(_, name)| name.as_str() == annotation)
+            .map(|(column, _)| *column)
+            .collect();
+        assert!(
+            !columns.is_empty(),
+            "no column is annotated with \"{}\"",
+            annotation
+        );
+        self.excluded_columns.extend(columns);
+        self
+    }
+
+    /// Sets the number of random values that each cell is set to. Defaults to 1.
+    pub fn random_mutations(mut self, count: usize) -> Self {
+        self.random_mutations = count;
+        self
+    }
+
+    /// Runs the mutation tests, returning the mutations that still verify.
+    ///
+    /// Returns the original failures if the unmutated witness does not verify.
+    pub fn run(&self, mut rng: impl RngCore) -> Result<Vec<SurvivingMutation>, Vec<VerifyFailure>> {
+        self.prover.verify()?;
+
+        let candidates = self.candidates(&mut rng);
+        if candidates.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let num_threads = multicore::current_num_threads();
+        let chunk = (candidates.len() + num_threads - 1) / num_threads;
+        let mut results = vec![vec![]; candidates.chunks(chunk).len()];
+        multicore::scope(|scope| {
+            for (candidates, survivors) in candidates.chunks(chunk).zip(results.iter_mut()) {
+                scope.spawn(move |_| {
+                    // Each thread mutates its own copy of the witness.
+                    let mut prover = self.prover.clone();
+                    for candidate in candidates {
+                        let original = prover.advice[candidate.column][candidate.row];
+                        prover.advice[candidate.column][candidate.row] =
+                            CellValue::Assigned(candidate.value);
+                        if prover.verify().is_ok() {
+                            survivors.push(candidate);
+                        }
+                        prover.advice[candidate.column][candidate.row] = original;
+                    }
+                });
+            }
+        });
+
+        Ok(results
+            .into_iter()
+            .flatten()
+            .map(|candidate| {
+                let region = &self.prover.regions[candidate.region];
+                SurvivingMutation {
+                    region: (candidate.region, region.name.clone()).into(),
+                    column: metadata::Column::annotated(
+                        Column::new(candidate.column, Any::Advice),
+                        &self.prover.cs.column_annotations,
+                    ),
+                    offset: candidate.row - region.rows.unwrap().0,
+                    mutation: candidate.mutation,
+                    value: util::format_value(candidate.value),
+                }
+            })
+            .collect())
+    }
+
+    /// Enumerates the mutations to check. Mutations that would not change the value of
+    /// a cell are skipped.
+    fn candidates(&self, mut rng: impl RngCore) -> Vec<Candidate<F>> {
+        let advice = &self.prover.advice;
+        let usable_rows = &self.prover.usable_rows;
+
+        let mut candidates = vec![];
+        for (r_i, r) in self.prover.regions.iter().enumerate() {
+            if self.excluded_regions.contains(&r.name) {
+                continue;
+            }
+
+            // Cells may be assigned more than once; only mutate them once.
+            let cells: BTreeSet<_> = r
+                .cells
+                .iter()
+                .filter(|(column, _)| {
+                    *column.column_type() == Any::Advice && !self.excluded_columns.contains(column)
+                })
+                .map(|(column, row)| (column.index(), *row))
+                .collect();
+
+            for (column, row) in cells {
+                let original = match advice[column][row] {
+                    CellValue::Assigned(value) => value,
+                    _ => continue,
+                };
+
+                let neighbour = |rotation: i32| {
+                    let neighbour_row = row as i32 + rotation;
+                    if neighbour_row < 0 || !usable_rows.contains(&(neighbour_row as usize)) {
+                        return None;
+                    }
+                    match advice[column][neighbour_row as usize] {
+                        CellValue::Assigned(value) => Some((Mutation::Neighbour(rotation), value)),
+                        _ => None,
+                    }
+                };
+
+                let mutations = vec![(Mutation::Zero, F::zero()), (Mutation::MinusOne, -F::one())]
+                    .into_iter()
+                    .chain(
+                        (0..self.random_mutations).map(|_| (Mutation::Random, F::random(&mut rng))),
+                    )
+                    .chain(neighbour(-1))
+                    .chain(neighbour(1));
+
+                candidates.extend(mutations.filter(|(_, value)| *value != original).map(
+                    |(mutation, value)| Candidate {
+                        region: r_i,
+                        column,
+                        row,
+                        mutation,
+                        value,
+                    },
+                ));
+            }
+        }
+
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+    use rand_core::OsRng;
+
+    use super::{Mutation, MutationTester, SurvivingMutation};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct MulConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        d: Column<Advice>,
+        q: Selector,
+    }
+
+    #[derive(Default)]
+    struct MulCircuit {
+        free_region: bool,
+    }
+
+    impl Circuit<Fp> for MulCircuit {
+        type Config = MulConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.advice_column();
+            let d = meta.advice_column();
+            let q = meta.selector();
+
+            meta.create_gate("mul", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let c = cells.query_advice(c, Rotation::cur());
+                let q = cells.query_selector(q);
+
+                vec![q * (a * b - c)]
+            });
+
+            meta.annotate_column(d, || "free d");
+
+            MulConfig { a, b, c, d, q }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    config.q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(2)))?;
+                    region.assign_advice(|| "b", config.b, 0, || Ok(Fp::from(3)))?;
+                    region.assign_advice(|| "c", config.c, 0, || Ok(Fp::from(6)))?;
+                    Ok(())
+                },
+            )?;
+
+            if self.free_region {
+                layouter.assign_region(
+                    || "free",
+                    |mut region| {
+                        // BUG: d is not constrained by anything.
+                        region.assign_advice(|| "d", config.d, 0, || Ok(Fp::from(7)))?;
+                        Ok(())
+                    },
+                )?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn constrained_circuit() {
+        let prover = MockProver::run(4, &MulCircuit::default(), vec![]).unwrap();
+        assert_eq!(
+            MutationTester::new(&prover).random_mutations(2).run(OsRng),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn unconstrained_cell() {
+        let prover = MockProver::run(4, &MulCircuit { free_region: true }, vec![]).unwrap();
+
+        let survivors = MutationTester::new(&prover).run(OsRng).unwrap();
+        let mutations: Vec<_> = survivors.iter().map(|s| s.mutation).collect();
+        assert_eq!(
+            mutations,
+            vec![Mutation::Zero, Mutation::MinusOne, Mutation::Random]
+        );
+        assert_eq!(
+            survivors[0],
+            SurvivingMutation {
+                region: (1, "free").into(),
+                column: (Any::Advice, 3, "free d").into(),
+                offset: 0,
+                mutation: Mutation::Zero,
+                value: "0".to_string(),
+            }
+        );
+
+        // The cell can be excluded by region name, by column, or by column annotation.
+        assert_eq!(
+            MutationTester::new(&prover)
+                .exclude_region("free")
+                .run(OsRng),
+            Ok(vec![])
+        );
+        assert_eq!(
+            MutationTester::new(&prover)
+                .exclude_column(Column::new(3, Any::Advice))
+                .run(OsRng),
+            Ok(vec![])
+        );
+        assert_eq!(
+            MutationTester::new(&prover)
+                .exclude_annotated_column("free d")
+                .run(OsRng),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no column is annotated with \"e\"")]
+    fn unknown_annotation() {
+        let prover = MockProver::run(4, &MulCircuit::default(), vec![]).unwrap();
+        MutationTester::new(&prover).exclude_annotated_column("e");
+    }
+}
@@ -0,0 +1,538 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::ops::Range;
+
+use ff::Field;
+
+use super::layout::CollectedLayout;
+use crate::{circuit::layouter::RegionColumn, plonk::Circuit};
+
+/// The width of a cell in a rendered SVG, in pixels.
+const SVG_CELL_WIDTH: usize = 16;
+/// The height of a cell in a rendered SVG, in pixels.
+const SVG_CELL_HEIGHT: usize = 8;
+
+/// Text and SVG renderer for circuit layouts.
+///
+/// This renders the same floor plan as [`CircuitLayout`], but writes it directly to a
+/// string, and so does not require the `dev-graph` feature. The text rendering is
+/// intended to be checked in or printed in CI logs, so that changes to a circuit's
+/// layout show up in diffs.
+///
+/// In the text rendering, each column of the circuit is one character wide, and each
+/// row is one line:
+/// - The header line shows the column types: `I` for instance, `A` for advice, `F` for
+///   fixed, and `S` for fixed columns holding selectors.
+/// - Cells within a region are shown with the region's letter, in upper case if the
+///   cell has been assigned to (or the selector enabled). Regions are lettered `a`
+///   to `z` in the order they were assigned, wrapping around after `z`; the legend
+///   lists each region's name and rows, followed by any column annotations.
+/// - `*` marks a cell assigned outside of any region, and `#` a cell that has been
+///   assigned more than once (which is usually a mistake).
+/// - `=` marks a cell involved in an equality constraint, if enabled.
+/// - `~` marks an empty cell in the unusable rows at the end of the circuit.
+///
+/// [`CircuitLayout`]: crate::dev::CircuitLayout
+///
+/// # Examples
+///
+/// ```ignore
+/// use halo2_proofs::dev::LayoutRenderer;
+///
+/// let circuit = MyCircuit::default();
+/// let k = 5; // Suitable size for MyCircuit
+/// println!("{}", LayoutRenderer::default().view_height(0..16).render_ascii(k, &circuit));
+/// ```
+#[derive(Debug, Default)]
+pub struct LayoutRenderer {
+    mark_equality_cells: bool,
+    view_width: Option<Range<usize>>,
+    view_height: Option<Range<usize>>,
+}
+
+/// The per-cell details of a layout that are needed by both renderers.
+struct Grid<F: Field> {
+    collected: CollectedLayout<F>,
+    n: usize,
+    /// The region whose bounding box contains each cell.
+    region_cells: HashMap<(usize, usize), usize>,
+    /// The number of times each cell has been assigned to.
+    assignments: HashMap<(usize, usize), usize>,
+    /// The cells involved in equality constraints.
+    equality_cells: HashSet<(usize, usize)>,
+}
+
+impl<F: Field> Grid<F> {
+    fn new<ConcreteCircuit: Circuit<F>>(k: u32, circuit: &ConcreteCircuit) -> Self {
+        let collected = CollectedLayout::<F>::collect(k, circuit);
+
+        let mut region_cells = HashMap::new();
+        let mut assignments = HashMap::new();
+        for (index, region) in collected.layout.regions.iter().enumerate() {
+            if let Some(offset) = region.offset {
+                for column in region.columns.iter() {
+                    let column = collected.column_index(*column);
+                    for row in offset..(offset + region.rows) {
+                        region_cells.insert((column, row), index);
+                    }
+                }
+            }
+            for (column, row) in region.cells.iter() {
+                *assignments
+                    .entry((collected.column_index(*column), *row))
+                    .or_insert(0) += 1;
+            }
+        }
+        for (column, row) in collected.layout.loose_cells.iter() {
+            *assignments
+                .entry((collected.column_index(*column), *row))
+                .or_insert(0) += 1;
+        }
+
+        let mut equality_cells = HashSet::new();
+        for (l_col, l_row, r_col, r_row) in collected.layout.equality.iter() {
+            equality_cells.insert((collected.column_index(RegionColumn::from(*l_col)), *l_row));
+            equality_cells.insert((collected.column_index(RegionColumn::from(*r_col)), *r_row));
+        }
+
+        Grid {
+            collected,
+            n: 1 << k,
+            region_cells,
+            assignments,
+            equality_cells,
+        }
+    }
+
+    /// Returns the character used for the type of the given column.
+    fn column_type(&self, column: usize) -> char {
+        let cs = &self.collected.cs;
+        if column < cs.num_instance_columns {
+            'I'
+        } else if column < cs.num_instance_columns + cs.num_advice_columns {
+            'A'
+        } else if column < self.collected.total_columns() - self.collected.selector_columns {
+            'F'
+        } else {
+            'S'
+        }
+    }
+
+    fn usable_rows(&self) -> usize {
+        self.collected.usable_rows(self.n)
+    }
+
+    /// The regions that have been placed, as `(index, name, rows)`.
+    fn placed_regions(&self) -> impl Iterator<Item = (usize, &str, Range<usize>)> + '_ {
+        self.collected
+            .layout
+            .regions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, region)| {
+                region
+                    .offset
+                    .map(|offset| (index, region.name.as_str(), offset..(offset + region.rows)))
+            })
+    }
+}
+
+fn region_letter(index: usize) -> char {
+    (b'a' + (index % 26) as u8) as char
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl LayoutRenderer {
+    /// Marks cells involved in equality constraints.
+    ///
+    /// The default is to not mark these cells.
+    pub fn mark_equality_cells(mut self, show: bool) -> Self {
+        self.mark_equality_cells = show;
+        self
+    }
+
+    /// Sets the view width for this layout, as a number of columns.
+    pub fn view_width(mut self, width: Range<usize>) -> Self {
+        self.view_width = Some(width);
+        self
+    }
+
+    /// Sets the view height for this layout, as a number of rows.
+    pub fn view_height(mut self, height: Range<usize>) -> Self {
+        self.view_height = Some(height);
+        self
+    }
+
+    fn view<F: Field>(&self, grid: &Grid<F>) -> (Range<usize>, Range<usize>) {
+        let width = self
+            .view_width
+            .clone()
+            .unwrap_or(0..grid.collected.total_columns());
+        let height = self.view_height.clone().unwrap_or(0..grid.n);
+        (width, height)
+    }
+
+    /// Renders the given circuit as text.
+    pub fn render_ascii<F: Field, ConcreteCircuit: Circuit<F>>(
+        self,
+        k: u32,
+        circuit: &ConcreteCircuit,
+    ) -> String {
+        let grid = Grid::<F>::new(k, circuit);
+        let (view_width, view_height) = self.view(&grid);
+        let usable_rows = grid.usable_rows();
+
+        let label_width = view_height.end.saturating_sub(1).to_string().len();
+        let mut out = String::new();
+
+        // Header line with the column types.
+        let header: String = view_width.clone().map(|c| grid.column_type(c)).collect();
+        writeln!(out, "{:>width$} {}", "", header, width = label_width).unwrap();
+
+        for row in view_height {
+            let line: String = view_width
+                .clone()
+                .map(|column| {
+                    let cell = (column, row);
+                    let region = grid.region_cells.get(&cell);
+                    match grid.assignments.get(&cell) {
+                        _ if self.mark_equality_cells && grid.equality_cells.contains(&cell) => '=',
+                        Some(count) if *count > 1 => '#',
+                        Some(_) => region
+                            .map(|index| region_letter(*index).to_ascii_uppercase())
+                            .unwrap_or('*'),
+                        None => match region {
+                            Some(index) => region_letter(*index),
+                            None if row >= usable_rows => '~',
+                            None => '.',
+                        },
+                    }
+                })
+                .collect();
+            writeln!(out, "{:>width$} {}", row, line, width = label_width).unwrap();
+        }
+
+        // Legend.
+        for (index, name, rows) in grid.placed_regions() {
+            writeln!(
+                out,
+                "{}: Region {} ('{}') at rows {}..{}",
+                region_letter(index),
+                index,
+                name,
+                rows.start,
+                rows.end
+            )
+            .unwrap();
+        }
+        let mut annotations: Vec<_> = grid
+            .collected
+            .cs
+            .column_annotations
+            .iter()
+            .map(|(column, annotation)| (grid.collected.column_index((*column).into()), annotation))
+            .filter(|(column, _)| view_width.contains(column))
+            .collect();
+        annotations.sort_unstable();
+        for (column, annotation) in annotations {
+            writeln!(out, "Column {}: '{}'", column, annotation).unwrap();
+        }
+        writeln!(
+            out,
+            "{} used rows, {} usable rows",
+            grid.collected.layout.total_rows, usable_rows
+        )
+        .unwrap();
+
+        out
+    }
+
+    /// Renders the given circuit as an SVG image.
+    ///
+    /// The colours match those used by [`CircuitLayout`]: advice columns have a red
+    /// background, fixed columns blue, and instance columns white. Regions are shaded
+    /// green and labelled, assigned cells are darkened, and the unusable rows at the end
+    /// of the circuit are shaded red.
+    ///
+    /// [`CircuitLayout`]: crate::dev::CircuitLayout
+    pub fn render_svg<F: Field, ConcreteCircuit: Circuit<F>>(
+        self,
+        k: u32,
+        circuit: &ConcreteCircuit,
+    ) -> String {
+        let grid = Grid::<F>::new(k, circuit);
+        let (view_width, view_height) = self.view(&grid);
+        let cs = &grid.collected.cs;
+
+        let width = view_width.len() * SVG_CELL_WIDTH;
+        let height = view_height.len() * SVG_CELL_HEIGHT;
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            width, height, width, height
+        )
+        .unwrap();
+
+        // Draws a rectangle covering the given columns and rows, clipped to the view.
+        let rect = |out: &mut String, columns: Range<usize>, rows: Range<usize>, style: &str| {
+            let columns = columns.start.max(view_width.start)..columns.end.min(view_width.end);
+            let rows = rows.start.max(view_height.start)..rows.end.min(view_height.end);
+            if !columns.is_empty() && !rows.is_empty() {
+                writeln!(
+                    out,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                    (columns.start - view_width.start) * SVG_CELL_WIDTH,
+                    (rows.start - view_height.start) * SVG_CELL_HEIGHT,
+                    columns.len() * SVG_CELL_WIDTH,
+                    rows.len() * SVG_CELL_HEIGHT,
+                    style
+                )
+                .unwrap();
+            }
+        };
+
+        // Column backgrounds.
+        let all_rows = 0..grid.n;
+        let advice_start = cs.num_instance_columns;
+        let fixed_start = advice_start + cs.num_advice_columns;
+        let selector_start = grid.collected.total_columns() - grid.collected.selector_columns;
+        rect(
+            &mut out,
+            0..advice_start,
+            all_rows.clone(),
+            r#"fill="white""#,
+        );
+        rect(
+            &mut out,
+            advice_start..fixed_start,
+            all_rows.clone(),
+            r#"fill="red" fill-opacity="0.2""#,
+        );
+        rect(
+            &mut out,
+            fixed_start..selector_start,
+            all_rows.clone(),
+            r#"fill="blue" fill-opacity="0.2""#,
+        );
+        rect(
+            &mut out,
+            selector_start..grid.collected.total_columns(),
+            all_rows.clone(),
+            r#"fill="blue" fill-opacity="0.1""#,
+        );
+
+        // Mark the unusable rows of the circuit.
+        rect(
+            &mut out,
+            0..grid.collected.total_columns(),
+            grid.usable_rows()..grid.n,
+            r#"fill="red" fill-opacity="0.4""#,
+        );
+
+        // Render the regions, as one box per contiguous run of columns.
+        for (index, _, rows) in grid.placed_regions() {
+            let mut columns: Vec<_> = grid.collected.layout.regions[index]
+                .columns
+                .iter()
+                .map(|column| grid.collected.column_index(*column))
+                .collect();
+            columns.sort_unstable();
+
+            let mut runs: Vec<Range<usize>> = vec![];
+            for column in columns {
+                match runs.last_mut() {
+                    Some(run) if run.end == column => run.end += 1,
+                    _ => runs.push(column..(column + 1)),
+                }
+            }
+            for run in runs {
+                rect(
+                    &mut out,
+                    run,
+                    rows.clone(),
+                    r#"fill="green" fill-opacity="0.2" stroke="black""#,
+                );
+            }
+        }
+
+        // Darken the cells that have been assigned to, once per assignment.
+        let mut assigned: Vec<_> = grid.assignments.iter().collect();
+        assigned.sort_unstable();
+        for ((column, row), count) in assigned {
+            for _ in 0..*count {
+                rect(
+                    &mut out,
+                    *column..(column + 1),
+                    *row..(row + 1),
+                    r#"fill="black" fill-opacity="0.1""#,
+                );
+            }
+        }
+
+        // Mark equality-constrained cells.
+        if self.mark_equality_cells {
+            let mut cells: Vec<_> = grid.equality_cells.iter().collect();
+            cells.sort_unstable();
+            for (column, row) in cells {
+                rect(
+                    &mut out,
+                    *column..(column + 1),
+                    *row..(row + 1),
+                    r#"fill="red" fill-opacity="0.5""#,
+                );
+            }
+        }
+
+        // Render labels last, on top of everything else.
+        for (index, name, rows) in grid.placed_regions() {
+            let column = grid.collected.layout.regions[index]
+                .columns
+                .iter()
+                .map(|column| grid.collected.column_index(*column))
+                .min()
+                .unwrap_or(0);
+            if view_width.contains(&column) && view_height.contains(&rows.start) {
+                writeln!(
+                    out,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="10" dominant-baseline="hanging">{}</text>"#,
+                    (column - view_width.start) * SVG_CELL_WIDTH + 2,
+                    (rows.start - view_height.start) * SVG_CELL_HEIGHT,
+                    escape_xml(name)
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(out, "</svg>").unwrap();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::LayoutRenderer;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct TestConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        s: Selector,
+    }
+
+    struct TestCircuit;
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let s = meta.selector();
+
+            meta.enable_equality(a);
+            meta.annotate_column(b, || "b");
+
+            meta.create_gate("equal", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let s = cells.query_selector(s);
+                vec![s * (a - b)]
+            });
+
+            TestConfig { a, b, s }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let a = layouter.assign_region(
+                || "first",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(|| "b", config.b, 0, || Ok(Fp::one()))?;
+                    region.assign_advice(|| "a", config.a, 0, || Ok(Fp::one()))
+                },
+            )?;
+
+            layouter.assign_region(
+                || "second <copy>",
+                |mut region| a.copy_advice(|| "a", &mut region, config.a, 0),
+            )?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn render_ascii() {
+        assert_eq!(
+            LayoutRenderer::default()
+                .view_height(0..3)
+                .render_ascii(4, &TestCircuit),
+            r#####"  AAS
+0 AAA
+1 B..
+2 ...
+a: Region 0 ('first') at rows 0..1
+b: Region 1 ('second <copy>') at rows 1..2
+Column 1: 'b'
+2 used rows, 10 usable rows
+"#####
+        );
+
+        let rendered = LayoutRenderer::default()
+            .mark_equality_cells(true)
+            .view_height(8..16)
+            .render_ascii(4, &TestCircuit);
+        assert_eq!(
+            rendered.lines().take(9).collect::<Vec<_>>(),
+            vec![
+                "   AAS", " 8 ...", " 9 ...", "10 ~~~", "11 ~~~", "12 ~~~", "13 ~~~", "14 ~~~",
+                "15 ~~~",
+            ]
+        );
+
+        let rendered = LayoutRenderer::default()
+            .mark_equality_cells(true)
+            .view_height(0..2)
+            .render_ascii(4, &TestCircuit);
+        assert_eq!(
+            rendered.lines().take(3).collect::<Vec<_>>(),
+            vec!["  AAS", "0 =AA", "1 =.."]
+        );
+    }
+
+    #[test]
+    fn render_svg() {
+        let svg = LayoutRenderer::default()
+            .mark_equality_cells(true)
+            .render_svg(4, &TestCircuit);
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="48" height="128" viewBox="0 0 48 128">"#
+        ));
+        assert!(svg.contains(">second &lt;copy&gt;</text>"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use super::{metadata, CellValue, MockProver};
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{Any, Column, Expression},
+    poly::Rotation,
+};
+
+/// A likely soundness issue found by [`MockProver::find_unconstrained`].
+#[derive(Debug, PartialEq)]
+pub enum ConstraintWarning {
+    /// An advice cell was assigned, but is not used by any active gate, any active
+    /// lookup, or any equality constraint. The prover can set it to any value.
+    UnconstrainedCell {
+        /// The region in which the cell was assigned.
+        region: metadata::Region,
+        /// The column in which the cell was assigned.
+        column: metadata::Column,
+        /// The offset (relative to the start of the region) at which the cell was
+        /// assigned.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for ConstraintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnconstrainedCell {
+                region,
+                column,
+                offset,
+            } => write!(
+                f,
+                "{} assigns cell in {} at offset {}, which is not used by any gate, lookup or equality constraint",
+                region, column, offset
+            ),
+        }
+    }
+}
+
+/// Returns the advice cells, as `(column_index, row)`, that the given expression depends
+/// on when it is evaluated at `row`.
+///
+/// Fixed and instance cells are evaluated, so that advice queries multiplied by a fixed
+/// cell that is zero on this row (such as a disabled selector) are not included.
+fn live_advice_cells<F: FieldExt>(
+    prover: &MockProver<F>,
+    expression: &Expression<F>,
+    row: usize,
+) -> BTreeSet<(usize, usize)> {
+    fn merge(
+        mut a: BTreeSet<(usize, usize)>,
+        mut b: BTreeSet<(usize, usize)>,
+    ) -> BTreeSet<(usize, usize)> {
+        a.append(&mut b);
+        a
+    }
+
+    let n = prover.n as i32;
+    let at = |rotation: Rotation| ((row as i32 + n + rotation.0) % n) as usize;
+    let known = |value: F| (Some(value), BTreeSet::default());
+
+    // Each sub-expression evaluates to its value if it does not depend on any advice
+    // cells, and to the advice cells it depends on.
+    let (_, cells) = expression.evaluate(
+        &known,
+        &|_| panic!("virtual selectors are removed during optimization"),
+        &|_, column_index, rotation| match prover.fixed[column_index][at(rotation)] {
+            CellValue::Assigned(value) => known(value),
+            // Cells that haven't been explicitly assigned to, default to zero.
+            _ => known(F::zero()),
+        },
+        &|_, column_index, rotation| {
+            (
+                None,
+                std::iter::once((column_index, at(rotation))).collect(),
+            )
+        },
+        &|_, column_index, rotation| known(prover.instance[column_index][at(rotation)]),
+        &|(a, cells)| (a.map(|a| -a), cells),
+        &|(a, a_cells), (b, b_cells)| (a.zip(b).map(|(a, b)| a + b), merge(a_cells, b_cells)),
+        &|(a, a_cells), (b, b_cells)| {
+            if a == Some(F::zero()) || b == Some(F::zero()) {
+                known(F::zero())
+            } else {
+                (a.zip(b).map(|(a, b)| a * b), merge(a_cells, b_cells))
+            }
+        },
+        &|(a, cells), scalar| {
+            if scalar == F::zero() {
+                known(F::zero())
+            } else {
+                (a.map(|a| a * scalar), cells)
+            }
+        },
+    );
+    cells
+}
+
+impl<F: FieldExt> MockProver<F> {
+    /// Searches the circuit for assignments that are likely to be under-constrained.
+    ///
+    /// `MockProver::verify` only checks that the constraints hold for the witness that
+    /// was assigned; it cannot detect an advice cell that the constraints don't depend
+    /// on at all. This method reports advice cells that were assigned inside a region,
+    /// but are not queried by any gate on a row where that gate's selectors are enabled,
+    /// are not used by any lookup input on a row where that lookup is active, and are not
+    /// equality-constrained to any other cell.
+    ///
+    /// Gates without any selectors are treated as active on every usable row. A lookup
+    /// input is treated as using an advice cell on a row unless the cell is multiplied by
+    /// a fixed or instance cell that is zero on that row. The analysis is therefore
+    /// conservative: an empty result does not prove that the circuit is sound, but every
+    /// reported cell is worth a second look.
+    ///
+    /// Equality constraints applied to columns that have not been equality-enabled are
+    /// reported by [`MockProver::verify`], for provers created with
+    /// [`MockProver::run_reporting_copy_failures`].
+    pub fn find_unconstrained(&self) -> Vec<ConstraintWarning> {
+        let n = self.n as i32;
+
+        // Advice cells that are queried by a gate on a row where it is active.
+        let mut gate_cells = HashSet::new();
+        for gate in self.cs.gates.iter() {
+            let active_rows: Vec<usize> = if gate.queried_selectors().is_empty() {
+                self.usable_rows.clone().collect()
+            } else {
+                self.usable_rows
+                    .clone()
+                    .filter(|row| {
+                        gate.queried_selectors()
+                            .iter()
+                            .any(|selector| self.selectors[selector.0][*row])
+                    })
+                    .collect()
+            };
+
+            for cell in gate
+                .queried_cells()
+                .iter()
+                .filter(|cell| *cell.column.column_type() == Any::Advice)
+            {
+                for row in active_rows.iter() {
+                    let cell_row = (*row as i32 + n + cell.rotation.0) % n;
+                    gate_cells.insert((cell.column.index(), cell_row as usize));
+                }
+            }
+        }
+
+        // Advice cells that a lookup input depends on, on a row where the lookup is
+        // active. Table expressions don't count: an unconstrained cell in a table only
+        // lets the prover add arbitrary entries to it.
+        let lookup_cells: HashSet<_> = self
+            .cs
+            .lookups
+            .iter()
+            .flat_map(|lookup| lookup.input_expressions.iter())
+            .flat_map(|expression| {
+                self.usable_rows
+                    .clone()
+                    .flat_map(move |row| live_advice_cells(self, expression, row))
+            })
+            .collect();
+
+        // Whether the given cell is in a non-trivial cycle of the permutation.
+        let permutation_columns = self.cs.permutation.get_columns();
+        let is_copied = |column: Column<Any>, row: usize| {
+            permutation_columns
+                .iter()
+                .position(|c| *c == column)
+                .map(|i| self.permutation.mapping[i][row] != (i, row))
+                .unwrap_or(false)
+        };
+
+        let unconstrained_cells = self.regions.iter().enumerate().flat_map(|(r_i, r)| {
+            // Cells may be assigned more than once; only report them once.
+            let cells: BTreeSet<_> = r
+                .cells
+                .iter()
+                .filter(|(column, _)| *column.column_type() == Any::Advice)
+                .collect();
+
+            cells
+                .into_iter()
+                .filter(|(column, row)| {
+                    !(gate_cells.contains(&(column.index(), *row))
+                        || lookup_cells.contains(&(column.index(), *row))
+                        || is_copied(*column, *row))
+                })
+                .map(move |(column, row)| ConstraintWarning::UnconstrainedCell {
+                    region: (r_i, r.name.clone()).into(),
+                    column: metadata::Column::annotated(*column, &self.cs.column_annotations),
+                    offset: row - r.rows.unwrap().0,
+                })
+                .collect::<Vec<_>>()
+        });
+
+        unconstrained_cells.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::ConstraintWarning;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Selector, TableColumn},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct TestConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        q: Selector,
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> TestConfig {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let c = meta.advice_column();
+        let q = meta.selector();
+
+        meta.enable_equality(a);
+
+        meta.create_gate("Equality check", |cells| {
+            let a = cells.query_advice(a, Rotation::cur());
+            let b = cells.query_advice(b, Rotation::cur());
+            let q = cells.query_selector(q);
+
+            vec![q * (a - b)]
+        });
+
+        TestConfig { a, b, c, q }
+    }
+
+    #[test]
+    fn unconstrained_cell() {
+        const K: u32 = 4;
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = TestConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                configure(meta)
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+
+                        // a and b are constrained by the gate.
+                        let a = region.assign_advice(|| "a", config.a, 0, || Ok(Fp::one()))?;
+                        region.assign_advice(|| "b", config.b, 0, || Ok(Fp::one()))?;
+
+                        // This cell is constrained by the equality constraint.
+                        let a_copy = region.assign_advice(|| "a", config.a, 1, || Ok(Fp::one()))?;
+                        region.constrain_equal(a.cell(), a_copy.cell())?;
+
+                        // BUG: c is never used by any constraint.
+                        region.assign_advice(|| "c", config.c, 1, || Ok(Fp::one()))?;
+
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert_eq!(
+            prover.find_unconstrained(),
+            vec![ConstraintWarning::UnconstrainedCell {
+                region: (0, "Faulty synthesis").into(),
+                column: (Any::Advice, 2).into(),
+                offset: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_equality() {
+        const K: u32 = 4;
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = TestConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                configure(meta)
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+
+                        let a = region.assign_advice(|| "a", config.a, 0, || Ok(Fp::one()))?;
+                        region.assign_advice(|| "b", config.b, 0, || Ok(Fp::one()))?;
+
+                        // BUG: c is not equality-enabled.
+                        let c = region.assign_advice(|| "c", config.c, 0, || Ok(Fp::one()))?;
+                        region.constrain_equal(a.cell(), c.cell())?;
+
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let location = || FailureLocation::InRegion {
+            region: (0, "Faulty synthesis").into(),
+            offset: 0,
+        };
+
+        // By default, the mock prover rejects the circuit like the real prover does.
+        assert!(matches!(
+            MockProver::run(K, &FaultyCircuit {}, vec![]),
+            Err(Error::ColumnNotInPermutation(column)) if column == Column::new(2, Any::Advice)
+        ));
+
+        let prover = MockProver::run_reporting_copy_failures(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::ColumnNotInPermutation {
+                column: (Any::Advice, 2).into(),
+                location: location(),
+            }])
+        );
+        assert_eq!(
+            prover.find_unconstrained(),
+            vec![ConstraintWarning::UnconstrainedCell {
+                region: (0, "Faulty synthesis").into(),
+                column: (Any::Advice, 2).into(),
+                offset: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn lookup_rows() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct LookupConfig {
+            a: Column<Advice>,
+            q: Selector,
+            table: TableColumn,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = LookupConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.complex_selector();
+                let table = meta.lookup_table_column();
+
+                meta.lookup(|cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    vec![(q * a, table)]
+                });
+
+                LookupConfig { a, q, table }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "Small table",
+                    |mut table| {
+                        (0..4)
+                            .map(|i| {
+                                table.assign_cell(
+                                    || format!("table[{}]", i),
+                                    config.table,
+                                    i,
+                                    || Ok(Fp::from(i as u64)),
+                                )
+                            })
+                            .fold(Ok(()), |acc, res| acc.and(res))
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        // a is range-checked by the lookup on row 0.
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", config.a, 0, || Ok(Fp::one()))?;
+
+                        // BUG: the lookup is not enabled on row 1.
+                        region.assign_advice(|| "a", config.a, 1, || Ok(Fp::from(7)))?;
+
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert_eq!(
+            prover.find_unconstrained(),
+            vec![ConstraintWarning::UnconstrainedCell {
+                region: (0, "Faulty synthesis").into(),
+                column: (Any::Advice, 0).into(),
+                offset: 1,
+            }]
+        );
+    }
+}
@@ -1,11 +1,11 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 use group::ff::Field;
 use pasta_curves::arithmetic::FieldExt;
 
 use super::{metadata, Value};
 use crate::{
-    plonk::{Any, Expression, Gate, VirtualCell},
+    plonk::{Any, Column, Expression, Gate, VirtualCell},
     poly::Rotation,
 };
 
@@ -26,8 +26,78 @@ pub(super) fn format_value<F: Field>(v: F) -> String {
     }
 }
 
+/// Returns the short label of a column, such as `A0` for advice column 0.
+pub(super) fn column_label(column: Column<Any>) -> String {
+    let prefix = match column.column_type() {
+        Any::Advice => "A",
+        Any::Fixed => "F",
+        Any::Instance => "I",
+    };
+    format!("{}{}", prefix, column.index())
+}
+
+/// Formats an expression as a string, using `column_name` to name the queried columns.
+pub(super) fn format_expression<F: Field>(
+    expression: &Expression<F>,
+    column_name: &impl Fn(Column<Any>) -> String,
+) -> String {
+    expression.evaluate(
+        &format_value,
+        &|selector| format!("S{}", selector.0),
+        &|_, column, rotation| {
+            format!(
+                "{}@{}",
+                column_name(Column::new(column, Any::Fixed)),
+                rotation.0
+            )
+        },
+        &|_, column, rotation| {
+            format!(
+                "{}@{}",
+                column_name(Column::new(column, Any::Advice)),
+                rotation.0
+            )
+        },
+        &|_, column, rotation| {
+            format!(
+                "{}@{}",
+                column_name(Column::new(column, Any::Instance)),
+                rotation.0
+            )
+        },
+        &|a| {
+            if a.contains(' ') {
+                format!("-({})", a)
+            } else {
+                format!("-{}", a)
+            }
+        },
+        &|a, b| {
+            if let Some(b) = b.strip_prefix('-') {
+                format!("{} - {}", a, b)
+            } else {
+                format!("{} + {}", a, b)
+            }
+        },
+        &|a, b| match (a.contains(' '), b.contains(' ')) {
+            (false, false) => format!("{} * {}", a, b),
+            (false, true) => format!("{} * ({})", a, b),
+            (true, false) => format!("({}) * {}", a, b),
+            (true, true) => format!("({}) * ({})", a, b),
+        },
+        &|a, s| {
+            if a.contains(' ') {
+                format!("({}) * {}", a, format_value(s))
+            } else {
+                format!("{} * {}", a, format_value(s))
+            }
+        },
+    )
+}
+
 fn cell_value<'a, F: FieldExt>(
     virtual_cells: &'a [VirtualCell],
+    annotations: &'a HashMap<Column<Any>, String>,
     column_type: Any,
     load: impl Fn(usize, usize, Rotation) -> Value<F> + 'a,
 ) -> impl Fn(usize, usize, Rotation) -> BTreeMap<metadata::VirtualCell, String> + 'a {
@@ -42,7 +112,11 @@ fn cell_value<'a, F: FieldExt>(
             // None indicates a selector, which we don't bother showing.
             .map(|cell| {
                 (
-                    cell.clone().into(),
+                    (
+                        metadata::Column::annotated(cell.column, annotations),
+                        cell.rotation.0,
+                    )
+                        .into(),
                     match load(query_index, column_index, rotation) {
                         Value::Real(v) => format_value(v),
                         Value::Poison => unreachable!(),
@@ -57,6 +131,7 @@ fn cell_value<'a, F: FieldExt>(
 pub(super) fn cell_values<'a, F: FieldExt>(
     gate: &Gate<F>,
     poly: &Expression<F>,
+    annotations: &HashMap<Column<Any>, String>,
     load_fixed: impl Fn(usize, usize, Rotation) -> Value<F> + 'a,
     load_advice: impl Fn(usize, usize, Rotation) -> Value<F> + 'a,
     load_instance: impl Fn(usize, usize, Rotation) -> Value<F> + 'a,
@@ -65,9 +140,9 @@ pub(super) fn cell_values<'a, F: FieldExt>(
     let cell_values = poly.evaluate(
         &|_| BTreeMap::default(),
         &|_| panic!("virtual selectors are removed during optimization"),
-        &cell_value(virtual_cells, Any::Fixed, load_fixed),
-        &cell_value(virtual_cells, Any::Advice, load_advice),
-        &cell_value(virtual_cells, Any::Instance, load_instance),
+        &cell_value(virtual_cells, annotations, Any::Fixed, load_fixed),
+        &cell_value(virtual_cells, annotations, Any::Advice, load_advice),
+        &cell_value(virtual_cells, annotations, Any::Instance, load_instance),
         &|a| a,
         &|mut a, mut b| {
             a.append(&mut b);
@@ -24,6 +24,15 @@
 // Remove this once we update pasta_curves
 #![allow(unused_imports)]
 
+/// Enters a span that lasts until the end of the enclosing block, if the `tracing`
+/// feature is enabled. The arguments are those of `tracing::info_span!`.
+macro_rules! enter_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($($args)*).entered();
+    };
+}
+
 pub mod arithmetic;
 pub mod circuit;
 pub use pasta_curves as pasta;
@@ -25,10 +25,12 @@ mod vanishing;
 
 mod prover;
 mod verifier;
+pub(crate) mod witness_dump;
 
 pub use assigned::*;
 pub use circuit::*;
 pub use error::*;
+pub(crate) use keygen::assemble_fixed;
 pub use keygen::*;
 pub use prover::*;
 pub use verifier::*;
@@ -129,11 +131,15 @@ pub struct PinnedVerificationKey<'a, C: CurveAffine> {
 #[derive(Debug)]
 pub struct ProvingKey<C: CurveAffine> {
     vk: VerifyingKey<C>,
-    l0: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    l_blind: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
-    l_last: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
+    l0: Polynomial<C::Scalar, Coeff>,
+    l_blind: Polynomial<C::Scalar, Coeff>,
+    l_last: Polynomial<C::Scalar, Coeff>,
+    /// `l0`, `l_blind` and `l_last` over the extended domain, in that order. Empty if the
+    /// proving key was generated by [`keygen_pk_without_cosets`].
+    l_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
     fixed_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
     fixed_polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    /// Empty if the proving key was generated by [`keygen_pk_without_cosets`].
     fixed_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
     permutation: permutation::ProvingKey<C>,
 }
@@ -2,6 +2,7 @@ use core::cmp::max;
 use core::ops::{Add, Mul};
 use ff::Field;
 use std::{
+    collections::HashMap,
     convert::TryFrom,
     ops::{Neg, Sub},
 };
@@ -251,6 +252,10 @@ impl TryFrom<Column<Any>> for Column<Instance> {
 pub struct Selector(pub(crate) usize, bool);
 
 impl Selector {
+    pub(crate) fn new(index: usize, simple: bool) -> Self {
+        Selector(index, simple)
+    }
+
     /// Enable this selector at the given offset within the given region.
     pub fn enable<F: Field>(&self, region: &mut Region<F>, offset: usize) -> Result<(), Error> {
         region.enable_selector(|| "", self, offset)
@@ -291,6 +296,32 @@ impl TableColumn {
     }
 }
 
+/// How an [`Assignment`] can be read from threads other than the one assigning to it,
+/// which [`Layouter::assign_regions`] needs in order to assign regions in parallel.
+///
+/// [`Layouter::assign_regions`]: crate::circuit::Layouter::assign_regions
+pub enum WitnessAccess<'a, F: Field> {
+    /// The assignment cannot be read from other threads, so regions must be assigned
+    /// serially.
+    Serial,
+    /// Witness values are not computed in this context (as during key generation), so
+    /// witness closures need not be called, and instance values are unknown.
+    Skipped,
+    /// Witness values are computed, and instance values can be read with the given
+    /// function, which behaves like [`Assignment::query_instance`].
+    Computed(Box<dyn Fn(Column<Instance>, usize) -> Result<Option<F>, Error> + Sync + 'a>),
+}
+
+impl<'a, F: Field> std::fmt::Debug for WitnessAccess<'a, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WitnessAccess::Serial => f.write_str("Serial"),
+            WitnessAccess::Skipped => f.write_str("Skipped"),
+            WitnessAccess::Computed(_) => f.write_str("Computed"),
+        }
+    }
+}
+
 /// This trait allows a [`Circuit`] to direct some backend to assign a witness
 /// for a constraint system.
 pub trait Assignment<F: Field> {
@@ -331,6 +362,17 @@ pub trait Assignment<F: Field> {
     /// Returns the cell's value, if known.
     fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error>;
 
+    /// Returns how this assignment can be read from other threads.
+    ///
+    /// Not intended for downstream consumption; [`Layouter::assign_regions`] uses this to
+    /// assign regions in parallel. The default implementation returns
+    /// [`WitnessAccess::Serial`].
+    ///
+    /// [`Layouter::assign_regions`]: crate::circuit::Layouter::assign_regions
+    fn witness_access(&self) -> WitnessAccess<'_, F> {
+        WitnessAccess::Serial
+    }
+
     /// Assign an advice column value (witness)
     fn assign_advice<V, VR, A, AR>(
         &mut self,
@@ -818,6 +860,10 @@ pub struct ConstraintSystem<F: Field> {
     pub(crate) constants: Vec<Column<Fixed>>,
 
     pub(crate) minimum_degree: Option<usize>,
+
+    // Human-readable names for columns, used by the `dev` tooling. These do not affect
+    // the circuit.
+    pub(crate) column_annotations: HashMap<Column<Any>, String>,
 }
 
 /// Represents the minimal parameters that determine a `ConstraintSystem`.
@@ -866,6 +912,7 @@ impl<F: Field> Default for ConstraintSystem<F> {
             lookups: Vec::new(),
             constants: vec![],
             minimum_degree: None,
+            column_annotations: HashMap::new(),
         }
     }
 }
@@ -904,6 +951,20 @@ impl<F: Field> ConstraintSystem<F> {
         }
     }
 
+    /// Annotates a column with a name.
+    ///
+    /// Annotations have no effect on the circuit; they are used by the tools in
+    /// [`dev`](crate::dev) when reporting on the column.
+    pub fn annotate_column<C, A, AR>(&mut self, column: C, annotation: A)
+    where
+        C: Into<Column<Any>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.column_annotations
+            .insert(column.into(), annotation().into());
+    }
+
     /// Enable the ability to enforce equality over cells in this column
     pub fn enable_equality<C: Into<Column<Any>>>(&mut self, column: C) {
         let column = column.into();
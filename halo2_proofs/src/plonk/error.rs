@@ -29,6 +29,14 @@ pub enum Error {
     },
     /// Instance provided exceeds number of available rows
     InstanceTooLarge,
+    /// A proving or verifying key was generated for a different `k` than that of the
+    /// parameters it is used with.
+    KeyParamsMismatch {
+        /// The value of `k` for the parameters.
+        params_k: u32,
+        /// The value of `k` for the key.
+        key_k: u32,
+    },
     /// Circuit synthesis requires global constants, but circuit configuration did not
     /// call [`ConstraintSystem::enable_constant`] on fixed columns with sufficient space.
     ///
@@ -37,6 +45,14 @@ pub enum Error {
     /// The instance sets up a copy constraint involving a column that has not been
     /// included in the permutation.
     ColumnNotInPermutation(Column<Any>),
+    /// The prover was given a [`QuotientStrategy::Chunked`] with no parts per chunk.
+    ///
+    /// [`QuotientStrategy::Chunked`]: crate::plonk::QuotientStrategy::Chunked
+    InvalidQuotientStrategy,
+    /// Proof creation was cancelled by its [`ProofObserver`].
+    ///
+    /// [`ProofObserver`]: crate::plonk::ProofObserver
+    Cancelled,
 }
 
 impl From<io::Error> for Error {
@@ -68,6 +84,11 @@ impl fmt::Display for Error {
                 current_k,
             ),
             Error::InstanceTooLarge => write!(f, "Instance vectors are larger than the circuit"),
+            Error::KeyParamsMismatch { params_k, key_k } => write!(
+                f,
+                "The key was generated for k = {}, but the parameters have k = {}",
+                key_k, params_k,
+            ),
             Error::NotEnoughColumnsForConstants => {
                 write!(
                     f,
@@ -79,6 +100,10 @@ impl fmt::Display for Error {
                 "Column {:?} must be included in the permutation. Help: try applying `meta.enable_equalty` on the column",
                 column
             ),
+            Error::InvalidQuotientStrategy => {
+                write!(f, "The quotient strategy must evaluate at least one part at a time")
+            }
+            Error::Cancelled => write!(f, "Proof creation was cancelled"),
         }
     }
 }
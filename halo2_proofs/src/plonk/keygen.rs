@@ -8,7 +8,7 @@ use group::Curve;
 use super::{
     circuit::{
         Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Instance,
-        Selector,
+        Selector, WitnessAccess,
     },
     permutation, Assigned, Error, LagrangeCoeff, Polynomial, ProvingKey, VerifyingKey,
 };
@@ -16,7 +16,10 @@ use crate::poly::{
     commitment::{Blind, Params},
     EvaluationDomain,
 };
-use crate::{arithmetic::CurveAffine, poly::batch_invert_assigned};
+use crate::{
+    arithmetic::{CurveAffine, FieldExt},
+    poly::batch_invert_assigned,
+};
 
 pub(crate) fn create_domain<C, ConcreteCircuit>(
     params: &Params<C>,
@@ -87,6 +90,10 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         Ok(None)
     }
 
+    fn witness_access(&self) -> WitnessAccess<'_, F> {
+        WitnessAccess::Skipped
+    }
+
     fn assign_advice<V, VR, A, AR>(
         &mut self,
         _: A,
@@ -180,27 +187,45 @@ impl<F: Field> Assignment<F> for Assembly<F> {
     }
 }
 
-/// Generate a `VerifyingKey` from an instance of `Circuit`.
-pub fn keygen_vk<C, ConcreteCircuit>(
-    params: &Params<C>,
+/// Synthesizes the fixed columns, selectors and copy constraints of a circuit with `2^k`
+/// rows, given its configured constraint system.
+///
+/// Returns the constraint system after selector compression, the values of every fixed
+/// column (including the fixed columns that selectors were compressed into), and the
+/// permutation assembly produced by the copy constraints.
+#[allow(clippy::type_complexity)]
+fn synthesize_fixed<F, ConcreteCircuit>(
+    k: u32,
     circuit: &ConcreteCircuit,
-) -> Result<VerifyingKey<C>, Error>
+    cs: ConstraintSystem<F>,
+    config: ConcreteCircuit::Config,
+) -> Result<
+    (
+        ConstraintSystem<F>,
+        Vec<Polynomial<F, LagrangeCoeff>>,
+        permutation::keygen::Assembly,
+    ),
+    Error,
+>
 where
-    C: CurveAffine,
-    ConcreteCircuit: Circuit<C::Scalar>,
+    F: FieldExt,
+    ConcreteCircuit: Circuit<F>,
 {
-    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params);
+    let n = 1 << k;
 
-    if (params.n as usize) < cs.minimum_rows() {
-        return Err(Error::not_enough_rows_available(params.k));
+    if n < cs.minimum_rows() {
+        return Err(Error::not_enough_rows_available(k));
     }
 
-    let mut assembly: Assembly<C::Scalar> = Assembly {
-        k: params.k,
-        fixed: vec![domain.empty_lagrange_assigned(); cs.num_fixed_columns],
-        permutation: permutation::keygen::Assembly::new(params.n as usize, &cs.permutation),
-        selectors: vec![vec![false; params.n as usize]; cs.num_selectors],
-        usable_rows: 0..params.n as usize - (cs.blinding_factors() + 1),
+    let mut assembly: Assembly<F> = Assembly {
+        k,
+        fixed: vec![
+            Polynomial::from_lagrange_values(vec![F::zero().into(); n]);
+            cs.num_fixed_columns
+        ],
+        permutation: permutation::keygen::Assembly::new(n, &cs.permutation),
+        selectors: vec![vec![false; n]; cs.num_selectors],
+        usable_rows: 0..n - (cs.blinding_factors() + 1),
         _marker: std::marker::PhantomData,
     };
 
@@ -217,12 +242,51 @@ where
     fixed.extend(
         selector_polys
             .into_iter()
-            .map(|poly| domain.lagrange_from_vec(poly)),
+            .map(Polynomial::from_lagrange_values),
     );
 
-    let permutation_vk = assembly
-        .permutation
-        .build_vk(params, &domain, &cs.permutation);
+    Ok((cs, fixed, assembly.permutation))
+}
+
+/// Synthesizes the fixed columns of a circuit with `2^k` rows, in the same way as
+/// [`keygen_vk`], but without committing to them.
+///
+/// Returns the constraint system after selector compression, the values of every fixed
+/// column (including the fixed columns that selectors were compressed into), and the
+/// mapping of the permutation argument that the copy constraints produce.
+#[allow(clippy::type_complexity)]
+pub(crate) fn assemble_fixed<F, ConcreteCircuit>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+) -> Result<(ConstraintSystem<F>, Vec<Vec<F>>, Vec<Vec<(usize, usize)>>), Error>
+where
+    F: FieldExt,
+    ConcreteCircuit: Circuit<F>,
+{
+    let mut cs = ConstraintSystem::default();
+    let config = ConcreteCircuit::configure(&mut cs);
+
+    let (cs, fixed, permutation) = synthesize_fixed(k, circuit, cs, config)?;
+    let fixed = fixed.iter().map(|poly| poly.to_vec()).collect();
+
+    Ok((cs, fixed, permutation.mapping))
+}
+
+/// Generate a `VerifyingKey` from an instance of `Circuit`.
+pub fn keygen_vk<C, ConcreteCircuit>(
+    params: &Params<C>,
+    circuit: &ConcreteCircuit,
+) -> Result<VerifyingKey<C>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    enter_span!("keygen_vk", k = params.k);
+    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params);
+
+    let (cs, fixed, permutation) = synthesize_fixed(params.k, circuit, cs, config)?;
+
+    let permutation_vk = permutation.build_vk(params, &domain, &cs.permutation);
 
     let fixed_commitments = fixed
         .iter()
@@ -247,60 +311,72 @@ where
     C: CurveAffine,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let mut cs = ConstraintSystem::default();
-    let config = ConcreteCircuit::configure(&mut cs);
-
-    let cs = cs;
-
-    if (params.n as usize) < cs.minimum_rows() {
-        return Err(Error::not_enough_rows_available(params.k));
-    }
+    keygen_pk_inner(params, vk, circuit, true)
+}
 
-    let mut assembly: Assembly<C::Scalar> = Assembly {
-        k: params.k,
-        fixed: vec![vk.domain.empty_lagrange_assigned(); cs.num_fixed_columns],
-        permutation: permutation::keygen::Assembly::new(params.n as usize, &cs.permutation),
-        selectors: vec![vec![false; params.n as usize]; cs.num_selectors],
-        usable_rows: 0..params.n as usize - (cs.blinding_factors() + 1),
-        _marker: std::marker::PhantomData,
-    };
+/// Generate a `ProvingKey` from a `VerifyingKey` and an instance of `Circuit`, without
+/// precomputing the fixed, permutation and boundary polynomials over the extended
+/// domain.
+///
+/// The resulting proving key is several times smaller, and is intended for proofs
+/// created with [`QuotientStrategy::Chunked`] (set with
+/// [`ProverOptions::quotient_strategy`]), which does not use the precomputed
+/// polynomials. With the default [`QuotientStrategy::Full`], they are instead computed
+/// during each proof. The proofs created with either proving key are identical.
+///
+/// [`QuotientStrategy::Chunked`]: crate::plonk::QuotientStrategy::Chunked
+/// [`QuotientStrategy::Full`]: crate::plonk::QuotientStrategy::Full
+/// [`ProverOptions::quotient_strategy`]: crate::plonk::ProverOptions::quotient_strategy
+pub fn keygen_pk_without_cosets<C, ConcreteCircuit>(
+    params: &Params<C>,
+    vk: VerifyingKey<C>,
+    circuit: &ConcreteCircuit,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    keygen_pk_inner(params, vk, circuit, false)
+}
 
-    // Synthesize the circuit to obtain URS
-    ConcreteCircuit::FloorPlanner::synthesize(
-        &mut assembly,
-        circuit,
-        config,
-        cs.constants.clone(),
-    )?;
+fn keygen_pk_inner<C, ConcreteCircuit>(
+    params: &Params<C>,
+    vk: VerifyingKey<C>,
+    circuit: &ConcreteCircuit,
+    precompute_cosets: bool,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    enter_span!("keygen_pk", k = params.k);
+    let mut cs = ConstraintSystem::default();
+    let config = ConcreteCircuit::configure(&mut cs);
 
-    let mut fixed = batch_invert_assigned(assembly.fixed);
-    let (cs, selector_polys) = cs.compress_selectors(assembly.selectors);
-    fixed.extend(
-        selector_polys
-            .into_iter()
-            .map(|poly| vk.domain.lagrange_from_vec(poly)),
-    );
+    let (cs, fixed, permutation) = synthesize_fixed(params.k, circuit, cs, config)?;
 
     let fixed_polys: Vec<_> = fixed
         .iter()
         .map(|poly| vk.domain.lagrange_to_coeff(poly.clone()))
         .collect();
 
-    let fixed_cosets = fixed_polys
-        .iter()
-        .map(|poly| vk.domain.coeff_to_extended(poly.clone()))
-        .collect();
+    let fixed_cosets = if precompute_cosets {
+        fixed_polys
+            .iter()
+            .map(|poly| vk.domain.coeff_to_extended(poly.clone()))
+            .collect()
+    } else {
+        vec![]
+    };
 
-    let permutation_pk = assembly
-        .permutation
-        .build_pk(params, &vk.domain, &cs.permutation);
+    let permutation_pk =
+        permutation.build_pk(params, &vk.domain, &cs.permutation, precompute_cosets);
 
     // Compute l_0(X)
     // TODO: this can be done more efficiently
     let mut l0 = vk.domain.empty_lagrange();
     l0[0] = C::Scalar::one();
     let l0 = vk.domain.lagrange_to_coeff(l0);
-    let l0 = vk.domain.coeff_to_extended(l0);
 
     // Compute l_blind(X) which evaluates to 1 for each blinding factor row
     // and 0 otherwise over the domain.
@@ -309,20 +385,29 @@ where
         *evaluation = C::Scalar::one();
     }
     let l_blind = vk.domain.lagrange_to_coeff(l_blind);
-    let l_blind = vk.domain.coeff_to_extended(l_blind);
 
     // Compute l_last(X) which evaluates to 1 on the first inactive row (just
     // before the blinding factors) and 0 otherwise over the domain
     let mut l_last = vk.domain.empty_lagrange();
     l_last[params.n as usize - cs.blinding_factors() - 1] = C::Scalar::one();
     let l_last = vk.domain.lagrange_to_coeff(l_last);
-    let l_last = vk.domain.coeff_to_extended(l_last);
+
+    let l_cosets = if precompute_cosets {
+        vec![
+            vk.domain.coeff_to_extended(l0.clone()),
+            vk.domain.coeff_to_extended(l_blind.clone()),
+            vk.domain.coeff_to_extended(l_last.clone()),
+        ]
+    } else {
+        vec![]
+    };
 
     Ok(ProvingKey {
         vk,
         l0,
         l_blind,
         l_last,
+        l_cosets,
         fixed_values: fixed,
         fixed_polys,
         fixed_cosets,
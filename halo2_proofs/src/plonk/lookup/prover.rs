@@ -100,6 +100,7 @@ impl<F: FieldExt> Argument<F> {
         C: CurveAffine<ScalarExt = F>,
         C::Curve: Mul<F, Output = C::Curve> + MulAssign<F>,
     {
+        enter_span!("lookup_commit_permuted");
         // Closure to get values of expressions and compress them
         let compress_expressions = |expressions: &[Expression<C::Scalar>]| {
             // Values of input expressions involved in the lookup
@@ -205,10 +206,10 @@ impl<F: FieldExt> Argument<F> {
         // Hash permuted table commitment
         transcript.write_point(permuted_table_commitment)?;
 
-        let permuted_input_coset = coset_evaluator
-            .register_poly(pk.vk.domain.coeff_to_extended(permuted_input_poly.clone()));
-        let permuted_table_coset = coset_evaluator
-            .register_poly(pk.vk.domain.coeff_to_extended(permuted_table_poly.clone()));
+        let permuted_input_coset =
+            coset_evaluator.register_coeff_poly(&pk.vk.domain, permuted_input_poly.clone());
+        let permuted_table_coset =
+            coset_evaluator.register_coeff_poly(&pk.vk.domain, permuted_table_poly.clone());
 
         Ok(Permuted {
             unpermuted_input_expressions,
@@ -248,6 +249,7 @@ impl<C: CurveAffine, Ev: Copy + Send + Sync> Permuted<C, Ev> {
         mut rng: R,
         transcript: &mut T,
     ) -> Result<Committed<C, Ev>, Error> {
+        enter_span!("lookup_commit_product");
         let blinding_factors = pk.vk.cs.blinding_factors();
         // Goal is to compute the products of fractions
         //
@@ -380,7 +382,7 @@ impl<C: CurveAffine, Ev: Copy + Send + Sync> Permuted<C, Ev> {
         let product_blind = Blind(C::Scalar::random(rng));
         let product_commitment = params.commit_lagrange(&z, product_blind).to_affine();
         let z = pk.vk.domain.lagrange_to_coeff(z);
-        let product_coset = evaluator.register_poly(pk.vk.domain.coeff_to_extended(z.clone()));
+        let product_coset = evaluator.register_coeff_poly(&pk.vk.domain, z.clone());
 
         // Hash product commitment
         transcript.write_point(product_commitment)?;
@@ -493,6 +495,7 @@ impl<C: CurveAffine> Constructed<C> {
         x: ChallengeX<C>,
         transcript: &mut T,
     ) -> Result<Evaluated<C>, Error> {
+        enter_span!("lookup_evaluate");
         let domain = &pk.vk.domain;
         let x_inv = domain.rotate_omega(*x, Rotation::prev());
         let x_next = domain.rotate_omega(*x, Rotation::next());
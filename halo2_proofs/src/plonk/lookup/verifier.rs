@@ -72,6 +72,7 @@ impl<C: CurveAffine> Committed<C> {
         self,
         transcript: &mut T,
     ) -> Result<Evaluated<C>, Error> {
+        enter_span!("lookup_verifier_evaluate");
         let product_eval = transcript.read_scalar()?;
         let product_next_eval = transcript.read_scalar()?;
         let permuted_input_eval = transcript.read_scalar()?;
@@ -99,6 +99,7 @@ impl<C: CurveAffine> VerifyingKey<C> {
 #[derive(Debug)]
 pub(crate) struct ProvingKey<C: CurveAffine> {
     permutations: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-    polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    pub(super) polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    /// Empty if the proving key was generated without cosets.
     pub(super) cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
 }
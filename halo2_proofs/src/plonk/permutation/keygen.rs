@@ -11,7 +11,7 @@ use crate::{
     },
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct Assembly {
     columns: Vec<Column<Any>>,
     pub(crate) mapping: Vec<Vec<(usize, usize)>>,
@@ -103,6 +103,7 @@ impl Assembly {
         domain: &EvaluationDomain<C::Scalar>,
         p: &Argument,
     ) -> VerifyingKey<C> {
+        enter_span!("permutation_build_vk");
         // Compute [omega^0, omega^1, ..., omega^{params.n - 1}]
         let mut omega_powers = Vec::with_capacity(params.n as usize);
         {
@@ -155,7 +156,9 @@ impl Assembly {
         params: &Params<C>,
         domain: &EvaluationDomain<C::Scalar>,
         p: &Argument,
+        precompute_cosets: bool,
     ) -> ProvingKey<C> {
+        enter_span!("permutation_build_pk");
         // Compute [omega^0, omega^1, ..., omega^{params.n - 1}]
         let mut omega_powers = Vec::with_capacity(params.n as usize);
         {
@@ -195,11 +198,14 @@ impl Assembly {
                 *p = deltaomega[permuted_i][permuted_j];
             }
 
-            // Store permutation polynomial and precompute its coset evaluation
+            // Store permutation polynomial and precompute its coset evaluation, if
+            // requested
             permutations.push(permutation_poly.clone());
             let poly = domain.lagrange_to_coeff(permutation_poly);
-            polys.push(poly.clone());
-            cosets.push(domain.coeff_to_extended(poly));
+            if precompute_cosets {
+                cosets.push(domain.coeff_to_extended(poly.clone()));
+            }
+            polys.push(poly);
         }
         ProvingKey {
             permutations,
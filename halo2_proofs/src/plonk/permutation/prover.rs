@@ -63,6 +63,7 @@ impl Argument {
         mut rng: R,
         transcript: &mut T,
     ) -> Result<Committed<C, Ev>, Error> {
+        enter_span!("permutation_commit");
         let domain = &pk.vk.domain;
 
         // How many columns can be included in a single permutation polynomial?
@@ -173,8 +174,7 @@ impl Argument {
             let z = domain.lagrange_to_coeff(z);
             let permutation_product_poly = z.clone();
 
-            let permutation_product_coset =
-                evaluator.register_poly(domain.coeff_to_extended(z.clone()));
+            let permutation_product_coset = evaluator.register_coeff_poly(domain, z.clone());
 
             let permutation_product_commitment =
                 permutation_product_commitment_projective.to_affine();
@@ -73,6 +73,7 @@ impl<C: CurveAffine> Committed<C> {
         self,
         transcript: &mut T,
     ) -> Result<Evaluated<C>, Error> {
+        enter_span!("permutation_verifier_evaluate");
         let mut sets = vec![];
 
         let mut iter = self.permutation_product_commitments.into_iter();
@@ -1,13 +1,15 @@
 use ff::Field;
 use group::Curve;
 use rand_core::RngCore;
+use std::fmt;
 use std::iter;
 use std::ops::RangeTo;
+use std::time::{Duration, Instant};
 
 use super::{
     circuit::{
         Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Instance,
-        Selector,
+        Selector, WitnessAccess,
     },
     lookup, permutation, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
     ChallengeY, Error, ProvingKey,
@@ -20,13 +22,326 @@ use crate::poly::{
 };
 use crate::{
     arithmetic::{eval_polynomial, CurveAffine, FieldExt},
-    plonk::Assigned,
+    plonk::{
+        witness_dump::{WitnessDump, WitnessRecorder},
+        Assigned,
+    },
 };
 use crate::{
     poly::batch_invert_assigned,
     transcript::{EncodedChallenge, TranscriptWrite},
 };
 
+/// The strategy that the prover uses to evaluate the quotient polynomial `h(X)`, which
+/// requires every column of the circuit to be evaluated over the extended domain.
+///
+/// The extended domain has `2^(k + j)` points, where `2^j` is the smallest power of two
+/// that is at least the degree of the constraint system minus one. It can be split into
+/// `2^j` parts, each of which is a coset of the `2^k`-row domain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotientStrategy {
+    /// Evaluates every column over the whole extended domain up front, and keeps these
+    /// evaluations in memory until `h(X)` has been computed.
+    ///
+    /// This is the fastest strategy, but it needs memory for `2^(k + j)` field elements
+    /// per column, on top of the rest of the witness.
+    Full,
+    /// Evaluates `h(X)` over `parts_per_chunk` parts of the extended domain at a time.
+    /// Columns are kept in coefficient form, and evaluated over the parts in each chunk
+    /// when that chunk is reached.
+    ///
+    /// The memory used for column evaluations is proportional to `parts_per_chunk`
+    /// rather than to `2^j`, so a smaller chunk uses less memory, while a larger chunk
+    /// gives each round of evaluation more work to parallelize. This strategy is
+    /// slower than [`QuotientStrategy::Full`], but creates identical proofs. It is best
+    /// combined with a proving key from [`keygen_pk_without_cosets`].
+    ///
+    /// [`keygen_pk_without_cosets`]: crate::plonk::keygen_pk_without_cosets
+    Chunked {
+        /// The number of parts to evaluate at a time. Proof creation fails with
+        /// [`Error::InvalidQuotientStrategy`] if this is zero.
+        parts_per_chunk: usize,
+    },
+}
+
+impl Default for QuotientStrategy {
+    fn default() -> Self {
+        QuotientStrategy::Full
+    }
+}
+
+impl QuotientStrategy {
+    fn parts_per_chunk(&self) -> Option<usize> {
+        match self {
+            QuotientStrategy::Full => None,
+            QuotientStrategy::Chunked { parts_per_chunk } => Some(*parts_per_chunk),
+        }
+    }
+}
+
+/// A phase of proof creation, as reported to a [`ProofObserver`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverPhase {
+    /// Synthesizing a circuit to obtain its witness. Reported once per circuit.
+    WitnessSynthesis,
+    /// Committing to a circuit's advice columns. Reported once per circuit.
+    AdviceCommitments,
+    /// Committing to the permuted input and table columns of the lookup arguments.
+    LookupPermutation,
+    /// Committing to the grand products of the permutation argument.
+    PermutationProducts,
+    /// Committing to the grand products of the lookup arguments.
+    LookupProducts,
+    /// Computing and committing to the quotient polynomial `h(X)`.
+    Quotient,
+    /// Evaluating the committed polynomials at the challenge point `x`.
+    Evaluations,
+    /// Creating the multi-opening proof.
+    Multiopen,
+}
+
+/// Observes the progress of proof creation, and can cancel it.
+///
+/// All methods have default implementations that do nothing, so an observer need only
+/// implement the ones it is interested in.
+pub trait ProofObserver {
+    /// Called when the prover enters `phase`.
+    fn phase_started(&mut self, _phase: ProverPhase) {}
+
+    /// Called when the prover completes `phase`, with the time that it took.
+    fn phase_finished(&mut self, _phase: ProverPhase, _elapsed: Duration) {}
+
+    /// Returns `true` if proof creation should be cancelled.
+    ///
+    /// This is polled before each phase starts; if it returns `true`, the prover stops
+    /// and returns [`Error::Cancelled`]. A phase that has started runs to completion.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// A phase that has been reported as started to the prover's observer, if any.
+struct PhaseTimer {
+    phase: ProverPhase,
+    start: Option<Instant>,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+/// Options that control how a proof is created.
+///
+/// Options are set with the builder methods, and can be combined freely:
+///
+/// ```ignore
+/// ProverOptions::new()
+///     .quotient_strategy(QuotientStrategy::Chunked { parts_per_chunk: 2 })
+///     .observer(&mut observer)
+///     .create_proof(&params, &pk, &[circuit], &[&[&instance]], OsRng, &mut transcript)?;
+/// ```
+///
+/// [`create_proof`] and [`create_multi_proof`] create proofs with the default options.
+pub struct ProverOptions<'a, C: CurveAffine> {
+    /// If set, a dump of each circuit's assignments is pushed onto this vector.
+    dumps: Option<&'a mut Vec<WitnessDump<C::Scalar>>>,
+    strategy: QuotientStrategy,
+    observer: Option<&'a mut dyn ProofObserver>,
+}
+
+impl<'a, C: CurveAffine> fmt::Debug for ProverOptions<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProverOptions")
+            .field("dumps", &self.dumps.is_some())
+            .field("strategy", &self.strategy)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+impl<'a, C: CurveAffine> Default for ProverOptions<'a, C> {
+    fn default() -> Self {
+        ProverOptions {
+            dumps: None,
+            strategy: QuotientStrategy::default(),
+            observer: None,
+        }
+    }
+}
+
+impl<'a, C: CurveAffine> ProverOptions<'a, C> {
+    /// Returns the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Captures a dump of each circuit's assignments into `dumps`.
+    ///
+    /// The dumps are captured even if proving fails; if a circuit fails to synthesize,
+    /// its dump contains everything that was assigned up to the failure. They can be
+    /// written to a file and replayed with [`MockProver::replay`] to debug the failure
+    /// without the original witness-generation code.
+    ///
+    /// [`MockProver::replay`]: crate::dev::MockProver::replay
+    pub fn dump_witnesses(mut self, dumps: &'a mut Vec<WitnessDump<C::Scalar>>) -> Self {
+        self.dumps = Some(dumps);
+        self
+    }
+
+    /// Evaluates the quotient polynomial with the given [`QuotientStrategy`].
+    pub fn quotient_strategy(mut self, strategy: QuotientStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Reports the progress of proof creation to `observer`, which may also cancel it.
+    pub fn observer(mut self, observer: &'a mut dyn ProofObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Creates a proof in the same way as [`create_proof`], with these options.
+    pub fn create_proof<
+        E: EncodedChallenge<C>,
+        R: RngCore,
+        T: TranscriptWrite<C, E>,
+        ConcreteCircuit: Circuit<C::Scalar>,
+    >(
+        self,
+        params: &Params<C>,
+        pk: &ProvingKey<C>,
+        circuits: &[ConcreteCircuit],
+        instances: &[&[&[C::Scalar]]],
+        rng: R,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        create_proof_inner(
+            params,
+            &[ProofGroup::new(pk, circuits, instances)],
+            rng,
+            transcript,
+            self,
+        )
+    }
+
+    /// Creates a proof in the same way as [`create_multi_proof`], with these options.
+    pub fn create_multi_proof<E: EncodedChallenge<C>, R: RngCore, T: TranscriptWrite<C, E>>(
+        self,
+        params: &Params<C>,
+        groups: &[ProofGroup<'_, C>],
+        rng: R,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        create_proof_inner(params, groups, rng, transcript, self)
+    }
+
+    /// Enters `phase`, unless the observer has cancelled proof creation.
+    ///
+    /// Phases are only timed if there is an observer, because `Instant` is unavailable
+    /// on some targets.
+    fn start_phase(&mut self, phase: ProverPhase) -> Result<PhaseTimer, Error> {
+        let start = match self.observer.as_mut() {
+            Some(observer) => {
+                if observer.is_cancelled() {
+                    return Err(Error::Cancelled);
+                }
+                observer.phase_started(phase);
+                Some(Instant::now())
+            }
+            None => None,
+        };
+        Ok(PhaseTimer {
+            phase,
+            start,
+            #[cfg(feature = "tracing")]
+            _span: tracing::info_span!("phase", phase = ?phase).entered(),
+        })
+    }
+
+    fn finish_phase(&mut self, timer: PhaseTimer) {
+        if let (Some(observer), Some(start)) = (self.observer.as_mut(), timer.start) {
+            observer.phase_finished(timer.phase, start.elapsed());
+        }
+    }
+}
+
+/// A group of circuits that share a [`ProvingKey`], to be proven together with other
+/// groups in a single proof by [`create_multi_proof`].
+pub struct ProofGroup<'a, C: CurveAffine> {
+    pk: &'a ProvingKey<C>,
+    circuits: Box<dyn SynthesizeCircuits<C::Scalar> + 'a>,
+    instances: &'a [&'a [&'a [C::Scalar]]],
+}
+
+impl<'a, C: CurveAffine> fmt::Debug for ProofGroup<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProofGroup")
+            .field("circuits", &self.circuits.num_circuits())
+            .field("instances", &self.instances)
+            .finish()
+    }
+}
+
+impl<'a, C: CurveAffine> ProofGroup<'a, C> {
+    /// Creates a group that proves `circuits` with the proving key `pk`, which was
+    /// generated for their circuit. `instances` holds the instance columns of each
+    /// circuit, as for [`create_proof`].
+    pub fn new<ConcreteCircuit: Circuit<C::Scalar>>(
+        pk: &'a ProvingKey<C>,
+        circuits: &'a [ConcreteCircuit],
+        instances: &'a [&'a [&'a [C::Scalar]]],
+    ) -> Self {
+        ProofGroup {
+            pk,
+            circuits: Box::new(circuits),
+            instances,
+        }
+    }
+}
+
+/// The circuits of a [`ProofGroup`], which may be of any concrete type.
+trait SynthesizeCircuits<F: Field> {
+    /// Returns the number of circuits.
+    fn num_circuits(&self) -> usize;
+
+    /// Synthesizes the circuit with the given index into `witness`, recording its
+    /// assignments into `dump` if it is set.
+    fn synthesize(
+        &self,
+        index: usize,
+        witness: &mut WitnessCollection<'_, F>,
+        constants: Vec<Column<Fixed>>,
+        dump: Option<&mut WitnessDump<F>>,
+    ) -> Result<(), Error>;
+}
+
+impl<'a, F: Field, ConcreteCircuit: Circuit<F>> SynthesizeCircuits<F> for &'a [ConcreteCircuit] {
+    fn num_circuits(&self) -> usize {
+        self.len()
+    }
+
+    fn synthesize(
+        &self,
+        index: usize,
+        witness: &mut WitnessCollection<'_, F>,
+        constants: Vec<Column<Fixed>>,
+        dump: Option<&mut WitnessDump<F>>,
+    ) -> Result<(), Error> {
+        let mut meta = ConstraintSystem::default();
+        let config = ConcreteCircuit::configure(&mut meta);
+
+        match dump {
+            Some(dump) => ConcreteCircuit::FloorPlanner::synthesize(
+                &mut WitnessRecorder::new(witness, dump),
+                &self[index],
+                config,
+                constants,
+            ),
+            None => {
+                ConcreteCircuit::FloorPlanner::synthesize(witness, &self[index], config, constants)
+            }
+        }
+    }
+}
+
 /// This creates a proof for the provided `circuit` when given the public
 /// parameters `params` and the proving key [`ProvingKey`] that was
 /// generated previously for the same circuit. The provided `instances`
@@ -42,411 +357,596 @@ pub fn create_proof<
     pk: &ProvingKey<C>,
     circuits: &[ConcreteCircuit],
     instances: &[&[&[C::Scalar]]],
-    mut rng: R,
+    rng: R,
     transcript: &mut T,
 ) -> Result<(), Error> {
-    for instance in instances.iter() {
-        if instance.len() != pk.vk.cs.num_instance_columns {
-            return Err(Error::InvalidInstances);
-        }
-    }
+    ProverOptions::new().create_proof(params, pk, circuits, instances, rng, transcript)
+}
 
-    // Hash verification key into transcript
-    pk.vk.hash_into(transcript)?;
+/// Creates a single proof for several groups of circuits, each of which has its own
+/// [`ProvingKey`], so that circuits of different types can be proven together.
+///
+/// The groups share one transcript and one multi-opening argument. The proof is
+/// checked with [`verify_multi_proof`], given the verifying keys and instances of the
+/// groups in the same order. A proof for a single group is the same as the proof
+/// created by [`create_proof`] for its circuits.
+///
+/// [`verify_multi_proof`]: crate::plonk::verify_multi_proof
+pub fn create_multi_proof<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    R: RngCore,
+    T: TranscriptWrite<C, E>,
+>(
+    params: &Params<C>,
+    groups: &[ProofGroup<'_, C>],
+    rng: R,
+    transcript: &mut T,
+) -> Result<(), Error> {
+    ProverOptions::new().create_multi_proof(params, groups, rng, transcript)
+}
 
-    let domain = &pk.vk.domain;
-    let mut meta = ConstraintSystem::default();
-    let config = ConcreteCircuit::configure(&mut meta);
+struct InstanceSingle<C: CurveAffine> {
+    pub instance_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    pub instance_polys: Vec<Polynomial<C::Scalar, Coeff>>,
+}
 
-    // Selector optimizations cannot be applied here; use the ConstraintSystem
-    // from the verification key.
-    let meta = &pk.vk.cs;
+struct AdviceSingle<C: CurveAffine> {
+    pub advice_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    pub advice_polys: Vec<Polynomial<C::Scalar, Coeff>>,
+    pub advice_blinds: Vec<Blind<C::Scalar>>,
+}
 
-    struct InstanceSingle<C: CurveAffine> {
-        pub instance_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-        pub instance_polys: Vec<Polynomial<C::Scalar, Coeff>>,
-        pub instance_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+/// The polynomials of one group of circuits, registered with the group's evaluators.
+struct GroupEvaluators<F: Field, Ev, Ec> {
+    value_evaluator: poly::Evaluator<Ev, F, LagrangeCoeff>,
+    fixed_values: Vec<poly::AstLeaf<Ev, LagrangeCoeff>>,
+    advice_values: Vec<Vec<poly::AstLeaf<Ev, LagrangeCoeff>>>,
+    instance_values: Vec<Vec<poly::AstLeaf<Ev, LagrangeCoeff>>>,
+    coset_evaluator: poly::Evaluator<Ec, F, ExtendedLagrangeCoeff>,
+    fixed_cosets: Vec<poly::AstLeaf<Ec, ExtendedLagrangeCoeff>>,
+    advice_cosets: Vec<Vec<poly::AstLeaf<Ec, ExtendedLagrangeCoeff>>>,
+    instance_cosets: Vec<Vec<poly::AstLeaf<Ec, ExtendedLagrangeCoeff>>>,
+    permutation_cosets: Vec<poly::AstLeaf<Ec, ExtendedLagrangeCoeff>>,
+    l0: poly::AstLeaf<Ec, ExtendedLagrangeCoeff>,
+    l_blind: poly::AstLeaf<Ec, ExtendedLagrangeCoeff>,
+    l_last: poly::AstLeaf<Ec, ExtendedLagrangeCoeff>,
+}
+
+struct WitnessCollection<'a, F: Field> {
+    k: u32,
+    pub advice: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
+    instances: &'a [&'a [F]],
+    usable_rows: RangeTo<usize>,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about regions in this context.
     }
 
-    let instance: Vec<InstanceSingle<C>> = instances
-        .iter()
-        .map(|instance| -> Result<InstanceSingle<C>, Error> {
-            let instance_values = instance
-                .iter()
-                .map(|values| {
-                    let mut poly = domain.empty_lagrange();
-                    assert_eq!(poly.len(), params.n as usize);
-                    if values.len() > (poly.len() - (meta.blinding_factors() + 1)) {
-                        return Err(Error::InstanceTooLarge);
-                    }
-                    for (poly, value) in poly.iter_mut().zip(values.iter()) {
-                        *poly = *value;
-                    }
-                    Ok(poly)
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-            let instance_commitments_projective: Vec<_> = instance_values
-                .iter()
-                .map(|poly| params.commit_lagrange(poly, Blind::default()))
-                .collect();
-            let mut instance_commitments =
-                vec![C::identity(); instance_commitments_projective.len()];
-            C::Curve::batch_normalize(&instance_commitments_projective, &mut instance_commitments);
-            let instance_commitments = instance_commitments;
-            drop(instance_commitments_projective);
-
-            for commitment in &instance_commitments {
-                transcript.common_point(*commitment)?;
-            }
+    fn exit_region(&mut self) {
+        // Do nothing; we don't care about regions in this context.
+    }
 
-            let instance_polys: Vec<_> = instance_values
-                .iter()
-                .map(|poly| {
-                    let lagrange_vec = domain.lagrange_from_vec(poly.to_vec());
-                    domain.lagrange_to_coeff(lagrange_vec)
-                })
-                .collect();
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about advice columns here
 
-            let instance_cosets: Vec<_> = instance_polys
-                .iter()
-                .map(|poly| domain.coeff_to_extended(poly.clone()))
-                .collect();
+        Ok(())
+    }
 
-            Ok(InstanceSingle {
-                instance_values,
-                instance_polys,
-                instance_cosets,
-            })
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        if !self.usable_rows.contains(&row) {
+            return Err(Error::not_enough_rows_available(self.k));
+        }
 
-    struct AdviceSingle<C: CurveAffine> {
-        pub advice_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-        pub advice_polys: Vec<Polynomial<C::Scalar, Coeff>>,
-        pub advice_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
-        pub advice_blinds: Vec<Blind<C::Scalar>>,
+        self.instances
+            .get(column.index())
+            .and_then(|column| column.get(row))
+            .map(|v| Some(*v))
+            .ok_or(Error::BoundsFailure)
     }
 
-    let advice: Vec<AdviceSingle<C>> = circuits
-        .iter()
-        .zip(instances.iter())
-        .map(|(circuit, instances)| -> Result<AdviceSingle<C>, Error> {
-            struct WitnessCollection<'a, F: Field> {
-                k: u32,
-                pub advice: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
-                instances: &'a [&'a [F]],
-                usable_rows: RangeTo<usize>,
-                _marker: std::marker::PhantomData<F>,
-            }
+    fn witness_access(&self) -> WitnessAccess<'_, F> {
+        WitnessAccess::Computed(Box::new(move |column, row| {
+            self.query_instance(column, row)
+        }))
+    }
 
-            impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
-                fn enter_region<NR, N>(&mut self, _: N)
-                where
-                    NR: Into<String>,
-                    N: FnOnce() -> NR,
-                {
-                    // Do nothing; we don't care about regions in this context.
-                }
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if !self.usable_rows.contains(&row) {
+            return Err(Error::not_enough_rows_available(self.k));
+        }
 
-                fn exit_region(&mut self) {
-                    // Do nothing; we don't care about regions in this context.
-                }
+        *self
+            .advice
+            .get_mut(column.index())
+            .and_then(|v| v.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = to()?.into();
 
-                fn enable_selector<A, AR>(
-                    &mut self,
-                    _: A,
-                    _: &Selector,
-                    _: usize,
-                ) -> Result<(), Error>
-                where
-                    A: FnOnce() -> AR,
-                    AR: Into<String>,
-                {
-                    // We only care about advice columns here
-
-                    Ok(())
-                }
+        Ok(())
+    }
 
-                fn query_instance(
-                    &self,
-                    column: Column<Instance>,
-                    row: usize,
-                ) -> Result<Option<F>, Error> {
-                    if !self.usable_rows.contains(&row) {
-                        return Err(Error::not_enough_rows_available(self.k));
-                    }
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about advice columns here
+
+        Ok(())
+    }
 
-                    self.instances
-                        .get(column.index())
-                        .and_then(|column| column.get(row))
-                        .map(|v| Some(*v))
-                        .ok_or(Error::BoundsFailure)
-                }
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        // We only care about advice columns here
 
-                fn assign_advice<V, VR, A, AR>(
-                    &mut self,
-                    _: A,
-                    column: Column<Advice>,
-                    row: usize,
-                    to: V,
-                ) -> Result<(), Error>
-                where
-                    V: FnOnce() -> Result<VR, Error>,
-                    VR: Into<Assigned<F>>,
-                    A: FnOnce() -> AR,
-                    AR: Into<String>,
-                {
-                    if !self.usable_rows.contains(&row) {
-                        return Err(Error::not_enough_rows_available(self.k));
-                    }
+        Ok(())
+    }
 
-                    *self
-                        .advice
-                        .get_mut(column.index())
-                        .and_then(|v| v.get_mut(row))
-                        .ok_or(Error::BoundsFailure)? = to()?.into();
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Option<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
 
-                    Ok(())
-                }
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
 
-                fn assign_fixed<V, VR, A, AR>(
-                    &mut self,
-                    _: A,
-                    _: Column<Fixed>,
-                    _: usize,
-                    _: V,
-                ) -> Result<(), Error>
-                where
-                    V: FnOnce() -> Result<VR, Error>,
-                    VR: Into<Assigned<F>>,
-                    A: FnOnce() -> AR,
-                    AR: Into<String>,
-                {
-                    // We only care about advice columns here
-
-                    Ok(())
-                }
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
 
-                fn copy(
-                    &mut self,
-                    _: Column<Any>,
-                    _: usize,
-                    _: Column<Any>,
-                    _: usize,
-                ) -> Result<(), Error> {
-                    // We only care about advice columns here
+/// Commits to the instance columns of a circuit, and hashes the commitments into the
+/// transcript.
+fn commit_instance<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    instance: &[&[C::Scalar]],
+    transcript: &mut T,
+) -> Result<InstanceSingle<C>, Error> {
+    let domain = &pk.vk.domain;
+    let instance_values = instance
+        .iter()
+        .map(|values| {
+            let mut poly = domain.empty_lagrange();
+            assert_eq!(poly.len(), params.n as usize);
+            if values.len() > (poly.len() - (pk.vk.cs.blinding_factors() + 1)) {
+                return Err(Error::InstanceTooLarge);
+            }
+            for (poly, value) in poly.iter_mut().zip(values.iter()) {
+                *poly = *value;
+            }
+            Ok(poly)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let instance_commitments_projective: Vec<_> = instance_values
+        .iter()
+        .map(|poly| params.commit_lagrange(poly, Blind::default()))
+        .collect();
+    let mut instance_commitments = vec![C::identity(); instance_commitments_projective.len()];
+    C::Curve::batch_normalize(&instance_commitments_projective, &mut instance_commitments);
+    let instance_commitments = instance_commitments;
+    drop(instance_commitments_projective);
 
-                    Ok(())
-                }
+    for commitment in &instance_commitments {
+        transcript.common_point(*commitment)?;
+    }
 
-                fn fill_from_row(
-                    &mut self,
-                    _: Column<Fixed>,
-                    _: usize,
-                    _: Option<Assigned<F>>,
-                ) -> Result<(), Error> {
-                    Ok(())
-                }
+    let instance_polys: Vec<_> = instance_values
+        .iter()
+        .map(|poly| {
+            let lagrange_vec = domain.lagrange_from_vec(poly.to_vec());
+            domain.lagrange_to_coeff(lagrange_vec)
+        })
+        .collect();
 
-                fn push_namespace<NR, N>(&mut self, _: N)
-                where
-                    NR: Into<String>,
-                    N: FnOnce() -> NR,
-                {
-                    // Do nothing; we don't care about namespaces in this context.
-                }
+    Ok(InstanceSingle {
+        instance_values,
+        instance_polys,
+    })
+}
 
-                fn pop_namespace(&mut self, _: Option<String>) {
-                    // Do nothing; we don't care about namespaces in this context.
-                }
-            }
+/// Synthesizes the circuit with the given index in `group` to obtain its witness, then
+/// commits to its advice columns and hashes the commitments into the transcript.
+fn commit_advice<C: CurveAffine, E: EncodedChallenge<C>, R: RngCore, T: TranscriptWrite<C, E>>(
+    params: &Params<C>,
+    group: &ProofGroup<'_, C>,
+    index: usize,
+    instances: &[&[C::Scalar]],
+    rng: &mut R,
+    transcript: &mut T,
+    options: &mut ProverOptions<'_, C>,
+) -> Result<AdviceSingle<C>, Error> {
+    let domain = &group.pk.vk.domain;
+    let meta = &group.pk.vk.cs;
+    let unusable_rows_start = params.n as usize - (meta.blinding_factors() + 1);
+
+    let mut witness = WitnessCollection {
+        k: params.k,
+        advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
+        instances,
+        // The prover will not be allowed to assign values to advice
+        // cells that exist within inactive rows, which include some
+        // number of blinding factors and an extra row for use in the
+        // permutation argument.
+        usable_rows: ..unusable_rows_start,
+        _marker: std::marker::PhantomData,
+    };
+
+    // Synthesize the circuit to obtain the witness and other information.
+    let phase = options.start_phase(ProverPhase::WitnessSynthesis)?;
+    if let Some(dumps) = options.dumps.as_mut() {
+        let mut dump = WitnessDump::new(
+            params.k,
+            instances.iter().map(|column| column.to_vec()).collect(),
+        );
+        let result =
+            group
+                .circuits
+                .synthesize(index, &mut witness, meta.constants.clone(), Some(&mut dump));
+        dumps.push(dump);
+        result?;
+    } else {
+        group
+            .circuits
+            .synthesize(index, &mut witness, meta.constants.clone(), None)?;
+    }
+    options.finish_phase(phase);
 
-            let unusable_rows_start = params.n as usize - (meta.blinding_factors() + 1);
-
-            let mut witness = WitnessCollection {
-                k: params.k,
-                advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
-                instances,
-                // The prover will not be allowed to assign values to advice
-                // cells that exist within inactive rows, which include some
-                // number of blinding factors and an extra row for use in the
-                // permutation argument.
-                usable_rows: ..unusable_rows_start,
-                _marker: std::marker::PhantomData,
-            };
+    let phase = options.start_phase(ProverPhase::AdviceCommitments)?;
+    let mut advice = batch_invert_assigned(witness.advice);
 
-            // Synthesize the circuit to obtain the witness and other information.
-            ConcreteCircuit::FloorPlanner::synthesize(
-                &mut witness,
-                circuit,
-                config.clone(),
-                meta.constants.clone(),
-            )?;
+    // Add blinding factors to advice columns
+    for advice in &mut advice {
+        for cell in &mut advice[unusable_rows_start..] {
+            *cell = C::Scalar::random(&mut *rng);
+        }
+    }
 
-            let mut advice = batch_invert_assigned(witness.advice);
+    // Compute commitments to advice column polynomials
+    let advice_blinds: Vec<_> = advice
+        .iter()
+        .map(|_| Blind(C::Scalar::random(&mut *rng)))
+        .collect();
+    let advice_commitments_projective: Vec<_> = advice
+        .iter()
+        .zip(advice_blinds.iter())
+        .map(|(poly, blind)| params.commit_lagrange(poly, *blind))
+        .collect();
+    let mut advice_commitments = vec![C::identity(); advice_commitments_projective.len()];
+    C::Curve::batch_normalize(&advice_commitments_projective, &mut advice_commitments);
+    let advice_commitments = advice_commitments;
+    drop(advice_commitments_projective);
 
-            // Add blinding factors to advice columns
-            for advice in &mut advice {
-                for cell in &mut advice[unusable_rows_start..] {
-                    *cell = C::Scalar::random(&mut rng);
-                }
-            }
+    for commitment in &advice_commitments {
+        transcript.write_point(*commitment)?;
+    }
 
-            // Compute commitments to advice column polynomials
-            let advice_blinds: Vec<_> = advice
-                .iter()
-                .map(|_| Blind(C::Scalar::random(&mut rng)))
-                .collect();
-            let advice_commitments_projective: Vec<_> = advice
-                .iter()
-                .zip(advice_blinds.iter())
-                .map(|(poly, blind)| params.commit_lagrange(poly, *blind))
-                .collect();
-            let mut advice_commitments = vec![C::identity(); advice_commitments_projective.len()];
-            C::Curve::batch_normalize(&advice_commitments_projective, &mut advice_commitments);
-            let advice_commitments = advice_commitments;
-            drop(advice_commitments_projective);
+    let advice_polys: Vec<_> = advice
+        .clone()
+        .into_iter()
+        .map(|poly| domain.lagrange_to_coeff(poly))
+        .collect();
+    options.finish_phase(phase);
 
-            for commitment in &advice_commitments {
-                transcript.write_point(*commitment)?;
+    Ok(AdviceSingle {
+        advice_values: advice,
+        advice_polys,
+        advice_blinds,
+    })
+}
+
+fn create_proof_inner<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    R: RngCore,
+    T: TranscriptWrite<C, E>,
+>(
+    params: &Params<C>,
+    groups: &[ProofGroup<'_, C>],
+    mut rng: R,
+    transcript: &mut T,
+    mut options: ProverOptions<'_, C>,
+) -> Result<(), Error> {
+    enter_span!(
+        "create_proof",
+        circuits = groups
+            .iter()
+            .map(|group| group.circuits.num_circuits())
+            .sum::<usize>()
+    );
+    if options.strategy.parts_per_chunk() == Some(0) {
+        return Err(Error::InvalidQuotientStrategy);
+    }
+    for group in groups {
+        let key_k = group.pk.vk.get_domain().k();
+        if key_k != params.k {
+            return Err(Error::KeyParamsMismatch {
+                params_k: params.k,
+                key_k,
+            });
+        }
+        for instance in group.instances.iter() {
+            if instance.len() != group.pk.vk.cs.num_instance_columns {
+                return Err(Error::InvalidInstances);
             }
+        }
+    }
 
-            let advice_polys: Vec<_> = advice
-                .clone()
-                .into_iter()
-                .map(|poly| domain.lagrange_to_coeff(poly))
-                .collect();
+    // Each group is proven in the same way as a single circuit, with the groups taking
+    // turns at each step, so that they share the challenges and the transcript.
+    let instance: Vec<Vec<InstanceSingle<C>>> = groups
+        .iter()
+        .map(|group| -> Result<Vec<_>, Error> {
+            // Hash verification key into transcript
+            group.pk.vk.hash_into(transcript)?;
 
-            let advice_cosets: Vec<_> = advice_polys
+            group
+                .instances
                 .iter()
-                .map(|poly| domain.coeff_to_extended(poly.clone()))
-                .collect();
+                .map(|instance| commit_instance(params, group.pk, instance, transcript))
+                .collect()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-            Ok(AdviceSingle {
-                advice_values: advice,
-                advice_polys,
-                advice_cosets,
-                advice_blinds,
-            })
+    let advice: Vec<Vec<AdviceSingle<C>>> = groups
+        .iter()
+        .map(|group| -> Result<Vec<_>, Error> {
+            (0..group.circuits.num_circuits())
+                .zip(group.instances.iter())
+                .map(|(index, instances)| {
+                    commit_advice(
+                        params,
+                        group,
+                        index,
+                        instances,
+                        &mut rng,
+                        transcript,
+                        &mut options,
+                    )
+                })
+                .collect()
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    // Create polynomial evaluator context for values.
-    let mut value_evaluator = poly::new_evaluator(|| {});
+    // The proving key's precomputed cosets are used if we are evaluating over the whole
+    // extended domain, and it has them.
+    let use_precomputed_cosets = options.strategy == QuotientStrategy::Full;
+    let parts_per_chunk = options.strategy.parts_per_chunk();
 
-    // Register fixed values with the polynomial evaluator.
-    let fixed_values: Vec<_> = pk
-        .fixed_values
+    let mut evaluators: Vec<_> = groups
         .iter()
-        .map(|poly| value_evaluator.register_poly(poly.clone()))
-        .collect();
+        .zip(instance.iter())
+        .zip(advice.iter())
+        .map(|((group, instance), advice)| {
+            let pk = group.pk;
+            let domain = &pk.vk.domain;
 
-    // Register advice values with the polynomial evaluator.
-    let advice_values: Vec<_> = advice
-        .iter()
-        .map(|advice| {
-            advice
-                .advice_values
+            // Create polynomial evaluator context for values.
+            let mut value_evaluator = poly::new_evaluator(|| {});
+
+            // Register fixed values with the polynomial evaluator.
+            let fixed_values: Vec<_> = pk
+                .fixed_values
                 .iter()
                 .map(|poly| value_evaluator.register_poly(poly.clone()))
-                .collect::<Vec<_>>()
-        })
-        .collect();
+                .collect();
 
-    // Register instance values with the polynomial evaluator.
-    let instance_values: Vec<_> = instance
-        .iter()
-        .map(|instance| {
-            instance
-                .instance_values
+            // Register advice values with the polynomial evaluator.
+            let advice_values: Vec<_> = advice
                 .iter()
-                .map(|poly| value_evaluator.register_poly(poly.clone()))
-                .collect::<Vec<_>>()
-        })
-        .collect();
+                .map(|advice| {
+                    advice
+                        .advice_values
+                        .iter()
+                        .map(|poly| value_evaluator.register_poly(poly.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
 
-    // Create polynomial evaluator context for cosets.
-    let mut coset_evaluator = poly::new_evaluator(|| {});
+            // Register instance values with the polynomial evaluator.
+            let instance_values: Vec<_> = instance
+                .iter()
+                .map(|instance| {
+                    instance
+                        .instance_values
+                        .iter()
+                        .map(|poly| value_evaluator.register_poly(poly.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
 
-    // Register fixed cosets with the polynomial evaluator.
-    let fixed_cosets: Vec<_> = pk
-        .fixed_cosets
-        .iter()
-        .map(|poly| coset_evaluator.register_poly(poly.clone()))
-        .collect();
+            // Create polynomial evaluator context for cosets. Polynomials registered in
+            // coefficient form are either transformed into the extended domain now, or
+            // kept in coefficient form and evaluated over parts of it on demand,
+            // depending on the strategy.
+            let mut coset_evaluator =
+                poly::new_evaluator(|| {}).with_parts_per_chunk(parts_per_chunk);
+
+            // Register fixed cosets with the polynomial evaluator.
+            let fixed_cosets: Vec<_> = if use_precomputed_cosets && !pk.fixed_cosets.is_empty() {
+                pk.fixed_cosets
+                    .iter()
+                    .map(|poly| coset_evaluator.register_poly(poly.clone()))
+                    .collect()
+            } else {
+                pk.fixed_polys
+                    .iter()
+                    .map(|poly| coset_evaluator.register_coeff_poly(domain, poly.clone()))
+                    .collect()
+            };
 
-    // Register advice cosets with the polynomial evaluator.
-    let advice_cosets: Vec<_> = advice
-        .iter()
-        .map(|advice| {
-            advice
-                .advice_cosets
+            // Register advice cosets with the polynomial evaluator.
+            let advice_cosets: Vec<_> = advice
                 .iter()
-                .map(|poly| coset_evaluator.register_poly(poly.clone()))
-                .collect::<Vec<_>>()
-        })
-        .collect();
+                .map(|advice| {
+                    advice
+                        .advice_polys
+                        .iter()
+                        .map(|poly| coset_evaluator.register_coeff_poly(domain, poly.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
 
-    // Register instance cosets with the polynomial evaluator.
-    let instance_cosets: Vec<_> = instance
-        .iter()
-        .map(|instance| {
-            instance
-                .instance_cosets
+            // Register instance cosets with the polynomial evaluator.
+            let instance_cosets: Vec<_> = instance
                 .iter()
-                .map(|poly| coset_evaluator.register_poly(poly.clone()))
-                .collect::<Vec<_>>()
-        })
-        .collect();
+                .map(|instance| {
+                    instance
+                        .instance_polys
+                        .iter()
+                        .map(|poly| coset_evaluator.register_coeff_poly(domain, poly.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
 
-    // Register permutation cosets with the polynomial evaluator.
-    let permutation_cosets: Vec<_> = pk
-        .permutation
-        .cosets
-        .iter()
-        .map(|poly| coset_evaluator.register_poly(poly.clone()))
+            // Register permutation cosets with the polynomial evaluator.
+            let permutation_cosets: Vec<_> =
+                if use_precomputed_cosets && !pk.permutation.cosets.is_empty() {
+                    pk.permutation
+                        .cosets
+                        .iter()
+                        .map(|poly| coset_evaluator.register_poly(poly.clone()))
+                        .collect()
+                } else {
+                    pk.permutation
+                        .polys
+                        .iter()
+                        .map(|poly| coset_evaluator.register_coeff_poly(domain, poly.clone()))
+                        .collect()
+                };
+
+            // Register boundary polynomials used in the lookup and permutation arguments.
+            let (l0, l_blind, l_last) = if use_precomputed_cosets && !pk.l_cosets.is_empty() {
+                (
+                    coset_evaluator.register_poly(pk.l_cosets[0].clone()),
+                    coset_evaluator.register_poly(pk.l_cosets[1].clone()),
+                    coset_evaluator.register_poly(pk.l_cosets[2].clone()),
+                )
+            } else {
+                (
+                    coset_evaluator.register_coeff_poly(domain, pk.l0.clone()),
+                    coset_evaluator.register_coeff_poly(domain, pk.l_blind.clone()),
+                    coset_evaluator.register_coeff_poly(domain, pk.l_last.clone()),
+                )
+            };
+
+            GroupEvaluators {
+                value_evaluator,
+                fixed_values,
+                advice_values,
+                instance_values,
+                coset_evaluator,
+                fixed_cosets,
+                advice_cosets,
+                instance_cosets,
+                permutation_cosets,
+                l0,
+                l_blind,
+                l_last,
+            }
+        })
         .collect();
 
-    // Register boundary polynomials used in the lookup and permutation arguments.
-    let l0 = coset_evaluator.register_poly(pk.l0.clone());
-    let l_blind = coset_evaluator.register_poly(pk.l_blind.clone());
-    let l_last = coset_evaluator.register_poly(pk.l_last.clone());
+    let phase = options.start_phase(ProverPhase::LookupPermutation)?;
 
     // Sample theta challenge for keeping lookup columns linearly independent
     let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
 
-    let lookups: Vec<Vec<lookup::prover::Permuted<C, _>>> = instance_values
+    let lookups: Vec<Vec<Vec<lookup::prover::Permuted<C, _>>>> = groups
         .iter()
-        .zip(instance_cosets.iter())
-        .zip(advice_values.iter())
-        .zip(advice_cosets.iter())
-        .map(|(((instance_values, instance_cosets), advice_values), advice_cosets)| -> Result<Vec<_>, Error> {
-            // Construct and commit to permuted values for each lookup
-            pk.vk
-                .cs
-                .lookups
+        .zip(evaluators.iter_mut())
+        .map(|(group, evaluators)| -> Result<Vec<_>, Error> {
+            let pk = group.pk;
+            let GroupEvaluators {
+                ref value_evaluator,
+                ref fixed_values,
+                ref advice_values,
+                ref instance_values,
+                ref mut coset_evaluator,
+                ref fixed_cosets,
+                ref advice_cosets,
+                ref instance_cosets,
+                ..
+            } = *evaluators;
+
+            instance_values
                 .iter()
-                .map(|lookup| {
-                    lookup.commit_permuted(
-                        pk,
-                        params,
-                        domain,
-                        &value_evaluator,
-                        &mut coset_evaluator,
-                        theta,
-                        advice_values,
-                        &fixed_values,
-                        instance_values,
-                        advice_cosets,
-                        &fixed_cosets,
-                        instance_cosets,
-                        &mut rng,
-                        transcript,
-                    )
-                })
+                .zip(instance_cosets.iter())
+                .zip(advice_values.iter())
+                .zip(advice_cosets.iter())
+                .map(
+                    |(((instance_values, instance_cosets), advice_values), advice_cosets)| {
+                        // Construct and commit to permuted values for each lookup
+                        pk.vk
+                            .cs
+                            .lookups
+                            .iter()
+                            .map(|lookup| {
+                                lookup.commit_permuted(
+                                    pk,
+                                    params,
+                                    &pk.vk.domain,
+                                    value_evaluator,
+                                    coset_evaluator,
+                                    theta,
+                                    advice_values,
+                                    fixed_values,
+                                    instance_values,
+                                    advice_cosets,
+                                    fixed_cosets,
+                                    instance_cosets,
+                                    &mut rng,
+                                    transcript,
+                                )
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    },
+                )
                 .collect()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    options.finish_phase(phase);
+
+    let phase = options.start_phase(ProverPhase::PermutationProducts)?;
 
     // Sample beta challenge
     let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
@@ -455,261 +955,349 @@ pub fn create_proof<
     let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
 
     // Commit to permutations.
-    let permutations: Vec<permutation::prover::Committed<C, _>> = instance
+    let permutations: Vec<Vec<permutation::prover::Committed<C, _>>> = groups
         .iter()
+        .zip(instance.iter())
         .zip(advice.iter())
-        .map(|(instance, advice)| {
-            pk.vk.cs.permutation.commit(
-                params,
-                pk,
-                &pk.permutation,
-                &advice.advice_values,
-                &pk.fixed_values,
-                &instance.instance_values,
-                beta,
-                gamma,
-                &mut coset_evaluator,
-                &mut rng,
-                transcript,
-            )
-        })
+        .zip(evaluators.iter_mut())
+        .map(
+            |(((group, instance), advice), evaluators)| -> Result<Vec<_>, Error> {
+                let pk = group.pk;
+                instance
+                    .iter()
+                    .zip(advice.iter())
+                    .map(|(instance, advice)| {
+                        pk.vk.cs.permutation.commit(
+                            params,
+                            pk,
+                            &pk.permutation,
+                            &advice.advice_values,
+                            &pk.fixed_values,
+                            &instance.instance_values,
+                            beta,
+                            gamma,
+                            &mut evaluators.coset_evaluator,
+                            &mut rng,
+                            transcript,
+                        )
+                    })
+                    .collect()
+            },
+        )
         .collect::<Result<Vec<_>, _>>()?;
+    options.finish_phase(phase);
 
-    let lookups: Vec<Vec<lookup::prover::Committed<C, _>>> = lookups
-        .into_iter()
-        .map(|lookups| -> Result<Vec<_>, _> {
-            // Construct and commit to products for each lookup
+    let phase = options.start_phase(ProverPhase::LookupProducts)?;
+    let lookups: Vec<Vec<Vec<lookup::prover::Committed<C, _>>>> = groups
+        .iter()
+        .zip(lookups.into_iter())
+        .zip(evaluators.iter_mut())
+        .map(|((group, lookups), evaluators)| -> Result<Vec<_>, Error> {
             lookups
                 .into_iter()
-                .map(|lookup| {
-                    lookup.commit_product(
-                        pk,
-                        params,
-                        theta,
-                        beta,
-                        gamma,
-                        &mut coset_evaluator,
-                        &mut rng,
-                        transcript,
-                    )
+                .map(|lookups| -> Result<Vec<_>, _> {
+                    // Construct and commit to products for each lookup
+                    lookups
+                        .into_iter()
+                        .map(|lookup| {
+                            lookup.commit_product(
+                                group.pk,
+                                params,
+                                theta,
+                                beta,
+                                gamma,
+                                &mut evaluators.coset_evaluator,
+                                &mut rng,
+                                transcript,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()
                 })
-                .collect::<Result<Vec<_>, _>>()
+                .collect()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    options.finish_phase(phase);
 
-    // Commit to the vanishing argument's random polynomial for blinding h(x_3)
-    let vanishing = vanishing::Argument::commit(params, domain, &mut rng, transcript)?;
+    let phase = options.start_phase(ProverPhase::Quotient)?;
+
+    // Commit to each group's vanishing argument's random polynomial for blinding h(x_3)
+    let vanishing = groups
+        .iter()
+        .map(|group| vanishing::Argument::commit(params, &group.pk.vk.domain, &mut rng, transcript))
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Obtain challenge for keeping all separate gates linearly independent
     let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
 
-    // Evaluate the h(X) polynomial's constraint system expressions for the permutation constraints.
-    let (permutations, permutation_expressions): (Vec<_>, Vec<_>) = permutations
-        .into_iter()
-        .zip(advice_cosets.iter())
-        .zip(instance_cosets.iter())
-        .map(|((permutation, advice), instance)| {
-            permutation.construct(
-                pk,
-                &pk.vk.cs.permutation,
-                advice,
-                &fixed_cosets,
-                instance,
-                &permutation_cosets,
-                l0,
-                l_blind,
-                l_last,
-                beta,
-                gamma,
-            )
-        })
-        .unzip();
-
-    let (lookups, lookup_expressions): (Vec<Vec<_>>, Vec<Vec<_>>) = lookups
-        .into_iter()
-        .map(|lookups| {
-            // Evaluate the h(X) polynomial's constraint system expressions for the lookup constraints, if any.
-            lookups
-                .into_iter()
-                .map(|p| p.construct(theta, beta, gamma, l0, l_blind, l_last))
-                .unzip()
-        })
-        .unzip();
-
-    let expressions = advice_cosets
+    // Each group has its own h(X) polynomial, because the groups' constraint systems
+    // have different degrees and so different extended domains.
+    let mut constructed = Vec::with_capacity(groups.len());
+    for ((((group, vanishing), evaluators), permutations), lookups) in groups
         .iter()
-        .zip(instance_cosets.iter())
-        .zip(permutation_expressions.into_iter())
-        .zip(lookup_expressions.into_iter())
-        .flat_map(
-            |(((advice_cosets, instance_cosets), permutation_expressions), lookup_expressions)| {
-                let fixed_cosets = &fixed_cosets;
-                iter::empty()
-                    // Custom constraints
-                    .chain(meta.gates.iter().flat_map(move |gate| {
-                        gate.polynomials().iter().map(move |expr| {
-                            expr.evaluate(
-                                &poly::Ast::ConstantTerm,
-                                &|_| panic!("virtual selectors are removed during optimization"),
-                                &|_, column_index, rotation| {
-                                    fixed_cosets[column_index].with_rotation(rotation).into()
-                                },
-                                &|_, column_index, rotation| {
-                                    advice_cosets[column_index].with_rotation(rotation).into()
-                                },
-                                &|_, column_index, rotation| {
-                                    instance_cosets[column_index].with_rotation(rotation).into()
-                                },
-                                &|a| -a,
-                                &|a, b| a + b,
-                                &|a, b| a * b,
-                                &|a, scalar| a * scalar,
-                            )
-                        })
-                    }))
-                    // Permutation constraints, if any.
-                    .chain(permutation_expressions.into_iter())
-                    // Lookup constraints, if any.
-                    .chain(lookup_expressions.into_iter().flatten())
-            },
-        );
-
-    // Construct the vanishing argument's h(X) commitments
-    let vanishing = vanishing.construct(
-        params,
-        domain,
-        coset_evaluator,
-        expressions,
-        y,
-        &mut rng,
-        transcript,
-    )?;
-
-    let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
-    let xn = x.pow(&[params.n as u64, 0, 0, 0]);
-
-    // Compute and hash instance evals for each circuit instance
-    for instance in instance.iter() {
-        // Evaluate polynomials at omega^i x
-        let instance_evals: Vec<_> = meta
-            .instance_queries
-            .iter()
-            .map(|&(column, at)| {
-                eval_polynomial(
-                    &instance.instance_polys[column.index()],
-                    domain.rotate_omega(*x, at),
+        .zip(vanishing.into_iter())
+        .zip(evaluators.into_iter())
+        .zip(permutations.into_iter())
+        .zip(lookups.into_iter())
+    {
+        let pk = group.pk;
+        let domain = &pk.vk.domain;
+        let GroupEvaluators {
+            coset_evaluator,
+            fixed_cosets,
+            advice_cosets,
+            instance_cosets,
+            permutation_cosets,
+            l0,
+            l_blind,
+            l_last,
+            ..
+        } = evaluators;
+
+        // Evaluate the h(X) polynomial's constraint system expressions for the permutation
+        // constraints.
+        let (permutations, permutation_expressions): (Vec<_>, Vec<_>) = permutations
+            .into_iter()
+            .zip(advice_cosets.iter())
+            .zip(instance_cosets.iter())
+            .map(|((permutation, advice), instance)| {
+                permutation.construct(
+                    pk,
+                    &pk.vk.cs.permutation,
+                    advice,
+                    &fixed_cosets,
+                    instance,
+                    &permutation_cosets,
+                    l0,
+                    l_blind,
+                    l_last,
+                    beta,
+                    gamma,
                 )
             })
-            .collect();
-
-        // Hash each instance column evaluation
-        for eval in instance_evals.iter() {
-            transcript.write_scalar(*eval)?;
-        }
-    }
-
-    // Compute and hash advice evals for each circuit instance
-    for advice in advice.iter() {
-        // Evaluate polynomials at omega^i x
-        let advice_evals: Vec<_> = meta
-            .advice_queries
-            .iter()
-            .map(|&(column, at)| {
-                eval_polynomial(
-                    &advice.advice_polys[column.index()],
-                    domain.rotate_omega(*x, at),
-                )
+            .unzip();
+
+        let (lookups, lookup_expressions): (Vec<Vec<_>>, Vec<Vec<_>>) = lookups
+            .into_iter()
+            .map(|lookups| {
+                // Evaluate the h(X) polynomial's constraint system expressions for the lookup
+                // constraints, if any.
+                lookups
+                    .into_iter()
+                    .map(|p| p.construct(theta, beta, gamma, l0, l_blind, l_last))
+                    .unzip()
             })
-            .collect();
+            .unzip();
 
-        // Hash each advice column evaluation
-        for eval in advice_evals.iter() {
-            transcript.write_scalar(*eval)?;
-        }
-    }
-
-    // Compute and hash fixed evals (shared across all circuit instances)
-    let fixed_evals: Vec<_> = meta
-        .fixed_queries
-        .iter()
-        .map(|&(column, at)| {
-            eval_polynomial(&pk.fixed_polys[column.index()], domain.rotate_omega(*x, at))
-        })
-        .collect();
-
-    // Hash each fixed column evaluation
-    for eval in fixed_evals.iter() {
-        transcript.write_scalar(*eval)?;
+        let expressions = advice_cosets
+            .iter()
+            .zip(instance_cosets.iter())
+            .zip(permutation_expressions.into_iter())
+            .zip(lookup_expressions.into_iter())
+            .flat_map(
+                |(
+                    ((advice_cosets, instance_cosets), permutation_expressions),
+                    lookup_expressions,
+                )| {
+                    let fixed_cosets = &fixed_cosets;
+                    iter::empty()
+                        // Custom constraints
+                        .chain(pk.vk.cs.gates.iter().flat_map(move |gate| {
+                            gate.polynomials().iter().map(move |expr| {
+                                expr.evaluate(
+                                    &poly::Ast::ConstantTerm,
+                                    &|_| {
+                                        panic!("virtual selectors are removed during optimization")
+                                    },
+                                    &|_, column_index, rotation| {
+                                        fixed_cosets[column_index].with_rotation(rotation).into()
+                                    },
+                                    &|_, column_index, rotation| {
+                                        advice_cosets[column_index].with_rotation(rotation).into()
+                                    },
+                                    &|_, column_index, rotation| {
+                                        instance_cosets[column_index].with_rotation(rotation).into()
+                                    },
+                                    &|a| -a,
+                                    &|a, b| a + b,
+                                    &|a, b| a * b,
+                                    &|a, scalar| a * scalar,
+                                )
+                            })
+                        }))
+                        // Permutation constraints, if any.
+                        .chain(permutation_expressions.into_iter())
+                        // Lookup constraints, if any.
+                        .chain(lookup_expressions.into_iter().flatten())
+                },
+            );
+
+        // Construct the vanishing argument's h(X) commitments
+        let vanishing = vanishing.construct(
+            params,
+            domain,
+            coset_evaluator,
+            expressions,
+            y,
+            &mut rng,
+            transcript,
+        )?;
+
+        constructed.push((vanishing, permutations, lookups));
     }
+    options.finish_phase(phase);
 
-    let vanishing = vanishing.evaluate(x, xn, domain, transcript)?;
-
-    // Evaluate common permutation data
-    pk.permutation.evaluate(x, transcript)?;
-
-    // Evaluate the permutations, if any, at omega^i x.
-    let permutations: Vec<permutation::prover::Evaluated<C>> = permutations
-        .into_iter()
-        .map(|permutation| -> Result<_, _> { permutation.evaluate(pk, x, transcript) })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    // Evaluate the lookups, if any, at omega^i x.
-    let lookups: Vec<Vec<lookup::prover::Evaluated<C>>> = lookups
-        .into_iter()
-        .map(|lookups| -> Result<Vec<_>, _> {
-            lookups
-                .into_iter()
-                .map(|p| p.evaluate(pk, x, transcript))
-                .collect::<Result<Vec<_>, _>>()
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+    let phase = options.start_phase(ProverPhase::Evaluations)?;
+    let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
+    let xn = x.pow(&[params.n as u64, 0, 0, 0]);
 
-    let instances = instance
+    let evaluated = groups
         .iter()
+        .zip(instance.iter())
         .zip(advice.iter())
-        .zip(permutations.iter())
-        .zip(lookups.iter())
-        .flat_map(|(((instance, advice), permutation), lookups)| {
-            iter::empty()
-                .chain(
-                    pk.vk
-                        .cs
+        .zip(constructed.into_iter())
+        .map(
+            |(((group, instance), advice), constructed)| -> Result<_, Error> {
+                let (vanishing, permutations, lookups) = constructed;
+                let pk = group.pk;
+                let domain = &pk.vk.domain;
+                let meta = &pk.vk.cs;
+
+                // Compute and hash instance evals for each circuit instance
+                for instance in instance.iter() {
+                    // Evaluate polynomials at omega^i x
+                    let instance_evals: Vec<_> = meta
                         .instance_queries
                         .iter()
-                        .map(move |&(column, at)| ProverQuery {
-                            point: domain.rotate_omega(*x, at),
-                            poly: &instance.instance_polys[column.index()],
-                            blind: Blind::default(),
-                        }),
-                )
-                .chain(
-                    pk.vk
-                        .cs
+                        .map(|&(column, at)| {
+                            eval_polynomial(
+                                &instance.instance_polys[column.index()],
+                                domain.rotate_omega(*x, at),
+                            )
+                        })
+                        .collect();
+
+                    // Hash each instance column evaluation
+                    for eval in instance_evals.iter() {
+                        transcript.write_scalar(*eval)?;
+                    }
+                }
+
+                // Compute and hash advice evals for each circuit instance
+                for advice in advice.iter() {
+                    // Evaluate polynomials at omega^i x
+                    let advice_evals: Vec<_> = meta
                         .advice_queries
                         .iter()
-                        .map(move |&(column, at)| ProverQuery {
-                            point: domain.rotate_omega(*x, at),
-                            poly: &advice.advice_polys[column.index()],
-                            blind: advice.advice_blinds[column.index()],
-                        }),
-                )
-                .chain(permutation.open(pk, x))
-                .chain(lookups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
-        })
-        .chain(
-            pk.vk
-                .cs
-                .fixed_queries
-                .iter()
-                .map(|&(column, at)| ProverQuery {
-                    point: domain.rotate_omega(*x, at),
-                    poly: &pk.fixed_polys[column.index()],
-                    blind: Blind::default(),
-                }),
+                        .map(|&(column, at)| {
+                            eval_polynomial(
+                                &advice.advice_polys[column.index()],
+                                domain.rotate_omega(*x, at),
+                            )
+                        })
+                        .collect();
+
+                    // Hash each advice column evaluation
+                    for eval in advice_evals.iter() {
+                        transcript.write_scalar(*eval)?;
+                    }
+                }
+
+                // Compute and hash fixed evals (shared across all circuit instances)
+                let fixed_evals: Vec<_> = meta
+                    .fixed_queries
+                    .iter()
+                    .map(|&(column, at)| {
+                        eval_polynomial(
+                            &pk.fixed_polys[column.index()],
+                            domain.rotate_omega(*x, at),
+                        )
+                    })
+                    .collect();
+
+                // Hash each fixed column evaluation
+                for eval in fixed_evals.iter() {
+                    transcript.write_scalar(*eval)?;
+                }
+
+                let vanishing = vanishing.evaluate(x, xn, domain, transcript)?;
+
+                // Evaluate common permutation data
+                pk.permutation.evaluate(x, transcript)?;
+
+                // Evaluate the permutations, if any, at omega^i x.
+                let permutations: Vec<permutation::prover::Evaluated<C>> = permutations
+                    .into_iter()
+                    .map(|permutation| -> Result<_, _> { permutation.evaluate(pk, x, transcript) })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // Evaluate the lookups, if any, at omega^i x.
+                let lookups: Vec<Vec<lookup::prover::Evaluated<C>>> = lookups
+                    .into_iter()
+                    .map(|lookups| -> Result<Vec<_>, _> {
+                        lookups
+                            .into_iter()
+                            .map(|p| p.evaluate(pk, x, transcript))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok((vanishing, permutations, lookups))
+            },
         )
-        .chain(pk.permutation.open(x))
-        // We query the h(X) polynomial at x
-        .chain(vanishing.open(x));
+        .collect::<Result<Vec<_>, _>>()?;
+    options.finish_phase(phase);
+
+    let phase = options.start_phase(ProverPhase::Multiopen)?;
 
-    multiopen::create_proof(params, rng, transcript, instances).map_err(|_| Error::Opening)
+    let instances =
+        groups
+            .iter()
+            .zip(instance.iter())
+            .zip(advice.iter())
+            .zip(evaluated.iter())
+            .flat_map(
+                |(((group, instance), advice), (vanishing, permutations, lookups))| {
+                    let pk = group.pk;
+                    let domain = &pk.vk.domain;
+                    instance
+                        .iter()
+                        .zip(advice.iter())
+                        .zip(permutations.iter())
+                        .zip(lookups.iter())
+                        .flat_map(move |(((instance, advice), permutation), lookups)| {
+                            iter::empty()
+                                .chain(pk.vk.cs.instance_queries.iter().map(
+                                    move |&(column, at)| ProverQuery {
+                                        point: domain.rotate_omega(*x, at),
+                                        poly: &instance.instance_polys[column.index()],
+                                        blind: Blind::default(),
+                                    },
+                                ))
+                                .chain(pk.vk.cs.advice_queries.iter().map(move |&(column, at)| {
+                                    ProverQuery {
+                                        point: domain.rotate_omega(*x, at),
+                                        poly: &advice.advice_polys[column.index()],
+                                        blind: advice.advice_blinds[column.index()],
+                                    }
+                                }))
+                                .chain(permutation.open(pk, x))
+                                .chain(lookups.iter().flat_map(move |p| p.open(pk, x)).into_iter())
+                        })
+                        .chain(pk.vk.cs.fixed_queries.iter().map(move |&(column, at)| {
+                            ProverQuery {
+                                point: domain.rotate_omega(*x, at),
+                                poly: &pk.fixed_polys[column.index()],
+                                blind: Blind::default(),
+                            }
+                        }))
+                        .chain(pk.permutation.open(x))
+                        // We query the h(X) polynomial at x
+                        .chain(vanishing.open(x))
+                },
+            );
+
+    multiopen::create_proof(params, rng, transcript, instances).map_err(|_| Error::Opening)?;
+    options.finish_phase(phase);
+
+    Ok(())
 }
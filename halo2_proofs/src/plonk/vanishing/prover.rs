@@ -41,6 +41,7 @@ impl<C: CurveAffine> Argument<C> {
         mut rng: R,
         transcript: &mut T,
     ) -> Result<Committed<C>, Error> {
+        enter_span!("vanishing_commit");
         // Sample a random polynomial of degree n - 1
         let mut random_poly = domain.empty_coeff();
         for coeff in random_poly.iter_mut() {
@@ -76,14 +77,13 @@ impl<C: CurveAffine> Committed<C> {
         mut rng: R,
         transcript: &mut T,
     ) -> Result<Constructed<C>, Error> {
+        enter_span!("vanishing_construct");
         // Evaluate the h(X) polynomial's constraint system expressions for the constraints provided
         let h_poly = expressions
             .reduce(|h_poly, v| &(&h_poly * *y) + &v) // Fold the gates together with the y challenge
             .unwrap_or_else(|| poly::Ast::ConstantTerm(C::Scalar::zero()));
-        let h_poly = evaluator.evaluate(&h_poly, domain); // Evaluate the h(X) polynomial
-
-        // Divide by t(X) = X^{params.n} - 1.
-        let h_poly = domain.divide_by_vanishing_poly(h_poly);
+        // Evaluate the h(X) polynomial, and divide by t(X) = X^{params.n} - 1.
+        let h_poly = evaluator.evaluate_quotient(&h_poly, domain);
 
         // Obtain final h(X) polynomial
         let h_poly = domain.extended_to_coeff(h_poly);
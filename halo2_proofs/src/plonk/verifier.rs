@@ -119,61 +119,110 @@ pub fn verify_proof<
     instances: &[&[&[C::Scalar]]],
     transcript: &mut T,
 ) -> Result<V::Output, Error> {
-    // Check that instances matches the expected number of instance columns
-    for instances in instances.iter() {
-        if instances.len() != vk.cs.num_instance_columns {
-            return Err(Error::InvalidInstances);
+    verify_multi_proof(params, &[(vk, instances)], strategy, transcript)
+}
+
+/// Verifies a proof created by [`create_multi_proof`], given the verifying key and
+/// instances of each of its groups of circuits, in the order in which they were proven.
+///
+/// [`create_multi_proof`]: crate::plonk::create_multi_proof
+pub fn verify_multi_proof<
+    'params,
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptRead<C, E>,
+    V: VerificationStrategy<'params, C>,
+>(
+    params: &'params Params<C>,
+    groups: &[(&VerifyingKey<C>, &[&[&[C::Scalar]]])],
+    strategy: V,
+    transcript: &mut T,
+) -> Result<V::Output, Error> {
+    enter_span!("verify_proof");
+    // Check that each key matches the parameters, and that instances matches the
+    // expected number of instance columns
+    for &(vk, instances) in groups.iter() {
+        let key_k = vk.get_domain().k();
+        if key_k != params.k {
+            return Err(Error::KeyParamsMismatch {
+                params_k: params.k,
+                key_k,
+            });
+        }
+        for instances in instances.iter() {
+            if instances.len() != vk.cs.num_instance_columns {
+                return Err(Error::InvalidInstances);
+            }
         }
     }
 
-    let instance_commitments = instances
+    let instance_commitments = groups
         .iter()
-        .map(|instance| {
-            instance
+        .map(|&(vk, instances)| {
+            instances
                 .iter()
                 .map(|instance| {
-                    if instance.len() > params.n as usize - (vk.cs.blinding_factors() + 1) {
-                        return Err(Error::InstanceTooLarge);
-                    }
-                    let mut poly = instance.to_vec();
-                    poly.resize(params.n as usize, C::Scalar::zero());
-                    let poly = vk.domain.lagrange_from_vec(poly);
-
-                    Ok(params.commit_lagrange(&poly, Blind::default()).to_affine())
+                    instance
+                        .iter()
+                        .map(|instance| {
+                            if instance.len() > params.n as usize - (vk.cs.blinding_factors() + 1) {
+                                return Err(Error::InstanceTooLarge);
+                            }
+                            let mut poly = instance.to_vec();
+                            poly.resize(params.n as usize, C::Scalar::zero());
+                            let poly = vk.domain.lagrange_from_vec(poly);
+
+                            Ok(params.commit_lagrange(&poly, Blind::default()).to_affine())
+                        })
+                        .collect::<Result<Vec<_>, _>>()
                 })
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let num_proofs = instance_commitments.len();
-
-    // Hash verification key into transcript
-    vk.hash_into(transcript)?;
-
-    for instance_commitments in instance_commitments.iter() {
-        // Hash the instance (external) commitments into the transcript
-        for commitment in instance_commitments {
-            transcript.common_point(*commitment)?
+    // Each group is verified in the same way as a single circuit, with the groups
+    // taking turns at each step, as they did when the proof was created.
+    for (&(vk, _), instance_commitments) in groups.iter().zip(instance_commitments.iter()) {
+        // Hash verification key into transcript
+        vk.hash_into(transcript)?;
+
+        for instance_commitments in instance_commitments.iter() {
+            // Hash the instance (external) commitments into the transcript
+            for commitment in instance_commitments {
+                transcript.common_point(*commitment)?
+            }
         }
     }
 
-    let advice_commitments = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> {
-            // Hash the prover's advice commitments into the transcript
-            read_n_points(transcript, vk.cs.num_advice_columns)
+    let advice_commitments = groups
+        .iter()
+        .zip(instance_commitments.iter())
+        .map(|(&(vk, _), instance_commitments)| {
+            (0..instance_commitments.len())
+                .map(|_| -> Result<Vec<_>, _> {
+                    // Hash the prover's advice commitments into the transcript
+                    read_n_points(transcript, vk.cs.num_advice_columns)
+                })
+                .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     // Sample theta challenge for keeping lookup columns linearly independent
     let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
 
-    let lookups_permuted = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> {
-            // Hash each lookup permuted commitment
-            vk.cs
-                .lookups
-                .iter()
-                .map(|argument| argument.read_permuted_commitments(transcript))
+    let lookups_permuted = groups
+        .iter()
+        .zip(instance_commitments.iter())
+        .map(|(&(vk, _), instance_commitments)| {
+            (0..instance_commitments.len())
+                .map(|_| -> Result<Vec<_>, _> {
+                    // Hash each lookup permuted commitment
+                    vk.cs
+                        .lookups
+                        .iter()
+                        .map(|argument| argument.read_permuted_commitments(transcript))
+                        .collect::<Result<Vec<_>, _>>()
+                })
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
@@ -184,202 +233,253 @@ pub fn verify_proof<
     // Sample gamma challenge
     let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
 
-    let permutations_committed = (0..num_proofs)
-        .map(|_| {
-            // Hash each permutation product commitment
-            vk.cs.permutation.read_product_commitments(vk, transcript)
+    let permutations_committed = groups
+        .iter()
+        .zip(instance_commitments.iter())
+        .map(|(&(vk, _), instance_commitments)| {
+            (0..instance_commitments.len())
+                .map(|_| {
+                    // Hash each permutation product commitment
+                    vk.cs.permutation.read_product_commitments(vk, transcript)
+                })
+                .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     let lookups_committed = lookups_permuted
         .into_iter()
-        .map(|lookups| {
-            // Hash each lookup product commitment
-            lookups
+        .map(|lookups_permuted| {
+            lookups_permuted
                 .into_iter()
-                .map(|lookup| lookup.read_product_commitment(transcript))
+                .map(|lookups| {
+                    // Hash each lookup product commitment
+                    lookups
+                        .into_iter()
+                        .map(|lookup| lookup.read_product_commitment(transcript))
+                        .collect::<Result<Vec<_>, _>>()
+                })
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let vanishing = vanishing::Argument::read_commitments_before_y(transcript)?;
+    let vanishing = groups
+        .iter()
+        .map(|_| vanishing::Argument::read_commitments_before_y(transcript))
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Sample y challenge, which keeps the gates linearly independent.
     let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
 
-    let vanishing = vanishing.read_commitments_after_y(vk, transcript)?;
+    let vanishing = groups
+        .iter()
+        .zip(vanishing.into_iter())
+        .map(|(&(vk, _), vanishing)| vanishing.read_commitments_after_y(vk, transcript))
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Sample x challenge, which is used to ensure the circuit is
     // satisfied with high probability.
     let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
-    let instance_evals = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> { read_n_scalars(transcript, vk.cs.instance_queries.len()) })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let advice_evals = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> { read_n_scalars(transcript, vk.cs.advice_queries.len()) })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let fixed_evals = read_n_scalars(transcript, vk.cs.fixed_queries.len())?;
-
-    let vanishing = vanishing.evaluate_after_x(transcript)?;
-
-    let permutations_common = vk.permutation.evaluate(transcript)?;
-
-    let permutations_evaluated = permutations_committed
-        .into_iter()
-        .map(|permutation| permutation.evaluate(transcript))
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let lookups_evaluated = lookups_committed
-        .into_iter()
-        .map(|lookups| -> Result<Vec<_>, _> {
-            lookups
-                .into_iter()
-                .map(|lookup| lookup.evaluate(transcript))
-                .collect::<Result<Vec<_>, _>>()
-        })
-        .collect::<Result<Vec<_>, _>>()?;
-
-    // This check ensures the circuit is satisfied so long as the polynomial
-    // commitments open to the correct values.
-    let vanishing = {
-        // x^n
-        let xn = x.pow(&[params.n as u64, 0, 0, 0]);
-
-        let blinding_factors = vk.cs.blinding_factors();
-        let l_evals = vk
-            .domain
-            .l_i_range(*x, xn, (-((blinding_factors + 1) as i32))..=0);
-        assert_eq!(l_evals.len(), 2 + blinding_factors);
-        let l_last = l_evals[0];
-        let l_blind: C::Scalar = l_evals[1..(1 + blinding_factors)]
-            .iter()
-            .fold(C::Scalar::zero(), |acc, eval| acc + eval);
-        let l_0 = l_evals[1 + blinding_factors];
+    // x^n
+    let xn = x.pow(&[params.n as u64, 0, 0, 0]);
+
+    let mut instance_evals = Vec::with_capacity(groups.len());
+    let mut advice_evals = Vec::with_capacity(groups.len());
+    let mut fixed_evals = Vec::with_capacity(groups.len());
+    let mut permutations_common = Vec::with_capacity(groups.len());
+    let mut permutations_evaluated = Vec::with_capacity(groups.len());
+    let mut lookups_evaluated = Vec::with_capacity(groups.len());
+    let mut vanishing_evaluated = Vec::with_capacity(groups.len());
+    for (((&(vk, _), vanishing), permutations_committed), lookups_committed) in groups
+        .iter()
+        .zip(vanishing.into_iter())
+        .zip(permutations_committed.into_iter())
+        .zip(lookups_committed.into_iter())
+    {
+        let num_proofs = permutations_committed.len();
+
+        let group_instance_evals = (0..num_proofs)
+            .map(|_| -> Result<Vec<_>, _> {
+                read_n_scalars(transcript, vk.cs.instance_queries.len())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let group_advice_evals = (0..num_proofs)
+            .map(|_| -> Result<Vec<_>, _> {
+                read_n_scalars(transcript, vk.cs.advice_queries.len())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let group_fixed_evals = read_n_scalars(transcript, vk.cs.fixed_queries.len())?;
+
+        let vanishing = vanishing.evaluate_after_x(transcript)?;
+
+        let group_permutations_common = vk.permutation.evaluate(transcript)?;
+
+        let group_permutations_evaluated = permutations_committed
+            .into_iter()
+            .map(|permutation| permutation.evaluate(transcript))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let group_lookups_evaluated = lookups_committed
+            .into_iter()
+            .map(|lookups| -> Result<Vec<_>, _> {
+                lookups
+                    .into_iter()
+                    .map(|lookup| lookup.evaluate(transcript))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // This check ensures the circuit is satisfied so long as the polynomial
+        // commitments open to the correct values.
+        let vanishing = {
+            let blinding_factors = vk.cs.blinding_factors();
+            let l_evals = vk
+                .domain
+                .l_i_range(*x, xn, (-((blinding_factors + 1) as i32))..=0);
+            assert_eq!(l_evals.len(), 2 + blinding_factors);
+            let l_last = l_evals[0];
+            let l_blind: C::Scalar = l_evals[1..(1 + blinding_factors)]
+                .iter()
+                .fold(C::Scalar::zero(), |acc, eval| acc + eval);
+            let l_0 = l_evals[1 + blinding_factors];
 
-        // Compute the expected value of h(x)
-        let expressions = advice_evals
-            .iter()
-            .zip(instance_evals.iter())
-            .zip(permutations_evaluated.iter())
-            .zip(lookups_evaluated.iter())
-            .flat_map(|(((advice_evals, instance_evals), permutation), lookups)| {
-                let fixed_evals = &fixed_evals;
-                std::iter::empty()
-                    // Evaluate the circuit using the custom gates provided
-                    .chain(vk.cs.gates.iter().flat_map(move |gate| {
-                        gate.polynomials().iter().map(move |poly| {
-                            poly.evaluate(
-                                &|scalar| scalar,
-                                &|_| panic!("virtual selectors are removed during optimization"),
-                                &|index, _, _| fixed_evals[index],
-                                &|index, _, _| advice_evals[index],
-                                &|index, _, _| instance_evals[index],
-                                &|a| -a,
-                                &|a, b| a + &b,
-                                &|a, b| a * &b,
-                                &|a, scalar| a * &scalar,
-                            )
-                        })
-                    }))
-                    .chain(permutation.expressions(
-                        vk,
-                        &vk.cs.permutation,
-                        &permutations_common,
-                        advice_evals,
-                        fixed_evals,
-                        instance_evals,
-                        l_0,
-                        l_last,
-                        l_blind,
-                        beta,
-                        gamma,
-                        x,
-                    ))
-                    .chain(
-                        lookups
-                            .iter()
-                            .zip(vk.cs.lookups.iter())
-                            .flat_map(move |(p, argument)| {
-                                p.expressions(
-                                    l_0,
-                                    l_last,
-                                    l_blind,
-                                    argument,
-                                    theta,
-                                    beta,
-                                    gamma,
-                                    advice_evals,
-                                    fixed_evals,
-                                    instance_evals,
+            // Compute the expected value of h(x)
+            let expressions = group_advice_evals
+                .iter()
+                .zip(group_instance_evals.iter())
+                .zip(group_permutations_evaluated.iter())
+                .zip(group_lookups_evaluated.iter())
+                .flat_map(|(((advice_evals, instance_evals), permutation), lookups)| {
+                    let fixed_evals = &group_fixed_evals;
+                    let permutations_common = &group_permutations_common;
+                    std::iter::empty()
+                        // Evaluate the circuit using the custom gates provided
+                        .chain(vk.cs.gates.iter().flat_map(move |gate| {
+                            gate.polynomials().iter().map(move |poly| {
+                                poly.evaluate(
+                                    &|scalar| scalar,
+                                    &|_| {
+                                        panic!("virtual selectors are removed during optimization")
+                                    },
+                                    &|index, _, _| fixed_evals[index],
+                                    &|index, _, _| advice_evals[index],
+                                    &|index, _, _| instance_evals[index],
+                                    &|a| -a,
+                                    &|a, b| a + &b,
+                                    &|a, b| a * &b,
+                                    &|a, scalar| a * &scalar,
                                 )
                             })
-                            .into_iter(),
-                    )
-            });
-
-        vanishing.verify(params, expressions, y, xn)
-    };
+                        }))
+                        .chain(permutation.expressions(
+                            vk,
+                            &vk.cs.permutation,
+                            permutations_common,
+                            advice_evals,
+                            fixed_evals,
+                            instance_evals,
+                            l_0,
+                            l_last,
+                            l_blind,
+                            beta,
+                            gamma,
+                            x,
+                        ))
+                        .chain(
+                            lookups
+                                .iter()
+                                .zip(vk.cs.lookups.iter())
+                                .flat_map(move |(p, argument)| {
+                                    p.expressions(
+                                        l_0,
+                                        l_last,
+                                        l_blind,
+                                        argument,
+                                        theta,
+                                        beta,
+                                        gamma,
+                                        advice_evals,
+                                        fixed_evals,
+                                        instance_evals,
+                                    )
+                                })
+                                .into_iter(),
+                        )
+                });
+
+            vanishing.verify(params, expressions, y, xn)
+        };
+
+        instance_evals.push(group_instance_evals);
+        advice_evals.push(group_advice_evals);
+        fixed_evals.push(group_fixed_evals);
+        permutations_common.push(group_permutations_common);
+        permutations_evaluated.push(group_permutations_evaluated);
+        lookups_evaluated.push(group_lookups_evaluated);
+        vanishing_evaluated.push(vanishing);
+    }
 
-    let queries = instance_commitments
-        .iter()
-        .zip(instance_evals.iter())
-        .zip(advice_commitments.iter())
-        .zip(advice_evals.iter())
-        .zip(permutations_evaluated.iter())
-        .zip(lookups_evaluated.iter())
-        .flat_map(
-            |(
-                (
-                    (((instance_commitments, instance_evals), advice_commitments), advice_evals),
-                    permutation,
-                ),
-                lookups,
-            )| {
-                iter::empty()
-                    .chain(vk.cs.instance_queries.iter().enumerate().map(
-                        move |(query_index, &(column, at))| {
-                            VerifierQuery::new_commitment(
-                                &instance_commitments[column.index()],
-                                vk.domain.rotate_omega(*x, at),
-                                instance_evals[query_index],
-                            )
-                        },
-                    ))
-                    .chain(vk.cs.advice_queries.iter().enumerate().map(
-                        move |(query_index, &(column, at))| {
-                            VerifierQuery::new_commitment(
-                                &advice_commitments[column.index()],
-                                vk.domain.rotate_omega(*x, at),
-                                advice_evals[query_index],
-                            )
-                        },
-                    ))
-                    .chain(permutation.queries(vk, x))
-                    .chain(
-                        lookups
-                            .iter()
-                            .flat_map(move |p| p.queries(vk, x))
-                            .into_iter(),
-                    )
-            },
-        )
-        .chain(
-            vk.cs
-                .fixed_queries
-                .iter()
-                .enumerate()
-                .map(|(query_index, &(column, at))| {
+    let queries = groups.iter().enumerate().flat_map(|(group, &(vk, _))| {
+        let fixed_evals = &fixed_evals[group];
+        instance_commitments[group]
+            .iter()
+            .zip(instance_evals[group].iter())
+            .zip(advice_commitments[group].iter())
+            .zip(advice_evals[group].iter())
+            .zip(permutations_evaluated[group].iter())
+            .zip(lookups_evaluated[group].iter())
+            .flat_map(
+                move |(
+                    (
+                        (
+                            ((instance_commitments, instance_evals), advice_commitments),
+                            advice_evals,
+                        ),
+                        permutation,
+                    ),
+                    lookups,
+                )| {
+                    iter::empty()
+                        .chain(vk.cs.instance_queries.iter().enumerate().map(
+                            move |(query_index, &(column, at))| {
+                                VerifierQuery::new_commitment(
+                                    &instance_commitments[column.index()],
+                                    vk.domain.rotate_omega(*x, at),
+                                    instance_evals[query_index],
+                                )
+                            },
+                        ))
+                        .chain(vk.cs.advice_queries.iter().enumerate().map(
+                            move |(query_index, &(column, at))| {
+                                VerifierQuery::new_commitment(
+                                    &advice_commitments[column.index()],
+                                    vk.domain.rotate_omega(*x, at),
+                                    advice_evals[query_index],
+                                )
+                            },
+                        ))
+                        .chain(permutation.queries(vk, x))
+                        .chain(
+                            lookups
+                                .iter()
+                                .flat_map(move |p| p.queries(vk, x))
+                                .into_iter(),
+                        )
+                },
+            )
+            .chain(vk.cs.fixed_queries.iter().enumerate().map(
+                move |(query_index, &(column, at))| {
                     VerifierQuery::new_commitment(
                         &vk.fixed_commitments[column.index()],
                         vk.domain.rotate_omega(*x, at),
                         fixed_evals[query_index],
                     )
-                }),
-        )
-        .chain(permutations_common.queries(&vk.permutation, x))
-        .chain(vanishing.queries(x));
+                },
+            ))
+            .chain(permutations_common[group].queries(&vk.permutation, x))
+            .chain(vanishing_evaluated[group].queries(x))
+    });
 
     // We are now convinced the circuit is satisfied so long as the
     // polynomial commitments open to the correct values.
@@ -0,0 +1,762 @@
+//! Capturing and replaying circuit assignments.
+
+use std::io::{self, Read};
+
+use ff::{BatchInvert, Field};
+
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Column, Error, Fixed, Instance, Selector, WitnessAccess,
+    },
+};
+
+/// The version of the [`WitnessDump`] encoding.
+const VERSION: u8 = 1;
+
+/// A single call made by a circuit to its [`Assignment`] during synthesis.
+#[derive(Clone, Debug, PartialEq)]
+enum Event<F> {
+    EnterRegion(String),
+    ExitRegion,
+    EnableSelector {
+        selector: usize,
+        simple: bool,
+        row: usize,
+    },
+    AssignAdvice {
+        column: usize,
+        row: usize,
+        value: F,
+    },
+    AssignFixed {
+        column: usize,
+        row: usize,
+        value: F,
+    },
+    Copy {
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    },
+    FillFromRow {
+        column: usize,
+        from_row: usize,
+        value: Option<F>,
+    },
+}
+
+impl<F> Event<F> {
+    /// Maps the assigned value of this event, if any, with `f`.
+    fn map<G>(self, mut f: impl FnMut(F) -> G) -> Event<G> {
+        match self {
+            Event::EnterRegion(name) => Event::EnterRegion(name),
+            Event::ExitRegion => Event::ExitRegion,
+            Event::EnableSelector {
+                selector,
+                simple,
+                row,
+            } => Event::EnableSelector {
+                selector,
+                simple,
+                row,
+            },
+            Event::AssignAdvice { column, row, value } => Event::AssignAdvice {
+                column,
+                row,
+                value: f(value),
+            },
+            Event::AssignFixed { column, row, value } => Event::AssignFixed {
+                column,
+                row,
+                value: f(value),
+            },
+            Event::Copy {
+                left_column,
+                left_row,
+                right_column,
+                right_row,
+            } => Event::Copy {
+                left_column,
+                left_row,
+                right_column,
+                right_row,
+            },
+            Event::FillFromRow {
+                column,
+                from_row,
+                value,
+            } => Event::FillFromRow {
+                column,
+                from_row,
+                value: value.map(f),
+            },
+        }
+    }
+
+    /// Returns the assigned value of this event, if any.
+    fn value(&self) -> Option<&F> {
+        match self {
+            Event::AssignAdvice { value, .. } | Event::AssignFixed { value, .. } => Some(value),
+            Event::FillFromRow { value, .. } => value.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// The maximum length of a region name in a witness dump.
+const MAX_NAME_LEN: usize = 1 << 16;
+
+/// The full assignment of a circuit: its instance values, and every region, selector,
+/// cell assignment and equality constraint that it made during synthesis, in order.
+///
+/// A dump is captured with [`MockProver::run_with_dump`] or
+/// [`ProverOptions::dump_witnesses`], and can be written to a compact binary file.
+/// Replaying it with [`MockProver::replay`] reproduces the synthesis exactly, without
+/// needing the original witness-generation code (although the circuit's `configure` is
+/// still used).
+///
+/// If synthesis failed, the dump contains everything that was assigned up to the
+/// failure. Replaying it gives a `MockProver` in that partial state, so that the
+/// assignments made before the failure can be inspected and verified.
+///
+/// [`MockProver::run_with_dump`]: crate::dev::MockProver::run_with_dump
+/// [`MockProver::replay`]: crate::dev::MockProver::replay
+/// [`ProverOptions::dump_witnesses`]: crate::plonk::ProverOptions::dump_witnesses
+#[derive(Clone, Debug, PartialEq)]
+pub struct WitnessDump<F> {
+    k: u32,
+    instance: Vec<Vec<F>>,
+    events: Vec<Event<F>>,
+}
+
+impl<F: Field> WitnessDump<F> {
+    /// Creates an empty dump for a circuit with `2^k` rows that is synthesized with the
+    /// given instance values.
+    pub(crate) fn new(k: u32, instance: Vec<Vec<F>>) -> Self {
+        WitnessDump {
+            k,
+            instance,
+            events: vec![],
+        }
+    }
+
+    /// Returns the `k` of the circuit that this dump was captured from.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Returns the instance values that the circuit was synthesized with.
+    pub fn instance(&self) -> &[Vec<F>] {
+        &self.instance
+    }
+
+    /// Replays the recorded synthesis into the given assignment.
+    pub(crate) fn replay<A: Assignment<F>>(&self, cs: &mut A) -> Result<(), Error> {
+        for event in self.events.iter() {
+            match event {
+                Event::EnterRegion(name) => cs.enter_region(|| name.clone()),
+                Event::ExitRegion => cs.exit_region(),
+                Event::EnableSelector {
+                    selector,
+                    simple,
+                    row,
+                } => cs.enable_selector(|| "", &Selector::new(*selector, *simple), *row)?,
+                Event::AssignAdvice { column, row, value } => cs.assign_advice(
+                    || "",
+                    Column::<Advice>::new(*column, Advice),
+                    *row,
+                    || Ok(*value),
+                )?,
+                Event::AssignFixed { column, row, value } => cs.assign_fixed(
+                    || "",
+                    Column::<Fixed>::new(*column, Fixed),
+                    *row,
+                    || Ok(*value),
+                )?,
+                Event::Copy {
+                    left_column,
+                    left_row,
+                    right_column,
+                    right_row,
+                } => cs.copy(*left_column, *left_row, *right_column, *right_row)?,
+                Event::FillFromRow {
+                    column,
+                    from_row,
+                    value,
+                } => cs.fill_from_row(
+                    Column::<Fixed>::new(*column, Fixed),
+                    *from_row,
+                    value.map(Assigned::from),
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_u32<W: io::Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    if value > u32::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "value too large for witness dump",
+        ));
+    }
+    writer.write_all(&(value as u32).to_le_bytes())
+}
+
+fn read_u32<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes) as usize)
+}
+
+fn read_u8<R: io::Read>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_field<F: FieldExt, W: io::Write>(writer: &mut W, value: &F) -> io::Result<()> {
+    writer.write_all(value.to_repr().as_ref())
+}
+
+fn read_field<F: FieldExt, R: io::Read>(reader: &mut R) -> io::Result<F> {
+    let mut repr = F::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+    Option::from(F::from_repr(repr))
+        .ok_or_else(|| invalid_data("invalid field element encoding in witness dump"))
+}
+
+fn write_column<W: io::Write>(writer: &mut W, column: &Column<Any>) -> io::Result<()> {
+    let column_type = match column.column_type() {
+        Any::Advice => 0,
+        Any::Fixed => 1,
+        Any::Instance => 2,
+    };
+    writer.write_all(&[column_type])?;
+    write_u32(writer, column.index())
+}
+
+fn read_column<R: io::Read>(reader: &mut R) -> io::Result<Column<Any>> {
+    let column_type = match read_u8(reader)? {
+        0 => Any::Advice,
+        1 => Any::Fixed,
+        2 => Any::Instance,
+        _ => return Err(invalid_data("invalid column type in witness dump")),
+    };
+    Ok(Column::new(read_u32(reader)?, column_type))
+}
+
+impl<F: FieldExt> WitnessDump<F> {
+    /// Writes this dump to a buffer.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b"h2wd")?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&self.k.to_le_bytes())?;
+
+        write_u32(writer, self.instance.len())?;
+        for column in self.instance.iter() {
+            write_u32(writer, column.len())?;
+            for value in column.iter() {
+                write_field(writer, value)?;
+            }
+        }
+
+        write_u32(writer, self.events.len())?;
+        for event in self.events.iter() {
+            match event {
+                Event::EnterRegion(name) => {
+                    writer.write_all(&[0])?;
+                    write_u32(writer, name.len())?;
+                    writer.write_all(name.as_bytes())?;
+                }
+                Event::ExitRegion => writer.write_all(&[1])?,
+                Event::EnableSelector {
+                    selector,
+                    simple,
+                    row,
+                } => {
+                    writer.write_all(&[2, *simple as u8])?;
+                    write_u32(writer, *selector)?;
+                    write_u32(writer, *row)?;
+                }
+                Event::AssignAdvice { column, row, value } => {
+                    writer.write_all(&[3])?;
+                    write_u32(writer, *column)?;
+                    write_u32(writer, *row)?;
+                    write_field(writer, value)?;
+                }
+                Event::AssignFixed { column, row, value } => {
+                    writer.write_all(&[4])?;
+                    write_u32(writer, *column)?;
+                    write_u32(writer, *row)?;
+                    write_field(writer, value)?;
+                }
+                Event::Copy {
+                    left_column,
+                    left_row,
+                    right_column,
+                    right_row,
+                } => {
+                    writer.write_all(&[5])?;
+                    write_column(writer, left_column)?;
+                    write_u32(writer, *left_row)?;
+                    write_column(writer, right_column)?;
+                    write_u32(writer, *right_row)?;
+                }
+                Event::FillFromRow {
+                    column,
+                    from_row,
+                    value,
+                } => {
+                    writer.write_all(&[6, value.is_some() as u8])?;
+                    write_u32(writer, *column)?;
+                    write_u32(writer, *from_row)?;
+                    if let Some(value) = value {
+                        write_field(writer, value)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a dump from a buffer.
+    pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != b"h2wd" {
+            return Err(invalid_data("not a witness dump"));
+        }
+        if read_u8(reader)? != VERSION {
+            return Err(invalid_data("unsupported witness dump version"));
+        }
+        let mut k = [0u8; 4];
+        reader.read_exact(&mut k)?;
+        let k = u32::from_le_bytes(k);
+
+        let instance = (0..read_u32(reader)?)
+            .map(|_| {
+                (0..read_u32(reader)?)
+                    .map(|_| read_field(reader))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let events = (0..read_u32(reader)?)
+            .map(|_| {
+                Ok(match read_u8(reader)? {
+                    0 => {
+                        // Don't trust the length to allocate the buffer up front.
+                        let len = read_u32(reader)?;
+                        if len > MAX_NAME_LEN {
+                            return Err(invalid_data("region name too long in witness dump"));
+                        }
+                        let mut name = Vec::with_capacity(len);
+                        reader.by_ref().take(len as u64).read_to_end(&mut name)?;
+                        if name.len() != len {
+                            return Err(io::ErrorKind::UnexpectedEof.into());
+                        }
+                        Event::EnterRegion(
+                            String::from_utf8(name)
+                                .map_err(|_| invalid_data("invalid region name in witness dump"))?,
+                        )
+                    }
+                    1 => Event::ExitRegion,
+                    2 => Event::EnableSelector {
+                        simple: read_u8(reader)? != 0,
+                        selector: read_u32(reader)?,
+                        row: read_u32(reader)?,
+                    },
+                    3 => Event::AssignAdvice {
+                        column: read_u32(reader)?,
+                        row: read_u32(reader)?,
+                        value: read_field(reader)?,
+                    },
+                    4 => Event::AssignFixed {
+                        column: read_u32(reader)?,
+                        row: read_u32(reader)?,
+                        value: read_field(reader)?,
+                    },
+                    5 => Event::Copy {
+                        left_column: read_column(reader)?,
+                        left_row: read_u32(reader)?,
+                        right_column: read_column(reader)?,
+                        right_row: read_u32(reader)?,
+                    },
+                    6 => {
+                        let has_value = read_u8(reader)? != 0;
+                        let column = read_u32(reader)?;
+                        let from_row = read_u32(reader)?;
+                        let value = if has_value {
+                            Some(read_field(reader)?)
+                        } else {
+                            None
+                        };
+                        Event::FillFromRow {
+                            column,
+                            from_row,
+                            value,
+                        }
+                    }
+                    _ => return Err(invalid_data("invalid event in witness dump")),
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(WitnessDump {
+            k,
+            instance,
+            events,
+        })
+    }
+}
+
+/// An [`Assignment`] that records every call made to it into a [`WitnessDump`], before
+/// forwarding it to another assignment.
+///
+/// Assigned values are kept as fractions while recording, and are evaluated with a
+/// single batch inversion when the recorder is dropped.
+#[derive(Debug)]
+pub(crate) struct WitnessRecorder<'a, F: Field, A: Assignment<F>> {
+    inner: &'a mut A,
+    dump: &'a mut WitnessDump<F>,
+    events: Vec<Event<Assigned<F>>>,
+}
+
+impl<'a, F: Field, A: Assignment<F>> WitnessRecorder<'a, F, A> {
+    /// Creates a recorder that forwards to `inner`, and records into `dump`.
+    pub(crate) fn new(inner: &'a mut A, dump: &'a mut WitnessDump<F>) -> Self {
+        WitnessRecorder {
+            inner,
+            dump,
+            events: vec![],
+        }
+    }
+}
+
+impl<'a, F: Field, A: Assignment<F>> Drop for WitnessRecorder<'a, F, A> {
+    fn drop(&mut self) {
+        // Flushing on drop ensures that the dump is complete even if synthesis failed.
+        let mut inv_denominators: Vec<F> = self
+            .events
+            .iter()
+            .filter_map(|event| event.value().and_then(|value| value.denominator()))
+            .collect();
+        inv_denominators.iter_mut().batch_invert();
+
+        let mut inv_denominators = inv_denominators.into_iter();
+        let mut evaluate = |value: Assigned<F>| match value.denominator() {
+            Some(_) => value.numerator() * inv_denominators.next().unwrap(),
+            None => value.numerator(),
+        };
+        self.dump
+            .events
+            .extend(self.events.drain(..).map(|event| event.map(&mut evaluate)));
+    }
+}
+
+impl<'a, F: Field, A: Assignment<F>> Assignment<F> for WitnessRecorder<'a, F, A> {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = name().into();
+        self.events.push(Event::EnterRegion(name.clone()));
+        self.inner.enter_region(|| name);
+    }
+
+    fn exit_region(&mut self) {
+        self.events.push(Event::ExitRegion);
+        self.inner.exit_region();
+    }
+
+    fn enable_selector<AN, AR>(
+        &mut self,
+        annotation: AN,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        AN: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.events.push(Event::EnableSelector {
+            selector: selector.0,
+            simple: selector.is_simple(),
+            row,
+        });
+        self.inner.enable_selector(annotation, selector, row)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.inner.query_instance(column, row)
+    }
+
+    fn witness_access(&self) -> WitnessAccess<'_, F> {
+        self.inner.witness_access()
+    }
+
+    fn assign_advice<V, VR, AN, AR>(
+        &mut self,
+        annotation: AN,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        AN: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = to()?.into();
+        self.events.push(Event::AssignAdvice {
+            column: column.index(),
+            row,
+            value,
+        });
+        self.inner
+            .assign_advice(annotation, column, row, || Ok(value))
+    }
+
+    fn assign_fixed<V, VR, AN, AR>(
+        &mut self,
+        annotation: AN,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        AN: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = to()?.into();
+        self.events.push(Event::AssignFixed {
+            column: column.index(),
+            row,
+            value,
+        });
+        self.inner
+            .assign_fixed(annotation, column, row, || Ok(value))
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.events.push(Event::Copy {
+            left_column,
+            left_row,
+            right_column,
+            right_row,
+        });
+        self.inner
+            .copy(left_column, left_row, right_column, right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        from_row: usize,
+        to: Option<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.events.push(Event::FillFromRow {
+            column: column.index(),
+            from_row,
+            value: to,
+        });
+        self.inner.fill_from_row(column, from_row, to)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.inner.push_namespace(name)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.inner.pop_namespace(gadget_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use pasta_curves::Fp;
+
+    use super::WitnessDump;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct TestConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        i: Column<Instance>,
+        s: Selector,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Option<u64>,
+        b: Option<u64>,
+    }
+
+    impl Circuit<Fp> for TestCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let i = meta.instance_column();
+            let s = meta.selector();
+
+            meta.enable_equality(a);
+            meta.enable_equality(i);
+
+            meta.create_gate("double", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let s = cells.query_selector(s);
+
+                vec![s * (a.clone() + a - b)]
+            });
+
+            TestConfig { a, b, i, s }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let a = layouter.assign_region(
+                || "double",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "b",
+                        config.b,
+                        0,
+                        || self.b.map(Fp::from).ok_or(Error::Synthesis),
+                    )?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.a.map(Fp::from).ok_or(Error::Synthesis),
+                    )
+                },
+            )?;
+
+            layouter.constrain_instance(a.cell(), config.i, 0)
+        }
+    }
+
+    fn roundtrip(dump: &WitnessDump<Fp>) -> WitnessDump<Fp> {
+        let mut buf = vec![];
+        dump.write(&mut buf).unwrap();
+        WitnessDump::read(&mut &buf[..]).unwrap()
+    }
+
+    #[test]
+    fn replay() {
+        let circuit = TestCircuit {
+            a: Some(3),
+            b: Some(7),
+        };
+        let instance = vec![vec![Fp::from(3)]];
+
+        let (prover, dump) = MockProver::run_with_dump(4, &circuit, instance.clone());
+        let prover = prover.unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [VerifyFailure::ConstraintNotSatisfied { .. }]
+        ));
+
+        let dump = roundtrip(&dump);
+        assert_eq!(dump.k(), 4);
+        assert_eq!(dump.instance(), &instance[..]);
+
+        // Replaying the dump reproduces the same failures, without the witness.
+        let replayed = MockProver::replay::<TestCircuit>(&dump).unwrap();
+        assert_eq!(replayed.verify().unwrap_err(), failures);
+    }
+
+    #[test]
+    fn replay_synthesis_failure() {
+        let circuit = TestCircuit {
+            a: None,
+            b: Some(6),
+        };
+
+        let (prover, dump) = MockProver::run_with_dump(4, &circuit, vec![vec![Fp::from(3)]]);
+        assert!(matches!(prover, Err(Error::Synthesis)));
+
+        // The dump contains the assignments up to the failure.
+        let dump = roundtrip(&dump);
+        assert_eq!(dump.events.len(), 3);
+
+        // Replaying a partial dump succeeds, and shows which cells are missing.
+        let replayed = MockProver::replay::<TestCircuit>(&dump).unwrap();
+        assert!(matches!(
+            replayed.verify().unwrap_err()[..],
+            [VerifyFailure::CellNotAssigned { .. }, ..]
+        ));
+    }
+
+    #[test]
+    fn read_region_name() {
+        let dump = |name_len: u32, name: &[u8]| {
+            let mut buf = b"h2wd".to_vec();
+            buf.push(super::VERSION);
+            buf.extend_from_slice(&4u32.to_le_bytes());
+            // No instance columns, and a single event.
+            buf.extend_from_slice(&0u32.to_le_bytes());
+            buf.extend_from_slice(&1u32.to_le_bytes());
+            buf.push(0);
+            buf.extend_from_slice(&name_len.to_le_bytes());
+            buf.extend_from_slice(name);
+            WitnessDump::<Fp>::read(&mut &buf[..])
+        };
+
+        assert_eq!(dump(3, b"abc").unwrap().events.len(), 1);
+
+        // The length of a region name is checked before anything is allocated for it.
+        assert_eq!(
+            dump(u32::MAX, b"abc").unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            dump(5, b"abc").unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
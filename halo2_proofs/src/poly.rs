@@ -132,6 +132,18 @@ impl<F, B> Polynomial<F, B> {
     }
 }
 
+impl<F> Polynomial<F, LagrangeCoeff> {
+    /// Obtains a polynomial in Lagrange form from its evaluations, for callers that
+    /// only know the size of the domain. `values` must have the size of the domain that
+    /// the polynomial is later used with.
+    pub(crate) fn from_lagrange_values(values: Vec<F>) -> Self {
+        Polynomial {
+            values,
+            _marker: PhantomData,
+        }
+    }
+}
+
 pub(crate) fn batch_invert_assigned<F: FieldExt>(
     assigned: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
 ) -> Vec<Polynomial<F, LagrangeCoeff>> {
@@ -5,7 +5,7 @@
 
 use super::{Coeff, LagrangeCoeff, Polynomial};
 use crate::arithmetic::{
-    best_fft, best_multiexp, parallelize, CurveAffine, CurveExt, FieldExt, Group,
+    best_multiexp, parallelize, CurveAffine, CurveExt, FftTwiddles, FieldExt, FixedBaseTable, Group,
 };
 use crate::helpers::CurveRead;
 
@@ -32,6 +32,15 @@ pub struct Params<C: CurveAffine> {
     pub(crate) g_lagrange: Vec<C>,
     pub(crate) w: C,
     pub(crate) u: C,
+    /// Tables for committing with `g` and `g_lagrange`, if they have been precomputed.
+    fixed_base_tables: Option<FixedBaseTables<C>>,
+}
+
+/// Precomputed tables for the fixed bases of [`Params`].
+#[derive(Debug)]
+struct FixedBaseTables<C: CurveAffine> {
+    g: FixedBaseTable<C>,
+    g_lagrange: FixedBaseTable<C>,
 }
 
 impl<C: CurveAffine> Params<C> {
@@ -81,7 +90,7 @@ impl<C: CurveAffine> Params<C> {
             alpha_inv = alpha_inv.square();
         }
         let mut g_lagrange_projective = g_projective;
-        best_fft(&mut g_lagrange_projective, alpha_inv, k);
+        FftTwiddles::new(alpha_inv, k).fft(&mut g_lagrange_projective, k);
         let minv = C::Scalar::TWO_INV.pow_vartime(&[k as u64, 0, 0, 0]);
         parallelize(&mut g_lagrange_projective, |g, _| {
             for g in g.iter_mut() {
@@ -112,13 +121,43 @@ impl<C: CurveAffine> Params<C> {
             g_lagrange,
             w,
             u,
+            fixed_base_tables: None,
         }
     }
 
+    /// Precomputes tables for the generators used by [`Params::commit`] and
+    /// [`Params::commit_lagrange`], so that commitments made with these parameters are
+    /// faster. The commitments themselves are unchanged.
+    ///
+    /// This stores `num_copies` copies of each of `g` and `g_lagrange` (see
+    /// [`FixedBaseTable`]), so the tables take `2 * num_copies * 2^k` affine points of
+    /// memory; for the Pasta curves this is `128 * num_copies * 2^k` bytes. The tables
+    /// are not written by [`Params::write`], and must be precomputed again after
+    /// [`Params::read`].
+    ///
+    /// Panics if `num_copies` is zero.
+    pub fn precompute_fixed_bases(&mut self, num_copies: usize) {
+        self.fixed_base_tables = Some(FixedBaseTables {
+            g: FixedBaseTable::new(&self.g, num_copies),
+            g_lagrange: FixedBaseTable::new(&self.g_lagrange, num_copies),
+        });
+    }
+
+    /// Returns `true` if tables for the generators have been precomputed with
+    /// [`Params::precompute_fixed_bases`].
+    pub fn has_fixed_base_tables(&self) -> bool {
+        self.fixed_base_tables.is_some()
+    }
+
     /// This computes a commitment to a polynomial described by the provided
     /// slice of coefficients. The commitment will be blinded by the blinding
     /// factor `r`.
     pub fn commit(&self, poly: &Polynomial<C::Scalar, Coeff>, r: Blind<C::Scalar>) -> C::Curve {
+        enter_span!("commit", n = poly.len());
+        if let Some(tables) = &self.fixed_base_tables {
+            return tables.g.multiexp(poly) + self.w * r.0;
+        }
+
         let mut tmp_scalars = Vec::with_capacity(poly.len() + 1);
         let mut tmp_bases = Vec::with_capacity(poly.len() + 1);
 
@@ -139,6 +178,11 @@ impl<C: CurveAffine> Params<C> {
         poly: &Polynomial<C::Scalar, LagrangeCoeff>,
         r: Blind<C::Scalar>,
     ) -> C::Curve {
+        enter_span!("commit_lagrange", n = poly.len());
+        if let Some(tables) = &self.fixed_base_tables {
+            return tables.g_lagrange.multiexp(poly) + self.w * r.0;
+        }
+
         let mut tmp_scalars = Vec::with_capacity(poly.len() + 1);
         let mut tmp_bases = Vec::with_capacity(poly.len() + 1);
 
@@ -198,6 +242,7 @@ impl<C: CurveAffine> Params<C> {
             g_lagrange,
             w,
             u,
+            fixed_base_tables: None,
         })
     }
 }
@@ -298,6 +343,35 @@ fn test_commit_lagrange_eqaffine() {
     assert_eq!(params.commit(&b, alpha), params.commit_lagrange(&a, alpha));
 }
 
+#[test]
+fn test_fixed_base_tables() {
+    const K: u32 = 6;
+
+    use rand_core::OsRng;
+
+    use crate::pasta::{EqAffine, Fp};
+    let mut params = Params::<EqAffine>::new(K);
+    let domain = super::EvaluationDomain::new(1, K);
+
+    let mut a = domain.empty_lagrange();
+    for a in a.iter_mut() {
+        *a = Fp::random(OsRng);
+    }
+    let b = domain.lagrange_to_coeff(a.clone());
+    let alpha = Blind(Fp::random(OsRng));
+
+    let commitment = params.commit(&b, alpha);
+    let commitment_lagrange = params.commit_lagrange(&a, alpha);
+
+    assert!(!params.has_fixed_base_tables());
+    for num_copies in 1..=3 {
+        params.precompute_fixed_bases(num_copies);
+        assert!(params.has_fixed_base_tables());
+        assert_eq!(params.commit(&b, alpha), commitment);
+        assert_eq!(params.commit_lagrange(&a, alpha), commitment_lagrange);
+    }
+}
+
 #[test]
 fn test_opening_proof() {
     const K: u32 = 6;
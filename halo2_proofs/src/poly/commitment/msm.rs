@@ -117,6 +117,7 @@ impl<'a, C: CurveAffine> MSM<'a, C> {
 
     /// Perform multiexp and check that it results in zero
     pub fn eval(self) -> bool {
+        enter_span!("msm_eval");
         let len = self.g_scalars.as_ref().map(|v| v.len()).unwrap_or(0)
             + self.w_scalar.map(|_| 1).unwrap_or(0)
             + self.u_scalar.map(|_| 1).unwrap_or(0)
@@ -2,13 +2,14 @@
 //! domain that is of a suitable size for the application.
 
 use crate::{
-    arithmetic::{best_fft, parallelize, FieldExt, Group},
+    arithmetic::{parallelize, FftTwiddles, FieldExt, Group},
     plonk::Assigned,
 };
 
 use super::{Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, Rotation};
 
 use group::ff::{BatchInvert, Field, PrimeField};
+use once_cell::sync::OnceCell;
 
 use std::marker::PhantomData;
 
@@ -23,14 +24,20 @@ pub struct EvaluationDomain<G: Group> {
     omega: G::Scalar,
     omega_inv: G::Scalar,
     extended_omega: G::Scalar,
-    extended_omega_inv: G::Scalar,
-    g_coset: G::Scalar,
     g_coset_inv: G::Scalar,
     quotient_poly_degree: u64,
     ifft_divisor: G::Scalar,
     extended_ifft_divisor: G::Scalar,
     t_evaluations: Vec<G::Scalar>,
     barycentric_weight: G::Scalar,
+    /// Twiddle factors for `extended_omega`, which also serve FFTs over the original
+    /// domain, as `omega` generates a subgroup of the extended domain. These are only
+    /// computed once an FFT is performed, so that the verifier never computes them.
+    twiddles: OnceCell<FftTwiddles<G::Scalar>>,
+    /// The generator of each part of the extended domain, as a coset of the original
+    /// domain: `coset_shifts[r] = zeta * extended_omega^r`. In particular,
+    /// `coset_shifts[0]` is the generator `zeta` of the extended domain's coset.
+    coset_shifts: Vec<G::Scalar>,
 }
 
 impl<G: Group> EvaluationDomain<G> {
@@ -60,7 +67,6 @@ impl<G: Group> EvaluationDomain<G> {
             extended_omega = extended_omega.square();
         }
         let extended_omega = extended_omega;
-        let mut extended_omega_inv = extended_omega; // Inversion computed later
 
         // Get omega, the 2^{k}'th root of unity (i.e. n'th root of unity)
         // The loop computes omega = extended_omega ^ {2 ^ (extended_k - k)}
@@ -119,10 +125,13 @@ impl<G: Group> EvaluationDomain<G> {
             .chain(Some(&mut ifft_divisor))
             .chain(Some(&mut extended_ifft_divisor))
             .chain(Some(&mut barycentric_weight))
-            .chain(Some(&mut extended_omega_inv))
             .chain(Some(&mut omega_inv))
             .batch_invert();
 
+        let coset_shifts = (0..(1 << (extended_k - k)))
+            .map(|part| g_coset * extended_omega.pow_vartime(&[part as u64, 0, 0, 0]))
+            .collect();
+
         EvaluationDomain {
             n,
             k,
@@ -130,14 +139,14 @@ impl<G: Group> EvaluationDomain<G> {
             omega,
             omega_inv,
             extended_omega,
-            extended_omega_inv,
-            g_coset,
             g_coset_inv,
             quotient_poly_degree,
             ifft_divisor,
             extended_ifft_divisor,
             t_evaluations,
             barycentric_weight,
+            twiddles: OnceCell::new(),
+            coset_shifts,
         }
     }
 
@@ -227,7 +236,7 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(a.values.len(), 1 << self.k);
 
         // Perform inverse FFT to obtain the polynomial in coefficient form
-        Self::ifft(&mut a.values, self.omega_inv, self.k, self.ifft_divisor);
+        self.ifft(&mut a.values, self.k, self.ifft_divisor);
 
         Polynomial {
             values: a.values,
@@ -245,7 +254,7 @@ impl<G: Group> EvaluationDomain<G> {
 
         self.distribute_powers_zeta(&mut a.values, true);
         a.values.resize(self.extended_len(), G::group_zero());
-        best_fft(&mut a.values, self.extended_omega, self.extended_k);
+        self.twiddles().fft(&mut a.values, self.extended_k);
 
         Polynomial {
             values: a.values,
@@ -253,6 +262,38 @@ impl<G: Group> EvaluationDomain<G> {
         }
     }
 
+    /// Evaluates a polynomial in coefficient form over one part of the extended
+    /// evaluation domain.
+    ///
+    /// The extended domain is split into [`EvaluationDomain::extended_parts`] parts,
+    /// each of which is a coset of the $2^k$ size domain; part $r$ contains the points
+    /// $\zeta \omega_e^{r + i m}$ for $0 \le i < n$, where $\omega_e$ generates the
+    /// extended domain and $m$ is the number of parts. The returned values are thus
+    /// entries $r, r + m, r + 2m, \ldots$ of [`EvaluationDomain::coeff_to_extended`],
+    /// computed with an FFT over $n$ points rather than over the whole extended domain.
+    ///
+    /// This function will panic if the provided polynomial is not the correct length,
+    /// or if `part` is out of range.
+    pub(crate) fn coeff_to_extended_part(&self, a: &Polynomial<G, Coeff>, part: usize) -> Vec<G> {
+        assert_eq!(a.values.len(), 1 << self.k);
+        assert!(part < self.extended_parts());
+
+        // Scale the i'th coefficient by (zeta * extended_omega^part)^i, so that an FFT
+        // over the original domain evaluates the polynomial over the shifted coset.
+        let shift = self.coset_shifts[part];
+        let mut values = a.values.clone();
+        parallelize(&mut values, |values, start| {
+            let mut power = shift.pow_vartime(&[start as u64]);
+            for value in values {
+                value.group_scale(&power);
+                power *= &shift;
+            }
+        });
+        self.twiddles().fft(&mut values, self.k);
+
+        values
+    }
+
     /// Rotate the extended domain polynomial over the original domain.
     pub fn rotate_extended(
         &self,
@@ -282,12 +323,7 @@ impl<G: Group> EvaluationDomain<G> {
         assert_eq!(a.values.len(), self.extended_len());
 
         // Inverse FFT
-        Self::ifft(
-            &mut a.values,
-            self.extended_omega_inv,
-            self.extended_k,
-            self.extended_ifft_divisor,
-        );
+        self.ifft(&mut a.values, self.extended_k, self.extended_ifft_divisor);
 
         // Distribute powers to move from coset; opposite from the
         // transformation we performed earlier.
@@ -325,6 +361,20 @@ impl<G: Group> EvaluationDomain<G> {
         }
     }
 
+    /// Returns the generator of the given part of the extended domain (see
+    /// [`EvaluationDomain::coeff_to_extended_part`]), as a coset of the original domain.
+    pub(crate) fn coset_shift(&self, part: usize) -> G::Scalar {
+        self.coset_shifts[part]
+    }
+
+    /// Returns the inverse of the vanishing polynomial $t(X) = X^n - 1$ over the given
+    /// part of the extended domain (see [`EvaluationDomain::coeff_to_extended_part`]),
+    /// on which it is constant.
+    pub(crate) fn vanishing_poly_inv_on_part(&self, part: usize) -> G::Scalar {
+        assert!(part < self.extended_parts());
+        self.t_evaluations[part]
+    }
+
     /// Given a slice of group elements `[a_0, a_1, a_2, ...]`, this returns
     /// `[a_0, [zeta]a_1, [zeta^2]a_2, a_3, [zeta]a_4, [zeta^2]a_5, a_6, ...]`,
     /// where zeta is a cube root of unity in the multiplicative subgroup with
@@ -333,10 +383,11 @@ impl<G: Group> EvaluationDomain<G> {
     /// `into_coset` should be set to `true` when moving into the coset,
     /// and `false` when moving out. This toggles the choice of `zeta`.
     fn distribute_powers_zeta(&self, a: &mut [G], into_coset: bool) {
+        let g_coset = self.coset_shifts[0];
         let coset_powers = if into_coset {
-            [self.g_coset, self.g_coset_inv]
+            [g_coset, self.g_coset_inv]
         } else {
-            [self.g_coset_inv, self.g_coset]
+            [self.g_coset_inv, g_coset]
         };
         parallelize(a, |a, mut index| {
             for a in a {
@@ -350,8 +401,14 @@ impl<G: Group> EvaluationDomain<G> {
         });
     }
 
-    fn ifft(a: &mut [G], omega_inv: G::Scalar, log_n: u32, divisor: G::Scalar) {
-        best_fft(a, omega_inv, log_n);
+    /// Returns the twiddle factors for `extended_omega`, computing them on first use.
+    fn twiddles(&self) -> &FftTwiddles<G::Scalar> {
+        self.twiddles
+            .get_or_init(|| FftTwiddles::new(self.extended_omega, self.extended_k))
+    }
+
+    fn ifft(&self, a: &mut [G], log_n: u32, divisor: G::Scalar) {
+        self.twiddles().inverse_fft(a, log_n);
         parallelize(a, |a, _| {
             for a in a {
                 // Finish iFFT
@@ -360,11 +417,22 @@ impl<G: Group> EvaluationDomain<G> {
         });
     }
 
+    /// Get $k$, where the domain has $2^k$ points.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
     /// Get the size of the extended domain
     pub fn extended_len(&self) -> usize {
         1 << self.extended_k
     }
 
+    /// Get the number of parts that the extended domain is split into by
+    /// [`EvaluationDomain::coeff_to_extended_part`].
+    pub(crate) fn extended_parts(&self) -> usize {
+        1 << (self.extended_k - self.k)
+    }
+
     /// Get $\omega$, the generator of the $2^k$ order multiplicative subgroup.
     pub fn get_omega(&self) -> G::Scalar {
         self.omega
@@ -545,3 +613,29 @@ fn test_l_i() {
         assert_eq!(eval_polynomial(&l[(8 - i) % 8][..], x), evaluations[7 - i]);
     }
 }
+
+#[test]
+fn test_coeff_to_extended_part() {
+    use rand_core::OsRng;
+
+    use crate::pasta::pallas::Scalar;
+
+    let domain = EvaluationDomain::<Scalar>::new(5, 3);
+    assert_eq!(domain.extended_parts(), 4);
+
+    let mut poly = domain.empty_coeff();
+    for coeff in poly.iter_mut() {
+        *coeff = Scalar::random(OsRng);
+    }
+
+    let extended = domain.coeff_to_extended(poly.clone());
+    for part in 0..domain.extended_parts() {
+        let expected: Vec<_> = extended
+            .iter()
+            .skip(part)
+            .step_by(domain.extended_parts())
+            .cloned()
+            .collect();
+        assert_eq!(domain.coeff_to_extended_part(&poly, part), expected);
+    }
+}
@@ -8,7 +8,7 @@ use std::{
     sync::Arc,
 };
 
-use group::ff::Field;
+use group::ff::{Field, PrimeField};
 use pasta_curves::arithmetic::FieldExt;
 
 use super::{
@@ -82,6 +82,27 @@ impl<E, B: Basis> AstLeaf<E, B> {
     }
 }
 
+/// A polynomial registered with an [`Evaluator`].
+enum Registered<F: Field, B: Basis> {
+    /// The evaluations of the polynomial in the evaluator's basis.
+    Evaluations(Polynomial<F, B>),
+    /// The coefficients of the polynomial. These are only registered with evaluators
+    /// that evaluate over parts of the extended domain, and are transformed into each
+    /// part as it is needed.
+    Coefficients(Polynomial<F, Coeff>),
+}
+
+impl<F: Field, B: Basis> Registered<F, B> {
+    fn evaluations(&self) -> &Polynomial<F, B> {
+        match self {
+            Registered::Evaluations(poly) => poly,
+            Registered::Coefficients(_) => {
+                panic!("polynomials in coefficient form can only be evaluated over parts")
+            }
+        }
+    }
+}
+
 /// An evaluation context for polynomial operations.
 ///
 /// This context enables us to de-duplicate queries of circuit columns (and the rotations
@@ -93,8 +114,16 @@ impl<E, B: Basis> AstLeaf<E, B> {
 /// - The references are then used to build up a [`Ast`] that represents the overall
 ///   operations to be applied to the polynomials.
 /// - Finally, we call [`Evaluator::evaluate`] passing in the [`Ast`].
+///
+/// An evaluator in the extended domain can instead evaluate over the parts of the
+/// extended domain a few at a time (see [`Evaluator::with_parts_per_chunk`]), in which
+/// case polynomials can be registered in coefficient form, and only the parts of them
+/// that are currently being evaluated are held in memory.
 pub(crate) struct Evaluator<E, F: Field, B: Basis> {
-    polys: Vec<Polynomial<F, B>>,
+    polys: Vec<Registered<F, B>>,
+    /// The number of parts of the extended domain to evaluate at a time, if the
+    /// evaluator evaluates over parts.
+    parts_per_chunk: Option<usize>,
     _context: E,
 }
 
@@ -107,16 +136,252 @@ pub(crate) struct Evaluator<E, F: Field, B: Basis> {
 pub(crate) fn new_evaluator<E: Fn() + Clone, F: Field, B: Basis>(context: E) -> Evaluator<E, F, B> {
     Evaluator {
         polys: vec![],
+        parts_per_chunk: None,
         _context: context,
     }
 }
 
+/// A node of an [`AstGraph`], referring to the nodes it depends on by their indices.
+#[derive(Clone, Copy, Debug)]
+enum Node<F> {
+    /// The leaf with the given index in [`AstGraph::leaves`].
+    Leaf(usize),
+    Add(usize, usize),
+    Mul(usize, usize),
+    Scale(usize, F),
+    LinearTerm(F),
+    ConstantTerm(F),
+}
+
+impl<F> Node<F> {
+    /// Returns the nodes that this node depends on.
+    fn operands(&self) -> [Option<usize>; 2] {
+        match self {
+            Node::Add(a, b) | Node::Mul(a, b) => [Some(*a), Some(*b)],
+            Node::Scale(a, _) => [Some(*a), None],
+            Node::Leaf(_) | Node::LinearTerm(_) | Node::ConstantTerm(_) => [None, None],
+        }
+    }
+}
+
+/// Identifies a [`Node`] structurally, with its scalar (if any) in canonical form.
+#[derive(PartialEq, Eq, Hash)]
+enum NodeKey {
+    Leaf(usize),
+    Add(usize, usize),
+    Mul(usize, usize),
+    Scale(usize, Vec<u8>),
+    LinearTerm(Vec<u8>),
+    ConstantTerm(Vec<u8>),
+}
+
+impl<F: PrimeField> Node<F> {
+    fn key(&self) -> NodeKey {
+        let bytes = |scalar: &F| scalar.to_repr().as_ref().to_vec();
+        match self {
+            Node::Leaf(leaf) => NodeKey::Leaf(*leaf),
+            Node::Add(a, b) => NodeKey::Add(*a, *b),
+            Node::Mul(a, b) => NodeKey::Mul(*a, *b),
+            Node::Scale(a, scalar) => NodeKey::Scale(*a, bytes(scalar)),
+            Node::LinearTerm(scalar) => NodeKey::LinearTerm(bytes(scalar)),
+            Node::ConstantTerm(scalar) => NodeKey::ConstantTerm(bytes(scalar)),
+        }
+    }
+}
+
+/// An [`Ast`] compiled into a graph in which each distinct sub-expression appears once.
+///
+/// `Ast`s built separately (for example, for different gates) often contain identical
+/// sub-expressions, such as the same selector multiplied by the same column, and
+/// query the same polynomials at the same rotations. Compiling deduplicates these
+/// structurally (treating addition and multiplication as commutative), so that each is
+/// evaluated once per point however many times it appears.
+struct AstGraph<E, F: Field, B: Basis> {
+    /// The distinct leaves of the `Ast`.
+    leaves: Vec<AstLeaf<E, B>>,
+    /// The distinct nodes of the `Ast`, each after the nodes it depends on. The last
+    /// node is the root.
+    nodes: Vec<Node<F>>,
+    /// The index of the last node that depends on each node, after which its values
+    /// are no longer needed.
+    last_use: Vec<usize>,
+}
+
+/// The state of [`AstGraph::compile`].
+struct Compiler<'a, E, F: Field, B: Basis> {
+    leaves: Vec<AstLeaf<E, B>>,
+    leaf_indices: HashMap<AstLeaf<E, B>, usize>,
+    nodes: Vec<Node<F>>,
+    node_indices: HashMap<NodeKey, usize>,
+    /// The node for each `Ast` that has been compiled, so that shared sub-trees are only
+    /// traversed once.
+    compiled: HashMap<*const Ast<E, F, B>, usize>,
+    _marker: PhantomData<&'a Ast<E, F, B>>,
+}
+
+impl<'a, E: Copy, F: FieldExt, B: Basis> Compiler<'a, E, F, B> {
+    fn compile(&mut self, ast: &'a Ast<E, F, B>) -> usize {
+        let ptr = ast as *const _;
+        if let Some(index) = self.compiled.get(&ptr) {
+            return *index;
+        }
+
+        let node = match ast {
+            Ast::Poly(leaf) => {
+                let leaf = match self.leaf_indices.get(leaf) {
+                    Some(index) => *index,
+                    None => {
+                        let index = self.leaves.len();
+                        self.leaves.push(*leaf);
+                        self.leaf_indices.insert(*leaf, index);
+                        index
+                    }
+                };
+                Node::Leaf(leaf)
+            }
+            Ast::Add(a, b) => {
+                let (a, b) = (self.compile(a), self.compile(b));
+                Node::Add(cmp::min(a, b), cmp::max(a, b))
+            }
+            Ast::Mul(AstMul(a, b)) => {
+                let (a, b) = (self.compile(a), self.compile(b));
+                Node::Mul(cmp::min(a, b), cmp::max(a, b))
+            }
+            Ast::Scale(a, scalar) => Node::Scale(self.compile(a), *scalar),
+            Ast::LinearTerm(scalar) => Node::LinearTerm(*scalar),
+            Ast::ConstantTerm(scalar) => Node::ConstantTerm(*scalar),
+        };
+
+        let key = node.key();
+        let index = match self.node_indices.get(&key) {
+            Some(index) => *index,
+            None => {
+                let index = self.nodes.len();
+                self.nodes.push(node);
+                self.node_indices.insert(key, index);
+                index
+            }
+        };
+        self.compiled.insert(ptr, index);
+        index
+    }
+}
+
+impl<E: Copy, F: FieldExt, B: Basis> AstGraph<E, F, B> {
+    fn compile(ast: &Ast<E, F, B>) -> Self {
+        let mut compiler = Compiler {
+            leaves: vec![],
+            leaf_indices: HashMap::default(),
+            nodes: vec![],
+            node_indices: HashMap::default(),
+            compiled: HashMap::default(),
+            _marker: PhantomData,
+        };
+        let root = compiler.compile(ast);
+        // Every node is reachable from the root, which is compiled last.
+        assert_eq!(root, compiler.nodes.len() - 1);
+
+        let mut last_use = vec![0; compiler.nodes.len()];
+        for (index, node) in compiler.nodes.iter().enumerate() {
+            for operand in node.operands().iter().flatten() {
+                last_use[*operand] = index;
+            }
+        }
+        last_use[root] = usize::MAX;
+
+        AstGraph {
+            leaves: compiler.leaves,
+            nodes: compiler.nodes,
+            last_use,
+        }
+    }
+}
+
+/// The context in which an [`AstGraph`] is evaluated over one chunk of points.
+struct AstContext<'a, F: Field> {
+    /// The values of each of the graph's leaves over the chunk.
+    leaves: &'a [&'a [F]],
+    /// Computes the values of the degree-1 term with the given coefficient over the
+    /// chunk.
+    linear_term: &'a dyn Fn(F) -> Vec<F>,
+    /// Computes the values of the degree-0 term with the given coefficient over the
+    /// chunk.
+    constant_term: &'a dyn Fn(F) -> Vec<F>,
+}
+
+impl<E, F: Field, B: Basis> AstGraph<E, F, B> {
+    /// Evaluates the graph over one chunk of points, in a single pass over its nodes.
+    ///
+    /// The values of each node are dropped after the last node that depends on them
+    /// has been evaluated, and their buffer is reused.
+    fn evaluate(&self, ctx: &AstContext<'_, F>) -> Vec<F> {
+        let mut values: Vec<Option<Vec<F>>> = vec![None; self.nodes.len()];
+        let mut buffers: Vec<Vec<F>> = vec![];
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let result = {
+                let value = |operand: usize| -> &[F] {
+                    match self.nodes[operand] {
+                        Node::Leaf(leaf) => ctx.leaves[leaf],
+                        _ => values[operand]
+                            .as_ref()
+                            .expect("operands are evaluated first"),
+                    }
+                };
+                let mut buffer = || {
+                    let mut buffer = buffers.pop().unwrap_or_default();
+                    buffer.clear();
+                    buffer
+                };
+                match *node {
+                    Node::Leaf(_) => continue,
+                    Node::Add(a, b) => {
+                        let mut out = buffer();
+                        out.extend(value(a).iter().zip(value(b)).map(|(a, b)| *a + b));
+                        out
+                    }
+                    Node::Mul(a, b) => {
+                        let mut out = buffer();
+                        out.extend(value(a).iter().zip(value(b)).map(|(a, b)| *a * b));
+                        out
+                    }
+                    Node::Scale(a, scalar) => {
+                        let mut out = buffer();
+                        out.extend(value(a).iter().map(|a| *a * scalar));
+                        out
+                    }
+                    Node::LinearTerm(scalar) => (ctx.linear_term)(scalar),
+                    Node::ConstantTerm(scalar) => (ctx.constant_term)(scalar),
+                }
+            };
+
+            for operand in node.operands().iter().flatten() {
+                if self.last_use[*operand] == index {
+                    if let Some(buffer) = values[*operand].take() {
+                        buffers.push(buffer);
+                    }
+                }
+            }
+            values[index] = Some(result);
+        }
+
+        match self.nodes.last().expect("graphs have a root") {
+            Node::Leaf(leaf) => ctx.leaves[*leaf].to_vec(),
+            _ => values.pop().flatten().expect("the root has been evaluated"),
+        }
+    }
+}
+
 impl<E, F: Field, B: Basis> Evaluator<E, F, B> {
     /// Registers the given polynomial for use in this evaluation context.
     ///
     /// This API treats each registered polynomial as unique, even if the same polynomial
     /// is added multiple times.
     pub(crate) fn register_poly(&mut self, poly: Polynomial<F, B>) -> AstLeaf<E, B> {
+        self.register(Registered::Evaluations(poly))
+    }
+
+    fn register(&mut self, poly: Registered<F, B>) -> AstLeaf<E, B> {
         let index = self.polys.len();
         self.polys.push(poly);
 
@@ -138,122 +403,53 @@ impl<E, F: Field, B: Basis> Evaluator<E, F, B> {
         F: FieldExt,
         B: BasisOps,
     {
-        // Traverse `ast` to collect the used leaves.
-        fn collect_rotations<E: Copy, F: Field, B: Basis>(
-            ast: &Ast<E, F, B>,
-        ) -> HashSet<AstLeaf<E, B>> {
-            match ast {
-                Ast::Poly(leaf) => vec![*leaf].into_iter().collect(),
-                Ast::Add(a, b) | Ast::Mul(AstMul(a, b)) => {
-                    let lhs = collect_rotations(a);
-                    let rhs = collect_rotations(b);
-                    lhs.union(&rhs).cloned().collect()
-                }
-                Ast::Scale(a, _) => collect_rotations(a),
-                Ast::LinearTerm(_) | Ast::ConstantTerm(_) => HashSet::default(),
-            }
-        }
-        let leaves = collect_rotations(ast);
+        enter_span!("evaluator_pass", n = domain.extended_len());
+        let graph = AstGraph::compile(ast);
 
         // Produce the rotated polynomials.
-        let rotated: HashMap<_, _> = leaves
+        let rotated: Vec<_> = graph
+            .leaves
             .iter()
-            .cloned()
-            .map(|leaf| {
-                (
-                    leaf,
-                    B::rotate(domain, &self.polys[leaf.index], leaf.rotation),
-                )
-            })
+            .map(|leaf| B::rotate(domain, self.polys[leaf.index].evaluations(), leaf.rotation))
             .collect();
 
         // We're working in a single basis, so all polynomials are the same length.
-        let poly_len = self.polys.first().unwrap().len();
+        let poly_len = self.polys.first().unwrap().evaluations().len();
         let (chunk_size, num_chunks) = get_chunk_params(poly_len);
 
         // Split each rotated polynomial into chunks.
-        let chunks: Vec<HashMap<_, _>> = (0..num_chunks)
+        let chunks: Vec<Vec<&[F]>> = (0..num_chunks)
             .map(|i| {
                 rotated
                     .iter()
-                    .map(|(leaf, poly)| {
-                        (
-                            *leaf,
-                            poly.chunks(chunk_size)
-                                .nth(i)
-                                .expect("num_chunks was calculated correctly"),
-                        )
+                    .map(|poly| {
+                        poly.chunks(chunk_size)
+                            .nth(i)
+                            .expect("num_chunks was calculated correctly")
                     })
                     .collect()
             })
             .collect();
 
-        struct AstContext<'a, E, F: FieldExt, B: Basis> {
-            domain: &'a EvaluationDomain<F>,
-            poly_len: usize,
-            chunk_size: usize,
-            chunk_index: usize,
-            leaves: &'a HashMap<AstLeaf<E, B>, &'a [F]>,
-        }
-
-        fn recurse<E, F: FieldExt, B: BasisOps>(
-            ast: &Ast<E, F, B>,
-            ctx: &AstContext<'_, E, F, B>,
-        ) -> Vec<F> {
-            match ast {
-                Ast::Poly(leaf) => ctx.leaves.get(leaf).expect("We prepared this").to_vec(),
-                Ast::Add(a, b) => {
-                    let mut lhs = recurse(a, ctx);
-                    let rhs = recurse(b, ctx);
-                    for (lhs, rhs) in lhs.iter_mut().zip(rhs.iter()) {
-                        *lhs += *rhs;
-                    }
-                    lhs
-                }
-                Ast::Mul(AstMul(a, b)) => {
-                    let mut lhs = recurse(a, ctx);
-                    let rhs = recurse(b, ctx);
-                    for (lhs, rhs) in lhs.iter_mut().zip(rhs.iter()) {
-                        *lhs *= *rhs;
-                    }
-                    lhs
-                }
-                Ast::Scale(a, scalar) => {
-                    let mut lhs = recurse(a, ctx);
-                    for lhs in lhs.iter_mut() {
-                        *lhs *= scalar;
-                    }
-                    lhs
-                }
-                Ast::LinearTerm(scalar) => B::linear_term(
-                    ctx.domain,
-                    ctx.poly_len,
-                    ctx.chunk_size,
-                    ctx.chunk_index,
-                    *scalar,
-                ),
-                Ast::ConstantTerm(scalar) => {
-                    B::constant_term(ctx.poly_len, ctx.chunk_size, ctx.chunk_index, *scalar)
-                }
-            }
-        }
-
         // Apply `ast` to each chunk in parallel, writing the result into an output
         // polynomial.
         let mut result = B::empty_poly(domain);
+        let graph = &graph;
         multicore::scope(|scope| {
             for (chunk_index, (out, leaves)) in
                 result.chunks_mut(chunk_size).zip(chunks.iter()).enumerate()
             {
                 scope.spawn(move |_| {
+                    let linear_term =
+                        |scalar| B::linear_term(domain, poly_len, chunk_size, chunk_index, scalar);
+                    let constant_term =
+                        |scalar| B::constant_term(poly_len, chunk_size, chunk_index, scalar);
                     let ctx = AstContext {
-                        domain,
-                        poly_len,
-                        chunk_size,
-                        chunk_index,
                         leaves,
+                        linear_term: &linear_term,
+                        constant_term: &constant_term,
                     };
-                    out.copy_from_slice(&recurse(ast, &ctx));
+                    out.copy_from_slice(&graph.evaluate(&ctx));
                 });
             }
         });
@@ -261,6 +457,200 @@ impl<E, F: Field, B: Basis> Evaluator<E, F, B> {
     }
 }
 
+impl<E, F: FieldExt> Evaluator<E, F, ExtendedLagrangeCoeff> {
+    /// Configures this evaluator to evaluate over `parts_per_chunk` parts of the
+    /// extended domain at a time (see [`EvaluationDomain::coeff_to_extended_part`]),
+    /// or over the whole extended domain at once if `None`.
+    ///
+    /// Evaluating over parts keeps polynomials registered with
+    /// [`Evaluator::register_coeff_poly`] in coefficient form, and transforms them into
+    /// each part as it is evaluated; the memory used during evaluation is proportional
+    /// to `parts_per_chunk` rather than to the number of parts. The result is the same
+    /// either way.
+    pub(crate) fn with_parts_per_chunk(mut self, parts_per_chunk: Option<usize>) -> Self {
+        assert!(self.polys.is_empty());
+        assert_ne!(parts_per_chunk, Some(0));
+        self.parts_per_chunk = parts_per_chunk;
+        self
+    }
+
+    /// Registers the given polynomial in coefficient form for use in this evaluation
+    /// context.
+    ///
+    /// If this evaluator evaluates over the whole extended domain, the polynomial is
+    /// transformed into it now. Otherwise the coefficients are kept, and transformed
+    /// into each part of the extended domain as it is evaluated.
+    pub(crate) fn register_coeff_poly(
+        &mut self,
+        domain: &EvaluationDomain<F>,
+        poly: Polynomial<F, Coeff>,
+    ) -> AstLeaf<E, ExtendedLagrangeCoeff> {
+        if self.parts_per_chunk.is_some() {
+            self.register(Registered::Coefficients(poly))
+        } else {
+            self.register_poly(domain.coeff_to_extended(poly))
+        }
+    }
+
+    /// Evaluates the given polynomial operation against this context, and divides the
+    /// result by the vanishing polynomial $t(X) = X^n - 1$.
+    ///
+    /// When evaluating over parts, each part is divided as it is evaluated, as $t(X)$ is
+    /// constant over each part; the result is the only buffer the size of the extended
+    /// domain.
+    pub(crate) fn evaluate_quotient(
+        &self,
+        ast: &Ast<E, F, ExtendedLagrangeCoeff>,
+        domain: &EvaluationDomain<F>,
+    ) -> Polynomial<F, ExtendedLagrangeCoeff>
+    where
+        E: Copy + Send + Sync,
+    {
+        match self.parts_per_chunk {
+            Some(parts_per_chunk) => self.evaluate_parts(ast, domain, parts_per_chunk, true),
+            None => domain.divide_by_vanishing_poly(self.evaluate(ast, domain)),
+        }
+    }
+
+    /// Returns the values of the registered polynomial with the given index over the
+    /// given part of the extended domain.
+    fn part_values(&self, domain: &EvaluationDomain<F>, index: usize, part: usize) -> Vec<F> {
+        match &self.polys[index] {
+            Registered::Evaluations(poly) => poly
+                .iter()
+                .skip(part)
+                .step_by(domain.extended_parts())
+                .cloned()
+                .collect(),
+            Registered::Coefficients(poly) => domain.coeff_to_extended_part(poly, part),
+        }
+    }
+
+    fn evaluate_parts(
+        &self,
+        ast: &Ast<E, F, ExtendedLagrangeCoeff>,
+        domain: &EvaluationDomain<F>,
+        parts_per_chunk: usize,
+        divide_by_vanishing_poly: bool,
+    ) -> Polynomial<F, ExtendedLagrangeCoeff>
+    where
+        E: Copy + Send + Sync,
+    {
+        enter_span!("evaluator_pass_chunked", parts_per_chunk);
+        let graph = AstGraph::compile(ast);
+        let indices: HashSet<usize> = graph.leaves.iter().map(|leaf| leaf.index).collect();
+
+        let num_parts = domain.extended_parts();
+        let part_len = domain.extended_len() / num_parts;
+        let parts: Vec<usize> = (0..num_parts).collect();
+
+        let mut result = domain.empty_extended();
+        for parts in parts.chunks(parts_per_chunk) {
+            // Transform each polynomial that `ast` uses into the parts in this chunk.
+            let values: HashMap<usize, Vec<Vec<F>>> = indices
+                .iter()
+                .map(|&index| {
+                    (
+                        index,
+                        parts
+                            .iter()
+                            .map(|&part| self.part_values(domain, index, part))
+                            .collect(),
+                    )
+                })
+                .collect();
+
+            // Rotating a polynomial in the extended domain by one row of the original
+            // domain moves each point to the next point within the same part, so each
+            // part is rotated on its own. The rotated parts of each leaf are laid out one
+            // after another.
+            let rotated: Vec<Vec<F>> = graph
+                .leaves
+                .iter()
+                .map(|leaf| {
+                    values[&leaf.index]
+                        .iter()
+                        .flat_map(|part_values| {
+                            let mut part_values = part_values.clone();
+                            let rotation = leaf.rotation.0.abs() as usize;
+                            if leaf.rotation.0 >= 0 {
+                                part_values.rotate_left(rotation);
+                            } else {
+                                part_values.rotate_right(rotation);
+                            }
+                            part_values
+                        })
+                        .collect()
+                })
+                .collect();
+            drop(values);
+
+            // Apply `ast` to the parts in parallel.
+            let chunk_len = parts.len() * part_len;
+            let (chunk_size, _) = get_chunk_params(chunk_len);
+            let mut out = vec![F::zero(); chunk_len];
+            multicore::scope(|scope| {
+                let rotated = &rotated;
+                let graph = &graph;
+                for (chunk_index, out) in out.chunks_mut(chunk_size).enumerate() {
+                    scope.spawn(move |_| {
+                        let start = chunk_index * chunk_size;
+                        let range = start..(start + out.len());
+                        let leaves: Vec<&[F]> = rotated
+                            .iter()
+                            .map(|values| &values[range.clone()])
+                            .collect();
+
+                        // The i'th point of part r is zeta * extended_omega^(r + i * num_parts)
+                        // = zeta * extended_omega^r * omega^i.
+                        let linear_term = |scalar: F| {
+                            let mut point = F::zero();
+                            range
+                                .clone()
+                                .map(|j| {
+                                    if j == start || j % part_len == 0 {
+                                        point = scalar
+                                            * domain.coset_shift(parts[j / part_len])
+                                            * domain
+                                                .get_omega()
+                                                .pow_vartime(&[(j % part_len) as u64]);
+                                    } else {
+                                        point *= domain.get_omega();
+                                    }
+                                    point
+                                })
+                                .collect()
+                        };
+                        let constant_term = |scalar: F| vec![scalar; range.len()];
+                        let ctx = AstContext {
+                            leaves: &leaves,
+                            linear_term: &linear_term,
+                            constant_term: &constant_term,
+                        };
+                        out.copy_from_slice(&graph.evaluate(&ctx));
+                    });
+                }
+            });
+
+            // Write the parts back into their places in the extended domain, dividing
+            // them by t(X) if requested.
+            for (&part, out) in parts.iter().zip(out.chunks(part_len)) {
+                if divide_by_vanishing_poly {
+                    let t_inv = domain.vanishing_poly_inv_on_part(part);
+                    for (i, value) in out.iter().enumerate() {
+                        result[part + i * num_parts] = *value * t_inv;
+                    }
+                } else {
+                    for (i, value) in out.iter().enumerate() {
+                        result[part + i * num_parts] = *value;
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
 /// Struct representing the [`Ast::Mul`] case.
 ///
 /// This struct exists to make the internals of this case private so that we don't
@@ -620,12 +1010,16 @@ impl BasisOps for ExtendedLagrangeCoeff {
 mod tests {
     use std::iter;
 
+    use group::ff::Field;
     use pasta_curves::pallas;
+    use rand_core::OsRng;
 
-    use super::{get_chunk_params, new_evaluator, Ast, BasisOps, Evaluator};
+    use super::{get_chunk_params, new_evaluator, Ast, AstGraph, BasisOps, Evaluator};
     use crate::{
         multicore,
-        poly::{Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff},
+        poly::{
+            Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, Rotation,
+        },
     };
 
     #[test]
@@ -666,4 +1060,107 @@ mod tests {
         test_case(k, new_evaluator::<_, _, LagrangeCoeff>(|| {}));
         test_case(k, new_evaluator::<_, _, ExtendedLagrangeCoeff>(|| {}));
     }
+
+    #[test]
+    fn evaluate_parts() {
+        let domain = EvaluationDomain::<pallas::Base>::new(5, 3);
+        assert_eq!(domain.extended_parts(), 4);
+
+        let random_poly = || {
+            let mut poly = domain.empty_coeff();
+            for coeff in poly.iter_mut() {
+                *coeff = pallas::Base::random(OsRng);
+            }
+            poly
+        };
+        let a = random_poly();
+        let b = random_poly();
+        let c = random_poly();
+        let scalar = pallas::Base::random(OsRng);
+
+        fn test_case<E: Copy + Send + Sync>(
+            mut evaluator: Evaluator<E, pallas::Base, ExtendedLagrangeCoeff>,
+            domain: &EvaluationDomain<pallas::Base>,
+            a: &Polynomial<pallas::Base, Coeff>,
+            b: &Polynomial<pallas::Base, Coeff>,
+            c: &Polynomial<pallas::Base, Coeff>,
+            scalar: pallas::Base,
+        ) -> (
+            Polynomial<pallas::Base, ExtendedLagrangeCoeff>,
+            Polynomial<pallas::Base, ExtendedLagrangeCoeff>,
+        ) {
+            let a = evaluator.register_coeff_poly(domain, a.clone());
+            let b = evaluator.register_coeff_poly(domain, b.clone());
+            // Polynomials registered in the extended domain can also be evaluated over
+            // parts.
+            let c = evaluator.register_poly(domain.coeff_to_extended(c.clone()));
+
+            let ast = (Ast::from(a.with_rotation(Rotation::next())) * b + Ast::LinearTerm(scalar))
+                * c
+                - Ast::from(a.with_rotation(Rotation::prev())) * scalar
+                + Ast::ConstantTerm(scalar);
+            let values = match evaluator.parts_per_chunk {
+                Some(parts_per_chunk) => {
+                    evaluator.evaluate_parts(&ast, domain, parts_per_chunk, false)
+                }
+                None => evaluator.evaluate(&ast, domain),
+            };
+            (values, evaluator.evaluate_quotient(&ast, domain))
+        }
+
+        let (expected, expected_quotient) =
+            test_case(new_evaluator(|| {}), &domain, &a, &b, &c, scalar);
+        assert_eq!(
+            domain.divide_by_vanishing_poly(expected.clone())[..],
+            expected_quotient[..]
+        );
+        for parts_per_chunk in 1..=4 {
+            let (values, quotient) = test_case(
+                new_evaluator(|| {}).with_parts_per_chunk(Some(parts_per_chunk)),
+                &domain,
+                &a,
+                &b,
+                &c,
+                scalar,
+            );
+            assert_eq!(values[..], expected[..]);
+            assert_eq!(quotient[..], expected_quotient[..]);
+        }
+    }
+
+    #[test]
+    fn shared_subexpressions() {
+        let domain = EvaluationDomain::<pallas::Base>::new(1, 4);
+        let random_poly = || {
+            let mut poly = domain.empty_lagrange();
+            for value in poly.iter_mut() {
+                *value = pallas::Base::random(OsRng);
+            }
+            poly
+        };
+        let a_values = random_poly();
+        let b_values = random_poly();
+        let c_values = random_poly();
+        let scalar = pallas::Base::random(OsRng);
+
+        let mut evaluator = new_evaluator(|| {});
+        let a = evaluator.register_poly(a_values.clone());
+        let b = evaluator.register_poly(b_values.clone());
+        let c = evaluator.register_poly(c_values.clone());
+
+        // `a * b` is built three times, once with its operands swapped, and `a` is
+        // queried at the same rotation in each.
+        let ast = Ast::from(a) * b + c + (Ast::from(a) * b) * scalar + Ast::from(b) * a;
+
+        let graph = AstGraph::compile(&ast);
+        assert_eq!(graph.leaves.len(), 3);
+        // The leaves, `a * b`, `a * b + c`, `(a * b) * scalar`, and the two outer sums.
+        assert_eq!(graph.nodes.len(), 8);
+
+        let result = evaluator.evaluate(&ast, &domain);
+        for (i, value) in result.iter().enumerate() {
+            let ab = a_values[i] * b_values[i];
+            assert_eq!(*value, ab + c_values[i] + ab * scalar + ab);
+        }
+    }
 }
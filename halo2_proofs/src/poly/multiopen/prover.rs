@@ -33,6 +33,7 @@ pub fn create_proof<
 where
     I: IntoIterator<Item = ProverQuery<'a, C>> + Clone,
 {
+    enter_span!("multiopen_prover");
     let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar();
     let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar();
 
@@ -29,6 +29,7 @@ pub fn verify_proof<
 where
     I: IntoIterator<Item = VerifierQuery<'r, 'params, C>> + Clone,
 {
+    enter_span!("multiopen_verifier");
     // Sample x_1 for compressing openings at the same point sets together
     let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar();
 
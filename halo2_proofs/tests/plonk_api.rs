@@ -3,387 +3,368 @@
 
 use assert_matches::assert_matches;
 use halo2_proofs::arithmetic::{CurveAffine, FieldExt};
-use halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner};
+use halo2_proofs::circuit::{
+    floor_planner::V1, AssignedCell, Cell, Layouter, Region, SimpleFloorPlanner,
+};
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::pasta::{Eq, EqAffine, Fp};
 use halo2_proofs::plonk::{
-    create_proof, keygen_pk, keygen_vk, verify_proof, Advice, BatchVerifier, Circuit, Column,
-    ConstraintSystem, Error, Fixed, SingleVerifier, TableColumn, VerificationStrategy,
+    create_multi_proof, create_proof, keygen_pk, keygen_pk_without_cosets, keygen_vk,
+    verify_multi_proof, verify_proof, Advice, BatchVerifier, Circuit, Column, ConstraintSystem,
+    Error, Fixed, FloorPlanner, Instance, ProofGroup, ProofObserver, ProverOptions, ProverPhase,
+    ProvingKey, QuotientStrategy, Selector, SingleVerifier, TableColumn, VerificationStrategy,
     VerifyingKey,
 };
 use halo2_proofs::poly::commitment::{Guard, MSM};
 use halo2_proofs::poly::{commitment::Params, Rotation};
 use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge};
-use rand_core::OsRng;
+use rand_core::{OsRng, RngCore};
 use std::marker::PhantomData;
 
-#[test]
-fn plonk_api() {
-    const K: u32 = 5;
+const K: u32 = 5;
 
-    /// This represents an advice column at a certain row in the ConstraintSystem
-    #[derive(Copy, Clone, Debug)]
-    pub struct Variable(Column<Advice>, usize);
+#[derive(Clone)]
+struct PlonkConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    d: Column<Advice>,
+    e: Column<Advice>,
 
-    // Initialize the polynomial commitment parameters
-    let params: Params<EqAffine> = Params::new(K);
+    sa: Column<Fixed>,
+    sb: Column<Fixed>,
+    sc: Column<Fixed>,
+    sm: Column<Fixed>,
+    sp: Column<Fixed>,
+    sl: TableColumn,
+}
 
-    #[derive(Clone)]
-    struct PlonkConfig {
-        a: Column<Advice>,
-        b: Column<Advice>,
-        c: Column<Advice>,
-        d: Column<Advice>,
-        e: Column<Advice>,
-
-        sa: Column<Fixed>,
-        sb: Column<Fixed>,
-        sc: Column<Fixed>,
-        sm: Column<Fixed>,
-        sp: Column<Fixed>,
-        sl: TableColumn,
-    }
+#[allow(clippy::type_complexity)]
+trait StandardCs<FF: FieldExt> {
+    fn raw_multiply<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        f: F,
+    ) -> Result<(Cell, Cell, Cell), Error>
+    where
+        F: FnMut() -> Result<(FF, FF, FF), Error>;
+    fn raw_add<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        f: F,
+    ) -> Result<(Cell, Cell, Cell), Error>
+    where
+        F: FnMut() -> Result<(FF, FF, FF), Error>;
+    fn copy(&self, layouter: &mut impl Layouter<FF>, a: Cell, b: Cell) -> Result<(), Error>;
+    fn public_input<F>(&self, layouter: &mut impl Layouter<FF>, f: F) -> Result<Cell, Error>
+    where
+        F: FnMut() -> Result<FF, Error>;
+    fn lookup_table(&self, layouter: &mut impl Layouter<FF>, values: &[FF]) -> Result<(), Error>;
+}
 
-    #[allow(clippy::type_complexity)]
-    trait StandardCs<FF: FieldExt> {
-        fn raw_multiply<F>(
-            &self,
-            layouter: &mut impl Layouter<FF>,
-            f: F,
-        ) -> Result<(Cell, Cell, Cell), Error>
-        where
-            F: FnMut() -> Result<(FF, FF, FF), Error>;
-        fn raw_add<F>(
-            &self,
-            layouter: &mut impl Layouter<FF>,
-            f: F,
-        ) -> Result<(Cell, Cell, Cell), Error>
-        where
-            F: FnMut() -> Result<(FF, FF, FF), Error>;
-        fn copy(&self, layouter: &mut impl Layouter<FF>, a: Cell, b: Cell) -> Result<(), Error>;
-        fn public_input<F>(&self, layouter: &mut impl Layouter<FF>, f: F) -> Result<Cell, Error>
-        where
-            F: FnMut() -> Result<FF, Error>;
-        fn lookup_table(
-            &self,
-            layouter: &mut impl Layouter<FF>,
-            values: &[FF],
-        ) -> Result<(), Error>;
+#[derive(Clone)]
+struct MyCircuit<F: FieldExt> {
+    a: Option<F>,
+    lookup_table: Vec<F>,
+}
+
+struct StandardPlonk<F: FieldExt> {
+    config: PlonkConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<FF: FieldExt> StandardPlonk<FF> {
+    fn new(config: PlonkConfig) -> Self {
+        StandardPlonk {
+            config,
+            _marker: PhantomData,
+        }
     }
+}
 
-    #[derive(Clone)]
-    struct MyCircuit<F: FieldExt> {
-        a: Option<F>,
-        lookup_table: Vec<F>,
+impl<FF: FieldExt> StandardCs<FF> for StandardPlonk<FF> {
+    fn raw_multiply<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        mut f: F,
+    ) -> Result<(Cell, Cell, Cell), Error>
+    where
+        F: FnMut() -> Result<(FF, FF, FF), Error>,
+    {
+        layouter.assign_region(
+            || "raw_multiply",
+            |mut region| {
+                let mut value = None;
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    self.config.a,
+                    0,
+                    || {
+                        value = Some(f()?);
+                        Ok(value.ok_or(Error::Synthesis)?.0)
+                    },
+                )?;
+                region.assign_advice(
+                    || "lhs^4",
+                    self.config.d,
+                    0,
+                    || Ok(value.ok_or(Error::Synthesis)?.0.square().square()),
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    self.config.b,
+                    0,
+                    || Ok(value.ok_or(Error::Synthesis)?.1),
+                )?;
+                region.assign_advice(
+                    || "rhs^4",
+                    self.config.e,
+                    0,
+                    || Ok(value.ok_or(Error::Synthesis)?.1.square().square()),
+                )?;
+                let out = region.assign_advice(
+                    || "out",
+                    self.config.c,
+                    0,
+                    || Ok(value.ok_or(Error::Synthesis)?.2),
+                )?;
+
+                region.assign_fixed(|| "a", self.config.sa, 0, || Ok(FF::zero()))?;
+                region.assign_fixed(|| "b", self.config.sb, 0, || Ok(FF::zero()))?;
+                region.assign_fixed(|| "c", self.config.sc, 0, || Ok(FF::one()))?;
+                region.assign_fixed(|| "a * b", self.config.sm, 0, || Ok(FF::one()))?;
+                Ok((lhs.cell(), rhs.cell(), out.cell()))
+            },
+        )
     }
+    fn raw_add<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        mut f: F,
+    ) -> Result<(Cell, Cell, Cell), Error>
+    where
+        F: FnMut() -> Result<(FF, FF, FF), Error>,
+    {
+        layouter.assign_region(
+            || "raw_add",
+            |mut region| {
+                let mut value = None;
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    self.config.a,
+                    0,
+                    || {
+                        value = Some(f()?);
+                        Ok(value.ok_or(Error::Synthesis)?.0)
+                    },
+                )?;
+                region.assign_advice(
+                    || "lhs^4",
+                    self.config.d,
+                    0,
+                    || Ok(value.ok_or(Error::Synthesis)?.0.square().square()),
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    self.config.b,
+                    0,
+                    || Ok(value.ok_or(Error::Synthesis)?.1),
+                )?;
+                region.assign_advice(
+                    || "rhs^4",
+                    self.config.e,
+                    0,
+                    || Ok(value.ok_or(Error::Synthesis)?.1.square().square()),
+                )?;
+                let out = region.assign_advice(
+                    || "out",
+                    self.config.c,
+                    0,
+                    || Ok(value.ok_or(Error::Synthesis)?.2),
+                )?;
 
-    struct StandardPlonk<F: FieldExt> {
-        config: PlonkConfig,
-        _marker: PhantomData<F>,
+                region.assign_fixed(|| "a", self.config.sa, 0, || Ok(FF::one()))?;
+                region.assign_fixed(|| "b", self.config.sb, 0, || Ok(FF::one()))?;
+                region.assign_fixed(|| "c", self.config.sc, 0, || Ok(FF::one()))?;
+                region.assign_fixed(|| "a * b", self.config.sm, 0, || Ok(FF::zero()))?;
+                Ok((lhs.cell(), rhs.cell(), out.cell()))
+            },
+        )
     }
+    fn copy(&self, layouter: &mut impl Layouter<FF>, left: Cell, right: Cell) -> Result<(), Error> {
+        layouter.assign_region(
+            || "copy",
+            |mut region| {
+                region.constrain_equal(left, right)?;
+                region.constrain_equal(left, right)
+            },
+        )
+    }
+    fn public_input<F>(&self, layouter: &mut impl Layouter<FF>, mut f: F) -> Result<Cell, Error>
+    where
+        F: FnMut() -> Result<FF, Error>,
+    {
+        layouter.assign_region(
+            || "public_input",
+            |mut region| {
+                let value = region.assign_advice(|| "value", self.config.a, 0, &mut f)?;
+                region.assign_fixed(|| "public", self.config.sp, 0, || Ok(FF::one()))?;
 
-    impl<FF: FieldExt> StandardPlonk<FF> {
-        fn new(config: PlonkConfig) -> Self {
-            StandardPlonk {
-                config,
-                _marker: PhantomData,
-            }
-        }
+                Ok(value.cell())
+            },
+        )
     }
+    fn lookup_table(&self, layouter: &mut impl Layouter<FF>, values: &[FF]) -> Result<(), Error> {
+        layouter.assign_table(
+            || "",
+            |mut table| {
+                for (index, &value) in values.iter().enumerate() {
+                    table.assign_cell(|| "table col", self.config.sl, index, || Ok(value))?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
 
-    impl<FF: FieldExt> StandardCs<FF> for StandardPlonk<FF> {
-        fn raw_multiply<F>(
-            &self,
-            layouter: &mut impl Layouter<FF>,
-            mut f: F,
-        ) -> Result<(Cell, Cell, Cell), Error>
-        where
-            F: FnMut() -> Result<(FF, FF, FF), Error>,
-        {
-            layouter.assign_region(
-                || "raw_multiply",
-                |mut region| {
-                    let mut value = None;
-                    let lhs = region.assign_advice(
-                        || "lhs",
-                        self.config.a,
-                        0,
-                        || {
-                            value = Some(f()?);
-                            Ok(value.ok_or(Error::Synthesis)?.0)
-                        },
-                    )?;
-                    region.assign_advice(
-                        || "lhs^4",
-                        self.config.d,
-                        0,
-                        || Ok(value.ok_or(Error::Synthesis)?.0.square().square()),
-                    )?;
-                    let rhs = region.assign_advice(
-                        || "rhs",
-                        self.config.b,
-                        0,
-                        || Ok(value.ok_or(Error::Synthesis)?.1),
-                    )?;
-                    region.assign_advice(
-                        || "rhs^4",
-                        self.config.e,
-                        0,
-                        || Ok(value.ok_or(Error::Synthesis)?.1.square().square()),
-                    )?;
-                    let out = region.assign_advice(
-                        || "out",
-                        self.config.c,
-                        0,
-                        || Ok(value.ok_or(Error::Synthesis)?.2),
-                    )?;
+impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+    type Config = PlonkConfig;
+    type FloorPlanner = SimpleFloorPlanner;
 
-                    region.assign_fixed(|| "a", self.config.sa, 0, || Ok(FF::zero()))?;
-                    region.assign_fixed(|| "b", self.config.sb, 0, || Ok(FF::zero()))?;
-                    region.assign_fixed(|| "c", self.config.sc, 0, || Ok(FF::one()))?;
-                    region.assign_fixed(|| "a * b", self.config.sm, 0, || Ok(FF::one()))?;
-                    Ok((lhs.cell(), rhs.cell(), out.cell()))
-                },
-            )
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: None,
+            lookup_table: self.lookup_table.clone(),
         }
-        fn raw_add<F>(
-            &self,
-            layouter: &mut impl Layouter<FF>,
-            mut f: F,
-        ) -> Result<(Cell, Cell, Cell), Error>
-        where
-            F: FnMut() -> Result<(FF, FF, FF), Error>,
-        {
-            layouter.assign_region(
-                || "raw_add",
-                |mut region| {
-                    let mut value = None;
-                    let lhs = region.assign_advice(
-                        || "lhs",
-                        self.config.a,
-                        0,
-                        || {
-                            value = Some(f()?);
-                            Ok(value.ok_or(Error::Synthesis)?.0)
-                        },
-                    )?;
-                    region.assign_advice(
-                        || "lhs^4",
-                        self.config.d,
-                        0,
-                        || Ok(value.ok_or(Error::Synthesis)?.0.square().square()),
-                    )?;
-                    let rhs = region.assign_advice(
-                        || "rhs",
-                        self.config.b,
-                        0,
-                        || Ok(value.ok_or(Error::Synthesis)?.1),
-                    )?;
-                    region.assign_advice(
-                        || "rhs^4",
-                        self.config.e,
-                        0,
-                        || Ok(value.ok_or(Error::Synthesis)?.1.square().square()),
-                    )?;
-                    let out = region.assign_advice(
-                        || "out",
-                        self.config.c,
-                        0,
-                        || Ok(value.ok_or(Error::Synthesis)?.2),
-                    )?;
+    }
 
-                    region.assign_fixed(|| "a", self.config.sa, 0, || Ok(FF::one()))?;
-                    region.assign_fixed(|| "b", self.config.sb, 0, || Ok(FF::one()))?;
-                    region.assign_fixed(|| "c", self.config.sc, 0, || Ok(FF::one()))?;
-                    region.assign_fixed(|| "a * b", self.config.sm, 0, || Ok(FF::zero()))?;
-                    Ok((lhs.cell(), rhs.cell(), out.cell()))
-                },
-            )
-        }
-        fn copy(
-            &self,
-            layouter: &mut impl Layouter<FF>,
-            left: Cell,
-            right: Cell,
-        ) -> Result<(), Error> {
-            layouter.assign_region(
-                || "copy",
-                |mut region| {
-                    region.constrain_equal(left, right)?;
-                    region.constrain_equal(left, right)
-                },
-            )
-        }
-        fn public_input<F>(&self, layouter: &mut impl Layouter<FF>, mut f: F) -> Result<Cell, Error>
-        where
-            F: FnMut() -> Result<FF, Error>,
-        {
-            layouter.assign_region(
-                || "public_input",
-                |mut region| {
-                    let value = region.assign_advice(|| "value", self.config.a, 0, &mut f)?;
-                    region.assign_fixed(|| "public", self.config.sp, 0, || Ok(FF::one()))?;
+    fn configure(meta: &mut ConstraintSystem<F>) -> PlonkConfig {
+        let e = meta.advice_column();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let sf = meta.fixed_column();
+        let c = meta.advice_column();
+        let d = meta.advice_column();
+        let p = meta.instance_column();
 
-                    Ok(value.cell())
-                },
-            )
-        }
-        fn lookup_table(
-            &self,
-            layouter: &mut impl Layouter<FF>,
-            values: &[FF],
-        ) -> Result<(), Error> {
-            layouter.assign_table(
-                || "",
-                |mut table| {
-                    for (index, &value) in values.iter().enumerate() {
-                        table.assign_cell(|| "table col", self.config.sl, index, || Ok(value))?;
-                    }
-                    Ok(())
-                },
-            )?;
-            Ok(())
-        }
-    }
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(c);
 
-    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
-        type Config = PlonkConfig;
-        type FloorPlanner = SimpleFloorPlanner;
+        let sm = meta.fixed_column();
+        let sa = meta.fixed_column();
+        let sb = meta.fixed_column();
+        let sc = meta.fixed_column();
+        let sp = meta.fixed_column();
+        let sl = meta.lookup_table_column();
 
-        fn without_witnesses(&self) -> Self {
-            Self {
-                a: None,
-                lookup_table: self.lookup_table.clone(),
-            }
-        }
+        /*
+         *   A         B      ...  sl
+         * [
+         *   instance  0      ...  0
+         *   a         a      ...  0
+         *   a         a^2    ...  0
+         *   a         a      ...  0
+         *   a         a^2    ...  0
+         *   ...       ...    ...  ...
+         *   ...       ...    ...  instance
+         *   ...       ...    ...  a
+         *   ...       ...    ...  a
+         *   ...       ...    ...  0
+         * ]
+         */
 
-        fn configure(meta: &mut ConstraintSystem<F>) -> PlonkConfig {
-            let e = meta.advice_column();
-            let a = meta.advice_column();
-            let b = meta.advice_column();
-            let sf = meta.fixed_column();
-            let c = meta.advice_column();
-            let d = meta.advice_column();
-            let p = meta.instance_column();
+        meta.lookup(|meta| {
+            let a_ = meta.query_any(a, Rotation::cur());
+            vec![(a_, sl)]
+        });
 
-            meta.enable_equality(a);
-            meta.enable_equality(b);
-            meta.enable_equality(c);
-
-            let sm = meta.fixed_column();
-            let sa = meta.fixed_column();
-            let sb = meta.fixed_column();
-            let sc = meta.fixed_column();
-            let sp = meta.fixed_column();
-            let sl = meta.lookup_table_column();
-
-            /*
-             *   A         B      ...  sl
-             * [
-             *   instance  0      ...  0
-             *   a         a      ...  0
-             *   a         a^2    ...  0
-             *   a         a      ...  0
-             *   a         a^2    ...  0
-             *   ...       ...    ...  ...
-             *   ...       ...    ...  instance
-             *   ...       ...    ...  a
-             *   ...       ...    ...  a
-             *   ...       ...    ...  0
-             * ]
-             */
-
-            meta.lookup(|meta| {
-                let a_ = meta.query_any(a, Rotation::cur());
-                vec![(a_, sl)]
-            });
+        meta.create_gate("Combined add-mult", |meta| {
+            let d = meta.query_advice(d, Rotation::next());
+            let a = meta.query_advice(a, Rotation::cur());
+            let sf = meta.query_fixed(sf, Rotation::cur());
+            let e = meta.query_advice(e, Rotation::prev());
+            let b = meta.query_advice(b, Rotation::cur());
+            let c = meta.query_advice(c, Rotation::cur());
 
-            meta.create_gate("Combined add-mult", |meta| {
-                let d = meta.query_advice(d, Rotation::next());
-                let a = meta.query_advice(a, Rotation::cur());
-                let sf = meta.query_fixed(sf, Rotation::cur());
-                let e = meta.query_advice(e, Rotation::prev());
-                let b = meta.query_advice(b, Rotation::cur());
-                let c = meta.query_advice(c, Rotation::cur());
+            let sa = meta.query_fixed(sa, Rotation::cur());
+            let sb = meta.query_fixed(sb, Rotation::cur());
+            let sc = meta.query_fixed(sc, Rotation::cur());
+            let sm = meta.query_fixed(sm, Rotation::cur());
 
-                let sa = meta.query_fixed(sa, Rotation::cur());
-                let sb = meta.query_fixed(sb, Rotation::cur());
-                let sc = meta.query_fixed(sc, Rotation::cur());
-                let sm = meta.query_fixed(sm, Rotation::cur());
+            vec![a.clone() * sa + b.clone() * sb + a * b * sm - (c * sc) + sf * (d * e)]
+        });
 
-                vec![a.clone() * sa + b.clone() * sb + a * b * sm - (c * sc) + sf * (d * e)]
-            });
+        meta.create_gate("Public input", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let p = meta.query_instance(p, Rotation::cur());
+            let sp = meta.query_fixed(sp, Rotation::cur());
 
-            meta.create_gate("Public input", |meta| {
-                let a = meta.query_advice(a, Rotation::cur());
-                let p = meta.query_instance(p, Rotation::cur());
-                let sp = meta.query_fixed(sp, Rotation::cur());
+            vec![sp * (a - p)]
+        });
 
-                vec![sp * (a - p)]
-            });
+        meta.enable_equality(sf);
+        meta.enable_equality(e);
+        meta.enable_equality(d);
+        meta.enable_equality(p);
+        meta.enable_equality(sm);
+        meta.enable_equality(sa);
+        meta.enable_equality(sb);
+        meta.enable_equality(sc);
+        meta.enable_equality(sp);
 
-            meta.enable_equality(sf);
-            meta.enable_equality(e);
-            meta.enable_equality(d);
-            meta.enable_equality(p);
-            meta.enable_equality(sm);
-            meta.enable_equality(sa);
-            meta.enable_equality(sb);
-            meta.enable_equality(sc);
-            meta.enable_equality(sp);
-
-            PlonkConfig {
-                a,
-                b,
-                c,
-                d,
-                e,
-                sa,
-                sb,
-                sc,
-                sm,
-                sp,
-                sl,
-            }
+        PlonkConfig {
+            a,
+            b,
+            c,
+            d,
+            e,
+            sa,
+            sb,
+            sc,
+            sm,
+            sp,
+            sl,
         }
+    }
 
-        fn synthesize(
-            &self,
-            config: PlonkConfig,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            let cs = StandardPlonk::new(config);
-
-            let _ = cs.public_input(&mut layouter, || Ok(F::one() + F::one()))?;
-
-            for _ in 0..10 {
-                let mut a_squared = None;
-                let (a0, _, c0) = cs.raw_multiply(&mut layouter, || {
-                    a_squared = self.a.map(|a| a.square());
-                    Ok((
-                        self.a.ok_or(Error::Synthesis)?,
-                        self.a.ok_or(Error::Synthesis)?,
-                        a_squared.ok_or(Error::Synthesis)?,
-                    ))
-                })?;
-                let (a1, b1, _) = cs.raw_add(&mut layouter, || {
-                    let fin = a_squared.and_then(|a2| self.a.map(|a| a + a2));
-                    Ok((
-                        self.a.ok_or(Error::Synthesis)?,
-                        a_squared.ok_or(Error::Synthesis)?,
-                        fin.ok_or(Error::Synthesis)?,
-                    ))
-                })?;
-                cs.copy(&mut layouter, a0, a1)?;
-                cs.copy(&mut layouter, b1, c0)?;
-            }
+    fn synthesize(&self, config: PlonkConfig, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let cs = StandardPlonk::new(config);
 
-            cs.lookup_table(&mut layouter, &self.lookup_table)?;
+        let _ = cs.public_input(&mut layouter, || Ok(F::one() + F::one()))?;
 
-            Ok(())
+        for _ in 0..10 {
+            let mut a_squared = None;
+            let (a0, _, c0) = cs.raw_multiply(&mut layouter, || {
+                a_squared = self.a.map(|a| a.square());
+                Ok((
+                    self.a.ok_or(Error::Synthesis)?,
+                    self.a.ok_or(Error::Synthesis)?,
+                    a_squared.ok_or(Error::Synthesis)?,
+                ))
+            })?;
+            let (a1, b1, _) = cs.raw_add(&mut layouter, || {
+                let fin = a_squared.and_then(|a2| self.a.map(|a| a + a2));
+                Ok((
+                    self.a.ok_or(Error::Synthesis)?,
+                    a_squared.ok_or(Error::Synthesis)?,
+                    fin.ok_or(Error::Synthesis)?,
+                ))
+            })?;
+            cs.copy(&mut layouter, a0, a1)?;
+            cs.copy(&mut layouter, b1, c0)?;
         }
+
+        cs.lookup_table(&mut layouter, &self.lookup_table)?;
+
+        Ok(())
     }
+}
 
+/// Returns the circuit used by these tests without and with its witness, along with
+/// its public input.
+fn test_circuits() -> (MyCircuit<Fp>, MyCircuit<Fp>, Fp) {
     let a = Fp::from(2834758237) * Fp::ZETA;
     let instance = Fp::one() + Fp::one();
     let lookup_table = vec![instance, a, a, Fp::zero()];
@@ -398,6 +379,67 @@ fn plonk_api() {
         lookup_table,
     };
 
+    (empty_circuit, circuit, instance)
+}
+
+/// A deterministic RNG (SplitMix64), so that proofs can be compared byte-for-byte.
+struct TestRng(u64);
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Proves two copies of `circuit` with the given options, using a fixed seed for the
+/// randomness.
+fn prove_deterministic(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: &MyCircuit<Fp>,
+    instance: Fp,
+    options: ProverOptions<'_, EqAffine>,
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    options.create_proof(
+        params,
+        pk,
+        &[circuit.clone(), circuit.clone()],
+        &[&[&[instance]], &[&[instance]]],
+        TestRng(42),
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+#[test]
+fn plonk_api() {
+    /// This represents an advice column at a certain row in the ConstraintSystem
+    #[derive(Copy, Clone, Debug)]
+    pub struct Variable(Column<Advice>, usize);
+
+    // Initialize the polynomial commitment parameters
+    let params: Params<EqAffine> = Params::new(K);
+
+    let (empty_circuit, circuit, instance) = test_circuits();
+
     // Check that we get an error if we try to initialize the proving key with a value of
     // k that is too small for the minimum required number of rows.
     let much_too_small_params: Params<EqAffine> = Params::new(1);
@@ -976,3 +1018,484 @@ fn plonk_api() {
         );
     }
 }
+
+// Check that the quotient strategies and proving keys without cosets all create the same
+// proof, given the same randomness.
+#[test]
+fn quotient_strategies() {
+    let params: Params<EqAffine> = Params::new(K);
+    let (empty_circuit, circuit, instance) = test_circuits();
+
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk_without_cosets = keygen_pk_without_cosets(&params, vk, &empty_circuit)
+        .expect("keygen_pk_without_cosets should not fail");
+
+    let prove = |pk: &ProvingKey<EqAffine>, strategy| {
+        let options = ProverOptions::new().quotient_strategy(strategy);
+        prove_deterministic(&params, pk, &circuit, instance, options)
+            .expect("proof generation should not fail")
+    };
+
+    let proof = prove(&pk, QuotientStrategy::Full);
+    for parts_per_chunk in 1..=4 {
+        let strategy = QuotientStrategy::Chunked { parts_per_chunk };
+        assert_eq!(prove(&pk, strategy), proof);
+        assert_eq!(prove(&pk_without_cosets, strategy), proof);
+    }
+    assert_eq!(prove(&pk_without_cosets, QuotientStrategy::Full), proof);
+
+    let strategy = QuotientStrategy::Chunked { parts_per_chunk: 0 };
+    assert_matches!(
+        prove_deterministic(
+            &params,
+            &pk,
+            &circuit,
+            instance,
+            ProverOptions::new().quotient_strategy(strategy),
+        ),
+        Err(Error::InvalidQuotientStrategy)
+    );
+
+    let strategy = SingleVerifier::new(&params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    assert!(verify_proof(
+        &params,
+        pk.get_vk(),
+        strategy,
+        &[&[&[instance]], &[&[instance]]],
+        &mut transcript,
+    )
+    .is_ok());
+}
+
+// Check that observing the prover reports each phase in order, and does not change the
+// proof. Cancelling it stops the prover before the next phase.
+#[test]
+fn proof_observer() {
+    #[derive(Default)]
+    struct Observer {
+        events: Vec<(ProverPhase, bool)>,
+        cancel_at: Option<ProverPhase>,
+    }
+
+    impl ProofObserver for Observer {
+        fn phase_started(&mut self, phase: ProverPhase) {
+            self.events.push((phase, true));
+        }
+
+        fn phase_finished(&mut self, phase: ProverPhase, _: std::time::Duration) {
+            self.events.push((phase, false));
+        }
+
+        fn is_cancelled(&self) -> bool {
+            self.cancel_at.is_some()
+                && self.cancel_at == self.events.last().map(|(phase, _)| *phase)
+        }
+    }
+
+    let params: Params<EqAffine> = Params::new(K);
+    let (empty_circuit, circuit, instance) = test_circuits();
+
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let proof = prove_deterministic(&params, &pk, &circuit, instance, ProverOptions::new())
+        .expect("proof generation should not fail");
+    let prove_observed = |observer: &mut Observer, strategy| {
+        let options = ProverOptions::new()
+            .quotient_strategy(strategy)
+            .observer(observer);
+        prove_deterministic(&params, &pk, &circuit, instance, options)
+    };
+
+    // Options combine: an observed proof with a chunked quotient is also unchanged.
+    let mut observer = Observer::default();
+    let strategy = QuotientStrategy::Chunked { parts_per_chunk: 2 };
+    assert_eq!(prove_observed(&mut observer, strategy).unwrap(), proof);
+
+    let mut observer = Observer::default();
+    assert_eq!(
+        prove_observed(&mut observer, QuotientStrategy::Full).unwrap(),
+        proof
+    );
+    let phases = [
+        ProverPhase::WitnessSynthesis,
+        ProverPhase::AdviceCommitments,
+        ProverPhase::WitnessSynthesis,
+        ProverPhase::AdviceCommitments,
+        ProverPhase::LookupPermutation,
+        ProverPhase::PermutationProducts,
+        ProverPhase::LookupProducts,
+        ProverPhase::Quotient,
+        ProverPhase::Evaluations,
+        ProverPhase::Multiopen,
+    ];
+    assert_eq!(
+        observer.events,
+        phases
+            .iter()
+            .flat_map(|phase| vec![(*phase, true), (*phase, false)])
+            .collect::<Vec<_>>()
+    );
+
+    let mut observer = Observer {
+        cancel_at: Some(ProverPhase::PermutationProducts),
+        ..Observer::default()
+    };
+    assert_matches!(
+        prove_observed(&mut observer, QuotientStrategy::Full),
+        Err(Error::Cancelled)
+    );
+    assert_eq!(
+        observer.events.last(),
+        Some(&(ProverPhase::PermutationProducts, false))
+    );
+}
+
+// Check that circuits of different types can be proven and verified together.
+#[test]
+fn multi_proof() {
+    #[derive(Clone, Default)]
+    struct SquareCircuit {
+        a: Option<Fp>,
+    }
+
+    #[derive(Clone, Debug)]
+    struct SquareConfig {
+        a: Column<Advice>,
+        s: Selector,
+    }
+
+    impl Circuit<Fp> for SquareCircuit {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            let a = meta.advice_column();
+            let b: Column<Instance> = meta.instance_column();
+            let s = meta.selector();
+
+            meta.create_gate("square", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_instance(b, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a.clone() * a - b)]
+            });
+
+            SquareConfig { a, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: SquareConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || self.a.ok_or(Error::Synthesis))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params: Params<EqAffine> = Params::new(K);
+    let (empty_circuit, circuit, instance) = test_circuits();
+
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let square_vk =
+        keygen_vk(&params, &SquareCircuit::default()).expect("keygen_vk should not fail");
+    let square_pk = keygen_pk(&params, square_vk, &SquareCircuit::default())
+        .expect("keygen_pk should not fail");
+
+    let circuits = [circuit.clone(), circuit.clone()];
+    let instances: &[&[&[Fp]]] = &[&[&[instance]], &[&[instance]]];
+    let square_circuits = [SquareCircuit {
+        a: Some(Fp::from(3)),
+    }];
+    let square_instances: &[&[&[Fp]]] = &[&[&[Fp::from(9)]]];
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_multi_proof(
+        &params,
+        &[
+            ProofGroup::new(&pk, &circuits, instances),
+            ProofGroup::new(&square_pk, &square_circuits, square_instances),
+        ],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    let verify = |square_instances: &[&[&[Fp]]]| {
+        let strategy = SingleVerifier::new(&params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        verify_multi_proof(
+            &params,
+            &[
+                (pk.get_vk(), instances),
+                (square_pk.get_vk(), square_instances),
+            ],
+            strategy,
+            &mut transcript,
+        )
+    };
+    assert!(verify(square_instances).is_ok());
+    assert!(verify(&[&[&[Fp::from(10)]]]).is_err());
+
+    // A multi-proof of a single group is the same as a proof of its circuits.
+    let proof = prove_deterministic(&params, &pk, &circuit, instance, ProverOptions::new())
+        .expect("proof generation should not fail");
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_multi_proof(
+        &params,
+        &[ProofGroup::new(&pk, &circuits, instances)],
+        TestRng(42),
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    assert_eq!(transcript.finalize(), proof);
+
+    // Every group's key must have been generated for the parameters' `k`.
+    let large_params: Params<EqAffine> = Params::new(K + 1);
+    let large_vk =
+        keygen_vk(&large_params, &SquareCircuit::default()).expect("keygen_vk should not fail");
+    let large_pk = keygen_pk(&large_params, large_vk, &SquareCircuit::default())
+        .expect("keygen_pk should not fail");
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    assert_matches!(
+        create_multi_proof(
+            &params,
+            &[
+                ProofGroup::new(&pk, &circuits, instances),
+                ProofGroup::new(&large_pk, &square_circuits, square_instances),
+            ],
+            OsRng,
+            &mut transcript,
+        ),
+        Err(Error::KeyParamsMismatch { params_k, key_k }) if params_k == K && key_k == K + 1
+    );
+
+    let strategy = SingleVerifier::new(&params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    assert_matches!(
+        verify_multi_proof(
+            &params,
+            &[
+                (pk.get_vk(), instances),
+                (large_pk.get_vk(), square_instances),
+            ],
+            strategy,
+            &mut transcript,
+        ),
+        Err(Error::KeyParamsMismatch { params_k, key_k }) if params_k == K && key_k == K + 1
+    );
+}
+
+// Check that regions assigned in parallel are run once per floor planner pass, without
+// their witnesses during keygen, and give the same circuit as regions assigned serially.
+#[test]
+fn parallel_regions() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const K: u32 = 6;
+
+    #[derive(Clone)]
+    struct ScaleConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        f: Column<Fixed>,
+        s: Selector,
+        instance: Column<Instance>,
+    }
+
+    struct ScaleCircuit<P: FloorPlanner> {
+        seed: Option<Fp>,
+        parallel: bool,
+        // The number of times the region copying the public input has been run.
+        runs: Arc<AtomicUsize>,
+        _marker: PhantomData<P>,
+    }
+
+    impl<P: FloorPlanner> Circuit<Fp> for ScaleCircuit<P> {
+        type Config = ScaleConfig;
+        type FloorPlanner = P;
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let f = meta.fixed_column();
+            let s = meta.selector();
+            let instance = meta.instance_column();
+            let constants = meta.fixed_column();
+
+            meta.enable_equality(a);
+            meta.enable_equality(b);
+            meta.enable_equality(instance);
+            meta.enable_constant(constants);
+
+            // b = a * f
+            meta.create_gate("scale", |cells| {
+                let a = cells.query_advice(a, Rotation::cur());
+                let b = cells.query_advice(b, Rotation::cur());
+                let f = cells.query_fixed(f, Rotation::cur());
+                let s = cells.query_selector(s);
+
+                vec![s * (a * f - b)]
+            });
+
+            ScaleConfig {
+                a,
+                b,
+                f,
+                s,
+                instance,
+            }
+        }
+
+        fn without_witnesses(&self) -> Self {
+            ScaleCircuit {
+                seed: None,
+                parallel: self.parallel,
+                runs: self.runs.clone(),
+                _marker: PhantomData,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let seed = layouter.assign_region(
+                || "seed",
+                |mut region| {
+                    region.assign_advice(
+                        || "seed",
+                        config.a,
+                        0,
+                        || self.seed.ok_or(Error::Synthesis),
+                    )
+                },
+            )?;
+
+            // Each region scales the seed by its index (plus one), and the last one
+            // also copies in the public input.
+            let witnessed = self.seed.is_some();
+            let scale = |i: usize| {
+                let config = config.clone();
+                let seed = seed.clone();
+                let runs = self.runs.clone();
+                move |mut region: Region<'_, Fp>| -> Result<AssignedCell<Fp, Fp>, Error> {
+                    let factor = Fp::from(i as u64 + 1);
+                    config.s.enable(&mut region, 0)?;
+                    seed.copy_advice(|| "a", &mut region, config.a, 0)?;
+                    region.assign_fixed(|| "f", config.f, 0, || Ok(factor))?;
+                    let b = region.assign_advice(
+                        || "b",
+                        config.b,
+                        0,
+                        || {
+                            // Keygen must not compute witness values.
+                            assert!(witnessed);
+                            seed.value().map(|v| *v * factor).ok_or(Error::Synthesis)
+                        },
+                    )?;
+                    region.assign_advice_from_constant(|| "one", config.a, 1, Fp::one())?;
+                    if i == 3 {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        let public = region.assign_advice_from_instance(
+                            || "public",
+                            config.instance,
+                            0,
+                            config.b,
+                            1,
+                        )?;
+                        region.constrain_equal(b.cell(), public.cell())?;
+                    }
+                    Ok(b)
+                }
+            };
+            let assignments: Vec<_> = (0..5).map(scale).collect();
+            let cells = if self.parallel {
+                layouter.assign_regions(|i| format!("scale {}", i), assignments)?
+            } else {
+                let mut cells = vec![];
+                for (i, assignment) in assignments.into_iter().enumerate() {
+                    cells.push(layouter.assign_region(|| format!("scale {}", i), assignment)?);
+                }
+                cells
+            };
+
+            layouter.constrain_instance(cells[3].cell(), config.instance, 0)
+        }
+    }
+
+    // `passes` is the number of times the floor planner runs each region assignment.
+    fn check<P: FloorPlanner>(passes: usize) {
+        let circuit = |parallel| ScaleCircuit::<P> {
+            seed: Some(Fp::from(7)),
+            parallel,
+            runs: Arc::new(AtomicUsize::new(0)),
+            _marker: PhantomData,
+        };
+        let instance = vec![vec![Fp::from(28)]];
+
+        let parallel_circuit = circuit(true);
+        let prover = MockProver::run(K, &parallel_circuit, instance.clone()).unwrap();
+        assert_eq!(parallel_circuit.runs.load(Ordering::SeqCst), passes);
+        assert_eq!(prover.verify(), Ok(()));
+
+        let params: Params<EqAffine> = Params::new(K);
+        // Keygen runs without witnesses, so any witness closure it calls will panic.
+        let serial = keygen_vk(&params, &circuit(false).without_witnesses()).unwrap();
+        let parallel = keygen_vk(&params, &parallel_circuit.without_witnesses()).unwrap();
+        assert_eq!(
+            format!("{:?}", serial.pinned()),
+            format!("{:?}", parallel.pinned())
+        );
+
+        let pk = keygen_pk(&params, parallel, &parallel_circuit.without_witnesses()).unwrap();
+        let instance: Vec<_> = instance.iter().map(|column| &column[..]).collect();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[circuit(true)],
+            &[&instance[..]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let strategy = SingleVerifier::new(&params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof(
+            &params,
+            pk.get_vk(),
+            strategy,
+            &[&instance[..]],
+            &mut transcript,
+        )
+        .is_ok());
+    }
+
+    check::<SimpleFloorPlanner>(1);
+    check::<V1>(2);
+}